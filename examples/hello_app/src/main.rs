@@ -16,32 +16,30 @@ fn create_menu(ctx: &impl ContextOwner) -> Menu {
     // App menu
     let quit_item = MenuItem::builder()
         .with_title("Quit")
-        .with_macos_short_code("q")
-        .with_action(Action::new_event("quit"))
-        .build(ctx);
+        .with_accelerator("CmdOrCtrl+Q".parse().unwrap())
+        .with_action(Action::new_callback(|app| app.stop()))
+        .build(ctx)
+        .unwrap();
 
     let app_menu = Menu::builder().with_item(quit_item).build(ctx);
 
     // Main menu
-    let app_item = MenuItem::builder().with_submenu(app_menu).build(ctx);
+    let app_item = MenuItem::builder()
+        .with_submenu(app_menu)
+        .build(ctx)
+        .unwrap();
     Menu::builder().with_item(app_item).build(ctx)
 }
 
 fn main() {
-    let app = Application::new().unwrap();
-    let menu = create_menu(&app);
-    app.run(
-        move |app: &mut ActiveApplication, event: Event| match event {
-            Event::Menu(action) => {
-                if action == "quit" {
-                    app.stop();
-                }
-            }
-            Event::LifeCycle(LifeCycle::Started) => {
-                println!("Hello, World!");
-                app.set_menu(Some(&menu));
-            }
-            _ => (),
-        },
-    );
+    let app = Application::builder()
+        .with_app_id("b3.HelloApp")
+        .with_menu(|ctx| Ok(create_menu(ctx)))
+        .build()
+        .unwrap();
+    app.run(move |_app: &mut ActiveApplication, event: Event| {
+        if let Event::LifeCycle(LifeCycle::Started) = event {
+            println!("Hello, World!");
+        }
+    });
 }
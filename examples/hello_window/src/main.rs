@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use b3_core::{
+    AboutMetadata,
     Action,
     ActiveApplication,
     Application,
@@ -14,6 +15,7 @@ use b3_core::{
     MenuItem,
     MouseEvent,
     NotificationBuilder,
+    PredefinedItem,
     Window,
     WindowEvent,
     WindowId,
@@ -21,73 +23,98 @@ use b3_core::{
 
 fn create_menu(ctx: &impl ContextOwner) -> Menu {
     // App menu
-    let settings_item = MenuItem::builder()
+    let about_item = MenuItem::predefined(
+        ctx,
+        PredefinedItem::About(AboutMetadata {
+            name: Some("Hello Window".into()),
+            version: Some(env!("CARGO_PKG_VERSION").into()),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+
+    let mut settings_item = MenuItem::builder()
         .with_title("Preferences...")
-        .with_icon(Icon::from_str(ctx, "gear").unwrap())
-        .with_macos_short_code("P")
+        .with_accelerator("CmdOrCtrl+Shift+P".parse().unwrap())
         .with_enabled(false) // Stub item
-        .build(ctx);
-
-    let quit_item = MenuItem::builder()
-        .with_title("Quit")
-        .with_macos_short_code("q")
-        .with_action(Action::new_event("quit"))
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
+    // Built-in icons differ between platforms, so the icon is optional here.
+    settings_item.set_icon(Icon::from_str(ctx, "gear").ok());
 
     let app_menu = Menu::builder()
+        .with_item(about_item)
+        .with_item(MenuItem::separator(ctx).unwrap())
         .with_item(settings_item)
-        .with_item(MenuItem::separator(ctx))
-        .with_item(quit_item)
+        .with_item(MenuItem::separator(ctx).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::Hide).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::HideOthers).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::ShowAll).unwrap())
+        .with_item(MenuItem::separator(ctx).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::Quit).unwrap())
         .build(ctx);
 
     // Window menu
 
     let new_window_item = MenuItem::builder()
         .with_title("New Window")
-        .with_macos_short_code("n")
+        .with_accelerator("CmdOrCtrl+N".parse().unwrap())
         .with_action(Action::new_event("new-window"))
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     let new_modal_item = MenuItem::builder()
         .with_title("New Modal Window")
-        .with_macos_short_code("m")
+        .with_accelerator("CmdOrCtrl+Shift+N".parse().unwrap())
         .with_action(Action::new_event("new-modal-window"))
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     let close_all_item = MenuItem::builder()
         .with_title("Close All")
-        .with_macos_short_code("k")
+        .with_accelerator("CmdOrCtrl+K".parse().unwrap())
         .with_action(Action::new_event("close-all"))
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     let window_menu = Menu::builder()
         .with_item(new_window_item)
         .with_item(new_modal_item)
-        .with_item(MenuItem::separator(ctx))
+        .with_item(MenuItem::separator(ctx).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::Minimize).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::Zoom).unwrap())
+        .with_item(MenuItem::predefined(ctx, PredefinedItem::Fullscreen).unwrap())
+        .with_item(MenuItem::separator(ctx).unwrap())
         .with_item(close_all_item)
         .build(ctx);
 
     // Notifications
     let new_notification_item = MenuItem::builder()
         .with_title("New Notification")
-        .with_macos_short_code("a")
+        .with_accelerator("CmdOrCtrl+A".parse().unwrap())
         .with_action(Action::new_event("new-notification"))
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     let notifications_menu = Menu::builder().with_item(new_notification_item).build(ctx);
 
     // Main menu
-    let app_item = MenuItem::builder().with_submenu(app_menu).build(ctx);
+    let app_item = MenuItem::builder()
+        .with_submenu(app_menu)
+        .build(ctx)
+        .unwrap();
 
     let window_item = MenuItem::builder()
         .with_title("Window")
         .with_submenu(window_menu)
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     let notifications_item = MenuItem::builder()
         .with_title("Notifications")
         .with_submenu(notifications_menu)
-        .build(ctx);
+        .build(ctx)
+        .unwrap();
 
     Menu::builder()
         .with_item(app_item)
@@ -110,7 +137,8 @@ impl State {
         let window = Window::builder()
             .with_title("Window 1")
             .with_physical_size((1920, 1280))
-            .build(ctx);
+            .build(ctx)
+            .unwrap();
         let mut windows = HashMap::new();
         windows.insert(window.id(), window);
 
@@ -127,7 +155,8 @@ impl State {
         let mut window = Window::builder()
             .with_title(format!("Window {}", self.window_counter))
             .with_physical_size((1920, 1280))
-            .build(app);
+            .build(app)
+            .unwrap();
         window.show(app);
         self.windows.insert(window.id(), window);
     }
@@ -143,7 +172,8 @@ impl State {
         self.modal_counter += 1;
         let mut window = Window::builder()
             .with_title(format!("Modal Window {}", self.modal_counter))
-            .build(app);
+            .build(app)
+            .unwrap();
         window.show_modal(app);
         self.windows.insert(window.id(), window);
     }
@@ -165,12 +195,13 @@ impl State {
 impl EventHandler for State {
     fn on_event(&mut self, app: &mut ActiveApplication, event: Event) {
         match event {
-            Event::Menu(action) => match action.as_ref() {
+            Event::Menu {
+                action, ..
+            } => match action.as_ref() {
                 "new-window" => self.new_window(app),
                 "new-modal-window" => self.new_modal_window(app),
                 "new-notification" => self.new_notification(app),
                 "close-all" => self.close_all(),
-                "quit" => app.stop(),
                 _ => (),
             },
             Event::LifeCycle(LifeCycle::Started) => {
@@ -0,0 +1,255 @@
+//! This module contains keyboard accelerators of menu items.
+
+use std::{fmt, str::FromStr};
+
+use crate::{Error, ErrorKind, Key, Modifiers, NamedKey};
+
+/// Names of keys that don't produce a character, the first one is used to
+/// format an accelerator.
+const NAMED_KEYS: &[(&str, NamedKey)] = &[
+    ("Enter", NamedKey::Enter),
+    ("Return", NamedKey::Enter),
+    ("Tab", NamedKey::Tab),
+    ("Space", NamedKey::Space),
+    ("Down", NamedKey::ArrowDown),
+    ("Left", NamedKey::ArrowLeft),
+    ("Right", NamedKey::ArrowRight),
+    ("Up", NamedKey::ArrowUp),
+    ("End", NamedKey::End),
+    ("Home", NamedKey::Home),
+    ("PageDown", NamedKey::PageDown),
+    ("PageUp", NamedKey::PageUp),
+    ("Backspace", NamedKey::Backspace),
+    ("Delete", NamedKey::Delete),
+    ("Insert", NamedKey::Insert),
+    ("Escape", NamedKey::Escape),
+    ("Esc", NamedKey::Escape),
+    ("Pause", NamedKey::Pause),
+    ("PrintScreen", NamedKey::PrintScreen),
+];
+
+/// Names of modifiers accepted by the parser.
+const MODIFIERS: &[(&str, Modifiers)] = &[
+    ("Ctrl", Modifiers::CONTROL),
+    ("Control", Modifiers::CONTROL),
+    ("Alt", Modifiers::ALT),
+    ("Option", Modifiers::ALT),
+    ("Shift", Modifiers::SHIFT),
+    ("Super", Modifiers::SUPER),
+    ("Cmd", Modifiers::SUPER),
+    ("Command", Modifiers::SUPER),
+    ("CmdOrCtrl", Modifiers::CMD_OR_CTRL),
+    ("CommandOrControl", Modifiers::CMD_OR_CTRL),
+];
+
+/// Names of modifiers used to format an accelerator, in the order of
+/// formatting.
+#[cfg(target_os = "macos")]
+const MODIFIER_NAMES: &[(&str, Modifiers)] = &[
+    ("Ctrl", Modifiers::CONTROL),
+    ("Option", Modifiers::ALT),
+    ("Shift", Modifiers::SHIFT),
+    ("Cmd", Modifiers::SUPER),
+];
+
+/// Names of modifiers used to format an accelerator, in the order of
+/// formatting.
+#[cfg(not(target_os = "macos"))]
+const MODIFIER_NAMES: &[(&str, Modifiers)] = &[
+    ("Ctrl", Modifiers::CONTROL),
+    ("Alt", Modifiers::ALT),
+    ("Shift", Modifiers::SHIFT),
+    ("Super", Modifiers::SUPER),
+];
+
+/// The last function key.
+const MAX_FUNCTION_KEY: u8 = 24;
+
+/// Keyboard shortcut of a menu item.
+///
+/// An accelerator is a key pressed with a set of modifiers. It can be parsed
+/// from a string of `+` separated modifiers followed by a key, e.g.
+/// `"CmdOrCtrl+Shift+P"`:
+/// * Modifiers are `Ctrl` (`Control`), `Alt` (`Option`), `Shift`, `Super`
+///   (`Cmd`, `Command`) and `CmdOrCtrl` (`CommandOrControl`), which is
+///   Command on macOS and Control on other platforms.
+/// * Keys are single characters, `Plus` for `+`, `F1` to `F24` and `Enter`,
+///   `Tab`, `Space`, `Up`, `Down`, `Left`, `Right`, `Home`, `End`, `PageUp`,
+///   `PageDown`, `Backspace`, `Delete`, `Insert`, `Escape`, `Pause`,
+///   `PrintScreen`.
+///
+/// Names are case-insensitive, letters are matched regardless of their case.
+/// A modifier can't be repeated.
+///
+/// Accelerators are formatted the same way with platform names of modifiers,
+/// e.g. `Ctrl+Shift+P`, or `Shift+Cmd+P` on macOS.
+///
+/// # Examples:
+///
+/// ```rust
+/// use b3_core::{Accelerator, Key, Modifiers};
+///
+/// let accelerator: Accelerator = "CmdOrCtrl+Shift+P".parse().unwrap();
+/// assert_eq!(accelerator.modifiers(), Modifiers::CMD_OR_CTRL | Modifiers::SHIFT);
+/// assert_eq!(accelerator.key(), &Key::Character("p".into()));
+///
+/// assert!("Ctrl+Hyper+P".parse::<Accelerator>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    modifiers: Modifiers,
+    key:       Key,
+}
+
+impl Accelerator {
+    /// Creates a new accelerator.
+    ///
+    /// Fails if the key is a modifier or it isn't a single character, or if
+    /// Caps Lock is one of the modifiers.
+    ///
+    /// # Parameters:
+    /// * `modifiers` - Modifiers held down with the key.
+    /// * `key` - Key.
+    pub fn new(modifiers: Modifiers, key: Key) -> Result<Self, Error> {
+        if modifiers.contains(Modifiers::CAPS_LOCK) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Caps Lock can't be an accelerator modifier.",
+            ));
+        }
+        let key = match key {
+            Key::Character(character) if character.chars().count() == 1 => {
+                Key::Character(character.to_lowercase())
+            }
+            Key::Named(NamedKey::F(number)) if (1..=MAX_FUNCTION_KEY).contains(&number) => key,
+            Key::Named(named) if NAMED_KEYS.iter().any(|(_, other)| *other == named) => key,
+            key => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{:?} can't be an accelerator key.", key),
+                ));
+            }
+        };
+        Ok(Self {
+            modifiers,
+            key,
+        })
+    }
+
+    /// Returns modifiers held down with the key.
+    #[inline]
+    pub fn modifiers(&self) -> Modifiers { self.modifiers }
+
+    /// Returns a key of the accelerator, characters are lowercase.
+    #[inline]
+    pub fn key(&self) -> &Key { &self.key }
+
+    /// Checks if a key press triggers the accelerator.
+    ///
+    /// Caps Lock is ignored. Shift is also ignored for characters without
+    /// case, like `+` or digits, unless the accelerator has it: keyboard
+    /// layouts may need Shift to type them, and the pressed key is already
+    /// the typed character.
+    ///
+    /// # Parameters:
+    /// * `key` - Pressed key.
+    /// * `modifiers` - Modifiers at the time of the key press.
+    pub fn matches(&self, key: &Key, mut modifiers: Modifiers) -> bool {
+        modifiers.set(Modifiers::CAPS_LOCK, false);
+        let is_same_key = match (&self.key, key) {
+            (Key::Character(character), Key::Character(other)) => {
+                if *character == character.to_uppercase()
+                    && !self.modifiers.contains(Modifiers::SHIFT)
+                {
+                    modifiers.set(Modifiers::SHIFT, false);
+                }
+                *character == other.to_lowercase()
+            }
+            (key, other) => key == other,
+        };
+        is_same_key && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for Accelerator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split('+').map(str::trim).collect::<Vec<_>>();
+        let key = match tokens.pop() {
+            Some(key) if !key.is_empty() => key,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("accelerator has no key: \"{}\".", s),
+                ));
+            }
+        };
+
+        let mut modifiers = Modifiers::empty();
+        for token in tokens.into_iter() {
+            let modifier = MODIFIERS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(token))
+                .map(|(_, modifier)| *modifier)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("unknown accelerator modifier: \"{}\".", token),
+                    )
+                })?;
+            if modifiers.contains(modifier) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("duplicate accelerator modifier: \"{}\".", token),
+                ));
+            }
+            modifiers |= modifier;
+        }
+
+        let named_key = NAMED_KEYS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, named_key)| *named_key);
+        let function_key = key
+            .strip_prefix(['F', 'f'])
+            .and_then(|number| number.parse::<u8>().ok())
+            .filter(|number| (1..=MAX_FUNCTION_KEY).contains(number))
+            .map(NamedKey::F);
+        let key = match (named_key.or(function_key), key.chars().count()) {
+            (Some(named_key), _) => Key::Named(named_key),
+            (None, _) if key.eq_ignore_ascii_case("Plus") => Key::Character("+".into()),
+            (None, 1) => Key::Character(key.into()),
+            (None, _) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("unknown accelerator key: \"{}\".", key),
+                ));
+            }
+        };
+        Self::new(modifiers, key)
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, modifier) in MODIFIER_NAMES.iter() {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match &self.key {
+            Key::Character(character) if character == "+" => f.write_str("Plus"),
+            Key::Character(character) => f.write_str(&character.to_uppercase()),
+            Key::Named(NamedKey::F(number)) => write!(f, "F{}", number),
+            Key::Named(named_key) => {
+                let (name, _) = NAMED_KEYS
+                    .iter()
+                    .find(|(_, other)| other == named_key)
+                    .expect("accelerator key has a name");
+                f.write_str(name)
+            }
+            Key::Unidentified => f.write_str("Unidentified"),
+        }
+    }
+}
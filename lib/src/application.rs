@@ -1,11 +1,24 @@
 //! This module contains a platform independent Application implementation.
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
 use crate::{
-    platform::{ActiveApplicationApi, ApplicationApi, Wrapper},
-    platform_impl::{ActiveApplicationImpl, ApplicationImpl, ContextImpl},
+    platform::{ActiveApplicationApi, ApplicationApi, EventLoopProxyApi, Wrapper},
+    platform_impl::{ActiveApplicationImpl, ApplicationImpl, ContextImpl, EventLoopProxyImpl},
+    Clipboard,
+    ClipboardKind,
     Error,
+    Event,
     EventHandler,
     Icon,
+    LifeCycle,
     Menu,
+    Monitor,
+    StartCause,
+    UserEvent,
 };
 
 /// Native context.
@@ -16,6 +29,11 @@ impl Context {
     pub(crate) fn new(ctx: ContextImpl) -> Self { Self(ctx) }
 }
 
+impl ContextOwner for Context {
+    #[inline]
+    fn context(&self) -> &Context { self }
+}
+
 impl Wrapper<ContextImpl> for Context {
     #[inline]
     fn get_impl(&self) -> &ContextImpl { &self.0 }
@@ -30,6 +48,54 @@ pub trait ContextOwner {
     fn context(&self) -> &Context;
 }
 
+/// Defines how the event loop waits for new events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlFlow {
+    /// The event loop sleeps until new events arrive.
+    #[default]
+    Wait,
+    /// The event loop starts a new iteration right after the previous one,
+    /// e.g. for continuous rendering.
+    Poll,
+    /// The event loop sleeps until new events arrive or the time is reached.
+    WaitUntil(Instant),
+}
+
+impl ControlFlow {
+    /// Returns the maximum wait time, `None` means an unlimited wait.
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        match self {
+            Self::Wait => None,
+            Self::Poll => Some(Duration::ZERO),
+            Self::WaitUntil(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+        }
+    }
+
+    /// Returns the reason why the event loop has woken up.
+    ///
+    /// # Parameters:
+    /// * `start` - Time when the wait has started.
+    pub(crate) fn start_cause(&self, start: Instant) -> StartCause {
+        match *self {
+            Self::Wait => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+            Self::Poll => StartCause::Poll,
+            Self::WaitUntil(requested_resume) if Instant::now() >= requested_resume => {
+                StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume,
+                }
+            }
+            Self::WaitUntil(requested_resume) => StartCause::WaitCancelled {
+                start,
+                requested_resume: Some(requested_resume),
+            },
+        }
+    }
+}
+
 /// This structure represents a platform independent running application.
 #[derive(Debug)]
 pub struct ActiveApplication(ActiveApplicationImpl);
@@ -45,10 +111,50 @@ impl ActiveApplication {
 
     /// Sets an application menu.
     ///
+    /// The menu keeps following changes made after it has been set:
+    /// * On macOS the menu is installed as is, so later changes of its items
+    ///   apply immediately.
+    /// * X11 and Wayland have no global menu, only accelerators of its items
+    ///   are triggered by key presses in windows. They are looked up in the
+    ///   menu on every key press, so added, removed, disabled items and
+    ///   changed accelerators apply immediately.
+    /// * The headless backend triggers accelerators like X11 and Wayland do,
+    ///   but `HeadlessExt::menu` returns a snapshot taken when the menu was
+    ///   set. Set the menu again to update it.
+    ///
     /// # Parameters:
     /// * `menu` - Application menu.
     pub fn set_menu(&mut self, menu: Option<&Menu>) { self.0.set_menu(menu); }
 
+    /// Sets how the event loop waits for new events.
+    ///
+    /// # Parameters:
+    /// * `control_flow` - Control flow.
+    pub fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        self.0.set_control_flow(control_flow);
+    }
+
+    /// Returns how the event loop waits for new events.
+    pub fn control_flow(&self) -> ControlFlow { self.0.control_flow() }
+
+    /// Returns all connected monitors.
+    pub fn monitors(&self) -> Vec<Monitor> { self.0.monitors() }
+
+    /// Returns a primary monitor of the system.
+    ///
+    /// Wayland has no notion of a primary monitor, the first one is returned
+    /// instead.
+    pub fn primary_monitor(&self) -> Option<Monitor> { self.0.primary_monitor() }
+
+    /// Returns the system clipboard.
+    pub fn clipboard(&self) -> Clipboard { self.0.clipboard(ClipboardKind::Clipboard) }
+
+    /// Returns the primary selection.
+    ///
+    /// Only X11 has the primary selection, accessing it fails with
+    /// [ErrorKind::NotSupported](crate::ErrorKind::NotSupported) elsewhere.
+    pub fn primary_selection(&self) -> Clipboard { self.0.clipboard(ClipboardKind::Primary) }
+
     /// Stops a running applicaiton.
     pub fn stop(&mut self) { self.0.stop(); }
 }
@@ -67,12 +173,56 @@ impl ContextOwner for ActiveApplication {
 
 unsafe impl Sync for ActiveApplication {}
 
+/// Defines how the application appears in the system UI.
+///
+/// The policy is applied on macOS only.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationPolicy {
+    /// An ordinary application that appears in the Dock and may have a menu
+    /// bar.
+    #[default]
+    Regular,
+    /// An application that doesn't appear in the Dock but may have windows.
+    Accessory,
+    /// An application that doesn't appear in the Dock and may not create
+    /// windows or be activated.
+    Prohibited,
+}
+
+/// Options passed to a backend when an application is created.
+#[derive(Debug, Clone)]
+pub(crate) struct ApplicationOptions {
+    pub(crate) app_id:            Option<String>,
+    pub(crate) activation_policy: ActivationPolicy,
+    pub(crate) activate_on_start: bool,
+}
+
+impl Default for ApplicationOptions {
+    fn default() -> Self {
+        Self {
+            app_id:            None,
+            activation_policy: ActivationPolicy::default(),
+            activate_on_start: true,
+        }
+    }
+}
+
+type MenuFactory = Box<dyn FnOnce(&Context) -> Result<Menu, Error>>;
+type IconFactory = Box<dyn FnOnce(&Context) -> Result<Icon, Error>>;
+
 /// The main entity that provides entrypoints to the event loop and other API.
 ///
 /// Any program that uses the **b3-core** crate must create an instance of
 /// this structure before using any other crate's entities.
+///
+/// `T` is a type of user events sent with an [EventLoopProxy].
 #[derive(Debug)]
-pub struct Application(ApplicationImpl);
+pub struct Application<T: 'static = ()> {
+    inner:   ApplicationImpl,
+    menu:    Option<Menu>,
+    icon:    Option<Icon>,
+    _marker: PhantomData<T>,
+}
 
 impl Application {
     /// Creates a new [Application] instance.
@@ -84,23 +234,270 @@ impl Application {
     ///
     /// let app = Application::new().unwrap();
     /// ```
-    pub fn new() -> Result<Self, Error> { Ok(Self(ApplicationImpl::new()?)) }
+    pub fn new() -> Result<Self, Error> { Self::builder().build() }
+
+    /// Returns a new builder instance.
+    pub fn builder() -> ApplicationBuilder { ApplicationBuilder::new() }
+}
+
+impl<T: Send + 'static> Application<T> {
+    /// Creates a new [Application] instance that handles user events of type
+    /// `T`.
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use b3_core::Application;
+    ///
+    /// let app = Application::<String>::with_user_event().unwrap();
+    /// ```
+    pub fn with_user_event() -> Result<Self, Error> { ApplicationBuilder::new().build() }
+
+    /// Creates a proxy that sends user events to the event loop.
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            proxy:   self.inner.create_proxy(),
+            _marker: PhantomData,
+        }
+    }
 
     /// Runs an application (event loop).
     ///
+    /// The default menu and icon are set before [LifeCycle::Started] is
+    /// delivered.
+    ///
     /// # Parameters:
     /// * `handler` - Event handler.
-    pub fn run(mut self, handler: impl EventHandler + 'static) { self.0.run(handler); }
+    ///
+    /// [LifeCycle::Started]: crate::LifeCycle::Started
+    pub fn run(mut self, mut handler: impl EventHandler<T> + 'static) {
+        let menu = self.menu.take();
+        let icon = self.icon.take();
+        self.inner.run(
+            move |app: &mut ActiveApplication, event: Event<UserEvent>| {
+                if let Event::LifeCycle(LifeCycle::Started) = event {
+                    if menu.is_some() {
+                        app.set_menu(menu.as_ref());
+                    }
+                    if icon.is_some() {
+                        app.set_icon(icon.as_ref());
+                    }
+                }
+                // Proxies only send events of type `T`, so a failed downcast
+                // is possible only for events queued by tests.
+                let event =
+                    event.try_map_user(|event| event.downcast::<T>().ok().map(|event| *event));
+                if let Some(event) = event {
+                    handler.on_event(app, event);
+                }
+            },
+        );
+    }
 }
 
-impl Wrapper<ApplicationImpl> for Application {
+impl<T> Wrapper<ApplicationImpl> for Application<T> {
     #[inline]
-    fn get_impl(&self) -> &ApplicationImpl { &self.0 }
+    fn get_impl(&self) -> &ApplicationImpl { &self.inner }
 
     #[inline]
-    fn get_impl_mut(&mut self) -> &mut ApplicationImpl { &mut self.0 }
+    fn get_impl_mut(&mut self) -> &mut ApplicationImpl { &mut self.inner }
 }
 
-impl ContextOwner for Application {
-    fn context(&self) -> &Context { self.0.context() }
+impl<T> ContextOwner for Application<T> {
+    fn context(&self) -> &Context { self.inner.context() }
+}
+
+/// Application builder.
+///
+/// # Examples:
+///
+/// ```rust
+/// use b3_core::{ActivationPolicy, Application, Menu, MenuItem};
+///
+/// let app = Application::builder()
+///     .with_app_id("org.example.Hello")
+///     .with_activation_policy(ActivationPolicy::Accessory)
+///     .with_menu(|ctx| {
+///         let quit_item = MenuItem::builder().with_title("Quit").build(ctx)?;
+///         Ok(Menu::builder().with_item(quit_item).build(ctx))
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ApplicationBuilder<T: 'static = ()> {
+    options: ApplicationOptions,
+    menu:    Option<MenuFactory>,
+    icon:    Option<IconFactory>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T: Send + 'static> ApplicationBuilder<T> {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            options: ApplicationOptions::default(),
+            menu:    None,
+            icon:    None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets a type of user events handled by the application under building.
+    pub fn with_user_event<U: Send + 'static>(self) -> ApplicationBuilder<U> {
+        ApplicationBuilder {
+            options: self.options,
+            menu:    self.menu,
+            icon:    self.icon,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets an application identifier.
+    ///
+    /// The identifier is used as `WM_CLASS` of windows on X11 and as their
+    /// `app_id` on Wayland, so the desktop environment can match them with the
+    /// application's desktop entry. macOS takes the identifier from the
+    /// application bundle.
+    ///
+    /// # Parameters:
+    /// * `app_id` - Application identifier, e.g. `org.example.App`.
+    pub fn with_app_id<S>(mut self, app_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.options.app_id = Some(app_id.into());
+        self
+    }
+
+    /// Sets an activation policy of the application under building.
+    ///
+    /// # Parameters:
+    /// * `policy` - Activation policy.
+    pub fn with_activation_policy(mut self, policy: ActivationPolicy) -> Self {
+        self.options.activation_policy = policy;
+        self
+    }
+
+    /// Sets whether the application is brought to the front on start, even if
+    /// another application is active.
+    ///
+    /// It is turned on by default and takes effect on macOS only.
+    ///
+    /// # Parameters:
+    /// * `activate` - Activation flag.
+    pub fn with_activate_on_start(mut self, activate: bool) -> Self {
+        self.options.activate_on_start = activate;
+        self
+    }
+
+    /// Sets a default menu of the application under building.
+    ///
+    /// Menus need an application context, so the menu is created by `factory`
+    /// once the application is built.
+    ///
+    /// # Parameters:
+    /// * `factory` - Creates the menu.
+    pub fn with_menu<F>(mut self, factory: F) -> Self
+    where
+        F: FnOnce(&Context) -> Result<Menu, Error> + 'static,
+    {
+        self.menu = Some(Box::new(factory));
+        self
+    }
+
+    /// Sets a default icon of the application under building.
+    ///
+    /// Icons need an application context, so the icon is created by `factory`
+    /// once the application is built.
+    ///
+    /// # Parameters:
+    /// * `factory` - Creates the icon.
+    pub fn with_icon<F>(mut self, factory: F) -> Self
+    where
+        F: FnOnce(&Context) -> Result<Icon, Error> + 'static,
+    {
+        self.icon = Some(Box::new(factory));
+        self
+    }
+
+    /// Builds a new application with specified options.
+    ///
+    /// Fails if the backend can't be initialized or the default menu or icon
+    /// can't be created.
+    pub fn build(self) -> Result<Application<T>, Error> {
+        let inner = ApplicationImpl::new(&self.options)?;
+        let menu = match self.menu {
+            Some(factory) => Some(factory(inner.context())?),
+            None => None,
+        };
+        let icon = match self.icon {
+            Some(factory) => Some(factory(inner.context())?),
+            None => None,
+        };
+
+        Ok(Application {
+            inner,
+            menu,
+            icon,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T> Debug for ApplicationBuilder<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApplicationBuilder")
+            .field("options", &self.options)
+            .field("menu", &self.menu.is_some())
+            .field("icon", &self.icon.is_some())
+            .finish()
+    }
+}
+
+/// A handle that wakes the event loop up and sends user events to it.
+///
+/// The proxy can be cloned and sent to other threads, the events are
+/// delivered as [Event::User].
+///
+/// # Examples:
+///
+/// ```rust
+/// use b3_core::Application;
+///
+/// let app = Application::<u32>::with_user_event().unwrap();
+/// let proxy = app.create_proxy();
+/// std::thread::spawn(move || {
+///     proxy.send_event(42).unwrap();
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub struct EventLoopProxy<T: 'static> {
+    proxy:   EventLoopProxyImpl,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T: Send + 'static> EventLoopProxy<T> {
+    /// Sends a user event to the event loop.
+    ///
+    /// # Parameters:
+    /// * `event` - User event.
+    pub fn send_event(&self, event: T) -> Result<(), Error> {
+        self.proxy.send_event(Box::new(event))
+    }
+}
+
+impl<T> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            proxy:   self.proxy.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for EventLoopProxy<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventLoopProxy").finish_non_exhaustive()
+    }
 }
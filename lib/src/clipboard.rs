@@ -0,0 +1,148 @@
+//! This module contains clipboard definitions.
+
+use crate::{
+    platform::{ClipboardApi, Wrapper},
+    platform_impl::ClipboardImpl,
+    Error,
+    ErrorKind,
+};
+
+/// Selections the application can read and write.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardKind {
+    /// The system clipboard used by copy and paste commands.
+    #[default]
+    Clipboard,
+    /// The primary selection holding the last selected text, pasted with the
+    /// middle mouse button.
+    ///
+    /// It is available on X11 only.
+    Primary,
+}
+
+/// Clipboard image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardImage {
+    rgba:   Vec<u8>,
+    width:  u32,
+    height: u32,
+}
+
+impl ClipboardImage {
+    /// Creates a new image from RGBA pixels.
+    ///
+    /// # Parameters:
+    /// * `rgba` - Pixels in RGBA order, 4 bytes per pixel, row by row.
+    /// * `width` - Image width.
+    /// * `height` - Image height.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "clipboard image must not be empty.",
+            ));
+        }
+        if rgba.len() as u64 != width as u64 * height as u64 * 4 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "clipboard image size does not match its dimensions.",
+            ));
+        }
+
+        Ok(Self {
+            rgba,
+            width,
+            height,
+        })
+    }
+
+    /// Returns RGBA pixels of the image.
+    #[inline]
+    pub fn rgba(&self) -> &[u8] { &self.rgba }
+
+    /// Returns a width of the image.
+    #[inline]
+    pub fn width(&self) -> u32 { self.width }
+
+    /// Returns a height of the image.
+    #[inline]
+    pub fn height(&self) -> u32 { self.height }
+
+    /// Consumes the image and returns its RGBA pixels.
+    #[inline]
+    pub fn into_rgba(self) -> Vec<u8> { self.rgba }
+}
+
+/// System clipboard or selection.
+///
+/// Data written to the clipboard is served by the application while it runs,
+/// so it is lost when the application quits unless a clipboard manager keeps
+/// it. Writing replaces the whole clipboard content.
+///
+/// Reading fails with [ErrorKind::NotFound] if the clipboard is empty or has
+/// no data of the requested type. Images are exchanged as PNG and aren't
+/// supported on macOS.
+#[derive(Debug, Clone)]
+pub struct Clipboard(ClipboardImpl);
+
+impl Clipboard {
+    pub(crate) fn new(clipboard_impl: ClipboardImpl) -> Self { Self(clipboard_impl) }
+
+    /// Returns which selection the clipboard gives access to.
+    #[inline]
+    pub fn kind(&self) -> ClipboardKind { self.0.kind() }
+
+    /// Puts text to the clipboard.
+    ///
+    /// # Parameters:
+    /// * `text` - Text.
+    pub fn set_text<S>(&mut self, text: S) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.0.set_text(text.as_ref())
+    }
+
+    /// Returns text from the clipboard.
+    pub fn text(&self) -> Result<String, Error> { self.0.text() }
+
+    /// Puts an image to the clipboard.
+    ///
+    /// # Parameters:
+    /// * `image` - Image.
+    pub fn set_image(&mut self, image: &ClipboardImage) -> Result<(), Error> {
+        self.0.set_image(image)
+    }
+
+    /// Returns an image from the clipboard.
+    pub fn image(&self) -> Result<ClipboardImage, Error> { self.0.image() }
+
+    /// Puts data of a custom type to the clipboard.
+    ///
+    /// # Parameters:
+    /// * `mime_type` - MIME type of the data, e.g. `application/json`.
+    /// * `data` - Data.
+    pub fn set_data(&mut self, mime_type: &str, data: &[u8]) -> Result<(), Error> {
+        self.0.set_data(mime_type, data)
+    }
+
+    /// Returns data of a custom type from the clipboard.
+    ///
+    /// # Parameters:
+    /// * `mime_type` - MIME type of the data.
+    pub fn data(&self, mime_type: &str) -> Result<Vec<u8>, Error> { self.0.data(mime_type) }
+
+    /// Returns types of data the clipboard holds.
+    pub fn mime_types(&self) -> Result<Vec<String>, Error> { self.0.mime_types() }
+
+    /// Removes the clipboard content if the application owns it.
+    pub fn clear(&mut self) { self.0.clear(); }
+}
+
+impl Wrapper<ClipboardImpl> for Clipboard {
+    #[inline]
+    fn get_impl(&self) -> &ClipboardImpl { &self.0 }
+
+    #[inline]
+    fn get_impl_mut(&mut self) -> &mut ClipboardImpl { &mut self.0 }
+}
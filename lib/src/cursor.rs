@@ -0,0 +1,139 @@
+//! This module contains mouse cursor definitions.
+
+use std::sync::Arc;
+
+pub use cursor_icon::CursorIcon;
+
+use crate::{Error, ErrorKind};
+
+/// Cursor grab modes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorGrabMode {
+    /// The cursor moves freely.
+    #[default]
+    None,
+    /// The cursor is confined to the window content area.
+    Confined,
+    /// The cursor is locked in place.
+    ///
+    /// Only [MouseEvent::RawMotion](crate::MouseEvent::RawMotion) events are
+    /// delivered while the cursor is locked.
+    Locked,
+}
+
+#[derive(Debug)]
+struct CursorImage {
+    rgba:      Vec<u8>,
+    width:     u16,
+    height:    u16,
+    hotspot_x: u16,
+    hotspot_y: u16,
+}
+
+/// Custom cursor image.
+///
+/// The image is shared, so cloning is cheap.
+#[derive(Debug, Clone)]
+pub struct CustomCursor(Arc<CursorImage>);
+
+impl CustomCursor {
+    /// Creates a new cursor from RGBA pixels.
+    ///
+    /// # Parameters:
+    /// * `rgba` - Pixels in RGBA order, 4 bytes per pixel, row by row.
+    /// * `width` - Image width.
+    /// * `height` - Image height.
+    /// * `hotspot_x` - Horizontal position of the cursor hotspot.
+    /// * `hotspot_y` - Vertical position of the cursor hotspot.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u16,
+        height: u16,
+        hotspot_x: u16,
+        hotspot_y: u16,
+    ) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cursor image must not be empty.",
+            ));
+        }
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cursor image size does not match its dimensions.",
+            ));
+        }
+        if hotspot_x >= width || hotspot_y >= height {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cursor hotspot is out of the image.",
+            ));
+        }
+
+        Ok(Self(Arc::new(CursorImage {
+            rgba,
+            width,
+            height,
+            hotspot_x,
+            hotspot_y,
+        })))
+    }
+
+    /// Returns RGBA pixels of the cursor image.
+    #[inline]
+    pub fn rgba(&self) -> &[u8] { &self.0.rgba }
+
+    /// Returns a width of the cursor image.
+    #[inline]
+    pub fn width(&self) -> u16 { self.0.width }
+
+    /// Returns a height of the cursor image.
+    #[inline]
+    pub fn height(&self) -> u16 { self.0.height }
+
+    /// Returns a position of the cursor hotspot.
+    #[inline]
+    pub fn hotspot(&self) -> (u16, u16) { (self.0.hotspot_x, self.0.hotspot_y) }
+
+    /// Returns pixels packed as premultiplied `0xAARRGGBB` words.
+    #[cfg(all(target_os = "linux", not(feature = "headless")))]
+    pub(crate) fn premultiplied_argb(&self) -> Vec<u32> {
+        self.0
+            .rgba
+            .chunks_exact(4)
+            .map(|p| {
+                let a = p[3] as u32;
+                let premultiply = |c: u8| (c as u32 * a + 127) / 255;
+                (a << 24) | (premultiply(p[0]) << 16) | (premultiply(p[1]) << 8) | premultiply(p[2])
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.0, &other.0) }
+}
+
+impl Eq for CustomCursor {}
+
+/// Mouse cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    /// A cursor from the standard set.
+    Icon(CursorIcon),
+    /// A custom cursor image.
+    Custom(CustomCursor),
+}
+
+impl Default for Cursor {
+    fn default() -> Self { Self::Icon(CursorIcon::Default) }
+}
+
+impl From<CursorIcon> for Cursor {
+    fn from(icon: CursorIcon) -> Self { Self::Icon(icon) }
+}
+
+impl From<CustomCursor> for Cursor {
+    fn from(cursor: CustomCursor) -> Self { Self::Custom(cursor) }
+}
@@ -1,20 +1,94 @@
-use std::fmt;
+use std::{error, fmt, sync::Arc};
+
+/// A category of an [Error].
+///
+/// The list may grow, so matching has to handle unknown kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The call must be made on the main thread.
+    NotMainThread,
+    /// Connection to the windowing system failed or has been lost.
+    Connection,
+    /// The operation is not supported by the platform.
+    NotSupported,
+    /// A requested resource has not been found.
+    NotFound,
+    /// An image could not be decoded.
+    ImageDecode,
+    /// An argument is malformed.
+    InvalidInput,
+    /// The event loop has been closed.
+    EventLoopClosed,
+    /// A platform call failed.
+    Os,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Self::NotMainThread => "not on the main thread",
+            Self::Connection => "connection failed",
+            Self::NotSupported => "not supported",
+            Self::NotFound => "not found",
+            Self::ImageDecode => "image decode failed",
+            Self::InvalidInput => "invalid input",
+            Self::EventLoopClosed => "event loop closed",
+            Self::Os => "platform error",
+        };
+        f.write_str(description)
+    }
+}
 
 /// This structure represents an internal error.
 ///
 /// You can intercept this error and override the behavior in case of a failure.
+/// The [ErrorKind] tells what has failed, the underlying cause (if any) is
+/// available through [std::error::Error::source].
 #[derive(Debug, Clone)]
-pub struct Error(String);
+pub struct Error {
+    kind:    ErrorKind,
+    message: String,
+    source:  Option<Arc<dyn error::Error + Send + Sync>>,
+}
 
 impl Error {
-    pub(crate) fn new<S>(msg: S) -> Self
+    pub(crate) fn new<S>(kind: ErrorKind, msg: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            kind,
+            message: msg.into(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn with_source<S, E>(kind: ErrorKind, msg: S, source: E) -> Self
     where
         S: Into<String>,
+        E: error::Error + Send + Sync + 'static,
     {
-        Self(msg.into())
+        Self {
+            kind,
+            message: msg.into(),
+            source: Some(Arc::new(source)),
+        }
     }
+
+    /// Returns a category of the error.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind { self.kind }
 }
 
 impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self.0) }
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(&self.message) }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
 }
@@ -1,8 +1,15 @@
 //! This module contains all event that can be captured.
 
+use std::{
+    any::Any,
+    ops::{BitOr, BitOrAssign},
+    path::PathBuf,
+    time::Instant,
+};
+
 use dpi::{PhysicalPosition, PhysicalSize};
 
-use crate::{ActiveApplication, WindowId};
+use crate::{ActiveApplication, CheckState, WindowId};
 
 /// Life cycle events.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -13,6 +20,32 @@ pub enum LifeCycle {
     Finished,
 }
 
+/// The reason why the event loop has woken up (see
+/// [ControlFlow](crate::ControlFlow)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StartCause {
+    /// The event loop has been started.
+    Init,
+    /// The event loop has been woken up by new events while waiting.
+    WaitCancelled {
+        /// Time when the wait has started.
+        start:            Instant,
+        /// Time when the wait was going to finish.
+        requested_resume: Option<Instant>,
+    },
+    /// The wait requested by [ControlFlow::WaitUntil](crate::ControlFlow::WaitUntil)
+    /// has finished.
+    ResumeTimeReached {
+        /// Time when the wait has started.
+        start:            Instant,
+        /// Time when the wait was requested to finish.
+        requested_resume: Instant,
+    },
+    /// The event loop has been woken up after a poll
+    /// (see [ControlFlow::Poll](crate::ControlFlow::Poll)).
+    Poll,
+}
+
 /// Mouse buttons.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MouseButton {
@@ -86,12 +119,331 @@ pub enum MouseEvent {
         /// New cursor position.
         position: PhysicalPosition<f64>,
     },
+    /// Raw mouse motion.
+    ///
+    /// Unlike [MouseEvent::Moved], deltas are not accelerated and keep coming
+    /// while the cursor is locked (see
+    /// [CursorGrabMode::Locked](crate::CursorGrabMode::Locked)).
+    RawMotion {
+        /// Motion delta.
+        delta: (f64, f64),
+    },
     /// Mouse position has entered into a window view frame.
     Entered,
     /// Mouse position has left a window view frame.
     Exited,
 }
 
+/// Touch phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TouchPhase {
+    /// A finger has touched the screen.
+    Started,
+    /// A finger has moved.
+    Moved,
+    /// A finger has been lifted.
+    Ended,
+    /// The system has cancelled the touch, e.g. a gesture has been recognized
+    /// by the compositor.
+    Cancelled,
+}
+
+/// Gesture phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GesturePhase {
+    /// The gesture has been recognized.
+    Started,
+    /// The gesture is in progress.
+    Changed,
+    /// Fingers have been lifted from the trackpad.
+    Ended,
+    /// The gesture has been cancelled, e.g. another finger has touched the
+    /// trackpad.
+    Cancelled,
+}
+
+/// Trackpad gesture events.
+///
+/// Every gesture except [GestureEvent::SmartMagnify] produces a sequence of
+/// events, started with [GesturePhase::Started] and finished with
+/// [GesturePhase::Ended] or [GesturePhase::Cancelled].
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum GestureEvent {
+    /// Pinch gesture, usually used to zoom.
+    Pinch {
+        /// Magnification delta, positive values zoom in.
+        delta: f64,
+        /// Gesture phase (see [GesturePhase]).
+        phase: GesturePhase,
+    },
+    /// Rotation gesture.
+    Rotate {
+        /// Rotation delta in degrees, positive values rotate counterclockwise.
+        delta: f64,
+        /// Gesture phase (see [GesturePhase]).
+        phase: GesturePhase,
+    },
+    /// Multi-finger swipe gesture.
+    ///
+    /// On macOS a swipe is reported once it has been recognized, with
+    /// [GesturePhase::Ended] only, and deltas are swipe directions (`-1.0`,
+    /// `0.0` or `1.0`).
+    Swipe {
+        /// Number of fingers, `0` if the system doesn't report it (macOS).
+        fingers: u32,
+        /// Motion delta in pixels.
+        delta:   (f64, f64),
+        /// Gesture phase (see [GesturePhase]).
+        phase:   GesturePhase,
+    },
+    /// Double tap with two fingers, usually used to toggle zoom.
+    ///
+    /// Reported on macOS only.
+    SmartMagnify,
+}
+
+/// Tablet tool types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TabletTool {
+    /// Pen or stylus.
+    Pen,
+    /// Eraser, usually the back end of a pen.
+    Eraser,
+    /// Other tool, e.g. a tablet mouse or a lens.
+    Other,
+}
+
+/// Pen tablet events.
+///
+/// Tablet tools move the mouse cursor too, so tablet events come along with
+/// mouse events.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum TabletEvent {
+    /// A tool has come in proximity of the tablet over the window.
+    ProximityEntered {
+        /// Tool type (see [TabletTool]).
+        tool: TabletTool,
+    },
+    /// The tool has gone out of proximity of the tablet or has left the
+    /// window.
+    ProximityLeft,
+    /// The tool tip has touched the tablet.
+    Down,
+    /// The tool tip has been lifted from the tablet.
+    Up,
+    /// The tool has been moved or its axes have been changed.
+    ///
+    /// Axes that the tool doesn't report are `0.0`.
+    Motion {
+        /// Tool position relative to the window content.
+        position: PhysicalPosition<f64>,
+        /// Normalized tip pressure from `0.0` to `1.0`.
+        pressure: f64,
+        /// Normalized tilt of the X and Y axes from `-1.0` to `1.0`, `0.0`
+        /// means that the tool is perpendicular to the tablet.
+        tilt:     (f64, f64),
+        /// Rotation of the tool around its axis in degrees, from `0.0` to
+        /// `360.0`. It isn't reported on X11.
+        rotation: f64,
+    },
+}
+
+/// Keyboard key state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyState {
+    /// Key has been pressed.
+    Pressed,
+    /// Key has been released.
+    Released,
+}
+
+/// Keyboard modifiers.
+///
+/// A set of modifier keys that are held down (or locked).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// Alt key (Option on macOS).
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    /// Caps Lock is on.
+    pub const CAPS_LOCK: Modifiers = Modifiers(1 << 4);
+    /// Command key on macOS, Control key on other platforms.
+    #[cfg(target_os = "macos")]
+    pub const CMD_OR_CTRL: Modifiers = Modifiers::SUPER;
+    /// Command key on macOS, Control key on other platforms.
+    #[cfg(not(target_os = "macos"))]
+    pub const CMD_OR_CTRL: Modifiers = Modifiers::CONTROL;
+    /// Control key.
+    pub const CONTROL: Modifiers = Modifiers(1 << 1);
+    /// Shift key.
+    pub const SHIFT: Modifiers = Modifiers(1 << 0);
+    /// Super key (Command on macOS, Windows key on PC keyboards).
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+
+    /// Returns an empty set of modifiers.
+    #[inline]
+    pub const fn empty() -> Self { Self(0) }
+
+    /// Returns `true` if no modifiers are set.
+    #[inline]
+    pub const fn is_empty(&self) -> bool { self.0 == 0 }
+
+    /// Returns `true` if all modifiers of `other` are set.
+    ///
+    /// # Parameters:
+    /// * `other` - Modifiers to check.
+    #[inline]
+    pub const fn contains(&self, other: Modifiers) -> bool { self.0 & other.0 == other.0 }
+
+    /// Sets or unsets modifiers.
+    ///
+    /// # Parameters:
+    /// * `other` - Modifiers to change.
+    /// * `value` - Set modifiers if `true`, unset them otherwise.
+    #[inline]
+    pub fn set(&mut self, other: Modifiers, value: bool) {
+        if value {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output { Self(self.0 | rhs.0) }
+}
+
+impl BitOrAssign for Modifiers {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) { self.0 |= rhs.0; }
+}
+
+/// Keys that don't produce a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NamedKey {
+    /// Alt key (Option on macOS).
+    Alt,
+    /// Caps Lock key.
+    CapsLock,
+    /// Control key.
+    Control,
+    /// Num Lock key.
+    NumLock,
+    /// Scroll Lock key.
+    ScrollLock,
+    /// Shift key.
+    Shift,
+    /// Super key (Command on macOS, Windows key on PC keyboards).
+    Super,
+    /// Enter (or Return) key.
+    Enter,
+    /// Tab key.
+    Tab,
+    /// Space bar.
+    Space,
+    /// Down arrow key.
+    ArrowDown,
+    /// Left arrow key.
+    ArrowLeft,
+    /// Right arrow key.
+    ArrowRight,
+    /// Up arrow key.
+    ArrowUp,
+    /// End key.
+    End,
+    /// Home key.
+    Home,
+    /// Page Down key.
+    PageDown,
+    /// Page Up key.
+    PageUp,
+    /// Backspace key.
+    Backspace,
+    /// Delete key.
+    Delete,
+    /// Insert key.
+    Insert,
+    /// Escape key.
+    Escape,
+    /// Context menu key.
+    ContextMenu,
+    /// Pause key.
+    Pause,
+    /// Print Screen key.
+    PrintScreen,
+    /// Function key (F1 to F24).
+    F(u8),
+}
+
+/// Logical key.
+///
+/// The meaning of a key according to the current keyboard layout.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    /// A key that doesn't produce a character.
+    Named(NamedKey),
+    /// A key that produces a character.
+    Character(String),
+    /// The key is unknown.
+    Unidentified,
+}
+
+/// Keyboard events.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum KeyboardEvent {
+    /// Keyboard key input event.
+    Input {
+        /// Physical scan code of the key.
+        ///
+        /// It doesn't depend on the keyboard layout. On Linux it is an evdev
+        /// code, on macOS it is a virtual key code.
+        scan_code: u32,
+        /// Logical key (see [Key]).
+        key:       Key,
+        /// Text produced by the key press, if any.
+        text:      Option<String>,
+        /// Key state (see [KeyState]).
+        state:     KeyState,
+        /// The event is generated by holding the key down.
+        repeat:    bool,
+        /// Modifiers at the time of the event (see [Modifiers]).
+        modifiers: Modifiers,
+    },
+    /// Modifiers have been changed.
+    ModifiersChanged(Modifiers),
+}
+
+/// Input method events.
+///
+/// Input methods compose text that can't be typed directly, e.g. CJK
+/// characters. The events are delivered only to windows that allow input
+/// methods (see [crate::Window::set_ime_allowed]).
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum ImeEvent {
+    /// An input method has been enabled for the window.
+    Enabled,
+    /// Composition text has been changed.
+    ///
+    /// An empty text means that the composition has been cleared.
+    Preedit {
+        /// Composition text.
+        text:   String,
+        /// Byte range of the cursor in the composition text.
+        ///
+        /// `None` means that the cursor should be hidden.
+        cursor: Option<(usize, usize)>,
+    },
+    /// Composed text has been committed.
+    Commit(String),
+    /// An input method has been disabled for the window.
+    Disabled,
+}
+
 /// Window events.
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum WindowEvent {
@@ -105,6 +457,8 @@ pub enum WindowEvent {
     Focused(bool),
     /// The window scale factor has been changed.
     ScaleFactorChanged(f64),
+    /// The window has entered or left a fullscreen mode.
+    FullscreenChanged(bool),
     /// The window will be redrawn.
     RedrawRequested,
     /// The window has been closed.
@@ -113,38 +467,160 @@ pub enum WindowEvent {
     Destroyed,
     /// Mouse event (see [MouseEvent]).
     Mouse(MouseEvent),
+    /// Keyboard event (see [KeyboardEvent]).
+    Keyboard(KeyboardEvent),
+    /// Input method event (see [ImeEvent]).
+    Ime(ImeEvent),
+    /// Touch event.
+    ///
+    /// Every finger produces its own sequence of events, started with
+    /// [TouchPhase::Started] and finished with [TouchPhase::Ended] or
+    /// [TouchPhase::Cancelled]. Touch screens are supported on Linux only.
+    Touch {
+        /// Touch point identifier, unique while the finger touches the screen.
+        id:       u64,
+        /// Touch phase (see [TouchPhase]).
+        phase:    TouchPhase,
+        /// Touch position relative to the window content.
+        position: PhysicalPosition<f64>,
+        /// Normalized touch pressure from `0.0` to `1.0`, if the device reports
+        /// it.
+        force:    Option<f64>,
+    },
+    /// Trackpad gesture event (see [GestureEvent]).
+    Gesture(GestureEvent),
+    /// Pen tablet event (see [TabletEvent]).
+    Tablet(TabletEvent),
+    /// Files or text have been dragged over the window.
+    DragEntered {
+        /// Dragged files.
+        paths:    Vec<PathBuf>,
+        /// Dragged text, or URIs which aren't local files, one per line.
+        text:     Option<String>,
+        /// Cursor position relative to the window content.
+        position: PhysicalPosition<f64>,
+    },
+    /// Dragged data has been moved over the window.
+    DragMoved {
+        /// Cursor position relative to the window content.
+        position: PhysicalPosition<f64>,
+    },
+    /// Dragged data has been dropped on the window.
+    DragDropped {
+        /// Dropped files.
+        paths:    Vec<PathBuf>,
+        /// Dropped text, or URIs which aren't local files, one per line.
+        text:     Option<String>,
+        /// Cursor position relative to the window content.
+        position: PhysicalPosition<f64>,
+    },
+    /// Dragged data has left the window or the drag has been cancelled.
+    DragLeft,
+    /// The context menu of the window has been closed.
+    ///
+    /// It follows the [Event::Menu] of the chosen item, if any.
+    ContextMenuClosed,
+}
+
+/// Tray icon ID.
+pub type TrayId = usize;
+
+/// Kind of a tray icon activation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrayEventKind {
+    /// The tray icon has been clicked with the primary button.
+    Click,
+    /// The tray icon has been double-clicked with the primary button.
+    ///
+    /// It follows the [TrayEventKind::Click] of the second click.
+    DoubleClick,
 }
 
 /// Main event enumeration.
 ///
 /// This enumeration is an entrypoint to all captured events.
+///
+/// `T` is a type of user events sent with an
+/// [EventLoopProxy](crate::EventLoopProxy).
 #[derive(Debug, PartialEq, PartialOrd)]
-pub enum Event {
+pub enum Event<T = ()> {
     /// The event indicates that a menu item has been clicked.
-    ///
-    /// It stores an action name of the clicked menu item.
-    Menu(String),
+    Menu {
+        /// Action name of the clicked menu item.
+        action: String,
+        /// New check state of the clicked menu item or `None` if it isn't
+        /// checkable.
+        state:  Option<CheckState>,
+    },
     /// Life cycle events (see [LifeCycle]).
     LifeCycle(LifeCycle),
     /// Window events (see [WindowEvent]).
     Window(WindowEvent, WindowId),
+    /// The tray icon has been activated.
+    Tray {
+        /// Identifier of the tray icon.
+        id:   TrayId,
+        /// Kind of the activation.
+        kind: TrayEventKind,
+    },
+    /// User event sent with an [EventLoopProxy](crate::EventLoopProxy).
+    User(T),
+    /// The event loop has woken up, it's sent before other events of the
+    /// iteration.
+    NewEvents(StartCause),
+    /// The event loop is about to wait for new events, it's sent after other
+    /// events of the iteration.
+    AboutToWait,
+}
+
+/// User event with an erased type, backends aren't generic over user events.
+pub(crate) type UserEvent = Box<dyn Any + Send>;
+
+impl<T> Event<T> {
+    /// Converts a user event into another type.
+    ///
+    /// Returns `None` if the user event can't be converted.
+    pub(crate) fn try_map_user<U>(self, f: impl FnOnce(T) -> Option<U>) -> Option<Event<U>> {
+        let event = match self {
+            Self::Menu {
+                action,
+                state,
+            } => Event::Menu {
+                action,
+                state,
+            },
+            Self::LifeCycle(life_cycle) => Event::LifeCycle(life_cycle),
+            Self::Window(event, window_id) => Event::Window(event, window_id),
+            Self::Tray {
+                id,
+                kind,
+            } => Event::Tray {
+                id,
+                kind,
+            },
+            Self::User(event) => Event::User(f(event)?),
+            Self::NewEvents(cause) => Event::NewEvents(cause),
+            Self::AboutToWait => Event::AboutToWait,
+        };
+        Some(event)
+    }
 }
 
 /// Event handler.
 ///
 /// Implement this trait to capture events.
-pub trait EventHandler {
+pub trait EventHandler<T = ()> {
     /// Override this method to capture events.
     ///
     /// # Parameters:
     /// * `app` - Active application.
     /// * `event` - [Event].
-    fn on_event(&mut self, app: &mut ActiveApplication, event: Event);
+    fn on_event(&mut self, app: &mut ActiveApplication, event: Event<T>);
 }
 
-impl<F> EventHandler for F
+impl<T, F> EventHandler<T> for F
 where
-    F: FnMut(&mut ActiveApplication, Event),
+    F: FnMut(&mut ActiveApplication, Event<T>),
 {
-    fn on_event(&mut self, app: &mut ActiveApplication, event: Event) { self(app, event); }
+    fn on_event(&mut self, app: &mut ActiveApplication, event: Event<T>) { self(app, event); }
 }
@@ -0,0 +1,168 @@
+//! This module contains the API of the headless backend for automated tests.
+
+use dpi::PhysicalPosition;
+
+#[cfg(feature = "tray")]
+use crate::TrayId;
+use crate::{
+    platform::Wrapper,
+    Accelerator,
+    ActivationPolicy,
+    CheckState,
+    ContextOwner,
+    Event,
+    UserEvent,
+    WindowId,
+};
+
+/// Snapshot of a menu item taken when a menu was set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItemState {
+    /// Item title.
+    pub title:       String,
+    /// Name of the event sent when the item is selected.
+    pub event:       Option<String>,
+    /// Keyboard accelerator of the item.
+    pub accelerator: Option<Accelerator>,
+    /// The item can be selected.
+    pub enabled:     bool,
+    /// The item is a separator.
+    pub separator:   bool,
+    /// Check state of a checkable item.
+    pub check_state: Option<CheckState>,
+    /// Name of the radio group of the item.
+    pub radio_group: Option<String>,
+    /// Items of the submenu.
+    pub submenu:     Option<Vec<MenuItemState>>,
+}
+
+/// Context menu recorded instead of being shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMenuState {
+    /// ID of the window the menu has been shown in.
+    pub window_id: WindowId,
+    /// Top-left corner of the menu relative to the window content.
+    pub position:  PhysicalPosition<i32>,
+    /// Snapshot of the menu items.
+    pub items:     Vec<MenuItemState>,
+}
+
+/// Notification recorded instead of being shown.
+#[cfg(feature = "notifications")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationState {
+    /// Notification title.
+    pub title:   Option<String>,
+    /// Notification message.
+    pub message: Option<String>,
+    /// Notification action.
+    pub action:  Option<String>,
+}
+
+/// Tray icon recorded instead of being shown.
+#[cfg(feature = "tray")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayState {
+    /// Tray icon ID.
+    pub id:      TrayId,
+    /// Tooltip text.
+    pub tooltip: Option<String>,
+    /// Snapshot of the tray icon menu.
+    pub menu:    Option<Vec<MenuItemState>>,
+}
+
+/// Access to the state of an application driven by the headless backend.
+///
+/// The event loop delivers queued events in order along with events sent with
+/// [EventLoopProxy](crate::EventLoopProxy). Once they are exhausted, the loop
+/// follows [ControlFlow](crate::ControlFlow): [ControlFlow::Wait](crate::ControlFlow::Wait)
+/// finishes it as if the application was stopped, other modes start a new
+/// iteration after the timeout.
+///
+/// # Examples:
+///
+/// ```rust
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// use b3_core::{
+///     ActiveApplication,
+///     Application,
+///     Event,
+///     HeadlessExt,
+///     LifeCycle,
+///     StartCause,
+///     WindowEvent,
+/// };
+///
+/// let app = Application::new().unwrap();
+/// app.queue_event(Event::Window(WindowEvent::CloseRequested, 1));
+/// app.queue_event(Event::Menu {
+///     action: "never-delivered".into(),
+///     state:  None,
+/// });
+///
+/// let events = Rc::new(RefCell::new(Vec::new()));
+/// let recorder = events.clone();
+/// app.run(move |app: &mut ActiveApplication, event: Event| {
+///     if let Event::Window(WindowEvent::CloseRequested, _) = event {
+///         app.stop();
+///     }
+///     recorder.borrow_mut().push(event);
+/// });
+///
+/// assert_eq!(*events.borrow(), vec![
+///     Event::LifeCycle(LifeCycle::Started),
+///     Event::NewEvents(StartCause::Init),
+///     Event::Window(WindowEvent::CloseRequested, 1),
+///     Event::LifeCycle(LifeCycle::Finished),
+/// ]);
+/// ```
+pub trait HeadlessExt: ContextOwner {
+    /// Queues an event to be delivered by the event loop.
+    ///
+    /// Key presses trigger accelerators of the application menu like on
+    /// Linux, the menu event is delivered instead of the key press then.
+    ///
+    /// # Parameters:
+    /// * `event` - Event.
+    fn queue_event(&self, event: Event) {
+        let event = event.try_map_user(|event| Some(Box::new(event) as UserEvent));
+        if let Some(event) = event {
+            self.context().get_impl().queue_event(event);
+        }
+    }
+
+    /// Returns a snapshot of the application menu.
+    fn menu(&self) -> Option<Vec<MenuItemState>> { self.context().get_impl().menu() }
+
+    /// Returns `true` if the application icon is set.
+    fn has_icon(&self) -> bool { self.context().get_impl().has_icon() }
+
+    /// Returns the application identifier set with
+    /// [ApplicationBuilder::with_app_id](crate::ApplicationBuilder::with_app_id).
+    fn app_id(&self) -> Option<String> { self.context().get_impl().options().app_id.clone() }
+
+    /// Returns the application activation policy.
+    fn activation_policy(&self) -> ActivationPolicy {
+        self.context().get_impl().options().activation_policy
+    }
+
+    /// Returns all context menus shown by the application in order of showing.
+    ///
+    /// Choosing an item and closing the menu can be simulated by queueing
+    /// [Event::Menu] and [WindowEvent::ContextMenuClosed](crate::WindowEvent::ContextMenuClosed).
+    fn context_menus(&self) -> Vec<ContextMenuState> { self.context().get_impl().context_menus() }
+
+    /// Returns all notifications shown by the application.
+    #[cfg(feature = "notifications")]
+    fn notifications(&self) -> Vec<NotificationState> { self.context().get_impl().notifications() }
+
+    /// Returns all tray icons of the application in order of creation.
+    ///
+    /// Activations of tray icons can be simulated by queueing
+    /// [Event::Tray](crate::Event::Tray).
+    #[cfg(feature = "tray")]
+    fn trays(&self) -> Vec<TrayState> { self.context().get_impl().trays() }
+}
+
+impl<T> HeadlessExt for T where T: ContextOwner {}
@@ -33,7 +33,7 @@ impl Icon {
     /// * `icon_type` - Icon type.
     pub fn from_data(
         ctx: &impl ContextOwner,
-        icon_data: &Vec<u8>,
+        icon_data: &[u8],
         icon_type: IconType,
     ) -> Result<Self, Error> {
         Ok(Self(IconImpl::from_data(ctx, icon_data, icon_type)?))
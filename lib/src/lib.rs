@@ -1,28 +1,52 @@
 //! This module contains all public exports of the b3-core crate.
+//!
+//! # Native handles
+//!
+//! The `dh` feature lets [Window] provide native window handlers on macOS
+//! only. The Linux backends talk to the X server and the Wayland compositor
+//! over pure Rust connections, which have no Xlib/XCB connection or
+//! `wl_display`/`wl_surface` pointers that graphics APIs expect, so X11 and
+//! Wayland windows don't implement `HasWindowHandler`.
 
 #![warn(missing_docs)]
 
+mod accelerator;
 mod application;
+mod clipboard;
+mod cursor;
 mod errors;
 mod events;
+#[cfg(feature = "headless")]
+mod headless;
 mod icon;
 mod menu;
+mod monitor;
 #[cfg(feature = "notifications")]
 mod notification;
 mod platform;
 mod platform_impl;
+#[cfg(feature = "tray")]
+mod tray;
 mod window;
 
+pub use accelerator::*;
 pub use application::*;
 #[cfg(feature = "dh")]
 #[doc(inline)]
 pub use b3_display_handler as dh;
+pub use clipboard::*;
+pub use cursor::*;
 #[doc(inline)]
 pub use dpi;
 pub use errors::*;
 pub use events::*;
+#[cfg(feature = "headless")]
+pub use headless::*;
 pub use icon::*;
 pub use menu::*;
+pub use monitor::*;
 #[cfg(feature = "notifications")]
 pub use notification::*;
+#[cfg(feature = "tray")]
+pub use tray::*;
 pub use window::*;
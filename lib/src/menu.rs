@@ -1,14 +1,19 @@
 //! This module contains a platform independent application menu implementation.
 
+use std::{cell::RefCell, fmt, rc::Rc};
+
 use crate::{
     platform::{MenuApi, MenuItemApi, Wrapper},
     platform_impl::{MenuImpl, MenuItemImpl},
+    Accelerator,
+    ActiveApplication,
     ContextOwner,
+    Error,
     Icon,
 };
 
 /// Menu item action.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Action {
     /// This variant will send an event with the specified action name into the
     /// event loop.
@@ -18,7 +23,9 @@ pub enum Action {
     Event(String),
 
     /// An action callback for a menu item.
-    Callback(fn()),
+    ///
+    /// The callback is called by the event loop instead of sending an event.
+    Callback(MenuCallback),
 }
 
 impl Action {
@@ -37,19 +44,227 @@ impl Action {
     ///
     /// # Parameters:
     /// * `callback` - Action callback.
-    pub fn new_callback(callback: fn()) -> Self { Self::Callback(callback) }
+    pub fn new_callback<F>(callback: F) -> Self
+    where
+        F: FnMut(&mut ActiveApplication) + 'static,
+    {
+        Self::Callback(MenuCallback::new(callback))
+    }
 }
 
-/// This structure represents short codes (menu hotkeys) for different
-/// platforms.
-#[derive(Debug, Default)]
-pub struct ShortCode {
-    pub(crate) macos: Option<String>,
+/// Callback of a menu item.
+///
+/// The callback receives the active application, so it can open windows,
+/// change menu items or stop the application. It may capture any state, which
+/// is shared by clones of the callback.
+///
+/// # Examples:
+///
+/// ```rust
+/// use std::{cell::Cell, rc::Rc};
+///
+/// use b3_core::{Action, ActiveApplication};
+///
+/// let counter = Rc::new(Cell::new(0));
+/// let action = Action::new_callback({
+///     let counter = counter.clone();
+///     move |app: &mut ActiveApplication| {
+///         counter.set(counter.get() + 1);
+///         if counter.get() == 3 {
+///             app.stop();
+///         }
+///     }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct MenuCallback(Rc<RefCell<CallbackFn>>);
+
+type CallbackFn = dyn FnMut(&mut ActiveApplication);
+
+impl MenuCallback {
+    /// Creates a new menu callback.
+    ///
+    /// # Parameters:
+    /// * `callback` - Callback.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(&mut ActiveApplication) + 'static,
+    {
+        Self(Rc::new(RefCell::new(callback)))
+    }
+
+    /// Calls the callback.
+    ///
+    /// A callback triggered from within itself isn't called again.
+    pub(crate) fn call(&self, app: &mut ActiveApplication) {
+        if let Ok(mut callback) = self.0.try_borrow_mut() {
+            callback(app);
+        }
+    }
 }
 
-impl ShortCode {
-    /// Returns a short code for macOS platform or `None`.
-    pub fn macos_code(&self) -> Option<&String> { self.macos.as_ref() }
+impl fmt::Debug for MenuCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MenuCallback").finish_non_exhaustive()
+    }
+}
+
+/// State of a checkable menu item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CheckState {
+    /// The item is unchecked.
+    Unchecked,
+    /// The item is checked.
+    Checked,
+    /// The item is partially checked, e.g. it stands for a selection where
+    /// the option is only on for some objects.
+    Mixed,
+}
+
+impl CheckState {
+    /// Returns the state of an item after it has been chosen.
+    ///
+    /// Items of radio groups are checked, other items are toggled.
+    pub(crate) fn chosen(self, is_radio: bool) -> Self {
+        match self {
+            Self::Checked if !is_radio => Self::Unchecked,
+            _ => Self::Checked,
+        }
+    }
+}
+
+impl From<bool> for CheckState {
+    #[inline]
+    fn from(checked: bool) -> Self {
+        match checked {
+            true => Self::Checked,
+            false => Self::Unchecked,
+        }
+    }
+}
+
+/// Information about the application shown by the About item.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AboutMetadata {
+    /// Application name, it's also a part of the item title.
+    pub name:      Option<String>,
+    /// Application version.
+    pub version:   Option<String>,
+    /// Copyright notice.
+    pub copyright: Option<String>,
+}
+
+/// Standard menu item.
+///
+/// Predefined items come with the conventional title and accelerator of the
+/// platform. On macOS they are handled by the system, e.g. Copy is sent to the
+/// focused view. Other platforms have no such roles, so the items send
+/// [Event::Menu](crate::Event::Menu) with the action returned by
+/// [PredefinedItem::name], except Quit, which stops the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredefinedItem {
+    /// Shows information about the application.
+    About(AboutMetadata),
+    /// Quits the application.
+    Quit,
+    /// Copies the selection to the clipboard.
+    Copy,
+    /// Cuts the selection to the clipboard.
+    Cut,
+    /// Pastes the clipboard content.
+    Paste,
+    /// Selects everything in the focused view.
+    SelectAll,
+    /// Undoes the last change.
+    Undo,
+    /// Redoes the last undone change.
+    Redo,
+    /// Minimizes the focused window.
+    Minimize,
+    /// Zooms the focused window.
+    Zoom,
+    /// Toggles the full screen mode of the focused window.
+    Fullscreen,
+    /// Hides the application.
+    Hide,
+    /// Hides other applications.
+    HideOthers,
+    /// Shows all applications.
+    ShowAll,
+    /// Closes the focused window.
+    CloseWindow,
+}
+
+impl PredefinedItem {
+    /// Returns the action name of the item sent on platforms without native
+    /// roles.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::About(_) => "about",
+            Self::Quit => "quit",
+            Self::Copy => "copy",
+            Self::Cut => "cut",
+            Self::Paste => "paste",
+            Self::SelectAll => "select-all",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Minimize => "minimize",
+            Self::Zoom => "zoom",
+            Self::Fullscreen => "fullscreen",
+            Self::Hide => "hide",
+            Self::HideOthers => "hide-others",
+            Self::ShowAll => "show-all",
+            Self::CloseWindow => "close-window",
+        }
+    }
+
+    /// Returns the default title of the item.
+    fn title(&self) -> String {
+        let title = match self {
+            Self::About(AboutMetadata {
+                name: Some(name), ..
+            }) => return format!("About {}", name),
+            Self::About(_) => "About",
+            Self::Quit => "Quit",
+            Self::Copy => "Copy",
+            Self::Cut => "Cut",
+            Self::Paste => "Paste",
+            Self::SelectAll => "Select All",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::Minimize => "Minimize",
+            Self::Zoom => "Zoom",
+            Self::Fullscreen => "Toggle Full Screen",
+            Self::Hide => "Hide",
+            Self::HideOthers => "Hide Others",
+            Self::ShowAll => "Show All",
+            Self::CloseWindow => "Close Window",
+        };
+        title.to_owned()
+    }
+
+    /// Returns the default accelerator of the item on the current platform.
+    fn accelerator(&self) -> Option<Accelerator> {
+        let is_macos = cfg!(target_os = "macos");
+        let accelerator = match self {
+            Self::Quit => "CmdOrCtrl+Q",
+            Self::Copy => "CmdOrCtrl+C",
+            Self::Cut => "CmdOrCtrl+X",
+            Self::Paste => "CmdOrCtrl+V",
+            Self::SelectAll => "CmdOrCtrl+A",
+            Self::Undo => "CmdOrCtrl+Z",
+            Self::Redo if is_macos => "Cmd+Shift+Z",
+            Self::Redo => "Ctrl+Y",
+            Self::Minimize if is_macos => "Cmd+M",
+            Self::Fullscreen if is_macos => "Ctrl+Cmd+F",
+            Self::Fullscreen => "F11",
+            Self::Hide if is_macos => "Cmd+H",
+            Self::HideOthers if is_macos => "Cmd+Alt+H",
+            Self::CloseWindow => "CmdOrCtrl+W",
+            _ => return None,
+        };
+        accelerator.parse().ok()
+    }
 }
 
 /// Application menu item.
@@ -60,13 +275,37 @@ impl MenuItem {
     /// Returns a new builder instance.
     pub fn builder() -> MenuItemBuilder { MenuItemBuilder::new() }
 
-    fn new(ctx: &impl ContextOwner) -> Self { Self(MenuItemImpl::new(ctx, false)) }
+    fn new(ctx: &impl ContextOwner) -> Result<Self, Error> {
+        Ok(Self(MenuItemImpl::new(ctx, false)?))
+    }
 
     /// Creates a new menu separator.
     ///
+    /// Fails with [ErrorKind::Os](crate::ErrorKind::Os) if the native menu
+    /// item can't be created.
+    ///
     /// # Parameters:
     /// * `ctx` - ContextOnwer
-    pub fn separator(ctx: &impl ContextOwner) -> Self { Self(MenuItemImpl::new(ctx, true)) }
+    pub fn separator(ctx: &impl ContextOwner) -> Result<Self, Error> {
+        Ok(Self(MenuItemImpl::new(ctx, true)?))
+    }
+
+    /// Creates a new standard menu item with the default title and
+    /// accelerator, which can be changed afterwards.
+    ///
+    /// Fails with [ErrorKind::Os](crate::ErrorKind::Os) if the native menu
+    /// item can't be created.
+    ///
+    /// # Parameters:
+    /// * `ctx` - ContextOnwer
+    /// * `item` - Kind of the item.
+    pub fn predefined(ctx: &impl ContextOwner, item: PredefinedItem) -> Result<Self, Error> {
+        let mut menu_item = Self::new(ctx)?;
+        menu_item.set_title(item.title());
+        menu_item.set_accelerator(item.accelerator());
+        menu_item.0.set_predefined(item);
+        Ok(menu_item)
+    }
 }
 
 impl MenuItem {
@@ -105,14 +344,19 @@ impl MenuItem {
     /// Checks if a menu item has a submenu.
     pub fn has_submenu(&self) -> bool { self.0.has_submenu() }
 
-    /// Sets short codes for different platforms.
+    /// Sets a keyboard accelerator of the menu item.
+    ///
+    /// The accelerator chooses the item of the application menu while a
+    /// window of the application is focused, menus show it next to the title.
     ///
     /// # Parameters:
-    /// * `short_code` - Short codes.
-    pub fn set_short_code(&mut self, short_code: ShortCode) { self.0.set_short_code(short_code); }
+    /// * `accelerator` - Keyboard accelerator.
+    pub fn set_accelerator(&mut self, accelerator: Option<Accelerator>) {
+        self.0.set_accelerator(accelerator);
+    }
 
-    /// Returns short codes for different platforms.
-    pub fn short_code(&self) -> &ShortCode { self.0.short_code() }
+    /// Returns a keyboard accelerator of the menu item.
+    pub fn accelerator(&self) -> Option<&Accelerator> { self.0.accelerator() }
 
     /// Turns on/off a menu item.
     ///
@@ -140,6 +384,49 @@ impl MenuItem {
 
     /// Returns a menu item icon.
     pub fn icon(&self) -> Option<&Icon> { self.0.icon() }
+
+    /// Sets a check state of the menu item.
+    ///
+    /// Checkable items are toggled when they are chosen, and the new state is
+    /// sent with [Event::Menu](crate::Event::Menu). `None` makes the item a
+    /// regular one.
+    ///
+    /// The state of other items of the radio group isn't changed, see
+    /// [Menu::check_radio].
+    ///
+    /// # Parameters:
+    /// * `state` - Check state.
+    pub fn set_check_state(&mut self, state: Option<CheckState>) { self.0.set_check_state(state); }
+
+    /// Returns a check state of the menu item or `None` if it isn't checkable.
+    pub fn check_state(&self) -> Option<CheckState> { self.0.check_state() }
+
+    /// Makes the menu item checkable and checks/unchecks it.
+    ///
+    /// # Parameters:
+    /// * `checked` - Check flag.
+    pub fn set_checked(&mut self, checked: bool) { self.0.set_check_state(Some(checked.into())); }
+
+    /// Checks if the menu item is checked.
+    pub fn checked(&self) -> bool { self.0.check_state() == Some(CheckState::Checked) }
+
+    /// Puts the menu item into a radio group.
+    ///
+    /// Choosing an item of a group checks it and unchecks other items of the
+    /// group in the same menu. Items of a group are checkable, so an item
+    /// without a check state becomes unchecked.
+    ///
+    /// # Parameters:
+    /// * `group` - Name of the radio group.
+    pub fn set_radio_group(&mut self, group: Option<String>) {
+        if group.is_some() && self.check_state().is_none() {
+            self.0.set_check_state(Some(CheckState::Unchecked));
+        }
+        self.0.set_radio_group(group);
+    }
+
+    /// Returns a name of the radio group of the menu item.
+    pub fn radio_group(&self) -> Option<String> { self.0.radio_group() }
 }
 
 impl Wrapper<MenuItemImpl> for MenuItem {
@@ -153,12 +440,14 @@ impl Wrapper<MenuItemImpl> for MenuItem {
 /// Menu item builder.
 #[derive(Debug, Default)]
 pub struct MenuItemBuilder {
-    title:      Option<String>,
-    action:     Option<Action>,
-    submenu:    Option<Menu>,
-    short_code: ShortCode,
-    enabled:    Option<bool>,
-    icon:       Option<Icon>,
+    title:       Option<String>,
+    action:      Option<Action>,
+    submenu:     Option<Menu>,
+    accelerator: Option<Accelerator>,
+    enabled:     Option<bool>,
+    icon:        Option<Icon>,
+    check_state: Option<CheckState>,
+    radio_group: Option<String>,
 }
 
 impl MenuItemBuilder {
@@ -198,15 +487,12 @@ impl MenuItemBuilder {
         self
     }
 
-    /// Sets short codes for the item under building.
+    /// Sets a keyboard accelerator for the item under building.
     ///
     /// # Parameters:
-    /// * `short_code` - Short codes.
-    pub fn with_macos_short_code<S>(mut self, short_code: S) -> MenuItemBuilder
-    where
-        S: Into<String>,
-    {
-        self.short_code.macos = Some(short_code.into());
+    /// * `accelerator` - Keyboard accelerator.
+    pub fn with_accelerator(mut self, accelerator: Accelerator) -> MenuItemBuilder {
+        self.accelerator = Some(accelerator);
         self
     }
 
@@ -228,12 +514,45 @@ impl MenuItemBuilder {
         self
     }
 
+    /// Sets a check state for the item under building.
+    ///
+    /// # Parameters:
+    /// * `state` - Check state.
+    pub fn with_check_state(mut self, state: CheckState) -> MenuItemBuilder {
+        self.check_state = Some(state);
+        self
+    }
+
+    /// Makes the item under building checkable and checks/unchecks it.
+    ///
+    /// # Parameters:
+    /// * `checked` - Check flag.
+    pub fn with_checked(mut self, checked: bool) -> MenuItemBuilder {
+        self.check_state = Some(checked.into());
+        self
+    }
+
+    /// Puts the item under building into a radio group.
+    ///
+    /// # Parameters:
+    /// * `group` - Name of the radio group.
+    pub fn with_radio_group<S>(mut self, group: S) -> MenuItemBuilder
+    where
+        S: Into<String>,
+    {
+        self.radio_group = Some(group.into());
+        self
+    }
+
     /// Build a new menu item with specified options.
     ///
+    /// Fails with [ErrorKind::Os](crate::ErrorKind::Os) if the native menu
+    /// item can't be created.
+    ///
     /// # Parameters:
     /// * `ctx` - Context owner.
-    pub fn build(self, ctx: &impl ContextOwner) -> MenuItem {
-        let mut item = MenuItem::new(ctx);
+    pub fn build(self, ctx: &impl ContextOwner) -> Result<MenuItem, Error> {
+        let mut item = MenuItem::new(ctx)?;
 
         if let Some(title) = self.title {
             item.set_title(title);
@@ -247,7 +566,9 @@ impl MenuItemBuilder {
             item.set_submenu(self.submenu);
         }
 
-        item.set_short_code(self.short_code);
+        if self.accelerator.is_some() {
+            item.set_accelerator(self.accelerator);
+        }
 
         if let Some(enabled) = self.enabled {
             item.set_enabled(enabled);
@@ -257,7 +578,15 @@ impl MenuItemBuilder {
             item.set_icon(self.icon);
         }
 
-        item
+        if self.check_state.is_some() {
+            item.set_check_state(self.check_state);
+        }
+
+        if self.radio_group.is_some() {
+            item.set_radio_group(self.radio_group);
+        }
+
+        Ok(item)
     }
 }
 
@@ -272,6 +601,88 @@ impl Menu {
     fn new(ctx: &impl ContextOwner, items: Vec<MenuItem>) -> Self {
         Self(MenuImpl::new(ctx, items))
     }
+
+    /// Appends an item to the menu.
+    ///
+    /// # Parameters:
+    /// * `item` - Menu item.
+    pub fn add_item(&mut self, item: MenuItem) { self.0.add_item(item); }
+
+    /// Inserts an item at the index, shifting all items after it.
+    ///
+    /// # Parameters:
+    /// * `index` - Position of the item.
+    /// * `item` - Menu item.
+    ///
+    /// # Panics:
+    /// Panics if `index` is greater than the number of items.
+    pub fn insert(&mut self, index: usize, item: MenuItem) { self.0.insert_item(index, item); }
+
+    /// Removes and returns the item at the index, shifting all items after it.
+    ///
+    /// # Parameters:
+    /// * `index` - Position of the item.
+    ///
+    /// # Panics:
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> MenuItem { self.0.remove_item(index) }
+
+    /// Checks the item at the index and unchecks other items of its radio
+    /// group.
+    ///
+    /// An item without a radio group is just checked.
+    ///
+    /// # Parameters:
+    /// * `index` - Position of the item.
+    ///
+    /// # Panics:
+    /// Panics if `index` is out of bounds.
+    pub fn check_radio(&mut self, index: usize) {
+        let items = self.0.items_mut();
+        let group = items[index].radio_group();
+        if group.is_some() {
+            for (i, item) in items.iter_mut().enumerate() {
+                if i != index && item.radio_group() == group {
+                    item.set_check_state(Some(CheckState::Unchecked));
+                }
+            }
+        }
+        items[index].set_checked(true);
+    }
+
+    /// Returns items of the menu.
+    pub fn items(&self) -> &[MenuItem] { self.0.items() }
+
+    /// Returns mutable items of the menu.
+    ///
+    /// Items can be changed in place, but swapping or replacing them doesn't
+    /// reorder a native menu. Use [Menu::remove] and [Menu::insert] instead.
+    pub fn items_mut(&mut self) -> &mut [MenuItem] { self.0.items_mut() }
+
+    /// Returns the number of items in the menu.
+    pub fn len(&self) -> usize { self.0.items().len() }
+
+    /// Checks if the menu has no items.
+    pub fn is_empty(&self) -> bool { self.0.items().is_empty() }
+
+    /// Finds an item that sends the event with the action name.
+    ///
+    /// Items of the menu are checked before items of its submenus.
+    ///
+    /// # Parameters:
+    /// * `name` - Action name, see [Action::Event].
+    pub fn find_by_action(&self, name: &str) -> Option<&MenuItem> {
+        let items = self.items();
+        items
+            .iter()
+            .find(|item| item.0.has_action(name))
+            .or_else(|| {
+                items
+                    .iter()
+                    .filter_map(|item| item.submenu())
+                    .find_map(|submenu| submenu.find_by_action(name))
+            })
+    }
 }
 
 impl Wrapper<MenuImpl> for Menu {
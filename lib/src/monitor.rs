@@ -0,0 +1,88 @@
+//! This module contains display monitor definitions.
+
+use dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::{
+    platform::{MonitorApi, Wrapper},
+    platform_impl::MonitorImpl,
+};
+
+/// Display monitor.
+///
+/// A monitor is a snapshot taken when it was requested, it isn't updated when
+/// the monitor configuration changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor(MonitorImpl);
+
+impl Monitor {
+    pub(crate) fn new(monitor_impl: MonitorImpl) -> Self { Self(monitor_impl) }
+
+    /// Returns a human-readable monitor name, if the platform reports it.
+    pub fn name(&self) -> Option<String> { self.0.name() }
+
+    /// Returns a position of the top-left corner of the monitor in the
+    /// desktop coordinates.
+    pub fn position(&self) -> PhysicalPosition<i32> { self.0.position() }
+
+    /// Returns a monitor resolution.
+    pub fn size(&self) -> PhysicalSize<u32> { self.0.size() }
+
+    /// Returns a scale factor of the monitor.
+    pub fn scale_factor(&self) -> f64 { self.0.scale_factor() }
+
+    /// Returns a refresh rate of the current video mode in millihertz, if
+    /// known.
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> { self.0.refresh_rate_millihertz() }
+
+    /// Returns video modes supported by the monitor.
+    pub fn video_modes(&self) -> Vec<VideoMode> { self.0.video_modes() }
+}
+
+impl Wrapper<MonitorImpl> for Monitor {
+    #[inline]
+    fn get_impl(&self) -> &MonitorImpl { &self.0 }
+
+    #[inline]
+    fn get_impl_mut(&mut self) -> &mut MonitorImpl { &mut self.0 }
+}
+
+/// Video mode of a monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoMode {
+    size:                    PhysicalSize<u32>,
+    bit_depth:               u16,
+    refresh_rate_millihertz: u32,
+    monitor:                 Monitor,
+}
+
+impl VideoMode {
+    pub(crate) fn new(
+        size: PhysicalSize<u32>,
+        bit_depth: u16,
+        refresh_rate_millihertz: u32,
+        monitor: Monitor,
+    ) -> Self {
+        Self {
+            size,
+            bit_depth,
+            refresh_rate_millihertz,
+            monitor,
+        }
+    }
+
+    /// Returns a resolution of the video mode.
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> { self.size }
+
+    /// Returns a color depth in bits per pixel.
+    #[inline]
+    pub fn bit_depth(&self) -> u16 { self.bit_depth }
+
+    /// Returns a refresh rate in millihertz.
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 { self.refresh_rate_millihertz }
+
+    /// Returns a monitor the video mode belongs to.
+    #[inline]
+    pub fn monitor(&self) -> &Monitor { &self.monitor }
+}
@@ -1,16 +1,42 @@
-use crate::{Error, EventHandler, Icon, Menu};
+use crate::{
+    platform_impl::EventLoopProxyImpl,
+    ApplicationOptions,
+    Clipboard,
+    ClipboardKind,
+    ControlFlow,
+    Error,
+    EventHandler,
+    Icon,
+    Menu,
+    Monitor,
+    UserEvent,
+};
 
 pub trait ActiveApplicationApi {
     fn set_menu(&mut self, menu: Option<&Menu>);
 
     fn set_icon(&mut self, icon: Option<&Icon>);
 
+    fn set_control_flow(&mut self, control_flow: ControlFlow);
+    fn control_flow(&self) -> ControlFlow;
+
+    fn monitors(&self) -> Vec<Monitor>;
+    fn primary_monitor(&self) -> Option<Monitor>;
+
+    fn clipboard(&self, kind: ClipboardKind) -> Clipboard;
+
     fn stop(&mut self);
 }
 
 pub(crate) trait ApplicationApi {
-    fn new() -> Result<Self, Error>
+    fn new(options: &ApplicationOptions) -> Result<Self, Error>
     where
         Self: Sized;
-    fn run(&mut self, handler: impl EventHandler + 'static);
+    fn run(&mut self, handler: impl EventHandler<UserEvent> + 'static);
+
+    fn create_proxy(&self) -> EventLoopProxyImpl;
+}
+
+pub(crate) trait EventLoopProxyApi: Clone + Send {
+    fn send_event(&self, event: UserEvent) -> Result<(), Error>;
 }
@@ -0,0 +1,18 @@
+use crate::{ClipboardImage, ClipboardKind, Error};
+
+pub(crate) trait ClipboardApi {
+    fn kind(&self) -> ClipboardKind;
+
+    fn set_text(&mut self, text: &str) -> Result<(), Error>;
+    fn text(&self) -> Result<String, Error>;
+
+    fn set_image(&mut self, image: &ClipboardImage) -> Result<(), Error>;
+    fn image(&self) -> Result<ClipboardImage, Error>;
+
+    fn set_data(&mut self, mime_type: &str, data: &[u8]) -> Result<(), Error>;
+    fn data(&self, mime_type: &str) -> Result<Vec<u8>, Error>;
+
+    fn mime_types(&self) -> Result<Vec<String>, Error>;
+
+    fn clear(&mut self);
+}
@@ -1,20 +1,33 @@
-use crate::{Action, ContextOwner, Icon, Menu, MenuItem, ShortCode};
+use crate::{
+    Accelerator,
+    Action,
+    CheckState,
+    ContextOwner,
+    Error,
+    Icon,
+    Menu,
+    MenuItem,
+    PredefinedItem,
+};
 
 pub(crate) trait MenuItemApi {
-    fn new(ctx: &impl ContextOwner, separator: bool) -> Self;
+    fn new(ctx: &impl ContextOwner, separator: bool) -> Result<Self, Error>
+    where
+        Self: Sized;
 
     fn set_title(&mut self, title: String);
     fn title(&self) -> String;
 
     fn set_action(&mut self, action: Option<Action>);
+    fn has_action(&self, name: &str) -> bool;
 
     fn set_submenu(&mut self, submenu: Option<Menu>);
     fn submenu(&self) -> Option<&Menu>;
     fn submenu_mut(&mut self) -> Option<&mut Menu>;
     fn has_submenu(&self) -> bool;
 
-    fn set_short_code(&mut self, short_code: ShortCode);
-    fn short_code(&self) -> &ShortCode;
+    fn set_accelerator(&mut self, accelerator: Option<Accelerator>);
+    fn accelerator(&self) -> Option<&Accelerator>;
 
     fn set_enabled(&mut self, enabled: bool);
     fn enabled(&self) -> bool;
@@ -24,10 +37,23 @@ pub(crate) trait MenuItemApi {
 
     fn set_icon(&mut self, icon: Option<Icon>);
     fn icon(&self) -> Option<&Icon>;
+
+    fn set_check_state(&mut self, state: Option<CheckState>);
+    fn check_state(&self) -> Option<CheckState>;
+
+    fn set_radio_group(&mut self, group: Option<String>);
+    fn radio_group(&self) -> Option<String>;
+
+    fn set_predefined(&mut self, item: PredefinedItem);
 }
 
 pub(crate) trait MenuApi {
     fn new(ctx: &impl ContextOwner, items: Vec<MenuItem>) -> Self;
 
     fn add_item(&mut self, item: MenuItem);
+    fn insert_item(&mut self, index: usize, item: MenuItem);
+    fn remove_item(&mut self, index: usize) -> MenuItem;
+
+    fn items(&self) -> &[MenuItem];
+    fn items_mut(&mut self) -> &mut [MenuItem];
 }
@@ -1,18 +1,27 @@
 mod application;
+mod clipboard;
 mod icon;
 mod menu;
+mod monitor;
 #[cfg(feature = "notifications")]
 mod notification;
+#[cfg(feature = "tray")]
+mod tray;
 mod window;
 
 pub(crate) use application::*;
+pub(crate) use clipboard::*;
 pub(crate) use icon::*;
 pub(crate) use menu::*;
+pub(crate) use monitor::*;
 #[cfg(feature = "notifications")]
 pub(crate) use notification::*;
+#[cfg(feature = "tray")]
+pub(crate) use tray::*;
 pub(crate) use window::*;
 
 pub(crate) trait Wrapper<T> {
     fn get_impl(&self) -> &T;
+    #[allow(dead_code)]
     fn get_impl_mut(&mut self) -> &mut T;
 }
@@ -0,0 +1,17 @@
+use dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::VideoMode;
+
+pub(crate) trait MonitorApi {
+    fn name(&self) -> Option<String>;
+
+    fn position(&self) -> PhysicalPosition<i32>;
+
+    fn size(&self) -> PhysicalSize<u32>;
+
+    fn scale_factor(&self) -> f64;
+
+    fn refresh_rate_millihertz(&self) -> Option<u32>;
+
+    fn video_modes(&self) -> Vec<VideoMode>;
+}
@@ -0,0 +1,19 @@
+use crate::{ContextOwner, Error, Icon, Menu, TrayId};
+
+pub(crate) trait TrayApi {
+    fn new(
+        ctx: &impl ContextOwner,
+        id: TrayId,
+        icon: Icon,
+        tooltip: Option<String>,
+        menu: Option<Menu>,
+    ) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    fn id(&self) -> TrayId;
+
+    fn set_icon(&mut self, icon: Icon);
+    fn set_tooltip(&mut self, tooltip: Option<String>);
+    fn set_menu(&mut self, menu: Option<Menu>);
+}
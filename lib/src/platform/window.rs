@@ -1,6 +1,18 @@
 use dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 
-use crate::{ActiveApplication, ContextOwner, InitMode, WindowId, WindowOptions};
+use crate::{
+    ActiveApplication,
+    ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    Error,
+    Fullscreen,
+    InitMode,
+    Menu,
+    Monitor,
+    WindowId,
+    WindowOptions,
+};
 
 pub(crate) trait WindowApi {
     fn new(
@@ -8,7 +20,9 @@ pub(crate) trait WindowApi {
         mode: InitMode,
         options: Option<WindowOptions>,
         size: Option<Size>,
-    ) -> Self;
+    ) -> Result<Self, Error>
+    where
+        Self: Sized;
 
     fn id(&self) -> WindowId;
 
@@ -21,7 +35,8 @@ pub(crate) trait WindowApi {
     fn show(&mut self, app: &ActiveApplication);
     fn show_modal(&mut self, app: &ActiveApplication);
 
-    fn toggle_fullscreen(&mut self);
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>);
+    fn fullscreen(&self) -> Option<Fullscreen>;
     fn is_fullscreen(&self) -> bool;
 
     fn set_frame_size(&mut self, size: Size);
@@ -51,4 +66,27 @@ pub(crate) trait WindowApi {
     fn restore(&mut self);
 
     fn scale_factor(&self) -> f64;
+
+    fn current_monitor(&self) -> Option<Monitor>;
+
+    fn set_ime_allowed(&mut self, allowed: bool);
+    fn is_ime_allowed(&self) -> bool;
+
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size);
+
+    fn request_redraw(&mut self);
+    fn pre_present_notify(&mut self);
+
+    fn set_cursor(&mut self, cursor: Cursor);
+    fn cursor(&self) -> Cursor;
+
+    fn set_cursor_visible(&mut self, visible: bool);
+    fn is_cursor_visible(&self) -> bool;
+
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error>;
+    fn cursor_grab(&self) -> CursorGrabMode;
+
+    fn set_cursor_position(&mut self, position: Position) -> Result<(), Error>;
+
+    fn show_context_menu(&mut self, menu: &Menu, position: Position) -> Result<(), Error>;
 }
@@ -0,0 +1,182 @@
+use super::{decode_png, encode_png};
+use crate::{
+    platform::ClipboardApi,
+    platform_impl::SelectionImpl,
+    ClipboardImage,
+    ClipboardKind,
+    Error,
+    ErrorKind,
+};
+
+pub(crate) const MIME_TEXT_UTF8: &str = "text/plain;charset=utf-8";
+pub(crate) const MIME_TEXT: &str = "text/plain";
+pub(crate) const MIME_IMAGE_PNG: &str = "image/png";
+
+/// Types of text in the order of preference.
+///
+/// X11 clients use atom names instead of MIME types, which Wayland clients
+/// offer as well for compatibility.
+const TEXT_MIME_TYPES: [&str; 5] = [MIME_TEXT_UTF8, "UTF8_STRING", MIME_TEXT, "STRING", "TEXT"];
+
+/// Latin-1 text type of X11.
+const MIME_STRING: &str = "STRING";
+
+/// Data the application offers through a selection.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ClipboardContent {
+    items: Vec<(String, Vec<u8>)>,
+}
+
+impl ClipboardContent {
+    fn from_text(text: &str) -> Self {
+        let items = TEXT_MIME_TYPES
+            .into_iter()
+            .map(|mime| {
+                let data = match mime {
+                    MIME_STRING => text
+                        .chars()
+                        .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                        .collect(),
+                    _ => text.as_bytes().to_vec(),
+                };
+                (mime.to_owned(), data)
+            })
+            .collect();
+        Self {
+            items,
+        }
+    }
+
+    fn from_image(image: &ClipboardImage) -> Result<Self, Error> {
+        let data = encode_png(image.rgba(), image.width(), image.height())?;
+        Ok(Self::from_data(MIME_IMAGE_PNG, data))
+    }
+
+    fn from_data(mime: &str, data: Vec<u8>) -> Self {
+        Self {
+            items: vec![(mime.to_owned(), data)],
+        }
+    }
+
+    /// Returns offered MIME types.
+    pub(crate) fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(|(mime, _)| mime.as_str())
+    }
+
+    /// Returns data of the MIME type.
+    pub(crate) fn get(&self, mime: &str) -> Option<&[u8]> {
+        self.items
+            .iter()
+            .find(|(offered, _)| offered == mime)
+            .map(|(_, data)| data.as_slice())
+    }
+}
+
+/// Clipboard implementation shared by platforms that exchange data as MIME
+/// types.
+///
+/// The platform specific part is a [SelectionImpl] that stores and receives
+/// raw data of a selection.
+#[derive(Debug, Clone)]
+pub(crate) struct ClipboardImpl {
+    selection: SelectionImpl,
+}
+
+impl ClipboardImpl {
+    #[inline]
+    pub(crate) fn new(selection: SelectionImpl) -> Self {
+        Self {
+            selection,
+        }
+    }
+
+    /// Receives data of the first offered MIME type out of `mime_types`.
+    fn receive_any(&self, mime_types: &[&str]) -> Result<(String, Vec<u8>), Error> {
+        let offered = self.selection.mime_types()?;
+        let mime = mime_types
+            .iter()
+            .find(|mime| offered.iter().any(|offered| offered == *mime))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "clipboard has no data of the requested type.",
+                )
+            })?;
+        let data = self.selection.receive(mime)?;
+        Ok((mime.to_string(), data))
+    }
+}
+
+impl ClipboardApi for ClipboardImpl {
+    #[inline]
+    fn kind(&self) -> ClipboardKind { self.selection.kind() }
+
+    #[inline]
+    fn set_text(&mut self, text: &str) -> Result<(), Error> {
+        self.selection.store(ClipboardContent::from_text(text))
+    }
+
+    fn text(&self) -> Result<String, Error> {
+        let (mime, data) = self.receive_any(&TEXT_MIME_TYPES)?;
+        let text = match mime.as_str() {
+            MIME_STRING => data.into_iter().map(char::from).collect(),
+            _ => String::from_utf8_lossy(&data).into_owned(),
+        };
+        Ok(text)
+    }
+
+    #[inline]
+    fn set_image(&mut self, image: &ClipboardImage) -> Result<(), Error> {
+        self.selection.store(ClipboardContent::from_image(image)?)
+    }
+
+    fn image(&self) -> Result<ClipboardImage, Error> {
+        let (_, data) = self.receive_any(&[MIME_IMAGE_PNG])?;
+        let (width, height, rgba) = decode_png(&data)?;
+        ClipboardImage::from_rgba(rgba, width, height)
+    }
+
+    #[inline]
+    fn set_data(&mut self, mime_type: &str, data: &[u8]) -> Result<(), Error> {
+        self.selection
+            .store(ClipboardContent::from_data(mime_type, data.to_vec()))
+    }
+
+    #[inline]
+    fn data(&self, mime_type: &str) -> Result<Vec<u8>, Error> {
+        self.receive_any(&[mime_type]).map(|(_, data)| data)
+    }
+
+    #[inline]
+    fn mime_types(&self) -> Result<Vec<String>, Error> { self.selection.mime_types() }
+
+    #[inline]
+    fn clear(&mut self) { self.selection.clear(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_text_of_all_types() {
+        let content = ClipboardContent::from_text("text");
+        assert_eq!(
+            content.mime_types().collect::<Vec<_>>(),
+            TEXT_MIME_TYPES.to_vec()
+        );
+        for mime in TEXT_MIME_TYPES {
+            assert_eq!(content.get(mime), Some(&b"text"[..]), "{}", mime);
+        }
+        assert_eq!(content.get(MIME_IMAGE_PNG), None);
+    }
+
+    #[test]
+    fn replaces_non_latin1_characters_in_string() {
+        let text = "café ∑ 日本";
+        let content = ClipboardContent::from_text(text);
+        assert_eq!(content.get(MIME_STRING), Some(&b"caf\xe9 ? ??"[..]));
+        assert_eq!(content.get("UTF8_STRING"), Some(text.as_bytes()));
+        assert_eq!(content.get(MIME_TEXT_UTF8), Some(text.as_bytes()));
+    }
+}
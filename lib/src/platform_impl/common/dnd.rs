@@ -0,0 +1,168 @@
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::PathBuf};
+
+use super::{MIME_TEXT, MIME_TEXT_UTF8};
+
+pub(crate) const MIME_URI_LIST: &str = "text/uri-list";
+
+/// Supported MIME types of dragged data in the order of preference.
+pub(crate) const DRAG_MIME_TYPES: [&str; 3] = [MIME_URI_LIST, MIME_TEXT_UTF8, MIME_TEXT];
+
+/// Dragged files and text.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DragData {
+    pub(crate) paths: Vec<PathBuf>,
+    pub(crate) text:  Option<String>,
+}
+
+impl DragData {
+    /// Parses dragged data of the MIME type.
+    ///
+    /// URIs of local files become paths, other URIs are passed as text.
+    pub(crate) fn from_mime(mime: &str, data: &[u8]) -> Self {
+        let data = String::from_utf8_lossy(data);
+        if mime != MIME_URI_LIST {
+            return Self {
+                paths: Vec::new(),
+                text:  Some(data.into_owned()),
+            };
+        }
+
+        let mut paths = Vec::new();
+        let mut uris = Vec::new();
+        // See: https://www.rfc-editor.org/rfc/rfc2483#section-5
+        for uri in data
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            match to_path(uri) {
+                Some(path) => paths.push(path),
+                None => uris.push(uri),
+            }
+        }
+
+        Self {
+            paths,
+            text: (!uris.is_empty()).then(|| uris.join("\n")),
+        }
+    }
+}
+
+/// Converts a `file` URI of a local file into a path.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc8089#section-2
+fn to_path(uri: &str) -> Option<PathBuf> {
+    let (scheme, path) = uri.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let path = match path.strip_prefix("//") {
+        Some(path) => {
+            // Local files have the host empty or set to `localhost`
+            let (host, path) = path.split_at(path.find('/')?);
+            (host.is_empty() || host.eq_ignore_ascii_case("localhost")).then_some(path)?
+        }
+        None => path,
+    };
+    if !path.starts_with('/') {
+        return None;
+    }
+    Some(PathBuf::from(OsStr::from_bytes(&percent_decode(path))))
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &str) -> Vec<PathBuf> {
+        DragData::from_mime(MIME_URI_LIST, list.as_bytes()).paths
+    }
+
+    #[test]
+    fn skips_comments_and_empty_lines() {
+        let data = DragData::from_mime(
+            MIME_URI_LIST,
+            b"# comment\n\nfile:///tmp/a\n#file:///tmp/b\n",
+        );
+        assert_eq!(data.paths, vec![PathBuf::from("/tmp/a")]);
+        assert_eq!(data.text, None);
+    }
+
+    #[test]
+    fn splits_crlf_lines() {
+        assert_eq!(
+            paths("file:///tmp/a\r\nfile:///tmp/b\r\n"),
+            vec![PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]
+        );
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(paths("file:///tmp/a%20b"), vec![PathBuf::from("/tmp/a b")]);
+        assert_eq!(
+            paths("file:///tmp/%D0%B0"),
+            vec![PathBuf::from("/tmp/\u{430}")]
+        );
+        // Invalid escapes are kept as is
+        assert_eq!(
+            paths("file:///tmp/100%\nfile:///tmp/%zz%2"),
+            vec![PathBuf::from("/tmp/100%"), PathBuf::from("/tmp/%zz%2")]
+        );
+    }
+
+    #[test]
+    fn accepts_local_file_uri_forms() {
+        assert_eq!(
+            paths("file:/tmp/a\nfile:///tmp/b\nfile://localhost/tmp/c\nFILE://LOCALHOST/tmp/d"),
+            vec![
+                PathBuf::from("/tmp/a"),
+                PathBuf::from("/tmp/b"),
+                PathBuf::from("/tmp/c"),
+                PathBuf::from("/tmp/d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn passes_other_uris_as_text() {
+        let data = DragData::from_mime(
+            MIME_URI_LIST,
+            b"https://example.com/a\nfile://host/tmp/b\nfile:tmp/c\nfile:///tmp/d",
+        );
+        assert_eq!(data.paths, vec![PathBuf::from("/tmp/d")]);
+        assert_eq!(
+            data.text.as_deref(),
+            Some("https://example.com/a\nfile://host/tmp/b\nfile:tmp/c")
+        );
+    }
+
+    #[test]
+    fn passes_text_as_is() {
+        let data = DragData::from_mime(MIME_TEXT_UTF8, b"file:///tmp/a\n");
+        assert_eq!(data.paths, Vec::<PathBuf>::new());
+        assert_eq!(data.text.as_deref(), Some("file:///tmp/a\n"));
+    }
+}
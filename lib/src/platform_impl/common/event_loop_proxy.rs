@@ -0,0 +1,163 @@
+#[cfg(feature = "tray")]
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+use std::{
+    os::fd::{AsFd, BorrowedFd, OwnedFd},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+};
+
+use rustix::{
+    event::{eventfd, EventfdFlags},
+    io,
+};
+
+use super::PendingEvent;
+#[cfg(feature = "tray")]
+use crate::MenuCallback;
+use crate::{platform::EventLoopProxyApi, Error, ErrorKind, Event, UserEvent};
+
+/// ID of a menu callback registered in [MenuCallbacks].
+#[cfg(feature = "tray")]
+pub(crate) type CallbackId = u64;
+
+/// Message sent to the event loop from another thread.
+#[derive(Debug)]
+pub(crate) enum LoopMessage {
+    /// Event delivered to the handler.
+    Event(Event<UserEvent>),
+    /// Menu callback called on the event loop thread.
+    #[cfg(feature = "tray")]
+    Callback(CallbackId),
+}
+
+/// Menu callbacks of menus served by other threads.
+///
+/// Callbacks can't leave the event loop thread, so other threads refer to them
+/// by IDs.
+#[cfg(feature = "tray")]
+#[derive(Debug, Default)]
+pub(crate) struct MenuCallbacks {
+    next_id:   Cell<CallbackId>,
+    callbacks: RefCell<HashMap<CallbackId, MenuCallback>>,
+}
+
+#[cfg(feature = "tray")]
+impl MenuCallbacks {
+    /// Registers the callback and returns its ID.
+    pub(crate) fn register(&self, callback: MenuCallback) -> CallbackId {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.callbacks.borrow_mut().insert(id, callback);
+        id
+    }
+
+    /// Removes callbacks, messages with their IDs are ignored afterwards.
+    pub(crate) fn unregister(&self, ids: impl IntoIterator<Item = CallbackId>) {
+        let mut callbacks = self.callbacks.borrow_mut();
+        for id in ids.into_iter() {
+            callbacks.remove(&id);
+        }
+    }
+
+    #[inline]
+    fn get(&self, id: CallbackId) -> Option<MenuCallback> {
+        self.callbacks.borrow().get(&id).cloned()
+    }
+}
+
+/// Sends user events to the event loop and wakes it up.
+#[derive(Debug, Clone)]
+pub(crate) struct EventLoopProxyImpl {
+    sender: Sender<LoopMessage>,
+    waker:  Arc<OwnedFd>,
+}
+
+impl EventLoopProxyImpl {
+    /// Sends a message to the event loop and wakes it up.
+    pub(crate) fn send(&self, message: LoopMessage) -> Result<(), Error> {
+        self.sender
+            .send(message)
+            .map_err(|_| Error::new(ErrorKind::EventLoopClosed, "event loop closed."))?;
+        // A failed write means the counter is already signaled
+        let _ = io::write(self.waker.as_fd(), &1u64.to_ne_bytes());
+        Ok(())
+    }
+}
+
+impl EventLoopProxyApi for EventLoopProxyImpl {
+    #[inline]
+    fn send_event(&self, event: UserEvent) -> Result<(), Error> {
+        self.send(LoopMessage::Event(Event::User(event)))
+    }
+}
+
+/// Receiving side of user events and messages of other threads.
+///
+/// The event loop polls its file descriptor along with the display
+/// connection.
+#[derive(Debug)]
+pub(crate) struct UserEvents {
+    sender:    Sender<LoopMessage>,
+    receiver:  Receiver<LoopMessage>,
+    waker:     Arc<OwnedFd>,
+    #[cfg(feature = "tray")]
+    callbacks: Rc<MenuCallbacks>,
+}
+
+impl UserEvents {
+    pub(crate) fn new() -> Result<Self, Error> {
+        let waker = eventfd(0, EventfdFlags::CLOEXEC | EventfdFlags::NONBLOCK)
+            .map_err(|e| Error::with_source(ErrorKind::Os, "event loop waker not created.", e))?;
+        let (sender, receiver) = mpsc::channel();
+
+        Ok(Self {
+            sender,
+            receiver,
+            waker: Arc::new(waker),
+            #[cfg(feature = "tray")]
+            callbacks: Rc::default(),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn fd(&self) -> BorrowedFd<'_> { self.waker.as_fd() }
+
+    #[inline]
+    pub(crate) fn create_proxy(&self) -> EventLoopProxyImpl {
+        EventLoopProxyImpl {
+            sender: self.sender.clone(),
+            waker:  self.waker.clone(),
+        }
+    }
+
+    /// Returns callbacks of menus served by other threads.
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(crate) fn callbacks(&self) -> Rc<MenuCallbacks> { self.callbacks.clone() }
+
+    /// Returns events sent since the last call and resets the waker.
+    pub(crate) fn take(&self) -> Vec<PendingEvent> {
+        let mut counter = [0u8; 8];
+        let _ = io::read(self.waker.as_fd(), &mut counter);
+        self.receiver
+            .try_iter()
+            .filter_map(|message| self.to_pending(message))
+            .collect()
+    }
+
+    /// Converts the message into an event, `None` means the callback isn't
+    /// registered anymore.
+    fn to_pending(&self, message: LoopMessage) -> Option<PendingEvent> {
+        match message {
+            LoopMessage::Event(event) => Some(PendingEvent::Event(event)),
+            #[cfg(feature = "tray")]
+            LoopMessage::Callback(id) => self.callbacks.get(id).map(PendingEvent::Callback),
+        }
+    }
+}
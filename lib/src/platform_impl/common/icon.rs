@@ -0,0 +1,142 @@
+use std::{fs, io::Cursor};
+
+use png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+
+use crate::{
+    platform::IconApi,
+    platform_impl::freedesktop::find_icon,
+    ContextOwner,
+    Error,
+    ErrorKind,
+    IconType,
+};
+
+/// Decodes a PNG image.
+///
+/// Returns the image width, height and RGBA pixels.
+pub(crate) fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), Error> {
+    let mut decoder = Decoder::new(Cursor::new(data));
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| Error::with_source(ErrorKind::ImageDecode, "PNG image not decoded.", e))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|e| Error::with_source(ErrorKind::ImageDecode, "PNG image not decoded.", e))?;
+    buffer.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        ColorType::Rgba => buffer,
+        ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+            .collect(),
+        ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        ColorType::Grayscale => buffer.iter().flat_map(|p| [*p, *p, *p, u8::MAX]).collect(),
+        ColorType::Indexed => {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                "indexed PNG images are not supported.",
+            ))
+        }
+    };
+
+    Ok((info.width, info.height, rgba))
+}
+
+/// Encodes RGBA pixels as a PNG image.
+pub(crate) fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    let mut encoder = Encoder::new(&mut data, width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| Error::with_source(ErrorKind::Os, "PNG image not encoded.", e))?;
+    Ok(data)
+}
+
+/// Platform independent icon.
+///
+/// Pixels are only kept for the X11 backend and tray icons, which send them to
+/// the system, other backends just validate the image.
+#[derive(Debug, Clone)]
+pub(crate) struct IconImpl {
+    /// Width, height and RGBA pixels.
+    #[cfg(all(
+        not(feature = "headless"),
+        any(feature = "tray", all(feature = "x11", not(feature = "wayland")))
+    ))]
+    image: (u32, u32, Vec<u8>),
+}
+
+impl IconImpl {
+    fn from_png(data: &[u8]) -> Result<Self, Error> {
+        let _image = decode_png(data)?;
+        Ok(Self {
+            #[cfg(all(
+                not(feature = "headless"),
+                any(feature = "tray", all(feature = "x11", not(feature = "wayland")))
+            ))]
+            image: _image,
+        })
+    }
+}
+
+#[cfg(all(
+    not(feature = "headless"),
+    any(feature = "tray", all(feature = "x11", not(feature = "wayland")))
+))]
+impl IconImpl {
+    #[inline]
+    pub(crate) fn width(&self) -> u32 { self.image.0 }
+
+    #[inline]
+    pub(crate) fn height(&self) -> u32 { self.image.1 }
+
+    /// Returns pixels packed as `0xAARRGGBB` words.
+    pub(crate) fn argb(&self) -> Vec<u32> {
+        self.image
+            .2
+            .chunks_exact(4)
+            .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]]))
+            .collect()
+    }
+}
+
+impl IconApi for IconImpl {
+    #[inline]
+    fn from_data(
+        _ctx: &impl ContextOwner,
+        icon_data: &[u8],
+        icon_type: IconType,
+    ) -> Result<Self, Error> {
+        match icon_type {
+            IconType::Png => Self::from_png(icon_data),
+            _ => Err(Error::new(
+                ErrorKind::NotSupported,
+                "only PNG icons are supported on this platform.",
+            )),
+        }
+    }
+
+    #[inline]
+    fn from_str(_ctx: &impl ContextOwner, title: &str) -> Result<Self, Error> {
+        let path = find_icon(title).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("icon \"{}\" not found.", title),
+            )
+        })?;
+        let data =
+            fs::read(path).map_err(|e| Error::with_source(ErrorKind::Os, "icon not loaded.", e))?;
+        Self::from_png(&data)
+    }
+}
@@ -0,0 +1,55 @@
+use xkeysym::{key, Keysym};
+
+use crate::{Key, NamedKey};
+
+/// Converts an X keysym into a logical key.
+///
+/// X11 and Wayland share keysyms, see: https://www.x.org/releases/current/doc/xproto/x11protocol.html#keysym_encoding
+pub(crate) fn keysym_to_key(keysym: Keysym) -> Key {
+    let named_key = match keysym.raw() {
+        key::Alt_L | key::Alt_R | key::Meta_L | key::Meta_R | key::ISO_Level3_Shift => {
+            NamedKey::Alt
+        }
+        key::Caps_Lock => NamedKey::CapsLock,
+        key::Control_L | key::Control_R => NamedKey::Control,
+        key::Num_Lock => NamedKey::NumLock,
+        key::Scroll_Lock => NamedKey::ScrollLock,
+        key::Shift_L | key::Shift_R => NamedKey::Shift,
+        key::Super_L | key::Super_R | key::Hyper_L | key::Hyper_R => NamedKey::Super,
+        key::Return | key::KP_Enter => NamedKey::Enter,
+        key::Tab | key::KP_Tab | key::ISO_Left_Tab => NamedKey::Tab,
+        key::space | key::KP_Space => NamedKey::Space,
+        key::Down | key::KP_Down => NamedKey::ArrowDown,
+        key::Left | key::KP_Left => NamedKey::ArrowLeft,
+        key::Right | key::KP_Right => NamedKey::ArrowRight,
+        key::Up | key::KP_Up => NamedKey::ArrowUp,
+        key::End | key::KP_End => NamedKey::End,
+        key::Home | key::KP_Home => NamedKey::Home,
+        key::Page_Down | key::KP_Page_Down => NamedKey::PageDown,
+        key::Page_Up | key::KP_Page_Up => NamedKey::PageUp,
+        key::BackSpace => NamedKey::Backspace,
+        key::Delete | key::KP_Delete => NamedKey::Delete,
+        key::Insert | key::KP_Insert => NamedKey::Insert,
+        key::Escape => NamedKey::Escape,
+        key::Menu => NamedKey::ContextMenu,
+        key::Pause => NamedKey::Pause,
+        key::Print => NamedKey::PrintScreen,
+        raw @ key::F1..=key::F24 => NamedKey::F((raw - key::F1 + 1) as u8),
+        _ => {
+            return keysym_to_text(keysym)
+                .map(Key::Character)
+                .unwrap_or(Key::Unidentified)
+        }
+    };
+    Key::Named(named_key)
+}
+
+/// Returns text produced by a keysym.
+///
+/// Control characters aren't considered as text.
+pub(crate) fn keysym_to_text(keysym: Keysym) -> Option<String> {
+    keysym
+        .key_char()
+        .filter(|character| !character.is_control())
+        .map(|character| character.to_string())
+}
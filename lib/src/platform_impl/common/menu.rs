@@ -0,0 +1,394 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    platform::{MenuApi, MenuItemApi, Wrapper},
+    Accelerator,
+    Action,
+    CheckState,
+    ContextOwner,
+    Error,
+    Event,
+    Icon,
+    Key,
+    Menu,
+    MenuCallback,
+    MenuItem,
+    Modifiers,
+    PredefinedItem,
+    UserEvent,
+};
+
+/// Check state of a menu item shared with snapshots of its menu, which update
+/// it when the item is chosen.
+type SharedCheckState = Arc<Mutex<Option<CheckState>>>;
+
+#[inline]
+fn lock(state: &SharedCheckState) -> MutexGuard<'_, Option<CheckState>> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Check state and radio group of an item in a menu snapshot.
+#[derive(Debug, Clone)]
+pub(crate) struct CheckEntry {
+    state:       SharedCheckState,
+    radio_group: Option<String>,
+}
+
+impl CheckEntry {
+    /// Returns the check entry of the item or `None` if it isn't checkable.
+    #[cfg(all(
+        not(feature = "headless"),
+        any(feature = "tray", all(feature = "x11", not(feature = "wayland")))
+    ))]
+    #[inline]
+    pub(crate) fn new(item: &MenuItemImpl) -> Option<Self> {
+        Self::from_entry(&item.entry.borrow())
+    }
+
+    fn from_entry(entry: &ItemEntry) -> Option<Self> {
+        let state = *lock(&entry.check_state);
+        state.map(|_| Self {
+            state:       entry.check_state.clone(),
+            radio_group: entry.radio_group.clone(),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn state(&self) -> CheckState { lock(&self.state).unwrap_or(CheckState::Unchecked) }
+
+    #[inline]
+    pub(crate) fn is_radio(&self) -> bool { self.radio_group.is_some() }
+
+    #[inline]
+    fn set_state(&self, state: CheckState) { *lock(&self.state) = Some(state); }
+
+    /// Updates the state of the chosen item and returns it.
+    ///
+    /// Other items of its radio group are unchecked.
+    ///
+    /// # Parameters:
+    /// * `siblings` - Check entries of the menu the item belongs to.
+    pub(crate) fn choose<'a, I>(&self, siblings: I) -> CheckState
+    where
+        I: IntoIterator<Item = &'a CheckEntry>,
+    {
+        if self.is_radio() {
+            siblings
+                .into_iter()
+                .filter(|entry| entry.radio_group == self.radio_group)
+                .filter(|entry| !Arc::ptr_eq(&entry.state, &self.state))
+                .for_each(|entry| entry.set_state(CheckState::Unchecked));
+        }
+        let state = self.state().chosen(self.is_radio());
+        self.set_state(state);
+        state
+    }
+}
+
+/// Event or menu callback waiting to be delivered by the event loop.
+#[derive(Debug)]
+pub(crate) enum PendingEvent {
+    Event(Event<UserEvent>),
+    Callback(MenuCallback),
+}
+
+impl From<Event<UserEvent>> for PendingEvent {
+    #[inline]
+    fn from(event: Event<UserEvent>) -> Self { Self::Event(event) }
+}
+
+/// Chosen item of a menu snapshot.
+#[derive(Debug, Default)]
+pub(crate) struct MenuChoice {
+    pub(crate) action: Option<Action>,
+    /// New check state of the item.
+    pub(crate) state:  Option<CheckState>,
+}
+
+impl MenuChoice {
+    /// Returns the event or the callback of the item to be delivered by the
+    /// event loop.
+    pub(crate) fn into_pending(self) -> Option<PendingEvent> {
+        match self.action? {
+            Action::Event(name) => Some(PendingEvent::Event(Event::Menu {
+                action: name,
+                state:  self.state,
+            })),
+            Action::Callback(callback) => Some(PendingEvent::Callback(callback)),
+        }
+    }
+}
+
+/// State of a menu item that accelerators read when a key is pressed.
+///
+/// The item shares it with the menu it belongs to, so accelerators of an
+/// installed menu follow later changes of its items.
+#[derive(Debug)]
+struct ItemEntry {
+    separator:   bool,
+    enabled:     bool,
+    accelerator: Option<Accelerator>,
+    action:      Option<Action>,
+    check_state: SharedCheckState,
+    radio_group: Option<String>,
+    submenu:     Option<SharedEntries>,
+}
+
+/// Entries of menu items in the order of the menu.
+type SharedEntries = Rc<RefCell<Vec<Rc<RefCell<ItemEntry>>>>>;
+
+/// Accelerators of the application menu.
+///
+/// Platforms without a global menu trigger them with key presses in windows.
+/// They are looked up in the menu on every key press, so changes of the menu
+/// after it has been set apply right away.
+#[derive(Debug, Default)]
+pub(crate) struct Accelerators {
+    entries: Option<SharedEntries>,
+}
+
+impl Accelerators {
+    #[inline]
+    pub(crate) fn new(menu: Option<&Menu>) -> Self {
+        Self {
+            entries: menu.map(|menu| menu.get_impl().entries.clone()),
+        }
+    }
+
+    /// Chooses the item whose accelerator is triggered by the key press.
+    ///
+    /// Returns `None` if no accelerator matches the key press.
+    ///
+    /// # Parameters:
+    /// * `key` - Pressed key.
+    /// * `modifiers` - Modifiers at the time of the key press.
+    #[inline]
+    pub(crate) fn choose(&self, key: &Key, modifiers: Modifiers) -> Option<MenuChoice> {
+        choose_item(self.entries.as_ref()?, key, modifiers)
+    }
+}
+
+/// Chooses an enabled item of the menu or its submenus whose accelerator is
+/// triggered by the key press.
+///
+/// Items are checked before their submenus.
+fn choose_item(entries: &SharedEntries, key: &Key, modifiers: Modifiers) -> Option<MenuChoice> {
+    let entries = entries.borrow();
+    for entry in entries.iter() {
+        let entry = entry.borrow();
+        if !entry.enabled || entry.separator {
+            continue;
+        }
+
+        let accelerator = entry.accelerator.as_ref();
+        if accelerator.is_some_and(|accelerator| accelerator.matches(key, modifiers)) {
+            let siblings = entries
+                .iter()
+                .filter_map(|sibling| CheckEntry::from_entry(&sibling.borrow()))
+                .collect::<Vec<_>>();
+            return Some(MenuChoice {
+                action: entry.action.clone(),
+                state:  CheckEntry::from_entry(&entry).map(|check| check.choose(&siblings)),
+            });
+        }
+
+        let choice = entry
+            .submenu
+            .as_ref()
+            .and_then(|submenu| choose_item(submenu, key, modifiers));
+        if choice.is_some() {
+            return choice;
+        }
+    }
+    None
+}
+
+/// Menu item for platforms without native menu objects.
+///
+/// The item only keeps its state, backends read it when they need to present
+/// the menu.
+#[derive(Debug)]
+pub(crate) struct MenuItemImpl {
+    title:       String,
+    action:      Option<Action>,
+    submenu:     Option<Menu>,
+    accelerator: Option<Accelerator>,
+    tooltip:     Option<String>,
+    icon:        Option<Icon>,
+    entry:       Rc<RefCell<ItemEntry>>,
+}
+
+// Read by backends that present menus themselves.
+#[cfg(any(
+    feature = "headless",
+    feature = "tray",
+    all(feature = "x11", not(feature = "wayland"))
+))]
+impl MenuItemImpl {
+    #[inline]
+    pub(crate) fn is_separator(&self) -> bool { self.entry.borrow().separator }
+
+    #[inline]
+    pub(crate) fn action(&self) -> Option<&Action> { self.action.as_ref() }
+}
+
+impl MenuItemApi for MenuItemImpl {
+    #[inline]
+    fn new(_ctx: &impl ContextOwner, separator: bool) -> Result<Self, Error> {
+        Ok(Self {
+            title:       String::new(),
+            action:      None,
+            submenu:     None,
+            accelerator: None,
+            tooltip:     None,
+            icon:        None,
+            entry:       Rc::new(RefCell::new(ItemEntry {
+                separator,
+                enabled: true,
+                accelerator: None,
+                action: None,
+                check_state: Default::default(),
+                radio_group: None,
+                submenu: None,
+            })),
+        })
+    }
+
+    #[inline]
+    fn set_title(&mut self, title: String) { self.title = title; }
+
+    #[inline]
+    fn title(&self) -> String { self.title.clone() }
+
+    #[inline]
+    fn set_action(&mut self, action: Option<Action>) {
+        self.entry.borrow_mut().action = action.clone();
+        self.action = action;
+    }
+
+    #[inline]
+    fn has_action(&self, name: &str) -> bool {
+        matches!(&self.action, Some(Action::Event(action)) if action == name)
+    }
+
+    #[inline]
+    fn set_submenu(&mut self, submenu: Option<Menu>) {
+        self.entry.borrow_mut().submenu =
+            submenu.as_ref().map(|menu| menu.get_impl().entries.clone());
+        self.submenu = submenu;
+    }
+
+    #[inline]
+    fn submenu(&self) -> Option<&Menu> { self.submenu.as_ref() }
+
+    #[inline]
+    fn submenu_mut(&mut self) -> Option<&mut Menu> { self.submenu.as_mut() }
+
+    #[inline]
+    fn has_submenu(&self) -> bool { self.submenu.is_some() }
+
+    #[inline]
+    fn set_accelerator(&mut self, accelerator: Option<Accelerator>) {
+        self.entry.borrow_mut().accelerator = accelerator.clone();
+        self.accelerator = accelerator;
+    }
+
+    #[inline]
+    fn accelerator(&self) -> Option<&Accelerator> { self.accelerator.as_ref() }
+
+    #[inline]
+    fn set_enabled(&mut self, enabled: bool) { self.entry.borrow_mut().enabled = enabled; }
+
+    #[inline]
+    fn enabled(&self) -> bool { self.entry.borrow().enabled }
+
+    #[inline]
+    fn set_tooltip(&mut self, tooltip: Option<String>) { self.tooltip = tooltip; }
+
+    #[inline]
+    fn tooltip(&self) -> Option<String> { self.tooltip.clone() }
+
+    #[inline]
+    fn set_icon(&mut self, icon: Option<Icon>) { self.icon = icon; }
+
+    #[inline]
+    fn icon(&self) -> Option<&Icon> { self.icon.as_ref() }
+
+    #[inline]
+    fn set_check_state(&mut self, state: Option<CheckState>) {
+        *lock(&self.entry.borrow().check_state) = state;
+    }
+
+    #[inline]
+    fn check_state(&self) -> Option<CheckState> { *lock(&self.entry.borrow().check_state) }
+
+    #[inline]
+    fn set_radio_group(&mut self, group: Option<String>) {
+        self.entry.borrow_mut().radio_group = group;
+    }
+
+    #[inline]
+    fn radio_group(&self) -> Option<String> { self.entry.borrow().radio_group.clone() }
+
+    fn set_predefined(&mut self, item: PredefinedItem) {
+        // There are no native roles, so the item only gets a portable action
+        self.set_action(Some(match item {
+            PredefinedItem::Quit => Action::new_callback(|app| app.stop()),
+            item => Action::new_event(item.name()),
+        }));
+    }
+}
+
+/// Menu for platforms without native menu objects.
+#[derive(Debug)]
+pub(crate) struct MenuImpl {
+    items:   Vec<MenuItem>,
+    /// Entries of the items, shared with accelerators of the installed menu.
+    entries: SharedEntries,
+}
+
+impl MenuApi for MenuImpl {
+    #[inline]
+    fn new(_ctx: &impl ContextOwner, items: Vec<MenuItem>) -> Self {
+        let entries = items
+            .iter()
+            .map(|item| item.get_impl().entry.clone())
+            .collect();
+        Self {
+            items,
+            entries: Rc::new(RefCell::new(entries)),
+        }
+    }
+
+    #[inline]
+    fn add_item(&mut self, item: MenuItem) {
+        self.entries
+            .borrow_mut()
+            .push(item.get_impl().entry.clone());
+        self.items.push(item);
+    }
+
+    #[inline]
+    fn insert_item(&mut self, index: usize, item: MenuItem) {
+        self.items.insert(index, item);
+        let entry = self.items[index].get_impl().entry.clone();
+        self.entries.borrow_mut().insert(index, entry);
+    }
+
+    #[inline]
+    fn remove_item(&mut self, index: usize) -> MenuItem {
+        let item = self.items.remove(index);
+        self.entries.borrow_mut().remove(index);
+        item
+    }
+
+    #[inline]
+    fn items(&self) -> &[MenuItem] { &self.items }
+
+    #[inline]
+    fn items_mut(&mut self) -> &mut [MenuItem] { &mut self.items }
+}
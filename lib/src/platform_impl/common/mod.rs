@@ -0,0 +1,45 @@
+mod clipboard;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+mod dnd;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+mod event_loop_proxy;
+mod icon;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+mod keyboard;
+mod menu;
+mod window_utils;
+
+pub(crate) use clipboard::*;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+pub(crate) use dnd::*;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+pub(crate) use event_loop_proxy::*;
+pub(crate) use icon::*;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "x11", feature = "wayland"),
+    not(feature = "headless")
+))]
+pub(crate) use keyboard::*;
+pub(crate) use menu::*;
+pub(crate) use window_utils::*;
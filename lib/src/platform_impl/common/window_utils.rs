@@ -0,0 +1,18 @@
+use crate::WindowOptions;
+
+/// Window options used when no options were passed to the window builder.
+///
+/// They match a regular titled window that can be resized, minimized and
+/// closed.
+#[inline]
+pub(crate) fn default_options() -> WindowOptions {
+    WindowOptions {
+        titled:      true,
+        minimizable: true,
+        closable:    true,
+        resizable:   true,
+        draggable:   true,
+        fullscreen:  true,
+        borderless:  false,
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+const ICON_SIZES: [&str; 7] = [
+    "512x512", "256x256", "128x128", "64x64", "48x48", "32x32", "16x16",
+];
+
+fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => dirs.push(PathBuf::from(dir)),
+        _ => {
+            if let Some(home) = env::var_os("HOME") {
+                dirs.push(Path::new(&home).join(".local/share"));
+            }
+        }
+    }
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+    dirs.extend(data_dirs.split(':').map(PathBuf::from));
+
+    dirs
+}
+
+fn find_in_size_dir(dir: &Path, name: &str) -> Option<PathBuf> {
+    let contexts = dir.read_dir().ok()?;
+    contexts
+        .filter_map(|context| context.ok())
+        .map(|context| context.path().join(format!("{}.png", name)))
+        .find(|path| path.is_file())
+}
+
+/// Looks up a PNG icon by its name in the `hicolor` icon theme and the
+/// legacy `pixmaps` directory.
+///
+/// See: https://specifications.freedesktop.org/icon-theme-spec/latest/
+pub(crate) fn find_icon(name: &str) -> Option<PathBuf> {
+    let dirs = data_dirs();
+
+    for size in ICON_SIZES.iter() {
+        for dir in dirs.iter() {
+            let size_dir = dir.join("icons/hicolor").join(size);
+            if let Some(path) = find_in_size_dir(&size_dir, name) {
+                return Some(path);
+            }
+        }
+    }
+
+    dirs.iter()
+        .map(|dir| dir.join("pixmaps").join(format!("{}.png", name)))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,13 @@
+//! Implementations of freedesktop.org specifications shared by Linux backends.
+
+mod icon_theme;
+#[cfg(all(feature = "notifications", not(feature = "headless")))]
+mod notification;
+#[cfg(all(feature = "tray", not(feature = "headless")))]
+mod tray;
+
+pub(crate) use icon_theme::*;
+#[cfg(all(feature = "notifications", not(feature = "headless")))]
+pub(crate) use notification::*;
+#[cfg(all(feature = "tray", not(feature = "headless")))]
+pub(crate) use tray::*;
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use zbus::{blocking::Connection, zvariant::Value};
+
+use crate::{platform::NotificationApi, ActiveApplication};
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// Desktop notifications sent through the session bus.
+///
+/// See: https://specifications.freedesktop.org/notification-spec/latest/
+#[derive(Debug)]
+pub(crate) struct NotificationImpl;
+
+impl NotificationApi for NotificationImpl {
+    fn show(
+        _app: &ActiveApplication,
+        title: Option<String>,
+        message: Option<String>,
+        _action: Option<String>,
+    ) {
+        let Ok(connection) = Connection::session() else {
+            return;
+        };
+
+        let hints: HashMap<&str, Value> = HashMap::new();
+        let _ = connection.call_method(
+            Some(DESTINATION),
+            PATH,
+            Some(INTERFACE),
+            "Notify",
+            &(
+                "",
+                0u32,
+                "",
+                title.unwrap_or_default(),
+                message.unwrap_or_default(),
+                Vec::<&str>::new(),
+                hints,
+                -1i32,
+            ),
+        );
+    }
+}
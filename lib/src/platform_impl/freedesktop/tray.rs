@@ -0,0 +1,686 @@
+use std::{
+    collections::HashMap,
+    process,
+    rc::Rc,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use zbus::{
+    blocking::{connection::Builder, Connection},
+    fdo,
+    interface,
+    object_server::SignalContext,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Str, Structure, Value},
+};
+
+use crate::{
+    platform::{MenuItemApi, Wrapper},
+    platform_impl::common::{
+        CallbackId,
+        CheckEntry,
+        EventLoopProxyImpl,
+        LoopMessage,
+        MenuCallbacks,
+    },
+    Accelerator,
+    Action,
+    CheckState,
+    Error,
+    ErrorKind,
+    Event,
+    Icon,
+    Key,
+    Menu,
+    Modifiers,
+    NamedKey,
+    TrayEventKind,
+    TrayId,
+};
+
+const ITEM_PATH: &str = "/StatusNotifierItem";
+const ITEM_INTERFACE: &str = "org.kde.StatusNotifierItem";
+const MENU_PATH: &str = "/MenuBar";
+const MENU_INTERFACE: &str = "com.canonical.dbusmenu";
+const WATCHER_DESTINATION: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+const WATCHER_INTERFACE: &str = "org.kde.StatusNotifierWatcher";
+
+/// Hosts report clicks one by one, so close clicks are paired as a double
+/// click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// ID of the root menu item.
+const ROOT_ID: i32 = 0;
+
+type Pixmap = (i32, i32, Vec<u8>);
+type Properties = HashMap<String, OwnedValue>;
+type Layout = (i32, Properties, Vec<OwnedValue>);
+
+/// Converts an icon into the pixmap format of the protocol.
+///
+/// Pixels are ARGB32 in the network byte order.
+fn to_pixmap(icon: &Icon) -> Pixmap {
+    let icon = icon.get_impl();
+    let data = icon
+        .argb()
+        .into_iter()
+        .flat_map(|pixel| pixel.to_be_bytes())
+        .collect();
+    (icon.width() as i32, icon.height() as i32, data)
+}
+
+/// Converts an accelerator into the shortcut format of the protocol, names of
+/// modifiers followed by the key.
+fn to_shortcut(accelerator: &Accelerator) -> Vec<String> {
+    let modifiers = [
+        (Modifiers::CONTROL, "Control"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::SUPER, "Super"),
+    ];
+    let mut shortcut = modifiers
+        .into_iter()
+        .filter(|(modifier, _)| accelerator.modifiers().contains(*modifier))
+        .map(|(_, name)| name.to_owned())
+        .collect::<Vec<_>>();
+    let key = match accelerator.key() {
+        Key::Character(character) => character.clone(),
+        Key::Named(NamedKey::F(number)) => format!("F{}", number),
+        Key::Named(named_key) => {
+            // Keys are named after their keysyms
+            let name = match named_key {
+                NamedKey::Enter => "Return",
+                NamedKey::Tab => "Tab",
+                NamedKey::Space => "space",
+                NamedKey::ArrowDown => "Down",
+                NamedKey::ArrowLeft => "Left",
+                NamedKey::ArrowRight => "Right",
+                NamedKey::ArrowUp => "Up",
+                NamedKey::End => "End",
+                NamedKey::Home => "Home",
+                NamedKey::PageDown => "Page_Down",
+                NamedKey::PageUp => "Page_Up",
+                NamedKey::Backspace => "BackSpace",
+                NamedKey::Delete => "Delete",
+                NamedKey::Insert => "Insert",
+                NamedKey::Escape => "Escape",
+                NamedKey::Pause => "Pause",
+                NamedKey::PrintScreen => "Print",
+                _ => return Vec::new(),
+            };
+            name.to_owned()
+        }
+        Key::Unidentified => return Vec::new(),
+    };
+    shortcut.push(key);
+    shortcut
+}
+
+/// Action of a menu entry, callbacks stay on the event loop thread.
+#[derive(Debug, Clone)]
+enum EntryAction {
+    Event(String),
+    Callback(CallbackId),
+}
+
+impl EntryAction {
+    /// Copies the action, a callback is registered to get its ID.
+    fn new(action: &Action, callbacks: &MenuCallbacks) -> Self {
+        match action {
+            Action::Event(name) => Self::Event(name.clone()),
+            Action::Callback(callback) => Self::Callback(callbacks.register(callback.clone())),
+        }
+    }
+}
+
+/// Menu item exported with the DBusMenu protocol.
+#[derive(Debug, Clone)]
+struct MenuEntry {
+    label:     String,
+    enabled:   bool,
+    separator: bool,
+    action:    Option<EntryAction>,
+    check:     Option<CheckEntry>,
+    shortcut:  Vec<String>,
+    children:  Vec<i32>,
+}
+
+impl MenuEntry {
+    fn properties(&self, names: &[String]) -> Properties {
+        let mut properties = Properties::new();
+        if self.separator {
+            properties.insert("type".into(), Str::from("separator").into());
+        } else {
+            // Underscores mark mnemonics
+            let label = self.label.replace('_', "__");
+            properties.insert("label".into(), Str::from(label).into());
+            properties.insert("enabled".into(), self.enabled.into());
+        }
+        if let Some(check) = &self.check {
+            let toggle_type = if check.is_radio() {
+                "radio"
+            } else {
+                "checkmark"
+            };
+            let toggle_state = match check.state() {
+                CheckState::Unchecked => 0,
+                CheckState::Checked => 1,
+                CheckState::Mixed => -1,
+            };
+            properties.insert("toggle-type".into(), Str::from(toggle_type).into());
+            properties.insert("toggle-state".into(), toggle_state.into());
+        }
+        if !self.shortcut.is_empty() {
+            let shortcut = vec![self.shortcut.clone()];
+            if let Ok(shortcut) = OwnedValue::try_from(Value::from(shortcut)) {
+                properties.insert("shortcut".into(), shortcut);
+            }
+        }
+        if !self.children.is_empty() {
+            properties.insert("children-display".into(), Str::from("submenu").into());
+        }
+        // No names stand for all properties
+        if !names.is_empty() {
+            properties.retain(|name, _| names.contains(name));
+        }
+        properties
+    }
+}
+
+/// Snapshot of a menu, item IDs are indices of entries.
+#[derive(Debug, Clone)]
+struct MenuLayout {
+    entries: Vec<MenuEntry>,
+}
+
+impl MenuLayout {
+    /// Takes a snapshot of the menu.
+    ///
+    /// # Parameters:
+    /// * `menu` - Menu.
+    /// * `callbacks` - Registry of callbacks of the snapshot.
+    fn new(menu: Option<&Menu>, callbacks: &MenuCallbacks) -> Self {
+        let mut layout = Self {
+            entries: vec![MenuEntry {
+                label:     String::new(),
+                enabled:   true,
+                separator: false,
+                action:    None,
+                check:     None,
+                shortcut:  Vec::new(),
+                children:  Vec::new(),
+            }],
+        };
+        if let Some(menu) = menu {
+            layout.entries[ROOT_ID as usize].children = layout.add_items(menu, callbacks);
+        }
+        layout
+    }
+
+    /// Adds items of the menu and returns their IDs.
+    fn add_items(&mut self, menu: &Menu, callbacks: &MenuCallbacks) -> Vec<i32> {
+        menu.items()
+            .iter()
+            .map(|item| {
+                let item = item.get_impl();
+                let id = self.entries.len();
+                self.entries.push(MenuEntry {
+                    label:     item.title(),
+                    enabled:   item.enabled(),
+                    separator: item.is_separator(),
+                    action:    item
+                        .action()
+                        .map(|action| EntryAction::new(action, callbacks)),
+                    check:     CheckEntry::new(item),
+                    shortcut:  item.accelerator().map(to_shortcut).unwrap_or_default(),
+                    children:  Vec::new(),
+                });
+                if let Some(submenu) = item.submenu() {
+                    self.entries[id].children = self.add_items(submenu, callbacks);
+                }
+                id as i32
+            })
+            .collect()
+    }
+
+    /// Returns IDs of registered callbacks of the snapshot.
+    fn callback_ids(&self) -> impl Iterator<Item = CallbackId> + '_ {
+        self.entries.iter().filter_map(|entry| match entry.action {
+            Some(EntryAction::Callback(id)) => Some(id),
+            _ => None,
+        })
+    }
+
+    #[inline]
+    fn entry(&self, id: i32) -> Option<&MenuEntry> {
+        usize::try_from(id).ok().and_then(|id| self.entries.get(id))
+    }
+
+    /// Updates check states of the chosen item and its radio group.
+    ///
+    /// Returns the new state of the item or `None` if it isn't checkable.
+    fn choose(&self, id: i32) -> Option<CheckState> {
+        let check = self.entry(id)?.check.as_ref()?;
+        let parent = self
+            .entries
+            .iter()
+            .find(|entry| entry.children.contains(&id))?;
+        let siblings = parent
+            .children
+            .iter()
+            .filter_map(|child| self.entry(*child)?.check.as_ref());
+        Some(check.choose(siblings))
+    }
+
+    /// Returns the layout of the item and its descendants.
+    ///
+    /// # Parameters:
+    /// * `id` - Item ID.
+    /// * `depth` - Depth of descendants, `-1` means all of them.
+    /// * `names` - Names of the returned properties, all if empty.
+    fn layout(&self, id: i32, depth: i32, names: &[String]) -> Option<Layout> {
+        let entry = self.entry(id)?;
+        let children = match depth {
+            0 => Vec::new(),
+            depth => entry
+                .children
+                .iter()
+                .filter_map(|child| self.layout(*child, depth - 1, names))
+                .filter_map(|layout| OwnedValue::try_from(Structure::from(layout)).ok())
+                .collect(),
+        };
+        Some((id, entry.properties(names), children))
+    }
+}
+
+/// State shared by the tray icon and its bus objects.
+#[derive(Debug)]
+struct ItemState {
+    pixmap:   Pixmap,
+    tooltip:  String,
+    menu:     MenuLayout,
+    revision: u32,
+}
+
+type SharedState = Arc<Mutex<ItemState>>;
+
+#[inline]
+fn lock(state: &SharedState) -> MutexGuard<'_, ItemState> {
+    // The state stays consistent even if a holder has panicked
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The `org.kde.StatusNotifierItem` object.
+struct ItemObject {
+    id:              TrayId,
+    app_id:          String,
+    state:           SharedState,
+    proxy:           EventLoopProxyImpl,
+    last_activation: Option<Instant>,
+}
+
+#[interface(name = "org.kde.StatusNotifierItem")]
+impl ItemObject {
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let now = Instant::now();
+        let is_double_click = self
+            .last_activation
+            .is_some_and(|last| now.duration_since(last) <= DOUBLE_CLICK_INTERVAL);
+        // The third click starts a new pair
+        self.last_activation = (!is_double_click).then_some(now);
+
+        let _ = self.proxy.send(LoopMessage::Event(Event::Tray {
+            id:   self.id,
+            kind: TrayEventKind::Click,
+        }));
+        if is_double_click {
+            let _ = self.proxy.send(LoopMessage::Event(Event::Tray {
+                id:   self.id,
+                kind: TrayEventKind::DoubleClick,
+            }));
+        }
+    }
+
+    fn secondary_activate(&self, _x: i32, _y: i32) {}
+
+    fn context_menu(&self, _x: i32, _y: i32) {}
+
+    fn scroll(&self, _delta: i32, _orientation: String) {}
+
+    #[zbus(property)]
+    fn category(&self) -> &str { "ApplicationStatus" }
+
+    #[zbus(property)]
+    fn id(&self) -> &str { &self.app_id }
+
+    #[zbus(property)]
+    fn title(&self) -> String { lock(&self.state).tooltip.clone() }
+
+    #[zbus(property)]
+    fn status(&self) -> &str { "Active" }
+
+    #[zbus(property)]
+    fn window_id(&self) -> i32 { 0 }
+
+    #[zbus(property)]
+    fn icon_name(&self) -> &str { "" }
+
+    #[zbus(property)]
+    fn icon_pixmap(&self) -> Vec<Pixmap> { vec![lock(&self.state).pixmap.clone()] }
+
+    #[zbus(property)]
+    fn tool_tip(&self) -> (String, Vec<Pixmap>, String, String) {
+        (
+            String::new(),
+            Vec::new(),
+            lock(&self.state).tooltip.clone(),
+            String::new(),
+        )
+    }
+
+    #[zbus(property)]
+    fn item_is_menu(&self) -> bool { false }
+
+    #[zbus(property)]
+    fn menu(&self) -> OwnedObjectPath { ObjectPath::from_static_str_unchecked(MENU_PATH).into() }
+
+    #[zbus(signal)]
+    async fn new_icon(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn new_title(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn new_tool_tip(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+}
+
+/// The `com.canonical.dbusmenu` object.
+struct MenuObject {
+    state: SharedState,
+    proxy: EventLoopProxyImpl,
+}
+
+impl MenuObject {
+    /// Triggers an action of the clicked item.
+    ///
+    /// A checkable item changes its state, which bumps the layout revision.
+    ///
+    /// Returns `false` if there is no item with the ID.
+    fn handle_event(&self, id: i32, event_id: &str) -> bool {
+        let (action, check_state) = {
+            let mut state = lock(&self.state);
+            let action = match state.menu.entry(id) {
+                Some(entry) => entry.action.clone(),
+                None => return false,
+            };
+            if event_id != "clicked" {
+                return true;
+            }
+            let check_state = state.menu.choose(id);
+            if check_state.is_some() {
+                state.revision += 1;
+            }
+            (action, check_state)
+        };
+
+        let message = match action {
+            Some(EntryAction::Event(name)) => LoopMessage::Event(Event::Menu {
+                action: name,
+                state:  check_state,
+            }),
+            Some(EntryAction::Callback(id)) => LoopMessage::Callback(id),
+            None => return true,
+        };
+        let _ = self.proxy.send(message);
+        true
+    }
+
+    /// Notifies the host that check states have changed since the revision.
+    async fn notify_layout(&self, ctxt: &SignalContext<'_>, revision: u32) {
+        let current = lock(&self.state).revision;
+        if current != revision {
+            let _ = Self::layout_updated(ctxt, current, ROOT_ID).await;
+        }
+    }
+}
+
+#[interface(name = "com.canonical.dbusmenu")]
+impl MenuObject {
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: Vec<String>,
+    ) -> fdo::Result<(u32, Layout)> {
+        let state = lock(&self.state);
+        let layout = state
+            .menu
+            .layout(parent_id, recursion_depth, &property_names)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown menu item: {}.", parent_id)))?;
+        Ok((state.revision, layout))
+    }
+
+    fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        property_names: Vec<String>,
+    ) -> Vec<(i32, Properties)> {
+        let state = lock(&self.state);
+        ids.into_iter()
+            .filter_map(|id| {
+                let entry = state.menu.entry(id)?;
+                Some((id, entry.properties(&property_names)))
+            })
+            .collect()
+    }
+
+    fn get_property(&self, id: i32, name: String) -> fdo::Result<OwnedValue> {
+        lock(&self.state)
+            .menu
+            .entry(id)
+            .and_then(|entry| entry.properties(std::slice::from_ref(&name)).remove(&name))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown property: {}.", name)))
+    }
+
+    async fn event(
+        &self,
+        id: i32,
+        event_id: String,
+        _data: OwnedValue,
+        _timestamp: u32,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) {
+        let revision = lock(&self.state).revision;
+        self.handle_event(id, &event_id);
+        self.notify_layout(&ctxt, revision).await;
+    }
+
+    async fn event_group(
+        &self,
+        events: Vec<(i32, String, OwnedValue, u32)>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Vec<i32> {
+        let revision = lock(&self.state).revision;
+        let errors = events
+            .into_iter()
+            .filter(|(id, event_id, ..)| !self.handle_event(*id, event_id))
+            .map(|(id, ..)| id)
+            .collect();
+        self.notify_layout(&ctxt, revision).await;
+        errors
+    }
+
+    fn about_to_show(&self, _id: i32) -> bool { false }
+
+    fn about_to_show_group(&self, _ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        (Vec::new(), Vec::new())
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> u32 { 3 }
+
+    #[zbus(property)]
+    fn text_direction(&self) -> &str { "ltr" }
+
+    #[zbus(property)]
+    fn status(&self) -> &str { "normal" }
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> Vec<String> { Vec::new() }
+
+    #[zbus(signal)]
+    async fn layout_updated(
+        ctxt: &SignalContext<'_>,
+        revision: u32,
+        parent: i32,
+    ) -> zbus::Result<()>;
+}
+
+/// Tray icon exported through the session bus.
+///
+/// The bus connection serves the objects from its own thread, which sends
+/// activations to the event loop.
+///
+/// See: https://www.freedesktop.org/wiki/Specifications/StatusNotifierItem/
+#[derive(Debug)]
+pub(crate) struct StatusNotifierItem {
+    id:         TrayId,
+    connection: Connection,
+    state:      SharedState,
+    callbacks:  Rc<MenuCallbacks>,
+}
+
+impl StatusNotifierItem {
+    /// Exports the tray icon and registers it in the StatusNotifierWatcher.
+    ///
+    /// # Parameters:
+    /// * `proxy` - Proxy of the event loop.
+    /// * `callbacks` - Registry of menu callbacks of the event loop.
+    /// * `id` - Tray icon ID.
+    /// * `app_id` - Application identifier.
+    /// * `icon` - Icon image.
+    /// * `tooltip` - Tooltip text.
+    /// * `menu` - Menu.
+    pub(crate) fn new(
+        proxy: EventLoopProxyImpl,
+        callbacks: Rc<MenuCallbacks>,
+        id: TrayId,
+        app_id: Option<&str>,
+        icon: &Icon,
+        tooltip: Option<String>,
+        menu: Option<&Menu>,
+    ) -> Result<Self, Error> {
+        let state = Arc::new(Mutex::new(ItemState {
+            pixmap:   to_pixmap(icon),
+            tooltip:  tooltip.unwrap_or_default(),
+            menu:     MenuLayout::new(menu, &callbacks),
+            revision: 1,
+        }));
+        let item = ItemObject {
+            id,
+            app_id: app_id.unwrap_or("b3").to_owned(),
+            state: state.clone(),
+            proxy: proxy.clone(),
+            last_activation: None,
+        };
+        let menu = MenuObject {
+            state: state.clone(),
+            proxy,
+        };
+
+        let name = format!("org.kde.StatusNotifierItem-{}-{}", process::id(), id);
+        let connection = Self::connect(&name, item, menu).inspect_err(|_| {
+            // The menu isn't served, so nothing refers to its callbacks
+            callbacks.unregister(lock(&state).menu.callback_ids());
+        })?;
+
+        Ok(Self {
+            id,
+            connection,
+            state,
+            callbacks,
+        })
+    }
+
+    /// Serves the objects on the session bus and registers the item in the
+    /// StatusNotifierWatcher.
+    ///
+    /// # Parameters:
+    /// * `name` - Bus name of the item.
+    /// * `item` - The `org.kde.StatusNotifierItem` object.
+    /// * `menu` - The `com.canonical.dbusmenu` object.
+    fn connect(name: &str, item: ItemObject, menu: MenuObject) -> Result<Connection, Error> {
+        let connection = Builder::session()
+            .and_then(|builder| builder.name(name))
+            .and_then(|builder| builder.serve_at(ITEM_PATH, item))
+            .and_then(|builder| builder.serve_at(MENU_PATH, menu))
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "session bus not connected.", e)
+            })?;
+        connection
+            .call_method(
+                Some(WATCHER_DESTINATION),
+                WATCHER_PATH,
+                Some(WATCHER_INTERFACE),
+                "RegisterStatusNotifierItem",
+                &name,
+            )
+            .map_err(|e| {
+                Error::with_source(
+                    ErrorKind::NotSupported,
+                    "StatusNotifierWatcher not found.",
+                    e,
+                )
+            })?;
+
+        Ok(connection)
+    }
+
+    #[inline]
+    pub(crate) fn id(&self) -> TrayId { self.id }
+
+    /// Notifies the host that a property of the item has changed.
+    #[inline]
+    fn emit_item_signal(&self, name: &str) {
+        let _ = self
+            .connection
+            .emit_signal(None::<()>, ITEM_PATH, ITEM_INTERFACE, name, &());
+    }
+
+    pub(crate) fn set_icon(&self, icon: &Icon) {
+        lock(&self.state).pixmap = to_pixmap(icon);
+        self.emit_item_signal("NewIcon");
+    }
+
+    pub(crate) fn set_tooltip(&self, tooltip: Option<String>) {
+        lock(&self.state).tooltip = tooltip.unwrap_or_default();
+        self.emit_item_signal("NewTitle");
+        self.emit_item_signal("NewToolTip");
+    }
+
+    pub(crate) fn set_menu(&self, menu: Option<&Menu>) {
+        let revision = {
+            let mut state = lock(&self.state);
+            self.callbacks.unregister(state.menu.callback_ids());
+            state.menu = MenuLayout::new(menu, &self.callbacks);
+            state.revision += 1;
+            state.revision
+        };
+        let _ = self.connection.emit_signal(
+            None::<()>,
+            MENU_PATH,
+            MENU_INTERFACE,
+            "LayoutUpdated",
+            &(revision, ROOT_ID),
+        );
+    }
+}
+
+impl Drop for StatusNotifierItem {
+    fn drop(&mut self) {
+        self.callbacks
+            .unregister(lock(&self.state).menu.callback_ids());
+    }
+}
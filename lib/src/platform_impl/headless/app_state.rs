@@ -0,0 +1,368 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    mem,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Instant,
+};
+
+#[cfg(feature = "notifications")]
+use crate::NotificationState;
+use crate::{
+    platform::{MenuItemApi, Wrapper},
+    platform_impl::common::{Accelerators, ClipboardContent, PendingEvent},
+    Action,
+    ActiveApplication,
+    ApplicationOptions,
+    ClipboardKind,
+    ContextMenuState,
+    ControlFlow,
+    Event,
+    EventHandler,
+    Icon,
+    KeyState,
+    KeyboardEvent,
+    LifeCycle,
+    Menu,
+    MenuCallback,
+    MenuItemState,
+    StartCause,
+    UserEvent,
+    WindowEvent,
+    WindowId,
+};
+#[cfg(feature = "tray")]
+use crate::{TrayId, TrayState};
+
+/// Takes a snapshot of menu items.
+pub(super) fn to_menu_state(menu: &Menu) -> Vec<MenuItemState> {
+    menu.items()
+        .iter()
+        .map(|item| {
+            let item = item.get_impl();
+            MenuItemState {
+                title:       item.title(),
+                event:       match item.action() {
+                    Some(Action::Event(name)) => Some(name.clone()),
+                    _ => None,
+                },
+                accelerator: item.accelerator().cloned(),
+                enabled:     item.enabled(),
+                separator:   item.is_separator(),
+                check_state: item.check_state(),
+                radio_group: item.radio_group(),
+                submenu:     item.submenu().map(to_menu_state),
+            }
+        })
+        .collect()
+}
+
+pub(super) struct AppState {
+    app:            RefCell<Option<ActiveApplication>>,
+    is_running:     Cell<bool>,
+    control_flow:   Cell<ControlFlow>,
+    handler:        RefCell<Option<Box<dyn EventHandler<UserEvent>>>>,
+    pending_events: RefCell<VecDeque<Event<UserEvent>>>,
+    redraws:        RefCell<Vec<WindowId>>,
+    user_sender:    Sender<UserEvent>,
+    user_receiver:  Receiver<UserEvent>,
+    next_window_id: Cell<WindowId>,
+    menu:           RefCell<Option<Vec<MenuItemState>>>,
+    accelerators:   RefCell<Accelerators>,
+    has_icon:       Cell<bool>,
+    options:        ApplicationOptions,
+    clipboards:     RefCell<HashMap<ClipboardKind, ClipboardContent>>,
+    context_menus:  RefCell<Vec<ContextMenuState>>,
+    #[cfg(feature = "notifications")]
+    notifications:  RefCell<Vec<NotificationState>>,
+    #[cfg(feature = "tray")]
+    trays:          RefCell<Vec<TrayState>>,
+}
+
+impl Debug for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState")
+            .field("is_running", &self.is_running)
+            .field("pending_events", &self.pending_events)
+            .field("menu", &self.menu)
+            .finish()
+    }
+}
+
+impl AppState {
+    pub(super) fn new(options: ApplicationOptions) -> Self {
+        let (user_sender, user_receiver) = mpsc::channel();
+
+        Self {
+            app: RefCell::new(None),
+            is_running: Cell::new(false),
+            control_flow: Cell::new(ControlFlow::default()),
+            handler: RefCell::new(None),
+            pending_events: RefCell::new(VecDeque::new()),
+            redraws: RefCell::new(Vec::new()),
+            user_sender,
+            user_receiver,
+            next_window_id: Cell::new(1),
+            menu: RefCell::new(None),
+            accelerators: RefCell::new(Accelerators::default()),
+            has_icon: Cell::new(false),
+            options,
+            clipboards: RefCell::new(HashMap::new()),
+            context_menus: RefCell::new(Vec::new()),
+            #[cfg(feature = "notifications")]
+            notifications: RefCell::new(Vec::new()),
+            #[cfg(feature = "tray")]
+            trays: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[inline]
+    pub(super) fn set_active_application(&self, active_application: ActiveApplication) {
+        *self.app.borrow_mut() = Some(active_application);
+    }
+
+    #[inline]
+    pub(super) fn set_handler(&self, handler: impl EventHandler<UserEvent> + 'static) {
+        *self.handler.borrow_mut() = Some(Box::new(handler));
+    }
+
+    #[inline]
+    pub(super) fn is_running(&self) -> bool { self.is_running.get() }
+
+    #[inline]
+    pub(super) fn set_is_running(&self, value: bool) { self.is_running.set(value) }
+
+    #[inline]
+    pub(super) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.control_flow.set(control_flow);
+    }
+
+    #[inline]
+    pub(super) fn control_flow(&self) -> ControlFlow { self.control_flow.get() }
+
+    #[inline]
+    pub(super) fn next_window_id(&self) -> WindowId {
+        let id = self.next_window_id.get();
+        self.next_window_id.set(id + 1);
+        id
+    }
+
+    #[inline]
+    pub(super) fn user_sender(&self) -> Sender<UserEvent> { self.user_sender.clone() }
+
+    #[inline]
+    pub(super) fn queue_event(&self, event: Event<UserEvent>) {
+        self.pending_events.borrow_mut().push_back(event);
+    }
+
+    /// Schedules a redraw of the window, repeated requests are coalesced.
+    pub(super) fn request_redraw(&self, window_id: WindowId) {
+        let mut redraws = self.redraws.borrow_mut();
+        if !redraws.contains(&window_id) {
+            redraws.push(window_id);
+        }
+    }
+
+    pub(super) fn handle_event(&self, event: Event<UserEvent>) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            let mut handler = self.handler.borrow_mut();
+
+            if let Some(handler) = handler.as_mut() {
+                handler.on_event(app, event);
+            }
+        }
+    }
+
+    /// Calls a menu callback with the active application.
+    fn handle_callback(&self, callback: &MenuCallback) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            callback.call(app);
+        }
+    }
+
+    /// Moves user events sent by proxies into the queue.
+    ///
+    /// Returns `true` if the queue isn't empty.
+    fn receive_user_events(&self) -> bool {
+        let mut pending_events = self.pending_events.borrow_mut();
+        pending_events.extend(self.user_receiver.try_iter().map(Event::User));
+        !pending_events.is_empty()
+    }
+
+    fn dispatch_pending_events(&self) {
+        while self.is_running() && self.receive_user_events() {
+            let event = self.pending_events.borrow_mut().pop_front();
+            match event.and_then(|event| self.trigger_accelerator(event)) {
+                Some(PendingEvent::Event(event)) => self.handle_event(event),
+                Some(PendingEvent::Callback(callback)) => self.handle_callback(&callback),
+                None => {}
+            }
+        }
+    }
+
+    /// Triggers the accelerator of the application menu matching a key press
+    /// like platforms without a global menu do.
+    ///
+    /// Returns the event or the callback to be delivered instead of the key
+    /// press, if any.
+    fn trigger_accelerator(&self, event: Event<UserEvent>) -> Option<PendingEvent> {
+        if let Event::Window(
+            WindowEvent::Keyboard(KeyboardEvent::Input {
+                key,
+                state: KeyState::Pressed,
+                modifiers,
+                ..
+            }),
+            _,
+        ) = &event
+        {
+            let choice = self.accelerators.borrow().choose(key, *modifiers);
+            if let Some(choice) = choice {
+                return choice.into_pending();
+            }
+        }
+        Some(PendingEvent::Event(event))
+    }
+
+    /// Delivers a single redraw event to every window that requested one.
+    ///
+    /// Redraws requested by the handler while redrawing are delivered in the
+    /// next iteration.
+    fn dispatch_redraw_requests(&self) {
+        let redraws = mem::take(&mut *self.redraws.borrow_mut());
+        for window_id in redraws.into_iter() {
+            if !self.is_running() {
+                break;
+            }
+            self.handle_event(Event::Window(WindowEvent::RedrawRequested, window_id));
+        }
+    }
+
+    pub(super) fn run(&self) {
+        self.set_is_running(true);
+        self.handle_event(Event::LifeCycle(LifeCycle::Started));
+        self.handle_event(Event::NewEvents(StartCause::Init));
+
+        while self.is_running() {
+            self.dispatch_pending_events();
+            self.dispatch_redraw_requests();
+            self.dispatch_pending_events();
+            if !self.is_running() {
+                break;
+            }
+            self.handle_event(Event::AboutToWait);
+            if !self.is_running() {
+                break;
+            }
+
+            // Nothing can wake the loop up once the queue is exhausted, so
+            // an unlimited wait finishes it as if the application was stopped.
+            let control_flow = self.control_flow();
+            let start = Instant::now();
+            if !self.receive_user_events() && self.redraws.borrow().is_empty() {
+                match control_flow.timeout() {
+                    Some(timeout) => thread::sleep(timeout),
+                    None => break,
+                }
+            }
+            self.handle_event(Event::NewEvents(control_flow.start_cause(start)));
+        }
+
+        self.handle_event(Event::LifeCycle(LifeCycle::Finished));
+        self.set_is_running(false);
+
+        // Break the reference cycle between the state and the active
+        // application, and release windows owned by the handler.
+        self.handler.borrow_mut().take();
+        self.app.borrow_mut().take();
+    }
+
+    #[inline]
+    pub(super) fn set_menu(&self, menu: Option<&Menu>) {
+        *self.menu.borrow_mut() = menu.map(to_menu_state);
+        *self.accelerators.borrow_mut() = Accelerators::new(menu);
+    }
+
+    #[inline]
+    pub(super) fn menu(&self) -> Option<Vec<MenuItemState>> { self.menu.borrow().clone() }
+
+    #[inline]
+    pub(super) fn set_icon(&self, icon: Option<&Icon>) { self.has_icon.set(icon.is_some()); }
+
+    #[inline]
+    pub(super) fn has_icon(&self) -> bool { self.has_icon.get() }
+
+    #[inline]
+    pub(super) fn options(&self) -> &ApplicationOptions { &self.options }
+
+    pub(super) fn set_clipboard(&self, kind: ClipboardKind, content: Option<ClipboardContent>) {
+        let mut clipboards = self.clipboards.borrow_mut();
+        match content {
+            Some(content) => clipboards.insert(kind, content),
+            None => clipboards.remove(&kind),
+        };
+    }
+
+    #[inline]
+    pub(super) fn clipboard(&self, kind: ClipboardKind) -> Option<ClipboardContent> {
+        self.clipboards.borrow().get(&kind).cloned()
+    }
+
+    #[cfg(feature = "notifications")]
+    #[inline]
+    pub(super) fn push_notification(&self, notification: NotificationState) {
+        self.notifications.borrow_mut().push(notification);
+    }
+
+    #[cfg(feature = "notifications")]
+    #[inline]
+    pub(super) fn notifications(&self) -> Vec<NotificationState> {
+        self.notifications.borrow().clone()
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn add_tray(&self, tray: TrayState) { self.trays.borrow_mut().push(tray); }
+
+    /// Updates the recorded state of the tray icon.
+    #[cfg(feature = "tray")]
+    pub(super) fn update_tray(&self, id: TrayId, f: impl FnOnce(&mut TrayState)) {
+        if let Some(tray) = self
+            .trays
+            .borrow_mut()
+            .iter_mut()
+            .find(|tray| tray.id == id)
+        {
+            f(tray);
+        }
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn remove_tray(&self, id: TrayId) {
+        self.trays.borrow_mut().retain(|tray| tray.id != id);
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn trays(&self) -> Vec<TrayState> { self.trays.borrow().clone() }
+
+    #[inline]
+    pub(super) fn push_context_menu(&self, context_menu: ContextMenuState) {
+        self.context_menus.borrow_mut().push(context_menu);
+    }
+
+    #[inline]
+    pub(super) fn context_menus(&self) -> Vec<ContextMenuState> {
+        self.context_menus.borrow().clone()
+    }
+
+    #[inline]
+    pub(super) fn stop(&self) { self.set_is_running(false); }
+}
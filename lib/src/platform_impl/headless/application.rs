@@ -0,0 +1,166 @@
+use std::{rc::Rc, sync::mpsc::Sender};
+
+use super::{app_state::AppState, clipboard::SelectionImpl, monitor::MonitorImpl, ClipboardImpl};
+#[cfg(feature = "notifications")]
+use crate::NotificationState;
+#[cfg(feature = "tray")]
+use crate::TrayState;
+use crate::{
+    platform::{ActiveApplicationApi, ApplicationApi, EventLoopProxyApi, Wrapper},
+    ActiveApplication,
+    ApplicationOptions,
+    Clipboard,
+    ClipboardKind,
+    Context,
+    ContextMenuState,
+    ContextOwner,
+    ControlFlow,
+    Error,
+    ErrorKind,
+    Event,
+    EventHandler,
+    Icon,
+    Menu,
+    MenuItemState,
+    Monitor,
+    UserEvent,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ContextImpl {
+    state: Rc<AppState>,
+}
+
+impl ContextImpl {
+    #[inline]
+    pub(super) fn state(&self) -> &Rc<AppState> { &self.state }
+
+    #[inline]
+    pub(crate) fn queue_event(&self, event: Event<UserEvent>) { self.state.queue_event(event); }
+
+    #[inline]
+    pub(crate) fn menu(&self) -> Option<Vec<MenuItemState>> { self.state.menu() }
+
+    #[inline]
+    pub(crate) fn has_icon(&self) -> bool { self.state.has_icon() }
+
+    #[inline]
+    pub(crate) fn options(&self) -> &ApplicationOptions { self.state.options() }
+
+    #[inline]
+    pub(crate) fn context_menus(&self) -> Vec<ContextMenuState> { self.state.context_menus() }
+
+    #[cfg(feature = "notifications")]
+    #[inline]
+    pub(crate) fn notifications(&self) -> Vec<NotificationState> { self.state.notifications() }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(crate) fn trays(&self) -> Vec<TrayState> { self.state.trays() }
+}
+
+#[derive(Debug)]
+pub(crate) struct ActiveApplicationImpl(Context);
+
+impl ActiveApplicationImpl {
+    #[inline]
+    fn new(context: Context) -> Self { Self(context) }
+
+    #[inline]
+    fn state(&self) -> &Rc<AppState> { self.0.get_impl().state() }
+}
+
+impl ActiveApplicationApi for ActiveApplicationImpl {
+    #[inline]
+    fn set_menu(&mut self, menu: Option<&Menu>) { self.state().set_menu(menu); }
+
+    #[inline]
+    fn set_icon(&mut self, icon: Option<&Icon>) { self.state().set_icon(icon); }
+
+    #[inline]
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        self.state().set_control_flow(control_flow);
+    }
+
+    #[inline]
+    fn control_flow(&self) -> ControlFlow { self.state().control_flow() }
+
+    #[inline]
+    fn monitors(&self) -> Vec<Monitor> { vec![Monitor::new(MonitorImpl::new())] }
+
+    #[inline]
+    fn primary_monitor(&self) -> Option<Monitor> { Some(Monitor::new(MonitorImpl::new())) }
+
+    #[inline]
+    fn clipboard(&self, kind: ClipboardKind) -> Clipboard {
+        let selection = SelectionImpl::new(self.state().clone(), kind);
+        Clipboard::new(ClipboardImpl::new(selection))
+    }
+
+    #[inline]
+    fn stop(&mut self) { self.state().stop(); }
+}
+
+impl ContextOwner for ActiveApplicationImpl {
+    #[inline]
+    fn context(&self) -> &Context { &self.0 }
+}
+
+#[derive(Debug)]
+pub(crate) struct ApplicationImpl {
+    context: Context,
+}
+
+impl ApplicationApi for ApplicationImpl {
+    #[inline]
+    fn new(options: &ApplicationOptions) -> Result<Self, Error> {
+        // Configure the application context
+        let state = Rc::new(AppState::new(options.clone()));
+        let context = Context::new(ContextImpl {
+            state: state.clone(),
+        });
+
+        // Configure the active application
+        let active_app = ActiveApplicationImpl::new(context.clone());
+        let active_app = ActiveApplication::new(active_app);
+        state.set_active_application(active_app);
+
+        Ok(Self {
+            context,
+        })
+    }
+
+    #[inline]
+    fn run(&mut self, handler: impl EventHandler<UserEvent> + 'static) {
+        let state = self.context.get_impl().state();
+        // Register an event handler
+        state.set_handler(handler);
+        state.run();
+    }
+
+    #[inline]
+    fn create_proxy(&self) -> EventLoopProxyImpl {
+        EventLoopProxyImpl {
+            sender: self.context.get_impl().state().user_sender(),
+        }
+    }
+}
+
+impl ContextOwner for ApplicationImpl {
+    #[inline]
+    fn context(&self) -> &Context { &self.context }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EventLoopProxyImpl {
+    sender: Sender<UserEvent>,
+}
+
+impl EventLoopProxyApi for EventLoopProxyImpl {
+    #[inline]
+    fn send_event(&self, event: UserEvent) -> Result<(), Error> {
+        self.sender
+            .send(event)
+            .map_err(|_| Error::new(ErrorKind::EventLoopClosed, "event loop closed."))
+    }
+}
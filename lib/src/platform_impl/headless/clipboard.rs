@@ -0,0 +1,54 @@
+use std::rc::Rc;
+
+use super::app_state::AppState;
+use crate::{platform_impl::common::ClipboardContent, ClipboardKind, Error, ErrorKind};
+
+/// In-memory selection, shared by all clipboards of the application.
+#[derive(Debug, Clone)]
+pub(crate) struct SelectionImpl {
+    state: Rc<AppState>,
+    kind:  ClipboardKind,
+}
+
+impl SelectionImpl {
+    #[inline]
+    pub(super) fn new(state: Rc<AppState>, kind: ClipboardKind) -> Self {
+        Self {
+            state,
+            kind,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn kind(&self) -> ClipboardKind { self.kind }
+
+    #[inline]
+    pub(crate) fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+        self.state.set_clipboard(self.kind, Some(content));
+        Ok(())
+    }
+
+    pub(crate) fn mime_types(&self) -> Result<Vec<String>, Error> {
+        let mime_types = self
+            .state
+            .clipboard(self.kind)
+            .map(|content| content.mime_types().map(String::from).collect())
+            .unwrap_or_default();
+        Ok(mime_types)
+    }
+
+    pub(crate) fn receive(&self, mime: &str) -> Result<Vec<u8>, Error> {
+        self.state
+            .clipboard(self.kind)
+            .and_then(|content| content.get(mime).map(<[u8]>::to_vec))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "clipboard has no data of the requested type.",
+                )
+            })
+    }
+
+    #[inline]
+    pub(crate) fn clear(&self) { self.state.set_clipboard(self.kind, None); }
+}
@@ -0,0 +1,22 @@
+//! Pure Rust backend without a windowing system, used by automated tests.
+
+mod app_state;
+mod application;
+mod clipboard;
+mod monitor;
+#[cfg(feature = "notifications")]
+mod notification;
+#[cfg(feature = "tray")]
+mod tray;
+mod window;
+
+pub(crate) use application::*;
+pub(crate) use clipboard::*;
+pub(crate) use monitor::*;
+#[cfg(feature = "notifications")]
+pub(crate) use notification::*;
+#[cfg(feature = "tray")]
+pub(crate) use tray::*;
+pub(crate) use window::*;
+
+pub(crate) use super::common::{ClipboardImpl, IconImpl, MenuImpl, MenuItemImpl};
@@ -0,0 +1,49 @@
+use dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::{platform::MonitorApi, Monitor, VideoMode};
+
+const MONITOR_NAME: &str = "Headless";
+const BIT_DEPTH: u16 = 32;
+const REFRESH_RATE: u32 = 60_000;
+// The first mode is the current one.
+const VIDEO_MODES: [(u32, u32); 3] = [(1920, 1080), (1280, 720), (800, 600)];
+
+/// Virtual monitor all windows are placed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MonitorImpl;
+
+impl MonitorImpl {
+    #[inline]
+    pub(super) fn new() -> Self { Self }
+}
+
+impl MonitorApi for MonitorImpl {
+    #[inline]
+    fn name(&self) -> Option<String> { Some(MONITOR_NAME.to_owned()) }
+
+    #[inline]
+    fn position(&self) -> PhysicalPosition<i32> { PhysicalPosition::default() }
+
+    #[inline]
+    fn size(&self) -> PhysicalSize<u32> { VIDEO_MODES[0].into() }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { 1.0 }
+
+    #[inline]
+    fn refresh_rate_millihertz(&self) -> Option<u32> { Some(REFRESH_RATE) }
+
+    fn video_modes(&self) -> Vec<VideoMode> {
+        VIDEO_MODES
+            .iter()
+            .map(|size| {
+                VideoMode::new(
+                    (*size).into(),
+                    BIT_DEPTH,
+                    REFRESH_RATE,
+                    Monitor::new(self.clone()),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{
+    platform::{NotificationApi, Wrapper},
+    ActiveApplication,
+    ContextOwner,
+    NotificationState,
+};
+
+/// Notifications recorded by the application instead of being shown.
+#[derive(Debug)]
+pub(crate) struct NotificationImpl;
+
+impl NotificationApi for NotificationImpl {
+    fn show(
+        app: &ActiveApplication,
+        title: Option<String>,
+        message: Option<String>,
+        action: Option<String>,
+    ) {
+        app.context()
+            .get_impl()
+            .state()
+            .push_notification(NotificationState {
+                title,
+                message,
+                action,
+            });
+    }
+}
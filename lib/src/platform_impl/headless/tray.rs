@@ -0,0 +1,63 @@
+use std::rc::Rc;
+
+use super::app_state::{to_menu_state, AppState};
+use crate::{
+    platform::{TrayApi, Wrapper},
+    ContextOwner,
+    Error,
+    Icon,
+    Menu,
+    TrayId,
+    TrayState,
+};
+
+/// Tray icon recorded by the application instead of being shown.
+#[derive(Debug)]
+pub(crate) struct TrayImpl {
+    state: Rc<AppState>,
+    id:    TrayId,
+}
+
+impl TrayApi for TrayImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        id: TrayId,
+        _icon: Icon,
+        tooltip: Option<String>,
+        menu: Option<Menu>,
+    ) -> Result<Self, Error> {
+        let state = ctx.context().get_impl().state().clone();
+        state.add_tray(TrayState {
+            id,
+            tooltip,
+            menu: menu.as_ref().map(to_menu_state),
+        });
+
+        Ok(Self {
+            state,
+            id,
+        })
+    }
+
+    #[inline]
+    fn id(&self) -> TrayId { self.id }
+
+    #[inline]
+    fn set_icon(&mut self, _icon: Icon) {}
+
+    #[inline]
+    fn set_tooltip(&mut self, tooltip: Option<String>) {
+        self.state
+            .update_tray(self.id, |tray| tray.tooltip = tooltip);
+    }
+
+    #[inline]
+    fn set_menu(&mut self, menu: Option<Menu>) {
+        self.state
+            .update_tray(self.id, |tray| tray.menu = menu.as_ref().map(to_menu_state));
+    }
+}
+
+impl Drop for TrayImpl {
+    fn drop(&mut self) { self.state.remove_tray(self.id); }
+}
@@ -0,0 +1,301 @@
+use std::rc::Rc;
+
+use dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+
+use super::{
+    app_state::{to_menu_state, AppState},
+    monitor::MonitorImpl,
+};
+use crate::{
+    platform::{WindowApi, Wrapper},
+    platform_impl::common::default_options,
+    ActiveApplication,
+    ContextMenuState,
+    ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    Error,
+    Event,
+    Fullscreen,
+    ImeEvent,
+    InitMode,
+    Menu,
+    Monitor,
+    MouseEvent,
+    WindowEvent,
+    WindowId,
+    WindowOptions,
+};
+
+const DEFAULT_SIZE: (f64, f64) = (800.0, 600.0);
+const SCALE_FACTOR: f64 = 1.0;
+
+/// In-memory window record.
+///
+/// Operations update the record and queue the events a windowing system
+/// would send in response.
+#[derive(Debug)]
+pub(crate) struct WindowImpl {
+    app_state:      Rc<AppState>,
+    id:             WindowId,
+    title:          String,
+    options:        WindowOptions,
+    init_mode:      Option<InitMode>,
+    size:           PhysicalSize<u32>,
+    position:       PhysicalPosition<i32>,
+    min_size:       Option<PhysicalSize<u32>>,
+    max_size:       Option<PhysicalSize<u32>>,
+    is_visible:     bool,
+    fullscreen:     Option<Fullscreen>,
+    is_maximized:   bool,
+    is_minimized:   bool,
+    is_destroyed:   bool,
+    is_ime_allowed: bool,
+    cursor:         Cursor,
+    cursor_visible: bool,
+    cursor_grab:    CursorGrabMode,
+}
+
+impl WindowImpl {
+    #[inline]
+    fn queue_event(&self, event: WindowEvent) {
+        self.app_state.queue_event(Event::Window(event, self.id));
+    }
+
+    fn clamp_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let min_size = self.min_size.unwrap_or_default();
+        let max_size = self.max_size();
+        PhysicalSize::new(
+            size.width.min(max_size.width).max(min_size.width),
+            size.height.min(max_size.height).max(min_size.height),
+        )
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        let size = self.clamp_size(size);
+        if size != self.size {
+            self.size = size;
+            self.queue_event(WindowEvent::Resized(size));
+        }
+    }
+}
+
+impl WindowApi for WindowImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        mode: InitMode,
+        options: Option<WindowOptions>,
+        size: Option<Size>,
+    ) -> Result<Self, Error> {
+        // Extract the application context
+        let app_state = ctx.context().get_impl().state().clone();
+
+        let size: PhysicalSize<u32> = size
+            .unwrap_or(Size::Logical(DEFAULT_SIZE.into()))
+            .to_physical(SCALE_FACTOR);
+
+        Ok(Self {
+            id: app_state.next_window_id(),
+            app_state,
+            title: String::new(),
+            options: options.unwrap_or_else(default_options),
+            init_mode: Some(mode),
+            size,
+            position: PhysicalPosition::default(),
+            min_size: None,
+            max_size: None,
+            is_visible: false,
+            fullscreen: None,
+            is_maximized: false,
+            is_minimized: false,
+            is_destroyed: false,
+            is_ime_allowed: false,
+            cursor: Cursor::default(),
+            cursor_visible: true,
+            cursor_grab: CursorGrabMode::None,
+        })
+    }
+
+    #[inline]
+    fn id(&self) -> WindowId { self.id }
+
+    #[inline]
+    fn set_title(&mut self, title: String) { self.title = title; }
+
+    #[inline]
+    fn title(&self) -> String { self.title.clone() }
+
+    #[inline]
+    fn set_options(&mut self, options: WindowOptions) { self.options = options; }
+
+    #[inline]
+    fn options(&self) -> WindowOptions { self.options }
+
+    fn show(&mut self, _app: &ActiveApplication) {
+        match self.init_mode.take() {
+            Some(InitMode::Minimized) => self.is_minimized = true,
+            Some(InitMode::Maximized) => self.is_maximized = true,
+            Some(InitMode::Fullscreen) if self.fullscreen.is_none() => {
+                self.set_fullscreen(Some(Fullscreen::Borderless(None)));
+            }
+            _ => (),
+        }
+
+        self.is_visible = true;
+        self.queue_event(WindowEvent::Showed);
+    }
+
+    #[inline]
+    fn show_modal(&mut self, app: &ActiveApplication) { self.show(app); }
+
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
+        let changed = fullscreen.is_some() != self.fullscreen.is_some();
+        self.fullscreen = fullscreen;
+        if changed {
+            self.queue_event(WindowEvent::FullscreenChanged(self.fullscreen.is_some()));
+        }
+    }
+
+    #[inline]
+    fn fullscreen(&self) -> Option<Fullscreen> { self.fullscreen.clone() }
+
+    #[inline]
+    fn is_fullscreen(&self) -> bool { self.fullscreen.is_some() }
+
+    #[inline]
+    fn set_frame_size(&mut self, size: Size) { self.resize(size.to_physical(SCALE_FACTOR)); }
+
+    #[inline]
+    fn frame_size(&self) -> PhysicalSize<u32> { self.size }
+
+    fn set_position(&mut self, position: Position) {
+        let position = position.to_physical(SCALE_FACTOR);
+        if position != self.position {
+            self.position = position;
+            self.queue_event(WindowEvent::Moved(position));
+        }
+    }
+
+    #[inline]
+    fn position(&self) -> PhysicalPosition<i32> { self.position }
+
+    fn set_min_size(&mut self, min_size: Size) {
+        self.min_size = Some(min_size.to_physical(SCALE_FACTOR));
+        self.resize(self.size);
+    }
+
+    #[inline]
+    fn min_size(&self) -> PhysicalSize<u32> { self.min_size.unwrap_or_default() }
+
+    fn set_max_size(&mut self, max_size: Size) {
+        self.max_size = Some(max_size.to_physical(SCALE_FACTOR));
+        self.resize(self.size);
+    }
+
+    #[inline]
+    fn max_size(&self) -> PhysicalSize<u32> {
+        self.max_size
+            .unwrap_or(PhysicalSize::new(u32::MAX, u32::MAX))
+    }
+
+    #[inline]
+    fn maximize(&mut self) { self.is_maximized = true; }
+
+    #[inline]
+    fn is_maximized(&self) -> bool { self.is_maximized }
+
+    #[inline]
+    fn content_size(&self) -> PhysicalSize<u32> { self.size }
+
+    #[inline]
+    fn is_visible(&self) -> bool { self.is_visible }
+
+    fn close(&mut self) {
+        if !self.is_destroyed {
+            self.is_destroyed = true;
+            self.is_visible = false;
+            self.queue_event(WindowEvent::Destroyed);
+        }
+    }
+
+    #[inline]
+    fn minimize(&mut self) { self.is_minimized = true; }
+
+    #[inline]
+    fn is_minimized(&self) -> bool { self.is_minimized }
+
+    #[inline]
+    fn restore(&mut self) { self.is_minimized = false; }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { SCALE_FACTOR }
+
+    #[inline]
+    fn current_monitor(&self) -> Option<Monitor> { Some(Monitor::new(MonitorImpl::new())) }
+
+    fn set_ime_allowed(&mut self, allowed: bool) {
+        if allowed != self.is_ime_allowed {
+            self.is_ime_allowed = allowed;
+            let event = if allowed {
+                ImeEvent::Enabled
+            } else {
+                ImeEvent::Disabled
+            };
+            self.queue_event(WindowEvent::Ime(event));
+        }
+    }
+
+    #[inline]
+    fn is_ime_allowed(&self) -> bool { self.is_ime_allowed }
+
+    #[inline]
+    fn set_ime_cursor_area(&mut self, _position: Position, _size: Size) {}
+
+    #[inline]
+    fn request_redraw(&mut self) {
+        if !self.is_destroyed {
+            self.app_state.request_redraw(self.id);
+        }
+    }
+
+    #[inline]
+    fn pre_present_notify(&mut self) {}
+
+    #[inline]
+    fn set_cursor(&mut self, cursor: Cursor) { self.cursor = cursor; }
+
+    #[inline]
+    fn cursor(&self) -> Cursor { self.cursor.clone() }
+
+    #[inline]
+    fn set_cursor_visible(&mut self, visible: bool) { self.cursor_visible = visible; }
+
+    #[inline]
+    fn is_cursor_visible(&self) -> bool { self.cursor_visible }
+
+    #[inline]
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error> {
+        self.cursor_grab = mode;
+        Ok(())
+    }
+
+    #[inline]
+    fn cursor_grab(&self) -> CursorGrabMode { self.cursor_grab }
+
+    fn set_cursor_position(&mut self, position: Position) -> Result<(), Error> {
+        let position = position.to_physical(SCALE_FACTOR);
+        self.queue_event(WindowEvent::Mouse(MouseEvent::Moved {
+            position,
+        }));
+        Ok(())
+    }
+
+    fn show_context_menu(&mut self, menu: &Menu, position: Position) -> Result<(), Error> {
+        self.app_state.push_context_menu(ContextMenuState {
+            window_id: self.id,
+            position:  position.to_physical(SCALE_FACTOR),
+            items:     to_menu_state(menu),
+        });
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@ use std::{
     fmt::Debug,
     mem,
     rc::Weak,
+    sync::mpsc::{self, Receiver, Sender},
+    time::Instant,
 };
 
 use objc2::{
@@ -17,33 +19,47 @@ use objc2::{
 use objc2_app_kit::{NSApp, NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate};
 use objc2_foundation::{MainThreadMarker, NSNotification, NSObject, NSObjectProtocol};
 
-use super::panicinfo::PanicInfo;
+use super::{panicinfo::PanicInfo, runloop::EventLoopWaker};
 use crate::{
     platform::Wrapper,
+    ActivationPolicy,
     ActiveApplication,
+    ApplicationOptions,
+    ControlFlow,
     Event,
     EventHandler,
     Icon,
     LifeCycle,
     Menu,
+    MenuCallback,
+    StartCause,
+    UserEvent,
     WindowEvent,
     WindowId,
 };
 
-#[derive(Debug)]
-pub(super) struct ActivationPolicy(NSApplicationActivationPolicy);
-
-impl Default for ActivationPolicy {
-    fn default() -> Self { Self(NSApplicationActivationPolicy::Regular) }
+impl From<ActivationPolicy> for NSApplicationActivationPolicy {
+    fn from(policy: ActivationPolicy) -> Self {
+        match policy {
+            ActivationPolicy::Regular => NSApplicationActivationPolicy::Regular,
+            ActivationPolicy::Accessory => NSApplicationActivationPolicy::Accessory,
+            ActivationPolicy::Prohibited => NSApplicationActivationPolicy::Prohibited,
+        }
+    }
 }
 
 pub(super) struct State {
     app: RefCell<Option<ActiveApplication>>,
-    activation_policy: ActivationPolicy,
+    activation_policy: NSApplicationActivationPolicy,
     activate_ignoring_other_apps: bool,
     is_running: Cell<bool>,
-    handler: RefCell<Option<Box<dyn EventHandler>>>,
-    pending_events: RefCell<VecDeque<Event>>,
+    control_flow: Cell<ControlFlow>,
+    wait_start: Cell<Instant>,
+    waker: RefCell<EventLoopWaker>,
+    handler: RefCell<Option<Box<dyn EventHandler<UserEvent>>>>,
+    pending_events: RefCell<VecDeque<Event<UserEvent>>>,
+    user_sender: Sender<UserEvent>,
+    user_receiver: Receiver<UserEvent>,
 }
 
 impl Debug for State {
@@ -88,13 +104,16 @@ declare_class!(
             // We need to delay setting the activation policy and activating the app
             // until `applicationDidFinishLaunching` has been called. Otherwise the
             // menu bar is initially unresponsive on macOS 10.15.
-            app.setActivationPolicy(self.ivars().activation_policy.0);
+            app.setActivationPolicy(self.ivars().activation_policy);
 
             #[allow(deprecated)]
             app.activateIgnoringOtherApps(self.ivars().activate_ignoring_other_apps);
 
             self.handle_event(Event::LifeCycle(LifeCycle::Started));
             self.set_is_running(true);
+            self.handle_event(Event::NewEvents(StartCause::Init));
+            // Run the first iteration of the loop as soon as possible
+            self.ivars().waker.borrow_mut().start();
         }
 
         #[method(applicationWillTerminate:)]
@@ -106,19 +125,30 @@ declare_class!(
 );
 
 impl AppDelegate {
-    pub(super) fn new(mtm: MainThreadMarker) -> Retained<Self> {
+    pub(super) fn new(mtm: MainThreadMarker, options: &ApplicationOptions) -> Retained<Self> {
+        let (user_sender, user_receiver) = mpsc::channel();
         let this = mtm.alloc();
         let this = this.set_ivars(State {
             app: RefCell::new(None),
-            activate_ignoring_other_apps: true,
-            activation_policy: Default::default(),
+            activate_ignoring_other_apps: options.activate_on_start,
+            activation_policy: options.activation_policy.into(),
             is_running: Cell::new(false),
+            control_flow: Cell::new(ControlFlow::default()),
+            wait_start: Cell::new(Instant::now()),
+            waker: RefCell::new(EventLoopWaker::new()),
             handler: RefCell::new(None),
             pending_events: RefCell::new(VecDeque::new()),
+            user_sender,
+            user_receiver,
         });
         unsafe { msg_send_id![super(this), init] }
     }
 
+    #[inline]
+    pub(super) fn activation_policy(&self) -> NSApplicationActivationPolicy {
+        self.ivars().activation_policy
+    }
+
     pub(super) fn get(mtm: MainThreadMarker) -> Retained<Self> {
         let app = NSApp(mtm);
         let delegate =
@@ -137,7 +167,7 @@ impl AppDelegate {
     }
 
     #[inline]
-    pub(super) fn set_handler(&self, handler: impl EventHandler + 'static) {
+    pub(super) fn set_handler(&self, handler: impl EventHandler<UserEvent> + 'static) {
         *self.ivars().handler.borrow_mut() = Some(Box::new(handler));
     }
 
@@ -147,7 +177,36 @@ impl AppDelegate {
     #[inline]
     pub(super) fn set_is_running(&self, value: bool) { self.ivars().is_running.set(value) }
 
-    pub(super) fn wakeup(&self, _panic_info: Weak<PanicInfo>) {}
+    #[inline]
+    pub(super) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.ivars().control_flow.set(control_flow);
+    }
+
+    #[inline]
+    pub(super) fn control_flow(&self) -> ControlFlow { self.ivars().control_flow.get() }
+
+    #[inline]
+    pub(super) fn user_sender(&self) -> Sender<UserEvent> { self.ivars().user_sender.clone() }
+
+    pub(super) fn wakeup(&self, panic_info: Weak<PanicInfo>) {
+        let panic_info = panic_info
+            .upgrade()
+            .expect("The panic info must exist here. This failure indicates a developer error.");
+        if panic_info.is_panicking() || !self.is_running() {
+            return;
+        }
+
+        let start_cause = self
+            .control_flow()
+            .start_cause(self.ivars().wait_start.get());
+        self.handle_event(Event::NewEvents(start_cause));
+
+        // User events are delivered with other pending events before the run
+        // loop goes to sleep.
+        for event in self.ivars().user_receiver.try_iter() {
+            self.queue_event(Event::User(event));
+        }
+    }
 
     pub(super) fn cleared(&self, panic_info: Weak<PanicInfo>) {
         let panic_info = panic_info
@@ -165,14 +224,23 @@ impl AppDelegate {
         for event in events.into_iter() {
             self.handle_event(event);
         }
+        self.handle_event(Event::AboutToWait);
+
+        let mut waker = self.ivars().waker.borrow_mut();
+        match self.control_flow() {
+            ControlFlow::Wait => waker.stop(),
+            ControlFlow::Poll => waker.start(),
+            ControlFlow::WaitUntil(deadline) => waker.start_at(deadline),
+        }
+        self.ivars().wait_start.set(Instant::now());
     }
 
     #[inline]
-    pub(super) fn queue_event(&self, event: Event) {
+    pub(super) fn queue_event(&self, event: Event<UserEvent>) {
         self.ivars().pending_events.borrow_mut().push_back(event);
     }
 
-    pub(super) fn handle_event(&self, event: Event) {
+    pub(super) fn handle_event(&self, event: Event<UserEvent>) {
         let mut app = self.ivars().app.borrow_mut();
 
         if let Some(app) = app.as_mut() {
@@ -184,6 +252,15 @@ impl AppDelegate {
         }
     }
 
+    /// Calls a menu callback with the active application.
+    pub(super) fn handle_callback(&self, callback: &MenuCallback) {
+        let mut app = self.ivars().app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            callback.call(app);
+        }
+    }
+
     pub(super) fn handle_redraw(&self, window_id: WindowId) {
         // Redraw request might come out of order from the OS.
         // -> Don't go back into the event handler when our callstack originates from there.
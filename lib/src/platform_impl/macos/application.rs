@@ -1,30 +1,41 @@
 use std::{
     panic::{catch_unwind, UnwindSafe},
     rc::{Rc, Weak},
+    sync::mpsc::Sender,
 };
 
 use objc2::{
     rc::{autoreleasepool, Retained},
     runtime::ProtocolObject,
 };
-use objc2_app_kit::{NSApp, NSApplication, NSApplicationActivationPolicy};
+use objc2_app_kit::{NSApp, NSApplication};
 use objc2_foundation::MainThreadMarker;
 
 use super::{
     app_delegate::AppDelegate,
+    clipboard::ClipboardImpl,
     events::dummy_event,
+    monitor::monitors,
     observers::setup_control_flow_observers,
     panicinfo::PanicInfo,
+    runloop::RunLoop,
 };
 use crate::{
-    platform::{ActiveApplicationApi, ApplicationApi, Wrapper},
+    platform::{ActiveApplicationApi, ApplicationApi, EventLoopProxyApi, Wrapper},
     ActiveApplication,
+    ApplicationOptions,
+    Clipboard,
+    ClipboardKind,
     Context,
     ContextOwner,
+    ControlFlow,
     Error,
+    ErrorKind,
     EventHandler,
     Icon,
     Menu,
+    Monitor,
+    UserEvent,
 };
 
 pub(super) fn stop_app_immediately(app: &NSApplication) {
@@ -94,8 +105,30 @@ impl ActiveApplicationApi for ActiveApplicationImpl {
     #[inline]
     fn set_icon(&mut self, icon: Option<&Icon>) { self.delegate().set_icon(icon); }
 
+    #[inline]
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        self.delegate().set_control_flow(control_flow);
+    }
+
+    #[inline]
+    fn control_flow(&self) -> ControlFlow { self.delegate().control_flow() }
+
     #[inline]
     fn stop(&mut self) { self.delegate().stop(); }
+
+    fn monitors(&self) -> Vec<Monitor> { monitors().into_iter().map(Monitor::new).collect() }
+
+    fn primary_monitor(&self) -> Option<Monitor> {
+        monitors()
+            .into_iter()
+            .find(|monitor| monitor.is_main())
+            .map(Monitor::new)
+    }
+
+    #[inline]
+    fn clipboard(&self, kind: ClipboardKind) -> Clipboard {
+        Clipboard::new(ClipboardImpl::new(kind))
+    }
 }
 
 impl ContextOwner for ActiveApplicationImpl {
@@ -111,10 +144,10 @@ pub(crate) struct ApplicationImpl {
 
 impl ApplicationApi for ApplicationImpl {
     #[inline]
-    fn new() -> Result<Self, Error> {
+    fn new(options: &ApplicationOptions) -> Result<Self, Error> {
         if let Some(mtm) = MainThreadMarker::new() {
             // Configure the application delegate
-            let delegate = AppDelegate::new(mtm);
+            let delegate = AppDelegate::new(mtm, options);
 
             // Initialize a new application.
             let app = NSApp(mtm);
@@ -142,18 +175,19 @@ impl ApplicationApi for ApplicationImpl {
             })
         } else {
             Err(Error::new(
+                ErrorKind::NotMainThread,
                 "application instance must be created on the main thread.",
             ))
         }
     }
 
     #[inline]
-    fn run(&mut self, handler: impl EventHandler + 'static) {
+    fn run(&mut self, handler: impl EventHandler<UserEvent> + 'static) {
         // Register an event handler
         self.context.get_impl().app_delegate().set_handler(handler);
         // Set an activation policy
-        self.native
-            .setActivationPolicy(NSApplicationActivationPolicy::Regular);
+        let policy = self.context.get_impl().app_delegate().activation_policy();
+        self.native.setActivationPolicy(policy);
 
         let panic_info: Rc<PanicInfo> = Default::default();
         setup_control_flow_observers(Rc::downgrade(&panic_info));
@@ -162,9 +196,34 @@ impl ApplicationApi for ApplicationImpl {
             unsafe { self.native.run() };
         });
     }
+
+    #[inline]
+    fn create_proxy(&self) -> EventLoopProxyImpl {
+        EventLoopProxyImpl {
+            sender:   self.context.get_impl().app_delegate().user_sender(),
+            run_loop: unsafe { RunLoop::get() },
+        }
+    }
 }
 
 impl ContextOwner for ApplicationImpl {
     #[inline]
     fn context(&self) -> &Context { &self.context }
 }
+
+#[derive(Debug, Clone)]
+pub(crate) struct EventLoopProxyImpl {
+    sender:   Sender<UserEvent>,
+    run_loop: RunLoop,
+}
+
+impl EventLoopProxyApi for EventLoopProxyImpl {
+    fn send_event(&self, event: UserEvent) -> Result<(), Error> {
+        self.sender
+            .send(event)
+            .map_err(|_| Error::new(ErrorKind::EventLoopClosed, "event loop closed."))?;
+        // Observers of the woken up run loop pick the event up
+        self.run_loop.wakeup();
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use objc2::{rc::Retained, Message};
+use objc2_app_kit::{NSPasteboard, NSPasteboardTypePNG, NSPasteboardTypeString};
+use objc2_foundation::{NSData, NSString};
+
+use crate::{platform::ClipboardApi, ClipboardImage, ClipboardKind, Error, ErrorKind};
+
+/// Converts a MIME type to a pasteboard type.
+///
+/// Common types have system identifiers, other ones are used as is.
+fn pasteboard_type(mime_type: &str) -> Retained<NSString> {
+    match mime_type {
+        "text/plain" | "text/plain;charset=utf-8" => unsafe { NSPasteboardTypeString }.retain(),
+        "image/png" => unsafe { NSPasteboardTypePNG }.retain(),
+        _ => NSString::from_str(mime_type),
+    }
+}
+
+/// General pasteboard.
+///
+/// macOS has no primary selection.
+#[derive(Debug, Clone)]
+pub(crate) struct ClipboardImpl {
+    kind: ClipboardKind,
+}
+
+impl ClipboardImpl {
+    #[inline]
+    pub(super) fn new(kind: ClipboardKind) -> Self {
+        Self {
+            kind,
+        }
+    }
+
+    fn pasteboard(&self) -> Result<Retained<NSPasteboard>, Error> {
+        match self.kind {
+            ClipboardKind::Clipboard => Ok(unsafe { NSPasteboard::generalPasteboard() }),
+            ClipboardKind::Primary => Err(Error::new(
+                ErrorKind::NotSupported,
+                "primary selection is not supported on macOS.",
+            )),
+        }
+    }
+}
+
+#[inline]
+fn not_found() -> Error {
+    Error::new(
+        ErrorKind::NotFound,
+        "clipboard has no data of the requested type.",
+    )
+}
+
+#[inline]
+fn images_not_supported() -> Error {
+    Error::new(
+        ErrorKind::NotSupported,
+        "clipboard images are not supported on macOS.",
+    )
+}
+
+impl ClipboardApi for ClipboardImpl {
+    #[inline]
+    fn kind(&self) -> ClipboardKind { self.kind }
+
+    fn set_text(&mut self, text: &str) -> Result<(), Error> {
+        let pasteboard = self.pasteboard()?;
+        let is_written = unsafe {
+            pasteboard.clearContents();
+            pasteboard.setString_forType(&NSString::from_str(text), NSPasteboardTypeString)
+        };
+        match is_written {
+            true => Ok(()),
+            false => Err(Error::new(ErrorKind::Os, "clipboard text not written.")),
+        }
+    }
+
+    fn text(&self) -> Result<String, Error> {
+        let pasteboard = self.pasteboard()?;
+        unsafe { pasteboard.stringForType(NSPasteboardTypeString) }
+            .map(|text| text.to_string())
+            .ok_or_else(not_found)
+    }
+
+    #[inline]
+    fn set_image(&mut self, _image: &ClipboardImage) -> Result<(), Error> {
+        Err(images_not_supported())
+    }
+
+    #[inline]
+    fn image(&self) -> Result<ClipboardImage, Error> { Err(images_not_supported()) }
+
+    fn set_data(&mut self, mime_type: &str, data: &[u8]) -> Result<(), Error> {
+        let pasteboard = self.pasteboard()?;
+        let data = NSData::with_bytes(data);
+        let is_written = unsafe {
+            pasteboard.clearContents();
+            pasteboard.setData_forType(Some(&data), &pasteboard_type(mime_type))
+        };
+        match is_written {
+            true => Ok(()),
+            false => Err(Error::new(ErrorKind::Os, "clipboard data not written.")),
+        }
+    }
+
+    fn data(&self, mime_type: &str) -> Result<Vec<u8>, Error> {
+        let pasteboard = self.pasteboard()?;
+        unsafe { pasteboard.dataForType(&pasteboard_type(mime_type)) }
+            .map(|data| data.bytes().to_vec())
+            .ok_or_else(not_found)
+    }
+
+    fn mime_types(&self) -> Result<Vec<String>, Error> {
+        let pasteboard = self.pasteboard()?;
+        let types = unsafe { pasteboard.types() }
+            .map(|types| types.iter().map(|type_| type_.to_string()).collect())
+            .unwrap_or_default();
+        Ok(types)
+    }
+
+    fn clear(&mut self) {
+        if let Ok(pasteboard) = self.pasteboard() {
+            unsafe { pasteboard.clearContents() };
+        }
+    }
+}
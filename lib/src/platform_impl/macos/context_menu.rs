@@ -0,0 +1,104 @@
+use std::ptr;
+
+use objc2::{
+    declare_class,
+    msg_send,
+    msg_send_id,
+    mutability,
+    rc::Retained,
+    runtime::AnyObject,
+    sel,
+    ClassType,
+    DeclaredClass,
+};
+use objc2_app_kit::{NSMenu, NSView};
+use objc2_foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSPoint};
+
+use super::app_delegate::AppDelegate;
+use crate::{Event, WindowEvent, WindowId};
+
+#[derive(Debug)]
+pub(super) struct Ivars {
+    menu:      Retained<NSMenu>,
+    view:      Retained<NSView>,
+    location:  NSPoint,
+    window_id: WindowId,
+}
+
+declare_class!(
+    /// Shows a context menu outside of the event handler.
+    ///
+    /// Menu tracking runs a nested event loop until the menu is closed, so the
+    /// menu pops up from the next run loop iteration instead.
+    #[derive(Debug)]
+    pub(super) struct ContextMenu;
+
+    unsafe impl ClassType for ContextMenu {
+        type Super = NSObject;
+        type Mutability = mutability::MainThreadOnly;
+        const NAME: &'static str = "CocoaContextMenu";
+    }
+
+    impl DeclaredClass for ContextMenu {
+        type Ivars = Ivars;
+    }
+
+    unsafe impl ContextMenu {
+        #[method(popUp)]
+        fn __pop_up(&self) {
+            let mtm = MainThreadMarker::from(self);
+            let ivars = self.ivars();
+
+            // Chosen items send their events while the menu is tracked
+            unsafe {
+                ivars.menu.popUpMenuPositioningItem_atLocation_inView(
+                    None,
+                    ivars.location,
+                    Some(&ivars.view),
+                )
+            };
+
+            let event = Event::Window(WindowEvent::ContextMenuClosed, ivars.window_id);
+            AppDelegate::get(mtm).handle_event(event);
+        }
+    }
+
+    unsafe impl NSObjectProtocol for ContextMenu {}
+);
+
+impl ContextMenu {
+    /// Schedules a context menu.
+    ///
+    /// # Parameters:
+    /// * `mtm` - Main thread marker.
+    /// * `menu` - Native menu.
+    /// * `view` - View the location is relative to.
+    /// * `location` - Top-left corner of the menu in the view coordinates.
+    /// * `window_id` - ID of the window the menu belongs to.
+    pub(super) fn show(
+        mtm: MainThreadMarker,
+        menu: Retained<NSMenu>,
+        view: Retained<NSView>,
+        location: NSPoint,
+        window_id: WindowId,
+    ) {
+        let this = mtm.alloc();
+        let this = this.set_ivars(Ivars {
+            menu,
+            view,
+            location,
+            window_id,
+        });
+        let this: Retained<Self> = unsafe { msg_send_id![super(this), init] };
+
+        // The run loop retains the receiver until the selector is performed
+        let _: () = unsafe {
+            msg_send![
+                &this,
+                performSelector: sel!(popUp),
+                withObject: ptr::null::<AnyObject>(),
+                afterDelay: 0.0f64
+            ]
+        };
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     platform::{IconApi, Wrapper},
     ContextOwner,
     Error,
+    ErrorKind,
     IconType,
 };
 
@@ -21,27 +22,27 @@ impl IconApi for IconImpl {
     #[inline]
     fn from_data(
         ctx: &impl ContextOwner,
-        icon_data: &Vec<u8>,
+        icon_data: &[u8],
         _icon_type: IconType,
     ) -> Result<Self, Error> {
         let mtm = ctx.context().get_impl().mtm();
         let allocated = mtm.alloc();
 
-        let data = NSData::with_bytes(&icon_data);
+        let data = NSData::with_bytes(icon_data);
         match NSImage::initWithData(allocated, &data) {
             Some(image) => Ok(Self(MainThreadBound::new(image, mtm))),
-            None => Err(Error::new("NSImage not created.")),
+            None => Err(Error::new(ErrorKind::ImageDecode, "NSImage not created.")),
         }
     }
 
     #[inline]
-    fn from_str(ctx: &impl ContextOwner, title: &String) -> Result<Self, Error> {
+    fn from_str(ctx: &impl ContextOwner, title: &str) -> Result<Self, Error> {
         let mtm = ctx.context().get_impl().mtm();
 
         let name = NSString::from_str(title);
         match unsafe { NSImage::imageWithSystemSymbolName_accessibilityDescription(&name, None) } {
             Some(image) => Ok(Self(MainThreadBound::new(image, mtm))),
-            None => Err(Error::new("NSImage not created.")),
+            None => Err(Error::new(ErrorKind::NotFound, "NSImage not created.")),
         }
     }
 }
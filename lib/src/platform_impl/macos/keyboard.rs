@@ -0,0 +1,173 @@
+use objc2_app_kit::{NSEvent, NSEventModifierFlags};
+
+use crate::{Key, KeyState, KeyboardEvent, Modifiers, NamedKey};
+
+// Virtual key codes of keys that don't depend on the keyboard layout, see:
+// https://developer.apple.com/documentation/carbon/1430321-virtual_key_codes
+const KVK_RETURN: u16 = 0x24;
+const KVK_TAB: u16 = 0x30;
+const KVK_SPACE: u16 = 0x31;
+const KVK_DELETE: u16 = 0x33;
+const KVK_ESCAPE: u16 = 0x35;
+const KVK_RIGHT_COMMAND: u16 = 0x36;
+const KVK_COMMAND: u16 = 0x37;
+const KVK_SHIFT: u16 = 0x38;
+const KVK_CAPS_LOCK: u16 = 0x39;
+const KVK_OPTION: u16 = 0x3A;
+const KVK_CONTROL: u16 = 0x3B;
+const KVK_RIGHT_SHIFT: u16 = 0x3C;
+const KVK_RIGHT_OPTION: u16 = 0x3D;
+const KVK_RIGHT_CONTROL: u16 = 0x3E;
+const KVK_F17: u16 = 0x40;
+const KVK_ANSI_KEYPAD_CLEAR: u16 = 0x47;
+const KVK_ANSI_KEYPAD_ENTER: u16 = 0x4C;
+const KVK_F18: u16 = 0x4F;
+const KVK_F19: u16 = 0x50;
+const KVK_F20: u16 = 0x5A;
+const KVK_F5: u16 = 0x60;
+const KVK_F6: u16 = 0x61;
+const KVK_F7: u16 = 0x62;
+const KVK_F3: u16 = 0x63;
+const KVK_F8: u16 = 0x64;
+const KVK_F9: u16 = 0x65;
+const KVK_F11: u16 = 0x67;
+const KVK_F13: u16 = 0x69;
+const KVK_F16: u16 = 0x6A;
+const KVK_F14: u16 = 0x6B;
+const KVK_F10: u16 = 0x6D;
+const KVK_CONTEXTUAL_MENU: u16 = 0x6E;
+const KVK_F12: u16 = 0x6F;
+const KVK_F15: u16 = 0x71;
+const KVK_HELP: u16 = 0x72;
+const KVK_HOME: u16 = 0x73;
+const KVK_PAGE_UP: u16 = 0x74;
+const KVK_FORWARD_DELETE: u16 = 0x75;
+const KVK_F4: u16 = 0x76;
+const KVK_END: u16 = 0x77;
+const KVK_F2: u16 = 0x78;
+const KVK_PAGE_DOWN: u16 = 0x79;
+const KVK_F1: u16 = 0x7A;
+const KVK_LEFT_ARROW: u16 = 0x7B;
+const KVK_RIGHT_ARROW: u16 = 0x7C;
+const KVK_DOWN_ARROW: u16 = 0x7D;
+const KVK_UP_ARROW: u16 = 0x7E;
+
+/// Function keys are reported with characters of this private use area.
+const FUNCTION_KEYS: std::ops::RangeInclusive<char> = '\u{F700}'..='\u{F8FF}';
+
+/// Converts a virtual key code of a layout independent key into a named key.
+pub(super) fn to_named_key(key_code: u16) -> Option<NamedKey> {
+    let named_key = match key_code {
+        KVK_RETURN | KVK_ANSI_KEYPAD_ENTER => NamedKey::Enter,
+        KVK_TAB => NamedKey::Tab,
+        KVK_SPACE => NamedKey::Space,
+        KVK_DELETE => NamedKey::Backspace,
+        KVK_ESCAPE => NamedKey::Escape,
+        KVK_COMMAND | KVK_RIGHT_COMMAND => NamedKey::Super,
+        KVK_SHIFT | KVK_RIGHT_SHIFT => NamedKey::Shift,
+        KVK_CAPS_LOCK => NamedKey::CapsLock,
+        KVK_OPTION | KVK_RIGHT_OPTION => NamedKey::Alt,
+        KVK_CONTROL | KVK_RIGHT_CONTROL => NamedKey::Control,
+        KVK_ANSI_KEYPAD_CLEAR => NamedKey::NumLock,
+        KVK_CONTEXTUAL_MENU => NamedKey::ContextMenu,
+        KVK_HELP => NamedKey::Insert,
+        KVK_HOME => NamedKey::Home,
+        KVK_PAGE_UP => NamedKey::PageUp,
+        KVK_FORWARD_DELETE => NamedKey::Delete,
+        KVK_END => NamedKey::End,
+        KVK_PAGE_DOWN => NamedKey::PageDown,
+        KVK_LEFT_ARROW => NamedKey::ArrowLeft,
+        KVK_RIGHT_ARROW => NamedKey::ArrowRight,
+        KVK_DOWN_ARROW => NamedKey::ArrowDown,
+        KVK_UP_ARROW => NamedKey::ArrowUp,
+        KVK_F1 => NamedKey::F(1),
+        KVK_F2 => NamedKey::F(2),
+        KVK_F3 => NamedKey::F(3),
+        KVK_F4 => NamedKey::F(4),
+        KVK_F5 => NamedKey::F(5),
+        KVK_F6 => NamedKey::F(6),
+        KVK_F7 => NamedKey::F(7),
+        KVK_F8 => NamedKey::F(8),
+        KVK_F9 => NamedKey::F(9),
+        KVK_F10 => NamedKey::F(10),
+        KVK_F11 => NamedKey::F(11),
+        KVK_F12 => NamedKey::F(12),
+        KVK_F13 => NamedKey::F(13),
+        KVK_F14 => NamedKey::F(14),
+        KVK_F15 => NamedKey::F(15),
+        KVK_F16 => NamedKey::F(16),
+        KVK_F17 => NamedKey::F(17),
+        KVK_F18 => NamedKey::F(18),
+        KVK_F19 => NamedKey::F(19),
+        KVK_F20 => NamedKey::F(20),
+        _ => return None,
+    };
+    Some(named_key)
+}
+
+/// Returns printable text, function keys and control characters are skipped.
+fn to_text(characters: String) -> Option<String> {
+    let is_printable = !characters.is_empty()
+        && characters
+            .chars()
+            .all(|character| !character.is_control() && !FUNCTION_KEYS.contains(&character));
+    is_printable.then_some(characters)
+}
+
+/// Converts Cocoa modifier flags into modifiers.
+pub(super) fn to_modifiers(flags: NSEventModifierFlags) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(
+        Modifiers::SHIFT,
+        flags.0 & NSEventModifierFlags::Shift.0 != 0,
+    );
+    modifiers.set(
+        Modifiers::CONTROL,
+        flags.0 & NSEventModifierFlags::Control.0 != 0,
+    );
+    modifiers.set(
+        Modifiers::ALT,
+        flags.0 & NSEventModifierFlags::Option.0 != 0,
+    );
+    modifiers.set(
+        Modifiers::SUPER,
+        flags.0 & NSEventModifierFlags::Command.0 != 0,
+    );
+    modifiers.set(
+        Modifiers::CAPS_LOCK,
+        flags.0 & NSEventModifierFlags::CapsLock.0 != 0,
+    );
+    modifiers
+}
+
+/// Converts a `keyDown:` or `keyUp:` event into a keyboard event.
+///
+/// # Parameters:
+/// * `event` - Cocoa key event.
+/// * `state` - Key state.
+pub(super) unsafe fn to_keyboard_event(event: &NSEvent, state: KeyState) -> KeyboardEvent {
+    let key_code = event.keyCode();
+    let key = match to_named_key(key_code) {
+        Some(named_key) => Key::Named(named_key),
+        None => event
+            .charactersIgnoringModifiers()
+            .and_then(|characters| to_text(characters.to_string()))
+            .map(Key::Character)
+            .unwrap_or(Key::Unidentified),
+    };
+    let text = match state {
+        KeyState::Pressed => event
+            .characters()
+            .and_then(|characters| to_text(characters.to_string())),
+        KeyState::Released => None,
+    };
+
+    KeyboardEvent::Input {
+        scan_code: key_code as u32,
+        key,
+        text,
+        state,
+        repeat: state == KeyState::Pressed && event.isARepeat(),
+        modifiers: to_modifiers(event.modifierFlags()),
+    }
+}
@@ -1,24 +1,159 @@
-use std::{cell::RefCell, fmt::Debug};
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Debug,
+    ptr,
+};
 
-use objc2::{declare_class, msg_send_id, mutability, rc::Retained, sel, ClassType, DeclaredClass};
-use objc2_app_kit::{NSEventModifierFlags, NSMenu, NSMenuItem};
-use objc2_foundation::{MainThreadBound, MainThreadMarker, NSObjectProtocol, NSString};
+use objc2::{
+    declare_class,
+    msg_send_id,
+    mutability,
+    rc::Retained,
+    runtime::AnyObject,
+    sel,
+    ClassType,
+    DeclaredClass,
+};
+use objc2_app_kit::{
+    NSAboutPanelOptionApplicationName,
+    NSAboutPanelOptionApplicationVersion,
+    NSAboutPanelOptionKey,
+    NSApp,
+    NSControlStateValueMixed,
+    NSControlStateValueOff,
+    NSControlStateValueOn,
+    NSEventModifierFlags,
+    NSMenu,
+    NSMenuItem,
+};
+use objc2_foundation::{
+    ns_string,
+    MainThreadBound,
+    MainThreadMarker,
+    NSDictionary,
+    NSObjectProtocol,
+    NSString,
+};
 
 use crate::{
     platform::{MenuApi, MenuItemApi, Wrapper},
     platform_impl::macos::app_delegate::AppDelegate,
+    AboutMetadata,
+    Accelerator,
     Action,
+    CheckState,
     ContextOwner,
+    Error,
+    ErrorKind,
     Event,
     Icon,
+    Key,
     Menu,
     MenuItem,
-    ShortCode,
+    Modifiers,
+    NamedKey,
+    PredefinedItem,
 };
 
+// Characters of function keys, see `NSEvent.h`.
+const UP_ARROW_FUNCTION_KEY: u32 = 0xf700;
+const DOWN_ARROW_FUNCTION_KEY: u32 = 0xf701;
+const LEFT_ARROW_FUNCTION_KEY: u32 = 0xf702;
+const RIGHT_ARROW_FUNCTION_KEY: u32 = 0xf703;
+const F1_FUNCTION_KEY: u32 = 0xf704;
+const INSERT_FUNCTION_KEY: u32 = 0xf727;
+const DELETE_FUNCTION_KEY: u32 = 0xf728;
+const HOME_FUNCTION_KEY: u32 = 0xf729;
+const END_FUNCTION_KEY: u32 = 0xf72b;
+const PAGE_UP_FUNCTION_KEY: u32 = 0xf72c;
+const PAGE_DOWN_FUNCTION_KEY: u32 = 0xf72d;
+const PRINT_SCREEN_FUNCTION_KEY: u32 = 0xf72e;
+const PAUSE_FUNCTION_KEY: u32 = 0xf730;
+
+/// Converts an accelerator into a key equivalent and its modifier mask.
+fn to_key_equivalent(accelerator: &Accelerator) -> (String, NSEventModifierFlags) {
+    let function_key = |code: u32| char::from_u32(code).map(String::from).unwrap_or_default();
+    let key = match accelerator.key() {
+        Key::Character(character) => character.clone(),
+        Key::Named(NamedKey::Enter) => "\r".into(),
+        Key::Named(NamedKey::Tab) => "\t".into(),
+        Key::Named(NamedKey::Space) => " ".into(),
+        Key::Named(NamedKey::Backspace) => "\u{8}".into(),
+        Key::Named(NamedKey::Escape) => "\u{1b}".into(),
+        Key::Named(NamedKey::ArrowUp) => function_key(UP_ARROW_FUNCTION_KEY),
+        Key::Named(NamedKey::ArrowDown) => function_key(DOWN_ARROW_FUNCTION_KEY),
+        Key::Named(NamedKey::ArrowLeft) => function_key(LEFT_ARROW_FUNCTION_KEY),
+        Key::Named(NamedKey::ArrowRight) => function_key(RIGHT_ARROW_FUNCTION_KEY),
+        Key::Named(NamedKey::F(number)) => {
+            function_key(F1_FUNCTION_KEY + u32::from(number.saturating_sub(1)))
+        }
+        Key::Named(NamedKey::Insert) => function_key(INSERT_FUNCTION_KEY),
+        Key::Named(NamedKey::Delete) => function_key(DELETE_FUNCTION_KEY),
+        Key::Named(NamedKey::Home) => function_key(HOME_FUNCTION_KEY),
+        Key::Named(NamedKey::End) => function_key(END_FUNCTION_KEY),
+        Key::Named(NamedKey::PageUp) => function_key(PAGE_UP_FUNCTION_KEY),
+        Key::Named(NamedKey::PageDown) => function_key(PAGE_DOWN_FUNCTION_KEY),
+        Key::Named(NamedKey::PrintScreen) => function_key(PRINT_SCREEN_FUNCTION_KEY),
+        Key::Named(NamedKey::Pause) => function_key(PAUSE_FUNCTION_KEY),
+        // Accelerators can't be created with other keys
+        Key::Named(_) | Key::Unidentified => String::new(),
+    };
+
+    let modifiers = accelerator.modifiers();
+    let mut mask = 0;
+    for (modifier, flag) in [
+        (
+            Modifiers::SHIFT,
+            NSEventModifierFlags::NSEventModifierFlagShift,
+        ),
+        (
+            Modifiers::CONTROL,
+            NSEventModifierFlags::NSEventModifierFlagControl,
+        ),
+        (
+            Modifiers::ALT,
+            NSEventModifierFlags::NSEventModifierFlagOption,
+        ),
+        (
+            Modifiers::SUPER,
+            NSEventModifierFlags::NSEventModifierFlagCommand,
+        ),
+    ] {
+        if modifiers.contains(modifier) {
+            mask |= flag.0;
+        }
+    }
+    (key, NSEventModifierFlags(mask))
+}
+
+type AboutOptions = NSDictionary<NSAboutPanelOptionKey, AnyObject>;
+
+/// Converts the metadata into options of the standard About panel, missing
+/// values are taken from the application bundle.
+fn to_about_options(metadata: &AboutMetadata) -> Retained<AboutOptions> {
+    let options = unsafe {
+        [
+            (NSAboutPanelOptionApplicationName, &metadata.name),
+            (NSAboutPanelOptionApplicationVersion, &metadata.version),
+            (ns_string!("Copyright"), &metadata.copyright),
+        ]
+    };
+    let (keys, values): (Vec<&NSString>, Vec<Retained<AnyObject>>) = options
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let value = NSString::from_str(value.as_deref()?);
+            Some((key, Retained::into_super(Retained::into_super(value))))
+        })
+        .unzip();
+    NSDictionary::from_vec(&keys, values)
+}
+
 #[derive(Debug, Default)]
 pub(super) struct Ivars {
-    action: RefCell<Option<Action>>,
+    action:      RefCell<Option<Action>>,
+    checkable:   Cell<bool>,
+    radio_group: RefCell<Option<String>>,
+    about:       RefCell<Option<AboutMetadata>>,
 }
 
 declare_class!(
@@ -38,17 +173,28 @@ declare_class!(
     unsafe impl CocoaMenuItem {
         #[method(callback)]
         fn __callback(&self) {
-            let action = self.ivars().action.borrow();
-            if let Some(action) = &*action {
-                match action {
-                    Action::Event(name) => {
-                        let delegate = AppDelegate::get(MainThreadMarker::new().unwrap());
-                        delegate.handle_event(Event::Menu(name.clone()));
-                    },
-                    Action::Callback(callback) => callback(),
-                }
+            let state = self.choose();
+            // The handler may replace the action of the item, so it isn't
+            // borrowed while the action is triggered
+            let action = self.ivars().action.borrow().clone();
+            let delegate = AppDelegate::get(MainThreadMarker::new().unwrap());
+            match action {
+                Some(Action::Event(name)) => delegate.handle_event(Event::Menu {
+                    action: name,
+                    state,
+                }),
+                Some(Action::Callback(callback)) => delegate.handle_callback(&callback),
+                None => {},
             }
         }
+
+        #[method(about:)]
+        fn about(&self, _sender: Option<&AnyObject>) {
+            let metadata = self.ivars().about.borrow().clone().unwrap_or_default();
+            let options = to_about_options(&metadata);
+            let app = NSApp(MainThreadMarker::new().unwrap());
+            unsafe { app.orderFrontStandardAboutPanelWithOptions(&options) };
+        }
     }
 
     unsafe impl NSObjectProtocol for CocoaMenuItem {}
@@ -56,10 +202,13 @@ declare_class!(
 
 impl CocoaMenuItem {
     #[inline]
-    fn new(mtm: MainThreadMarker) -> Retained<Self> {
+    fn new(mtm: MainThreadMarker) -> Option<Retained<Self>> {
         let this = mtm.alloc();
         let this = this.set_ivars(Ivars {
-            action: RefCell::new(None),
+            action:      RefCell::new(None),
+            checkable:   Cell::new(false),
+            radio_group: RefCell::new(None),
+            about:       RefCell::new(None),
         });
 
         unsafe { msg_send_id![super(this), init] }
@@ -76,14 +225,106 @@ impl CocoaMenuItem {
         }
         *self.ivars().action.borrow_mut() = action;
     }
+
+    #[inline]
+    fn has_action(&self, name: &str) -> bool {
+        matches!(&*self.ivars().action.borrow(), Some(Action::Event(action)) if action == name)
+    }
+
+    #[inline]
+    fn set_check_state(&self, state: Option<CheckState>) {
+        let value = match state {
+            Some(CheckState::Checked) => NSControlStateValueOn,
+            Some(CheckState::Mixed) => NSControlStateValueMixed,
+            _ => NSControlStateValueOff,
+        };
+        unsafe { self.setState(value) };
+        self.ivars().checkable.set(state.is_some());
+    }
+
+    #[inline]
+    fn check_state(&self) -> Option<CheckState> {
+        if !self.ivars().checkable.get() {
+            return None;
+        }
+        let value = unsafe { self.state() };
+        let state = if value == NSControlStateValueOn {
+            CheckState::Checked
+        } else if value == NSControlStateValueMixed {
+            CheckState::Mixed
+        } else {
+            CheckState::Unchecked
+        };
+        Some(state)
+    }
+
+    /// Assigns the standard action of the item.
+    ///
+    /// The actions have no target, so they are sent to the first responder
+    /// that accepts them, e.g. Copy goes to the focused view.
+    fn set_predefined(&self, item: PredefinedItem) {
+        *self.ivars().action.borrow_mut() = None;
+        let action = match item {
+            PredefinedItem::About(metadata) => {
+                // The panel options can't be passed with the action
+                *self.ivars().about.borrow_mut() = Some(metadata);
+                unsafe { self.setTarget(Some(self)) };
+                unsafe { self.setAction(Some(sel!(about:))) };
+                return;
+            }
+            PredefinedItem::Quit => sel!(terminate:),
+            PredefinedItem::Copy => sel!(copy:),
+            PredefinedItem::Cut => sel!(cut:),
+            PredefinedItem::Paste => sel!(paste:),
+            PredefinedItem::SelectAll => sel!(selectAll:),
+            PredefinedItem::Undo => sel!(undo:),
+            PredefinedItem::Redo => sel!(redo:),
+            PredefinedItem::Minimize => sel!(performMiniaturize:),
+            PredefinedItem::Zoom => sel!(performZoom:),
+            PredefinedItem::Fullscreen => sel!(toggleFullScreen:),
+            PredefinedItem::Hide => sel!(hide:),
+            PredefinedItem::HideOthers => sel!(hideOtherApplications:),
+            PredefinedItem::ShowAll => sel!(unhideAllApplications:),
+            PredefinedItem::CloseWindow => sel!(performClose:),
+        };
+        unsafe { self.setTarget(None) };
+        unsafe { self.setAction(Some(action)) };
+    }
+
+    /// Updates check states of the chosen item and its radio group.
+    ///
+    /// Returns the new state of the item or `None` if it isn't checkable.
+    fn choose(&self) -> Option<CheckState> {
+        let state = self.check_state()?;
+        let group = self.ivars().radio_group.borrow().clone();
+        let menu = unsafe { self.menu() };
+        if let (Some(group), Some(menu)) = (&group, menu) {
+            for item in unsafe { menu.itemArray() }.iter() {
+                let item: &NSMenuItem = &item;
+                // Separators are created without the ivars
+                let is_sibling = !ptr::eq(item, self.as_super())
+                    && item.isKindOfClass(Self::class())
+                    && !unsafe { item.isSeparatorItem() };
+                if is_sibling {
+                    let item = unsafe { &*(item as *const NSMenuItem).cast::<Self>() };
+                    if item.ivars().radio_group.borrow().as_ref() == Some(group) {
+                        item.set_check_state(Some(CheckState::Unchecked));
+                    }
+                }
+            }
+        }
+        let state = state.chosen(group.is_some());
+        self.set_check_state(Some(state));
+        Some(state)
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct MenuItemImpl {
-    native:     MainThreadBound<Retained<CocoaMenuItem>>,
-    short_code: ShortCode,
-    submenu:    Option<Menu>,
-    icon:       Option<Icon>,
+    native:      MainThreadBound<Retained<CocoaMenuItem>>,
+    accelerator: Option<Accelerator>,
+    submenu:     Option<Menu>,
+    icon:        Option<Icon>,
 }
 
 impl MenuItemImpl {
@@ -98,79 +339,24 @@ impl MenuItemImpl {
 
     #[inline]
     fn get_native(&self, mtm: MainThreadMarker) -> &Retained<CocoaMenuItem> { self.native.get(mtm) }
-
-    #[inline]
-    fn parse_short_code(&self, code: &String) {
-        let parts = code.split("+").collect::<Vec<&str>>();
-        let mut masks = Vec::new();
-        let mut code = String::from("");
-
-        for part in parts.iter() {
-            match *part {
-                "Control" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagControl.0);
-                }
-                "Command" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagCommand.0);
-                }
-                "Help" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagHelp.0);
-                }
-                "Function" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagFunction.0);
-                }
-                "Option" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagOption.0);
-                }
-                "Shift" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagShift.0);
-                }
-                "CapsLock" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagCapsLock.0);
-                }
-                "NumPad" => {
-                    masks.push(NSEventModifierFlags::NSEventModifierFlagNumericPad.0);
-                }
-                c => {
-                    code = c.to_owned();
-                }
-            }
-        }
-
-        self.native_on_main(|native| {
-            let code = NSString::from_str(&code);
-            unsafe { native.setKeyEquivalent(&code) };
-
-            let mut mask = if masks.is_empty() {
-                NSEventModifierFlags::NSEventModifierFlagCommand.0
-            } else {
-                masks[0]
-            };
-
-            for m in masks.iter().skip(1) {
-                mask |= m;
-            }
-
-            native.setKeyEquivalentModifierMask(NSEventModifierFlags(mask));
-        });
-    }
 }
 
 impl MenuItemApi for MenuItemImpl {
     #[inline]
-    fn new(ctx: &impl ContextOwner, separator: bool) -> Self {
+    fn new(ctx: &impl ContextOwner, separator: bool) -> Result<Self, Error> {
         let mtm = ctx.context().get_impl().mtm();
         let native = if separator {
             unsafe { msg_send_id![CocoaMenuItem::class(), separatorItem] }
         } else {
             CocoaMenuItem::new(mtm)
         };
-        Self {
-            native:     MainThreadBound::new(native, mtm),
-            short_code: Default::default(),
-            submenu:    None,
-            icon:       None,
-        }
+        let native = native.ok_or_else(|| Error::new(ErrorKind::Os, "menu item not created."))?;
+        Ok(Self {
+            native:      MainThreadBound::new(native, mtm),
+            accelerator: None,
+            submenu:     None,
+            icon:        None,
+        })
     }
 
     #[inline]
@@ -193,6 +379,11 @@ impl MenuItemApi for MenuItemImpl {
         });
     }
 
+    #[inline]
+    fn has_action(&self, name: &str) -> bool {
+        self.native_on_main(|native| native.has_action(name))
+    }
+
     #[inline]
     fn set_submenu(&mut self, submenu: Option<Menu>) {
         self.native_on_main(|native| {
@@ -217,16 +408,21 @@ impl MenuItemApi for MenuItemImpl {
     fn has_submenu(&self) -> bool { self.native_on_main(|native| unsafe { native.hasSubmenu() }) }
 
     #[inline]
-    fn set_short_code(&mut self, short_code: ShortCode) {
-        self.short_code = short_code;
-
-        if let Some(code) = &self.short_code.macos {
-            self.parse_short_code(code);
-        }
+    fn set_accelerator(&mut self, accelerator: Option<Accelerator>) {
+        let (key, mask) = accelerator
+            .as_ref()
+            .map(to_key_equivalent)
+            .unwrap_or_else(|| (String::new(), NSEventModifierFlags(0)));
+        self.native_on_main(|native| {
+            let key = NSString::from_str(&key);
+            unsafe { native.setKeyEquivalent(&key) };
+            native.setKeyEquivalentModifierMask(mask);
+        });
+        self.accelerator = accelerator;
     }
 
     #[inline]
-    fn short_code(&self) -> &ShortCode { &self.short_code }
+    fn accelerator(&self) -> Option<&Accelerator> { self.accelerator.as_ref() }
 
     #[inline]
     fn set_enabled(&mut self, enabled: bool) {
@@ -273,6 +469,31 @@ impl MenuItemApi for MenuItemImpl {
 
     #[inline]
     fn icon(&self) -> Option<&Icon> { self.icon.as_ref() }
+
+    #[inline]
+    fn set_check_state(&mut self, state: Option<CheckState>) {
+        self.native_on_main(|native| native.set_check_state(state));
+    }
+
+    #[inline]
+    fn check_state(&self) -> Option<CheckState> {
+        self.native_on_main(|native| native.check_state())
+    }
+
+    #[inline]
+    fn set_radio_group(&mut self, group: Option<String>) {
+        self.native_on_main(|native| *native.ivars().radio_group.borrow_mut() = group);
+    }
+
+    #[inline]
+    fn radio_group(&self) -> Option<String> {
+        self.native_on_main(|native| native.ivars().radio_group.borrow().clone())
+    }
+
+    #[inline]
+    fn set_predefined(&mut self, item: PredefinedItem) {
+        self.native_on_main(|native| native.set_predefined(item));
+    }
 }
 
 #[derive(Debug)]
@@ -325,4 +546,31 @@ impl MenuApi for MenuImpl {
         });
         self.items.push(item);
     }
+
+    fn insert_item(&mut self, index: usize, item: MenuItem) {
+        assert!(index <= self.items.len(), "menu item index out of bounds");
+        self.native_on_main(|native| {
+            let mtm = MainThreadMarker::new().unwrap();
+            let ns_menu_item = item.get_impl().get_native(mtm);
+            unsafe { native.insertItem_atIndex(&ns_menu_item, index as isize) };
+        });
+        self.items.insert(index, item);
+    }
+
+    fn remove_item(&mut self, index: usize) -> MenuItem {
+        let item = self.items.remove(index);
+        // Items are removed by reference, their order may be changed by swaps
+        self.native_on_main(|native| {
+            let mtm = MainThreadMarker::new().unwrap();
+            let ns_menu_item = item.get_impl().get_native(mtm);
+            unsafe { native.removeItem(&ns_menu_item) };
+        });
+        item
+    }
+
+    #[inline]
+    fn items(&self) -> &[MenuItem] { &self.items }
+
+    #[inline]
+    fn items_mut(&mut self) -> &mut [MenuItem] { &mut self.items }
 }
@@ -1,21 +1,31 @@
 mod app_delegate;
 mod application;
+mod clipboard;
+mod context_menu;
 mod events;
 mod icon;
+mod keyboard;
 mod menu;
+mod monitor;
 #[cfg(feature = "notifications")]
 mod notification;
 mod observers;
 mod panicinfo;
 mod runloop;
+#[cfg(feature = "tray")]
+mod tray;
 mod view;
 mod window;
 mod window_delegate;
 mod window_utils;
 
 pub(crate) use application::*;
+pub(crate) use clipboard::ClipboardImpl;
 pub(crate) use icon::*;
 pub(crate) use menu::*;
+pub(crate) use monitor::MonitorImpl;
 #[cfg(feature = "notifications")]
 pub(crate) use notification::*;
+#[cfg(feature = "tray")]
+pub(crate) use tray::*;
 pub(crate) use window::*;
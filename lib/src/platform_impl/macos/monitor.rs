@@ -0,0 +1,142 @@
+use std::ptr;
+
+use core_graphics::display::{CGDirectDisplayID, CGDisplay, CGDisplayMode, CGRect};
+use dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
+use objc2::{msg_send_id, rc::Retained};
+use objc2_app_kit::NSScreen;
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
+
+use crate::{platform::MonitorApi, Monitor, VideoMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MonitorImpl {
+    display_id: CGDirectDisplayID,
+}
+
+impl MonitorImpl {
+    #[inline]
+    pub(super) fn new(display_id: CGDirectDisplayID) -> Self {
+        Self {
+            display_id,
+        }
+    }
+
+    #[inline]
+    fn display(&self) -> CGDisplay { CGDisplay::new(self.display_id) }
+
+    #[inline]
+    pub(super) fn is_main(&self) -> bool { self.display().is_main() }
+
+    /// Returns the display frame in points, the origin is the top-left corner
+    /// of the main display.
+    #[inline]
+    fn bounds(&self) -> CGRect { self.display().bounds() }
+
+    /// Returns the screen showing the display.
+    ///
+    /// Screens are only accessible from the main thread.
+    fn screen(&self) -> Option<Retained<NSScreen>> {
+        let mtm = MainThreadMarker::new()?;
+        let bounds = self.bounds();
+        // Screen frames have the origin at the bottom-left corner
+        let main_height = CGDisplay::main().bounds().size.height;
+        let y = main_height - bounds.origin.y - bounds.size.height;
+
+        NSScreen::screens(mtm).into_iter().find(|screen| {
+            let frame = screen.frame();
+            frame.origin.x == bounds.origin.x
+                && frame.origin.y == y
+                && frame.size.width == bounds.size.width
+                && frame.size.height == bounds.size.height
+        })
+    }
+}
+
+impl MonitorApi for MonitorImpl {
+    fn name(&self) -> Option<String> {
+        let screen = self.screen()?;
+        let name: Retained<NSString> = unsafe { msg_send_id![&screen, localizedName] };
+        Some(name.to_string())
+    }
+
+    fn position(&self) -> PhysicalPosition<i32> {
+        let origin = self.bounds().origin;
+        LogicalPosition::new(origin.x, origin.y).to_physical(self.scale_factor())
+    }
+
+    fn size(&self) -> PhysicalSize<u32> {
+        match self.display().display_mode() {
+            Some(mode) => PhysicalSize::new(mode.pixel_width() as u32, mode.pixel_height() as u32),
+            None => {
+                let display = self.display();
+                PhysicalSize::new(display.pixels_wide() as u32, display.pixels_high() as u32)
+            }
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        // Retina modes have more pixels than points
+        self.display()
+            .display_mode()
+            .filter(|mode| mode.width() > 0)
+            .map(|mode| mode.pixel_width() as f64 / mode.width() as f64)
+            .unwrap_or(1.0)
+    }
+
+    fn refresh_rate_millihertz(&self) -> Option<u32> {
+        // Built-in displays report zero
+        self.display()
+            .display_mode()
+            .map(|mode| to_millihertz(&mode))
+            .filter(|refresh_rate| *refresh_rate > 0)
+    }
+
+    fn video_modes(&self) -> Vec<VideoMode> {
+        CGDisplayMode::all_display_modes(self.display_id, ptr::null())
+            .unwrap_or_default()
+            .iter()
+            .map(|mode| {
+                VideoMode::new(
+                    PhysicalSize::new(mode.pixel_width() as u32, mode.pixel_height() as u32),
+                    mode.bit_depth() as u16,
+                    to_millihertz(mode),
+                    Monitor::new(*self),
+                )
+            })
+            .collect()
+    }
+}
+
+#[inline]
+fn to_millihertz(mode: &CGDisplayMode) -> u32 { (mode.refresh_rate() * 1000.0).round() as u32 }
+
+/// Returns all active displays.
+pub(super) fn monitors() -> Vec<MonitorImpl> {
+    CGDisplay::active_displays()
+        .unwrap_or_default()
+        .into_iter()
+        .map(MonitorImpl::new)
+        .collect()
+}
+
+/// Returns the display with the largest part of the window frame.
+pub(super) fn monitor_for_frame(frame: NSRect) -> Option<MonitorImpl> {
+    // Convert to the display coordinates
+    let main_height = CGDisplay::main().bounds().size.height;
+    let top = main_height - frame.origin.y - frame.size.height;
+    let (left, right) = (frame.origin.x, frame.origin.x + frame.size.width);
+    let bottom = top + frame.size.height;
+
+    monitors()
+        .into_iter()
+        .map(|monitor| {
+            let bounds = monitor.bounds();
+            let width = right.min(bounds.origin.x + bounds.size.width) - left.max(bounds.origin.x);
+            let height =
+                bottom.min(bounds.origin.y + bounds.size.height) - top.max(bounds.origin.y);
+            (monitor, width.max(0.0) * height.max(0.0))
+        })
+        .filter(|(_, area)| *area > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(monitor, _)| monitor)
+}
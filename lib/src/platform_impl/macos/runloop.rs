@@ -1,15 +1,21 @@
-use std::ptr;
+use std::{ffi::c_void, ptr, time::Instant};
 
 use core_foundation::{
-    base::{Boolean, CFIndex, CFOptionFlags},
+    base::{Boolean, CFIndex, CFOptionFlags, CFRelease},
+    date::CFAbsoluteTimeGetCurrent,
     runloop::{
         kCFRunLoopCommonModes,
         CFRunLoopAddObserver,
+        CFRunLoopAddTimer,
         CFRunLoopGetMain,
         CFRunLoopObserverCallBack,
         CFRunLoopObserverContext,
         CFRunLoopObserverCreate,
         CFRunLoopRef,
+        CFRunLoopTimerCreate,
+        CFRunLoopTimerInvalidate,
+        CFRunLoopTimerRef,
+        CFRunLoopTimerSetNextFireDate,
         CFRunLoopWakeUp,
     },
 };
@@ -17,8 +23,14 @@ use core_foundation::{
 const FALSE: Boolean = 0;
 const TRUE: Boolean = 1;
 
+#[derive(Debug, Clone)]
 pub(super) struct RunLoop(CFRunLoopRef);
 
+// The main run loop lives as long as the process, and waking it up is
+// thread-safe.
+unsafe impl Send for RunLoop {}
+unsafe impl Sync for RunLoop {}
+
 impl RunLoop {
     pub(super) unsafe fn get() -> Self { RunLoop(unsafe { CFRunLoopGetMain() }) }
 
@@ -37,3 +49,71 @@ impl RunLoop {
         unsafe { CFRunLoopAddObserver(self.0, observer, kCFRunLoopCommonModes) };
     }
 }
+
+/// Timer that wakes the main run loop up according to the control flow.
+#[derive(Debug)]
+pub(super) struct EventLoopWaker {
+    timer:          CFRunLoopTimerRef,
+    next_fire_date: Option<Instant>,
+}
+
+impl EventLoopWaker {
+    pub(super) fn new() -> Self {
+        extern "C" fn wakeup_main_loop(_timer: CFRunLoopTimerRef, _info: *mut c_void) {}
+
+        unsafe {
+            // The timer repeats with a tiny interval to mimic polling, it's
+            // inactive until the first fire date is set.
+            let timer = CFRunLoopTimerCreate(
+                ptr::null_mut(),
+                f64::MAX,
+                0.000_000_1,
+                0,
+                0,
+                wakeup_main_loop,
+                ptr::null_mut(),
+            );
+            CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
+
+            Self {
+                timer,
+                next_fire_date: None,
+            }
+        }
+    }
+
+    /// Stops waking the run loop up.
+    pub(super) fn stop(&mut self) {
+        if self.next_fire_date.take().is_some() {
+            unsafe { CFRunLoopTimerSetNextFireDate(self.timer, f64::MAX) }
+        }
+    }
+
+    /// Wakes the run loop up continuously.
+    pub(super) fn start(&mut self) { self.start_at(Instant::now()); }
+
+    /// Wakes the run loop up at the specified time.
+    pub(super) fn start_at(&mut self, instant: Instant) {
+        if self.next_fire_date == Some(instant) {
+            return;
+        }
+        self.next_fire_date = Some(instant);
+
+        let timeout = instant.saturating_duration_since(Instant::now());
+        unsafe {
+            CFRunLoopTimerSetNextFireDate(
+                self.timer,
+                CFAbsoluteTimeGetCurrent() + timeout.as_secs_f64(),
+            )
+        }
+    }
+}
+
+impl Drop for EventLoopWaker {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopTimerInvalidate(self.timer);
+            CFRelease(self.timer as _);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use objc2::{
+    declare_class,
+    msg_send_id,
+    mutability,
+    rc::Retained,
+    runtime::{AnyObject, NSObjectProtocol},
+    sel,
+    ClassType,
+    DeclaredClass,
+};
+use objc2_app_kit::{NSApp, NSStatusBar, NSStatusItem};
+use objc2_foundation::{MainThreadBound, MainThreadMarker, NSObject, NSString};
+
+use super::app_delegate::AppDelegate;
+use crate::{
+    platform::{TrayApi, Wrapper},
+    ContextOwner,
+    Error,
+    Event,
+    Icon,
+    Menu,
+    TrayEventKind,
+    TrayId,
+};
+
+/// Value of `NSVariableStatusItemLength`, the item fits its content.
+const VARIABLE_LENGTH: f64 = -1.0;
+
+#[derive(Debug)]
+pub(super) struct Ivars {
+    id: TrayId,
+}
+
+declare_class!(
+    #[derive(Debug)]
+    pub(super) struct TrayTarget;
+
+    unsafe impl ClassType for TrayTarget {
+        type Super = NSObject;
+        type Mutability = mutability::MainThreadOnly;
+        const NAME: &'static str = "CocoaTrayTarget";
+    }
+
+    impl DeclaredClass for TrayTarget {
+        type Ivars = Ivars;
+    }
+
+    unsafe impl TrayTarget {
+        #[method(click:)]
+        fn __click(&self, _sender: Option<&AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            let id = self.ivars().id;
+            let click_count = NSApp(mtm)
+                .currentEvent()
+                .map(|event| unsafe { event.clickCount() })
+                .unwrap_or(1);
+
+            let delegate = AppDelegate::get(mtm);
+            delegate.handle_event(Event::Tray {
+                id,
+                kind: TrayEventKind::Click,
+            });
+            if click_count == 2 {
+                delegate.handle_event(Event::Tray {
+                    id,
+                    kind: TrayEventKind::DoubleClick,
+                });
+            }
+        }
+    }
+
+    unsafe impl NSObjectProtocol for TrayTarget {}
+);
+
+impl TrayTarget {
+    #[inline]
+    fn new(mtm: MainThreadMarker, id: TrayId) -> Retained<Self> {
+        let this = mtm.alloc();
+        let this = this.set_ivars(Ivars {
+            id,
+        });
+
+        unsafe { msg_send_id![super(this), init] }
+    }
+}
+
+/// Status bar item.
+#[derive(Debug)]
+pub(crate) struct TrayImpl {
+    id:      TrayId,
+    native:  MainThreadBound<Retained<NSStatusItem>>,
+    /// Buttons don't retain their targets.
+    _target: MainThreadBound<Retained<TrayTarget>>,
+    icon:    Icon,
+    /// The native menu refers to items owned by the menu.
+    _menu:   Option<Menu>,
+}
+
+impl TrayImpl {
+    #[inline]
+    fn mtm(&self) -> MainThreadMarker { MainThreadMarker::new().unwrap() }
+
+    fn set_icon_image(&self) {
+        let mtm = self.mtm();
+        if let Some(button) = unsafe { self.native.get(mtm).button(mtm) } {
+            let image = self.icon.get_impl().get_native(mtm);
+            unsafe { button.setImage(Some(&image)) };
+        }
+    }
+}
+
+impl TrayApi for TrayImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        id: TrayId,
+        icon: Icon,
+        tooltip: Option<String>,
+        menu: Option<Menu>,
+    ) -> Result<Self, Error> {
+        let mtm = ctx.context().get_impl().mtm();
+
+        let status_bar = unsafe { NSStatusBar::systemStatusBar() };
+        let native = unsafe { status_bar.statusItemWithLength(VARIABLE_LENGTH) };
+        let target = TrayTarget::new(mtm, id);
+        if let Some(button) = unsafe { native.button(mtm) } {
+            unsafe { button.setTarget(Some(&target)) };
+            unsafe { button.setAction(Some(sel!(click:))) };
+        }
+
+        let mut tray = Self {
+            id,
+            native: MainThreadBound::new(native, mtm),
+            _target: MainThreadBound::new(target, mtm),
+            icon,
+            _menu: None,
+        };
+        tray.set_icon_image();
+        tray.set_tooltip(tooltip);
+        tray.set_menu(menu);
+
+        Ok(tray)
+    }
+
+    #[inline]
+    fn id(&self) -> TrayId { self.id }
+
+    #[inline]
+    fn set_icon(&mut self, icon: Icon) {
+        self.icon = icon;
+        self.set_icon_image();
+    }
+
+    fn set_tooltip(&mut self, tooltip: Option<String>) {
+        let mtm = self.mtm();
+        if let Some(button) = unsafe { self.native.get(mtm).button(mtm) } {
+            let tooltip = tooltip.map(|tooltip| NSString::from_str(&tooltip));
+            unsafe { button.setToolTip(tooltip.as_deref()) };
+        }
+    }
+
+    fn set_menu(&mut self, menu: Option<Menu>) {
+        // A status item with a menu shows it instead of sending clicks
+        let mtm = self.mtm();
+        let native = self.native.get(mtm);
+        if let Some(menu) = &menu {
+            unsafe { native.setMenu(Some(&menu.get_impl().get_native(mtm))) };
+        } else {
+            unsafe { native.setMenu(None) };
+        }
+        self._menu = menu;
+    }
+}
+
+impl Drop for TrayImpl {
+    fn drop(&mut self) {
+        let mtm = self.mtm();
+        let native = self.native.get(mtm);
+        if let Some(button) = unsafe { native.button(mtm) } {
+            unsafe { button.setTarget(None) };
+        }
+        unsafe { NSStatusBar::systemStatusBar().removeStatusItem(native) };
+    }
+}
@@ -1,34 +1,61 @@
-use std::{cell::Cell, ptr};
+use std::{cell::Cell, path::PathBuf, ptr};
 
-use dpi::{LogicalPosition, LogicalSize};
+use dpi::{LogicalPosition, LogicalSize, PhysicalPosition};
 use objc2::{
     declare_class,
     msg_send_id,
     mutability,
     rc::{Retained, Weak},
-    runtime::NSObjectProtocol,
+    runtime::{NSObjectProtocol, ProtocolObject},
     sel,
     ClassType,
     DeclaredClass,
 };
 use objc2_app_kit::{
+    NSDragOperation,
+    NSDraggingInfo,
     NSEvent,
     NSEventPhase,
+    NSEventSubtype,
+    NSPasteboardTypeFileURL,
+    NSPasteboardTypeString,
+    NSPointingDeviceType,
     NSResponder,
     NSTrackingRectTag,
     NSView,
     NSViewFrameDidChangeNotification,
 };
-use objc2_foundation::{CGRect, MainThreadMarker, NSNotificationCenter, NSObject, NSRect};
+use objc2_foundation::{
+    CGRect,
+    MainThreadMarker,
+    NSArray,
+    NSNotificationCenter,
+    NSObject,
+    NSRect,
+    NSURL,
+};
 
-use super::{app_delegate::AppDelegate, CocoaWindow};
+use super::{
+    app_delegate::AppDelegate,
+    keyboard::{to_keyboard_event, to_modifiers, to_named_key},
+    CocoaWindow,
+};
 use crate::{
     Event,
+    GestureEvent,
+    GesturePhase,
+    Key,
+    KeyState,
+    KeyboardEvent,
+    Modifiers,
     MouseButton,
     MouseButtonState,
     MouseEvent,
+    NamedKey,
     ScrollingDelta,
     ScrollingPhase,
+    TabletEvent,
+    TabletTool,
     WindowEvent,
 };
 
@@ -43,6 +70,7 @@ pub(super) struct ViewState {
     app_delegate:      Retained<AppDelegate>,
     ns_window:         Weak<CocoaWindow>,
     tracking_rect_tag: Cell<Option<NSTrackingRectTag>>,
+    modifiers:         Cell<Modifiers>,
 }
 
 declare_class!(
@@ -88,8 +116,11 @@ declare_class!(
         }
 
         #[method(mouseDown:)]
-        fn mouse_down(&self, _event: &NSEvent) {
+        fn mouse_down(&self, event: &NSEvent) {
             self.queue_mouse_click(LEFT_MOUSE_BUTTON, MouseButtonState::Pressed);
+            if is_tablet_point(event) {
+                self.emit_tablet_event(TabletEvent::Down);
+            }
         }
 
         #[method(rightMouseDown:)]
@@ -103,8 +134,11 @@ declare_class!(
         }
 
         #[method(mouseUp:)]
-        fn mouse_up(&self, _event: &NSEvent) {
+        fn mouse_up(&self, event: &NSEvent) {
             self.queue_mouse_click(LEFT_MOUSE_BUTTON, MouseButtonState::Released);
+            if is_tablet_point(event) {
+                self.emit_tablet_event(TabletEvent::Up);
+            }
         }
 
         #[method(rightMouseUp:)]
@@ -145,6 +179,41 @@ declare_class!(
             });
         }
 
+        #[method(magnifyWithEvent:)]
+        unsafe fn magnify_with_event(&self, event: &NSEvent) {
+            if let Some(phase) = to_gesture_phase(event.phase()) {
+                self.emit_gesture_event(GestureEvent::Pinch {
+                    delta: event.magnification(),
+                    phase,
+                });
+            }
+        }
+
+        #[method(rotateWithEvent:)]
+        unsafe fn rotate_with_event(&self, event: &NSEvent) {
+            if let Some(phase) = to_gesture_phase(event.phase()) {
+                self.emit_gesture_event(GestureEvent::Rotate {
+                    delta: event.rotation() as f64,
+                    phase,
+                });
+            }
+        }
+
+        #[method(swipeWithEvent:)]
+        unsafe fn swipe_with_event(&self, event: &NSEvent) {
+            // Cocoa reports recognized swipes only
+            self.emit_gesture_event(GestureEvent::Swipe {
+                fingers: 0,
+                delta:   (event.deltaX(), event.deltaY()),
+                phase:   GesturePhase::Ended,
+            });
+        }
+
+        #[method(smartMagnifyWithEvent:)]
+        fn smart_magnify_with_event(&self, _event: &NSEvent) {
+            self.emit_gesture_event(GestureEvent::SmartMagnify);
+        }
+
         #[method(mouseEntered:)]
         fn mouse_entered(&self, _event: &NSEvent) {
             self.emit_mouse_event(MouseEvent::Entered);
@@ -163,6 +232,29 @@ declare_class!(
         #[method(mouseDragged:)]
         fn mouse_dragged(&self, event: &NSEvent) {
             self.emit_mouse_motion(event);
+            if is_tablet_point(event) {
+                self.emit_tablet_motion(event);
+            }
+        }
+
+        #[method(tabletPoint:)]
+        fn tablet_point(&self, event: &NSEvent) {
+            self.emit_tablet_motion(event);
+        }
+
+        #[method(tabletProximity:)]
+        unsafe fn tablet_proximity(&self, event: &NSEvent) {
+            let event = match event.isEnteringProximity() {
+                true => TabletEvent::ProximityEntered {
+                    tool: match event.pointingDeviceType() {
+                        NSPointingDeviceType::Pen => TabletTool::Pen,
+                        NSPointingDeviceType::Eraser => TabletTool::Eraser,
+                        _ => TabletTool::Other,
+                    },
+                },
+                false => TabletEvent::ProximityLeft,
+            };
+            self.emit_tablet_event(event);
         }
 
         #[method(rightMouseDragged:)]
@@ -175,27 +267,160 @@ declare_class!(
             self.emit_mouse_motion(event);
         }
 
+        #[method(keyDown:)]
+        unsafe fn key_down(&self, event: &NSEvent) {
+            self.emit_keyboard_event(to_keyboard_event(event, KeyState::Pressed));
+        }
+
+        #[method(keyUp:)]
+        unsafe fn key_up(&self, event: &NSEvent) {
+            self.emit_keyboard_event(to_keyboard_event(event, KeyState::Released));
+        }
+
+        #[method(flagsChanged:)]
+        unsafe fn flags_changed(&self, event: &NSEvent) {
+            let modifiers = to_modifiers(event.modifierFlags());
+            if modifiers == self.ivars().modifiers.replace(modifiers) {
+                return;
+            }
+
+            // Cocoa reports modifier keys only by flag changes, so the key state
+            // is derived from the modifiers. Characters aren't available for
+            // such events.
+            let key_code = event.keyCode();
+            let modifier = match to_named_key(key_code) {
+                Some(NamedKey::Shift) => Some((NamedKey::Shift, Modifiers::SHIFT)),
+                Some(NamedKey::Control) => Some((NamedKey::Control, Modifiers::CONTROL)),
+                Some(NamedKey::Alt) => Some((NamedKey::Alt, Modifiers::ALT)),
+                Some(NamedKey::Super) => Some((NamedKey::Super, Modifiers::SUPER)),
+                Some(NamedKey::CapsLock) => Some((NamedKey::CapsLock, Modifiers::CAPS_LOCK)),
+                _ => None,
+            };
+            if let Some((named_key, modifier)) = modifier {
+                let state = if modifiers.contains(modifier) {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                self.emit_keyboard_event(KeyboardEvent::Input {
+                    scan_code: key_code as u32,
+                    key: Key::Named(named_key),
+                    text: None,
+                    state,
+                    repeat: false,
+                    modifiers,
+                });
+            }
+
+            self.emit_keyboard_event(KeyboardEvent::ModifiersChanged(modifiers));
+        }
+
         #[method(acceptsFirstResponder)]
         fn accepts_first_responder(&self) -> bool {
             true
         }
+
+        #[method(draggingEntered:)]
+        fn dragging_entered(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> NSDragOperation {
+            let (paths, text) = drag_data(sender);
+            self.queue_window_event(WindowEvent::DragEntered {
+                paths,
+                text,
+                position: self.drag_position(sender),
+            });
+            NSDragOperation::Copy
+        }
+
+        #[method(draggingUpdated:)]
+        fn dragging_updated(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> NSDragOperation {
+            self.queue_window_event(WindowEvent::DragMoved {
+                position: self.drag_position(sender),
+            });
+            NSDragOperation::Copy
+        }
+
+        #[method(draggingExited:)]
+        fn dragging_exited(&self, _sender: Option<&ProtocolObject<dyn NSDraggingInfo>>) {
+            self.queue_window_event(WindowEvent::DragLeft);
+        }
+
+        #[method(performDragOperation:)]
+        fn perform_drag_operation(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> bool {
+            let (paths, text) = drag_data(sender);
+            self.queue_window_event(WindowEvent::DragDropped {
+                paths,
+                text,
+                position: self.drag_position(sender),
+            });
+            true
+        }
     }
 );
 
+/// Converts a phase of a trackpad gesture.
+///
+/// Returns `None` for phases that aren't reported, e.g. a gesture that may
+/// begin.
+fn to_gesture_phase(phase: NSEventPhase) -> Option<GesturePhase> {
+    match phase {
+        NSEventPhase::Began => Some(GesturePhase::Started),
+        NSEventPhase::Changed | NSEventPhase::Stationary => Some(GesturePhase::Changed),
+        NSEventPhase::Ended => Some(GesturePhase::Ended),
+        NSEventPhase::Cancelled => Some(GesturePhase::Cancelled),
+        _ => None,
+    }
+}
+
+/// Checks that the mouse event comes from a tablet tool.
+#[inline]
+fn is_tablet_point(event: &NSEvent) -> bool {
+    let subtype = unsafe { event.subtype() };
+    subtype == NSEventSubtype::TabletPoint
+}
+
+/// Returns dragged files and text.
+///
+/// Text is skipped for files, Finder puts their names there.
+fn drag_data(sender: &ProtocolObject<dyn NSDraggingInfo>) -> (Vec<PathBuf>, Option<String>) {
+    let pasteboard = unsafe { sender.draggingPasteboard() };
+    let paths: Vec<PathBuf> = unsafe { pasteboard.pasteboardItems() }
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| unsafe { item.stringForType(NSPasteboardTypeFileURL) })
+                .filter_map(|url| unsafe { NSURL::URLWithString(&url) })
+                .filter_map(|url| unsafe { url.path() })
+                .map(|path| PathBuf::from(path.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let text = match paths.is_empty() {
+        true => {
+            unsafe { pasteboard.stringForType(NSPasteboardTypeString) }.map(|text| text.to_string())
+        }
+        false => None,
+    };
+    (paths, text)
+}
+
 impl View {
     pub(super) fn new(
         app_delegate: Retained<AppDelegate>,
         ns_window: &Retained<CocoaWindow>,
-    ) -> Retained<Self> {
+    ) -> Option<Retained<Self>> {
         let mtm = MainThreadMarker::from(ns_window.as_ref());
         let this = mtm.alloc().set_ivars(ViewState {
             app_delegate,
             ns_window: Weak::from_retained(ns_window),
             tracking_rect_tag: Default::default(),
+            modifiers: Default::default(),
         });
-        let view: Retained<Self> = unsafe { msg_send_id![super(this), init] };
+        let view: Retained<Self> = unsafe { msg_send_id![super(this), init] }?;
 
         view.setPostsFrameChangedNotifications(true);
+        let dragged_types =
+            unsafe { NSArray::from_slice(&[NSPasteboardTypeFileURL, NSPasteboardTypeString]) };
+        view.registerForDraggedTypes(&dragged_types);
         let notification_center = unsafe { NSNotificationCenter::defaultCenter() };
         unsafe {
             notification_center.addObserver_selector_name_object(
@@ -206,7 +431,7 @@ impl View {
             )
         }
 
-        view
+        Some(view)
     }
 
     #[inline]
@@ -236,6 +461,21 @@ impl View {
         self.queue_window_event(WindowEvent::Mouse(event));
     }
 
+    #[inline]
+    fn emit_gesture_event(&self, event: GestureEvent) {
+        self.queue_window_event(WindowEvent::Gesture(event));
+    }
+
+    #[inline]
+    fn emit_tablet_event(&self, event: TabletEvent) {
+        self.queue_window_event(WindowEvent::Tablet(event));
+    }
+
+    #[inline]
+    fn emit_keyboard_event(&self, event: KeyboardEvent) {
+        self.queue_window_event(WindowEvent::Keyboard(event));
+    }
+
     fn queue_mouse_click(&self, button_number: u16, state: MouseButtonState) {
         let button = match button_number {
             LEFT_MOUSE_BUTTON => MouseButton::Left,
@@ -264,6 +504,25 @@ impl View {
         self.ivars().tracking_rect_tag.set(Some(tracking_rect_tag));
     }
 
+    fn drag_position(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> PhysicalPosition<f64> {
+        let window_location = unsafe { sender.draggingLocation() };
+        let position = self.convertPoint_fromView(window_location, None);
+        LogicalPosition::new(position.x, position.y).to_physical(self.scale_factor())
+    }
+
+    fn emit_tablet_motion(&self, event: &NSEvent) {
+        let window_location = unsafe { event.locationInWindow() };
+        let position = self.convertPoint_fromView(window_location, None);
+        let scale_factor = self.scale_factor();
+        let tilt = unsafe { event.tilt() };
+        self.emit_tablet_event(TabletEvent::Motion {
+            position: LogicalPosition::new(position.x, position.y).to_physical(scale_factor),
+            pressure: unsafe { event.pressure() } as f64,
+            tilt:     (tilt.x, tilt.y),
+            rotation: unsafe { event.rotation() } as f64,
+        });
+    }
+
     fn emit_mouse_motion(&self, event: &NSEvent) {
         let window_location = unsafe { event.locationInWindow() };
         let position = self.convertPoint_fromView(window_location, None);
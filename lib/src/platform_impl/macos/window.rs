@@ -21,14 +21,35 @@ use objc2_app_kit::{
     NSWindowStyleMask,
     NSWindowTitleVisibility,
 };
-use objc2_foundation::{CGPoint, CGSize, MainThreadBound, MainThreadMarker, NSObject, NSRect};
+use objc2_foundation::{
+    CGPoint,
+    CGSize,
+    MainThreadBound,
+    MainThreadMarker,
+    NSObject,
+    NSPoint,
+    NSRect,
+};
 
-use super::{view::View, window_delegate::WindowDelegate, window_utils::to_cgsize};
+use super::{
+    context_menu::ContextMenu,
+    monitor::monitor_for_frame,
+    view::View,
+    window_delegate::WindowDelegate,
+    window_utils::to_cgsize,
+};
 use crate::{
     platform::{WindowApi, Wrapper},
     ActiveApplication,
     ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    Error,
+    ErrorKind,
+    Fullscreen,
     InitMode,
+    Menu,
+    Monitor,
     WindowId,
     WindowOptions,
 };
@@ -58,8 +79,13 @@ impl CocoaWindow {
 
 #[derive(Debug)]
 pub(crate) struct WindowImpl {
-    delegate: MainThreadBound<Retained<WindowDelegate>>,
-    native:   MainThreadBound<Retained<CocoaWindow>>,
+    delegate:       MainThreadBound<Retained<WindowDelegate>>,
+    native:         MainThreadBound<Retained<CocoaWindow>>,
+    // Input methods aren't supported yet, only the setting is kept.
+    is_ime_allowed: bool,
+    // Cursor control isn't supported yet, only the settings are kept.
+    cursor:         Cursor,
+    cursor_visible: bool,
 }
 
 impl WindowImpl {
@@ -92,7 +118,7 @@ impl WindowApi for WindowImpl {
         mode: InitMode,
         options: Option<WindowOptions>,
         size: Option<Size>,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         // Extract the application context
         let mtm = ctx.context().get_impl().mtm();
         let app_delegate = ctx.context().get_impl().app_delegate().clone();
@@ -122,7 +148,7 @@ impl WindowApi for WindowImpl {
         let content_rect = NSRect::new(CGPoint::new(200.0, 200.0), cgsize);
 
         let this = mtm.alloc();
-        let window: Retained<CocoaWindow> = unsafe {
+        let window: Option<Retained<CocoaWindow>> = unsafe {
             msg_send_id![
                 super(this.set_ivars(())),
                 initWithContentRect: content_rect,
@@ -131,16 +157,19 @@ impl WindowApi for WindowImpl {
                 defer: false,
             ]
         };
+        let window = window.ok_or_else(|| Error::new(ErrorKind::Os, "window not created."))?;
 
         // Create a window delegate
-        let window_delegate = WindowDelegate::new(mtm, app_delegate.clone(), window.clone(), mode);
+        let window_delegate = WindowDelegate::new(mtm, app_delegate.clone(), window.clone(), mode)
+            .ok_or_else(|| Error::new(ErrorKind::Os, "window delegate not created."))?;
         autoreleasepool(|_| {
             let object = ProtocolObject::from_ref(&*window_delegate);
             window.setDelegate(Some(object));
         });
 
         // Create a root view
-        let view = View::new(app_delegate, &window);
+        let view = View::new(app_delegate, &window)
+            .ok_or_else(|| Error::new(ErrorKind::Os, "window content view not created."))?;
         window.setContentView(Some(&view));
 
         // Enable mouse motion events.
@@ -171,10 +200,13 @@ impl WindowApi for WindowImpl {
             _ => (),
         }
 
-        Self {
-            delegate: MainThreadBound::new(window_delegate, mtm),
-            native:   MainThreadBound::new(window, mtm),
-        }
+        Ok(Self {
+            delegate:       MainThreadBound::new(window_delegate, mtm),
+            native:         MainThreadBound::new(window, mtm),
+            is_ime_allowed: false,
+            cursor:         Cursor::default(),
+            cursor_visible: true,
+        })
     }
 
     #[inline]
@@ -213,12 +245,17 @@ impl WindowApi for WindowImpl {
     }
 
     #[inline]
-    fn toggle_fullscreen(&mut self) {
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
         self.delegate_on_main(|delegate| {
-            delegate.toggle_fullscreen();
+            delegate.set_fullscreen(fullscreen);
         });
     }
 
+    #[inline]
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        self.delegate_on_main(|delegate| delegate.fullscreen())
+    }
+
     #[inline]
     fn is_fullscreen(&self) -> bool { self.delegate_on_main(|delegate| delegate.is_fullscreen()) }
 
@@ -314,6 +351,83 @@ impl WindowApi for WindowImpl {
 
     #[inline]
     fn scale_factor(&self) -> f64 { self.delegate_on_main(|delegate| delegate.scale_factor()) }
+
+    fn current_monitor(&self) -> Option<Monitor> {
+        let frame = self.native_on_main(|native| native.frame());
+        monitor_for_frame(frame).map(Monitor::new)
+    }
+
+    #[inline]
+    fn set_ime_allowed(&mut self, allowed: bool) { self.is_ime_allowed = allowed; }
+
+    #[inline]
+    fn is_ime_allowed(&self) -> bool { self.is_ime_allowed }
+
+    #[inline]
+    fn set_ime_cursor_area(&mut self, _position: Position, _size: Size) {}
+
+    #[inline]
+    fn request_redraw(&mut self) {
+        self.delegate_on_main(|delegate| {
+            delegate.request_redraw();
+        });
+    }
+
+    #[inline]
+    fn pre_present_notify(&mut self) {}
+
+    #[inline]
+    fn set_cursor(&mut self, cursor: Cursor) { self.cursor = cursor; }
+
+    #[inline]
+    fn cursor(&self) -> Cursor { self.cursor.clone() }
+
+    #[inline]
+    fn set_cursor_visible(&mut self, visible: bool) { self.cursor_visible = visible; }
+
+    #[inline]
+    fn is_cursor_visible(&self) -> bool { self.cursor_visible }
+
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error> {
+        match mode {
+            CursorGrabMode::None => Ok(()),
+            _ => Err(Error::new(
+                ErrorKind::NotSupported,
+                "cursor grabbing is not supported on macOS yet.",
+            )),
+        }
+    }
+
+    #[inline]
+    fn cursor_grab(&self) -> CursorGrabMode { CursorGrabMode::None }
+
+    #[inline]
+    fn set_cursor_position(&mut self, _position: Position) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::NotSupported,
+            "cursor warping is not supported on macOS yet.",
+        ))
+    }
+
+    fn show_context_menu(&mut self, menu: &Menu, position: Position) -> Result<(), Error> {
+        let mtm = MainThreadMarker::new().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotMainThread,
+                "context menus must be shown on the main thread.",
+            )
+        })?;
+        let native = self.get_native(mtm);
+        let view = native
+            .contentView()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "window content view not found."))?;
+
+        // The Y axis of content views goes up
+        let position = position.to_logical::<f64>(native.backingScaleFactor() as f64);
+        let location = NSPoint::new(position.x, view.frame().size.height - position.y);
+        let menu = menu.get_impl().get_native(mtm).clone();
+        ContextMenu::show(mtm, menu, view, location, native.id());
+        Ok(())
+    }
 }
 
 #[cfg(feature = "dh")]
@@ -1,4 +1,4 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use objc2::{
@@ -26,7 +26,7 @@ use super::{
     window_utils::{to_b3_position, to_cgsize, to_macos_coords},
     CocoaWindow,
 };
-use crate::{Event, InitMode, WindowEvent, WindowId, WindowOptions};
+use crate::{Event, Fullscreen, InitMode, WindowEvent, WindowId, WindowOptions};
 
 #[derive(Debug)]
 pub(super) struct State {
@@ -34,6 +34,7 @@ pub(super) struct State {
     window:        Retained<CocoaWindow>,
     init_mode:     Cell<Option<InitMode>>,
     prev_position: Cell<PhysicalPosition<i32>>,
+    fullscreen:    RefCell<Option<Fullscreen>>,
 }
 
 declare_class!(
@@ -73,6 +74,17 @@ declare_class!(
             self.queue_event(WindowEvent::Focused(false));
         }
 
+        #[method(windowDidEnterFullScreen:)]
+        unsafe fn window_did_enter_full_screen(&self, _notification: &NSNotification) {
+            self.queue_event(WindowEvent::FullscreenChanged(true));
+        }
+
+        #[method(windowDidExitFullScreen:)]
+        unsafe fn window_did_exit_full_screen(&self, _notification: &NSNotification) {
+            self.ivars().fullscreen.borrow_mut().take();
+            self.queue_event(WindowEvent::FullscreenChanged(false));
+        }
+
         #[method(windowShouldClose:)]
         unsafe fn window_should_close(&self, _sender: &NSWindow) -> bool {
             self.queue_event(WindowEvent::CloseRequested);
@@ -96,7 +108,7 @@ impl WindowDelegate {
         app_delegate: Retained<AppDelegate>,
         window: Retained<CocoaWindow>,
         init_mode: InitMode,
-    ) -> Retained<WindowDelegate> {
+    ) -> Option<Retained<WindowDelegate>> {
         let this = mtm.alloc();
         let scale_factor = window.backingScaleFactor();
         let origin = to_b3_position(&window);
@@ -107,6 +119,7 @@ impl WindowDelegate {
             prev_position: Cell::new(
                 LogicalPosition::new(origin.x, origin.y).to_physical(scale_factor),
             ),
+            fullscreen: RefCell::new(None),
         });
         unsafe { msg_send_id![super(this), init] }
     }
@@ -215,8 +228,49 @@ impl WindowDelegate {
         self.sync_with_init_mode();
     }
 
-    #[inline]
-    pub(super) fn toggle_fullscreen(&self) { self.window().toggleFullScreen(None); }
+    pub(super) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        // Apps can't switch video modes of native fullscreen spaces, exclusive
+        // modes fall back to the borderless mode on the monitor of the mode.
+        let monitor = match &fullscreen {
+            Some(Fullscreen::Borderless(monitor)) => monitor.clone(),
+            Some(Fullscreen::Exclusive(mode)) => Some(mode.monitor().clone()),
+            None => None,
+        };
+        let is_fullscreen = fullscreen.is_some();
+        *self.ivars().fullscreen.borrow_mut() = fullscreen;
+
+        // AppKit makes windows fullscreen on the screen they are on.
+        if let Some(monitor) = monitor {
+            let position = monitor.position().to_logical(monitor.scale_factor());
+            self.set_position(Position::Logical(position));
+        }
+
+        // Hidden windows enter the mode once shown.
+        if !self.window().isVisible() {
+            let init_mode = &self.ivars().init_mode;
+            if is_fullscreen {
+                init_mode.set(Some(InitMode::Fullscreen));
+            } else if init_mode.get() == Some(InitMode::Fullscreen) {
+                init_mode.set(None);
+            }
+        } else if is_fullscreen != self.is_fullscreen() {
+            self.window().toggleFullScreen(None);
+        }
+    }
+
+    /// Returns the fullscreen mode requested for the window.
+    pub(super) fn fullscreen(&self) -> Option<Fullscreen> {
+        match self.is_fullscreen() {
+            true => Some(
+                self.ivars()
+                    .fullscreen
+                    .borrow()
+                    .clone()
+                    .unwrap_or(Fullscreen::Borderless(None)),
+            ),
+            false => None,
+        }
+    }
 
     #[inline]
     pub(super) fn is_fullscreen(&self) -> bool {
@@ -339,4 +393,15 @@ impl WindowDelegate {
 
     #[inline]
     pub(super) fn scale_factor(&self) -> f64 { self.window().backingScaleFactor() }
+
+    /// Marks the content view as needing display.
+    ///
+    /// AppKit coalesces the requests and calls `drawRect:` once per display
+    /// cycle.
+    #[inline]
+    pub(super) fn request_redraw(&self) {
+        if let Some(view) = self.window().contentView() {
+            view.setNeedsDisplay(true);
+        }
+    }
 }
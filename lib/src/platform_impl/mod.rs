@@ -1,4 +1,40 @@
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "linux", feature = "headless"))]
+mod common;
+#[cfg(any(target_os = "linux", feature = "headless"))]
+mod freedesktop;
+#[cfg(feature = "headless")]
+mod headless;
+#[cfg(all(target_os = "macos", not(feature = "headless")))]
 mod macos;
+#[cfg(all(target_os = "linux", feature = "wayland", not(feature = "headless")))]
+mod wayland;
+// Both backends implement the same platform types, so features stay additive by
+// building only the Wayland backend if both are enabled.
+#[cfg(all(
+    target_os = "linux",
+    feature = "x11",
+    not(any(feature = "wayland", feature = "headless"))
+))]
+mod x11;
 
+#[cfg(all(
+    target_os = "linux",
+    not(any(feature = "x11", feature = "wayland", feature = "headless"))
+))]
+compile_error!(
+    "b3-core requires either the `x11`, the `wayland` or the `headless` feature to be enabled on \
+     Linux."
+);
+
+#[cfg(feature = "headless")]
+pub(crate) use headless::*;
+#[cfg(all(target_os = "macos", not(feature = "headless")))]
 pub(crate) use macos::*;
+#[cfg(all(target_os = "linux", feature = "wayland", not(feature = "headless")))]
+pub(crate) use wayland::*;
+#[cfg(all(
+    target_os = "linux",
+    feature = "x11",
+    not(any(feature = "wayland", feature = "headless"))
+))]
+pub(crate) use x11::*;
@@ -0,0 +1,363 @@
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    collections::VecDeque,
+    fmt::Debug,
+    io,
+    mem,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rustix::{
+    event::{poll, PollFd, PollFlags},
+    io::Errno,
+};
+use smithay_client_toolkit::reexports::client::{
+    backend::WaylandError,
+    Connection,
+    EventQueue,
+    QueueHandle,
+};
+
+use super::{monitor::MonitorImpl, state::WaylandState, window::WindowState};
+#[cfg(feature = "tray")]
+use crate::platform_impl::common::MenuCallbacks;
+use crate::{
+    platform_impl::common::{Accelerators, EventLoopProxyImpl, PendingEvent, UserEvents},
+    ActiveApplication,
+    ControlFlow,
+    Error,
+    ErrorKind,
+    Event,
+    EventHandler,
+    Icon,
+    Key,
+    LifeCycle,
+    Menu,
+    MenuCallback,
+    Modifiers,
+    StartCause,
+    UserEvent,
+    WindowEvent,
+};
+
+pub(super) struct AppState {
+    conn:           Connection,
+    event_queue:    RefCell<EventQueue<WaylandState>>,
+    queue_handle:   QueueHandle<WaylandState>,
+    wayland:        RefCell<WaylandState>,
+    app:            RefCell<Option<ActiveApplication>>,
+    is_running:     Cell<bool>,
+    control_flow:   Cell<ControlFlow>,
+    handler:        RefCell<Option<Box<dyn EventHandler<UserEvent>>>>,
+    pending_events: RefCell<VecDeque<PendingEvent>>,
+    user_events:    UserEvents,
+    app_id:         Option<String>,
+    accelerators:   RefCell<Accelerators>,
+}
+
+impl Debug for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState")
+            .field("is_running", &self.is_running)
+            .field("pending_events", &self.pending_events)
+            .finish()
+    }
+}
+
+impl AppState {
+    pub(super) fn new(
+        conn: Connection,
+        event_queue: EventQueue<WaylandState>,
+        wayland: WaylandState,
+        user_events: UserEvents,
+        app_id: Option<String>,
+    ) -> Self {
+        Self {
+            conn,
+            queue_handle: event_queue.handle(),
+            event_queue: RefCell::new(event_queue),
+            wayland: RefCell::new(wayland),
+            app: RefCell::new(None),
+            is_running: Cell::new(false),
+            control_flow: Cell::new(ControlFlow::default()),
+            handler: RefCell::new(None),
+            pending_events: RefCell::new(VecDeque::new()),
+            user_events,
+            app_id,
+            accelerators: RefCell::new(Accelerators::default()),
+        }
+    }
+
+    #[inline]
+    pub(super) fn conn(&self) -> &Connection { &self.conn }
+
+    #[inline]
+    pub(super) fn app_id(&self) -> Option<&str> { self.app_id.as_deref() }
+
+    #[inline]
+    pub(super) fn queue_handle(&self) -> &QueueHandle<WaylandState> { &self.queue_handle }
+
+    /// Returns the protocol state.
+    ///
+    /// It is borrowed for the whole dispatch of the event queue, so it must not
+    /// be accessed from Wayland event handlers.
+    #[inline]
+    pub(super) fn wayland(&self) -> RefMut<'_, WaylandState> { self.wayland.borrow_mut() }
+
+    #[inline]
+    pub(super) fn set_active_application(&self, active_application: ActiveApplication) {
+        *self.app.borrow_mut() = Some(active_application);
+    }
+
+    #[inline]
+    pub(super) fn set_handler(&self, handler: impl EventHandler<UserEvent> + 'static) {
+        *self.handler.borrow_mut() = Some(Box::new(handler));
+    }
+
+    #[inline]
+    pub(super) fn is_running(&self) -> bool { self.is_running.get() }
+
+    #[inline]
+    pub(super) fn set_is_running(&self, value: bool) { self.is_running.set(value) }
+
+    #[inline]
+    pub(super) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.control_flow.set(control_flow);
+    }
+
+    #[inline]
+    pub(super) fn control_flow(&self) -> ControlFlow { self.control_flow.get() }
+
+    #[inline]
+    pub(super) fn register_window(&self, window: &Rc<WindowState>) {
+        self.wayland().register_window(window);
+    }
+
+    #[inline]
+    pub(super) fn unregister_window(&self, window: &WindowState) {
+        self.wayland().unregister_window(window);
+    }
+
+    #[inline]
+    pub(super) fn monitors(&self) -> Vec<MonitorImpl> { self.wayland().monitors() }
+
+    #[inline]
+    pub(super) fn create_proxy(&self) -> EventLoopProxyImpl { self.user_events.create_proxy() }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn menu_callbacks(&self) -> Rc<MenuCallbacks> { self.user_events.callbacks() }
+
+    #[inline]
+    pub(super) fn queue_event(&self, event: impl Into<PendingEvent>) {
+        self.pending_events.borrow_mut().push_back(event.into());
+    }
+
+    pub(super) fn handle_event(&self, event: Event<UserEvent>) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            let mut handler = self.handler.borrow_mut();
+
+            if let Some(handler) = handler.as_mut() {
+                handler.on_event(app, event);
+            }
+        }
+    }
+
+    /// Calls a menu callback with the active application.
+    fn handle_callback(&self, callback: &MenuCallback) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            callback.call(app);
+        }
+    }
+
+    fn dispatch_pending_events(&self) {
+        // The handler may queue new events (e.g. by showing a window), so the
+        // queue is drained until it stays empty.
+        loop {
+            let events = mem::take(&mut *self.pending_events.borrow_mut());
+            if events.is_empty() {
+                break;
+            }
+            for event in events.into_iter() {
+                match event {
+                    PendingEvent::Event(event) => self.handle_event(event),
+                    PendingEvent::Callback(callback) => self.handle_callback(&callback),
+                }
+            }
+        }
+    }
+
+    /// Delivers a single redraw event to every window that requested one.
+    ///
+    /// Redraws requested by the handler while redrawing are delivered in the
+    /// next iteration.
+    fn dispatch_redraw_requests(&self) {
+        let windows = self.wayland().windows();
+        for window in windows.iter() {
+            if window.take_redraw_request() {
+                self.handle_event(Event::Window(
+                    WindowEvent::RedrawRequested,
+                    window.window_id(),
+                ));
+            }
+        }
+    }
+
+    /// Waits for Wayland events and dispatches them.
+    ///
+    /// The wait is interrupted by user events and key repeats, which are
+    /// generated on the client side.
+    ///
+    /// # Parameters:
+    /// * `timeout` - Maximum wait time, `None` means an unlimited wait.
+    fn dispatch_wayland_events(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        let mut event_queue = self.event_queue.borrow_mut();
+        let mut wayland = self.wayland.borrow_mut();
+
+        let dispatched = event_queue.dispatch_pending(&mut wayland).map_err(|e| {
+            Error::with_source(ErrorKind::Connection, "Wayland events not dispatched.", e)
+        })?;
+        if dispatched == 0 {
+            event_queue.flush().map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "Wayland requests not sent.", e)
+            })?;
+
+            if let Some(guard) = event_queue.prepare_read() {
+                let repeat_timeout = wayland
+                    .key_repeat_deadline()
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+                // Events queued while the loop was about to wait must not be
+                // delayed
+                let has_pending = !self.pending_events.borrow().is_empty()
+                    || wayland.windows().iter().any(|window| window.needs_redraw());
+                let timeout = match has_pending {
+                    true => Some(Duration::ZERO),
+                    false => timeout.into_iter().chain(repeat_timeout).min(),
+                };
+                // Round up to not wake up before the deadline
+                let timeout = timeout.map(|timeout| {
+                    timeout.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32
+                });
+
+                let fd = guard.connection_fd();
+                let user_fd = self.user_events.fd();
+                let mut fds = [
+                    PollFd::new(&fd, PollFlags::IN | PollFlags::ERR),
+                    PollFd::new(&user_fd, PollFlags::IN),
+                ];
+                let is_ready = match poll(&mut fds, timeout.unwrap_or(-1)) {
+                    Ok(_) => !fds[0].revents().is_empty(),
+                    Err(Errno::INTR) => false,
+                    Err(e) => {
+                        return Err(Error::with_source(
+                            ErrorKind::Os,
+                            "Wayland events not polled.",
+                            e,
+                        ))
+                    }
+                };
+
+                if is_ready {
+                    match guard.read() {
+                        Ok(_) => (),
+                        Err(WaylandError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => (),
+                        Err(e) => {
+                            return Err(Error::with_source(
+                                ErrorKind::Connection,
+                                "Wayland events not read.",
+                                e,
+                            ))
+                        }
+                    }
+                }
+            }
+
+            event_queue.dispatch_pending(&mut wayland).map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "Wayland events not dispatched.", e)
+            })?;
+        }
+
+        wayland.handle_key_repeat();
+        Ok(())
+    }
+
+    pub(super) fn run(&self) {
+        self.set_is_running(true);
+        self.handle_event(Event::LifeCycle(LifeCycle::Started));
+        self.handle_event(Event::NewEvents(StartCause::Init));
+
+        while self.is_running() {
+            self.dispatch_pending_events();
+            self.dispatch_redraw_requests();
+            self.dispatch_pending_events();
+            if !self.is_running() {
+                break;
+            }
+            self.handle_event(Event::AboutToWait);
+            if !self.is_running() {
+                break;
+            }
+
+            // Protocol errors are fatal in Wayland, so a failed dispatch means
+            // the connection is gone.
+            let control_flow = self.control_flow();
+            let start = Instant::now();
+            if self
+                .dispatch_wayland_events(control_flow.timeout())
+                .is_err()
+            {
+                break;
+            }
+            self.handle_event(Event::NewEvents(control_flow.start_cause(start)));
+            self.pending_events
+                .borrow_mut()
+                .extend(self.user_events.take());
+        }
+
+        self.handle_event(Event::LifeCycle(LifeCycle::Finished));
+        self.set_is_running(false);
+
+        // Break the reference cycle between the state and the active
+        // application, and release windows owned by the handler.
+        self.handler.borrow_mut().take();
+        self.app.borrow_mut().take();
+        let _ = self.conn.flush();
+    }
+
+    #[inline]
+    pub(super) fn set_menu(&self, menu: Option<&Menu>) {
+        // Wayland has no global application menu, only its accelerators work.
+        *self.accelerators.borrow_mut() = Accelerators::new(menu);
+    }
+
+    /// Triggers the accelerator of the application menu matching the key
+    /// press.
+    ///
+    /// Returns `false` if no accelerator matches the key press.
+    pub(super) fn trigger_accelerator(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let choice = self.accelerators.borrow().choose(key, modifiers);
+        match choice {
+            Some(choice) => {
+                if let Some(event) = choice.into_pending() {
+                    self.queue_event(event);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    pub(super) fn set_icon(&self, _icon: Option<&Icon>) {
+        // Core xdg-shell has no window icons, compositors take them from the
+        // desktop entry matching the application ID.
+    }
+
+    #[inline]
+    pub(super) fn stop(&self) { self.set_is_running(false); }
+}
@@ -0,0 +1,100 @@
+use std::{io::Read, rc::Rc};
+
+use super::app_state::AppState;
+use crate::{platform_impl::common::ClipboardContent, ClipboardKind, Error, ErrorKind};
+
+/// Clipboard selection of the seat.
+///
+/// The primary selection requires a separate protocol, which isn't supported.
+#[derive(Debug, Clone)]
+pub(crate) struct SelectionImpl {
+    state: Rc<AppState>,
+    kind:  ClipboardKind,
+}
+
+impl SelectionImpl {
+    #[inline]
+    pub(super) fn new(state: Rc<AppState>, kind: ClipboardKind) -> Self {
+        Self {
+            state,
+            kind,
+        }
+    }
+
+    fn check_kind(&self) -> Result<(), Error> {
+        match self.kind {
+            ClipboardKind::Clipboard => Ok(()),
+            ClipboardKind::Primary => Err(Error::new(
+                ErrorKind::NotSupported,
+                "primary selection is not supported on Wayland.",
+            )),
+        }
+    }
+
+    #[inline]
+    fn flush(&self) -> Result<(), Error> {
+        self.state
+            .conn()
+            .flush()
+            .map_err(|e| Error::with_source(ErrorKind::Connection, "requests not sent.", e))
+    }
+
+    #[inline]
+    pub(crate) fn kind(&self) -> ClipboardKind { self.kind }
+
+    pub(crate) fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+        self.check_kind()?;
+        self.state
+            .wayland()
+            .set_selection(self.state.queue_handle(), content)?;
+        self.flush()
+    }
+
+    pub(crate) fn mime_types(&self) -> Result<Vec<String>, Error> {
+        self.check_kind()?;
+        let wayland = self.state.wayland();
+        let mime_types = match wayland.selection_content() {
+            Some(content) => content.mime_types().map(String::from).collect(),
+            None => wayland
+                .selection_offer()
+                .map(|offer| offer.with_mime_types(|mime_types| mime_types.to_vec()))
+                .unwrap_or_default(),
+        };
+        Ok(mime_types)
+    }
+
+    pub(crate) fn receive(&self, mime: &str) -> Result<Vec<u8>, Error> {
+        self.check_kind()?;
+        let not_found = || {
+            Error::new(
+                ErrorKind::NotFound,
+                "clipboard has no data of the requested type.",
+            )
+        };
+
+        let wayland = self.state.wayland();
+        // Receiving an own selection would wait for the data forever
+        if let Some(content) = wayland.selection_content() {
+            return content.get(mime).map(<[u8]>::to_vec).ok_or_else(not_found);
+        }
+        let offer = wayland.selection_offer().ok_or_else(not_found)?;
+        let mut pipe = offer
+            .receive(mime.to_owned())
+            .map_err(|e| Error::with_source(ErrorKind::Os, "clipboard data not received.", e))?;
+        drop(wayland);
+
+        // The owner won't write anything until the request is sent
+        self.flush()?;
+        let mut data = Vec::new();
+        pipe.read_to_end(&mut data)
+            .map_err(|e| Error::with_source(ErrorKind::Os, "clipboard data not received.", e))?;
+        Ok(data)
+    }
+
+    pub(crate) fn clear(&self) {
+        if self.check_kind().is_ok() {
+            self.state.wayland().clear_selection();
+            let _ = self.flush();
+        }
+    }
+}
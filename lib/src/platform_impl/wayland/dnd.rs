@@ -0,0 +1,52 @@
+use std::{io::Read, rc::Rc};
+
+use smithay_client_toolkit::{
+    data_device_manager::data_offer::DragOffer,
+    reexports::client::{protocol::wl_data_device_manager::DndAction, Connection},
+};
+
+use super::window::WindowState;
+use crate::platform_impl::common::{DragData, DRAG_MIME_TYPES};
+
+/// Drag over one of the application windows.
+#[derive(Debug)]
+pub(super) struct Drag {
+    window: Rc<WindowState>,
+    data:   Option<DragData>,
+}
+
+impl Drag {
+    /// Accepts the offer and fetches its data.
+    ///
+    /// Data is received before the drop, so that the window knows what is
+    /// dragged over it.
+    pub(super) fn new(conn: &Connection, window: Rc<WindowState>, offer: &DragOffer) -> Self {
+        let mime = offer.with_mime_types(|mime_types| {
+            DRAG_MIME_TYPES
+                .into_iter()
+                .find(|mime| mime_types.iter().any(|offered| offered == mime))
+        });
+        offer.accept_mime_type(offer.serial, mime.map(String::from));
+        offer.set_actions(DndAction::Copy, DndAction::Copy);
+
+        let data = mime.and_then(|mime| {
+            let mut pipe = offer.receive(mime.into()).ok()?;
+            // The source won't write anything until the request is sent
+            conn.flush().ok()?;
+            let mut data = Vec::new();
+            pipe.read_to_end(&mut data).ok()?;
+            Some(DragData::from_mime(mime, &data))
+        });
+
+        Self {
+            window,
+            data,
+        }
+    }
+
+    #[inline]
+    pub(super) fn window(&self) -> &Rc<WindowState> { &self.window }
+
+    #[inline]
+    pub(super) fn data(&self) -> Option<&DragData> { self.data.as_ref() }
+}
@@ -0,0 +1,225 @@
+use std::rc::Rc;
+
+use smithay_client_toolkit::reexports::client::{
+    protocol::wl_pointer::WlPointer,
+    Connection,
+    Dispatch,
+    QueueHandle,
+};
+use wayland_protocols::wp::pointer_gestures::zv1::client::{
+    zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+    zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+    zwp_pointer_gestures_v1::{self, ZwpPointerGesturesV1},
+};
+
+use super::{state::WaylandState, window::WindowState};
+use crate::{GestureEvent, GesturePhase};
+
+/// Trackpad gestures of a pointer (pointer-gestures protocol).
+///
+/// Update and end events don't refer to a surface, so the window is captured
+/// when a gesture begins, see: https://wayland.app/protocols/pointer-gestures-unstable-v1
+pub(super) struct PointerGestures {
+    pointer:  WlPointer,
+    swipe:    ZwpPointerGestureSwipeV1,
+    pinch:    ZwpPointerGesturePinchV1,
+    /// Window and number of fingers of the active swipe.
+    swiping:  Option<(Rc<WindowState>, u32)>,
+    /// Window and the last scale of the active pinch.
+    pinching: Option<(Rc<WindowState>, f64)>,
+}
+
+impl PointerGestures {
+    pub(super) fn new(
+        manager: &ZwpPointerGesturesV1,
+        pointer: WlPointer,
+        qh: &QueueHandle<WaylandState>,
+    ) -> Self {
+        let swipe = manager.get_swipe_gesture(&pointer, qh, ());
+        let pinch = manager.get_pinch_gesture(&pointer, qh, ());
+
+        Self {
+            pointer,
+            swipe,
+            pinch,
+            swiping: None,
+            pinching: None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn pointer(&self) -> &WlPointer { &self.pointer }
+
+    pub(super) fn destroy(&self) {
+        self.swipe.destroy();
+        self.pinch.destroy();
+    }
+}
+
+#[inline]
+fn end_phase(cancelled: i32) -> GesturePhase {
+    match cancelled {
+        0 => GesturePhase::Ended,
+        _ => GesturePhase::Cancelled,
+    }
+}
+
+impl Dispatch<ZwpPointerGesturesV1, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpPointerGesturesV1,
+        _event: zwp_pointer_gestures_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // The manager has no events
+    }
+}
+
+impl Dispatch<ZwpPointerGestureSwipeV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwpPointerGestureSwipeV1,
+        event: zwp_pointer_gesture_swipe_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let window = match &event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin {
+                surface, ..
+            } => state.window(surface),
+            _ => None,
+        };
+        let gestures = match state
+            .pointer_gestures_mut()
+            .iter_mut()
+            .find(|gestures| &gestures.swipe == proxy)
+        {
+            Some(gestures) => gestures,
+            None => return,
+        };
+
+        match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin {
+                fingers, ..
+            } => {
+                if let Some(window) = window {
+                    window.handle_gesture(GestureEvent::Swipe {
+                        fingers,
+                        delta: (0.0, 0.0),
+                        phase: GesturePhase::Started,
+                    });
+                    gestures.swiping = Some((window, fingers));
+                }
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update {
+                dx,
+                dy,
+                ..
+            } => {
+                if let Some((window, fingers)) = gestures.swiping.as_ref() {
+                    let scale_factor = window.scale_factor();
+                    window.handle_gesture(GestureEvent::Swipe {
+                        fingers: *fingers,
+                        delta:   (dx * scale_factor, dy * scale_factor),
+                        phase:   GesturePhase::Changed,
+                    });
+                }
+            }
+            zwp_pointer_gesture_swipe_v1::Event::End {
+                cancelled, ..
+            } => {
+                if let Some((window, fingers)) = gestures.swiping.take() {
+                    window.handle_gesture(GestureEvent::Swipe {
+                        fingers,
+                        delta: (0.0, 0.0),
+                        phase: end_phase(cancelled),
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZwpPointerGesturePinchV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwpPointerGesturePinchV1,
+        event: zwp_pointer_gesture_pinch_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let window = match &event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin {
+                surface, ..
+            } => state.window(surface),
+            _ => None,
+        };
+        let gestures = match state
+            .pointer_gestures_mut()
+            .iter_mut()
+            .find(|gestures| &gestures.pinch == proxy)
+        {
+            Some(gestures) => gestures,
+            None => return,
+        };
+
+        // A pinch reports both scale and rotation, so it drives two gestures
+        match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin {
+                ..
+            } => {
+                if let Some(window) = window {
+                    window.handle_gesture(GestureEvent::Pinch {
+                        delta: 0.0,
+                        phase: GesturePhase::Started,
+                    });
+                    window.handle_gesture(GestureEvent::Rotate {
+                        delta: 0.0,
+                        phase: GesturePhase::Started,
+                    });
+                    gestures.pinching = Some((window, 1.0));
+                }
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update {
+                scale,
+                rotation,
+                ..
+            } => {
+                if let Some((window, last_scale)) = gestures.pinching.as_mut() {
+                    // Scale is relative to the beginning of the gesture
+                    window.handle_gesture(GestureEvent::Pinch {
+                        delta: scale - *last_scale,
+                        phase: GesturePhase::Changed,
+                    });
+                    // Rotation is clockwise
+                    window.handle_gesture(GestureEvent::Rotate {
+                        delta: -rotation,
+                        phase: GesturePhase::Changed,
+                    });
+                    *last_scale = scale;
+                }
+            }
+            zwp_pointer_gesture_pinch_v1::Event::End {
+                cancelled, ..
+            } => {
+                if let Some((window, _)) = gestures.pinching.take() {
+                    let phase = end_phase(cancelled);
+                    window.handle_gesture(GestureEvent::Pinch {
+                        delta: 0.0,
+                        phase,
+                    });
+                    window.handle_gesture(GestureEvent::Rotate {
+                        delta: 0.0,
+                        phase,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+}
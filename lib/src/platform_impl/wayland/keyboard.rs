@@ -0,0 +1,401 @@
+use std::{
+    ffi::c_char,
+    fs::File,
+    io::Read,
+    os::fd::OwnedFd,
+    ptr,
+    time::{Duration, Instant},
+};
+
+use smithay_client_toolkit::reexports::client::{
+    protocol::{
+        wl_keyboard::{self, KeyState as WlKeyState, KeymapFormat, WlKeyboard},
+        wl_seat::WlSeat,
+        wl_surface::WlSurface,
+    },
+    Connection,
+    Dispatch,
+    Proxy,
+    QueueHandle,
+    WEnum,
+};
+use xkbcommon_dl::{
+    xkb_context,
+    xkb_context_flags,
+    xkb_keymap,
+    xkb_keymap_compile_flags,
+    xkb_keymap_format,
+    xkb_state,
+    xkb_state_component,
+    xkbcommon_option,
+    XkbCommon,
+    XKB_MOD_NAME_ALT,
+    XKB_MOD_NAME_CAPS,
+    XKB_MOD_NAME_CTRL,
+    XKB_MOD_NAME_LOGO,
+    XKB_MOD_NAME_SHIFT,
+};
+use xkeysym::Keysym;
+
+use super::state::WaylandState;
+use crate::{platform_impl::common::keysym_to_key, KeyState, KeyboardEvent, Modifiers};
+
+/// Offset between evdev scan codes and XKB keycodes.
+const XKB_OFFSET: u32 = 8;
+/// Repeat delay used until the compositor sends its own.
+const DEFAULT_REPEAT_DELAY: Duration = Duration::from_millis(600);
+/// Repeat interval used until the compositor sends its own.
+const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_millis(40);
+
+/// Compiled XKB keymap with its state.
+struct Xkb {
+    lib:     &'static XkbCommon,
+    context: *mut xkb_context,
+    keymap:  *mut xkb_keymap,
+    state:   *mut xkb_state,
+}
+
+impl Xkb {
+    /// Compiles a keymap shared by the compositor.
+    ///
+    /// Returns `None` if libxkbcommon isn't available or the keymap is
+    /// invalid.
+    fn new(fd: OwnedFd, size: u32) -> Option<Self> {
+        let lib = xkbcommon_option()?;
+
+        let mut buffer = Vec::with_capacity(size as usize);
+        File::from(fd)
+            .take(size as u64)
+            .read_to_end(&mut buffer)
+            .ok()?;
+        // The keymap is sent as a null-terminated string
+        while buffer.last() == Some(&0) {
+            buffer.pop();
+        }
+
+        unsafe {
+            let context = (lib.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS);
+            if context.is_null() {
+                return None;
+            }
+            let keymap = (lib.xkb_keymap_new_from_buffer)(
+                context,
+                buffer.as_ptr() as *const c_char,
+                buffer.len(),
+                xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            );
+            if keymap.is_null() {
+                (lib.xkb_context_unref)(context);
+                return None;
+            }
+            let state = (lib.xkb_state_new)(keymap);
+            if state.is_null() {
+                (lib.xkb_keymap_unref)(keymap);
+                (lib.xkb_context_unref)(context);
+                return None;
+            }
+
+            Some(Self {
+                lib,
+                context,
+                keymap,
+                state,
+            })
+        }
+    }
+
+    fn update_mask(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        unsafe {
+            (self.lib.xkb_state_update_mask)(self.state, depressed, latched, locked, 0, 0, group);
+        }
+    }
+
+    fn is_mod_active(&self, name: &[u8]) -> bool {
+        unsafe {
+            (self.lib.xkb_state_mod_name_is_active)(
+                self.state,
+                name.as_ptr() as *const c_char,
+                xkb_state_component::XKB_STATE_MODS_EFFECTIVE,
+            ) > 0
+        }
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::SHIFT, self.is_mod_active(XKB_MOD_NAME_SHIFT));
+        modifiers.set(Modifiers::CONTROL, self.is_mod_active(XKB_MOD_NAME_CTRL));
+        modifiers.set(Modifiers::ALT, self.is_mod_active(XKB_MOD_NAME_ALT));
+        modifiers.set(Modifiers::SUPER, self.is_mod_active(XKB_MOD_NAME_LOGO));
+        modifiers.set(Modifiers::CAPS_LOCK, self.is_mod_active(XKB_MOD_NAME_CAPS));
+        modifiers
+    }
+
+    fn keysym(&self, keycode: u32) -> Keysym {
+        Keysym::new(unsafe { (self.lib.xkb_state_key_get_one_sym)(self.state, keycode) })
+    }
+
+    /// Returns text produced by the key.
+    ///
+    /// Control characters aren't considered as text.
+    fn text(&self, keycode: u32) -> Option<String> {
+        let size =
+            unsafe { (self.lib.xkb_state_key_get_utf8)(self.state, keycode, ptr::null_mut(), 0) };
+        if size <= 0 {
+            return None;
+        }
+
+        // The size doesn't include the null terminator
+        let mut buffer = vec![0u8; size as usize + 1];
+        unsafe {
+            (self.lib.xkb_state_key_get_utf8)(
+                self.state,
+                keycode,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len(),
+            );
+        }
+        buffer.truncate(size as usize);
+
+        String::from_utf8(buffer)
+            .ok()
+            .filter(|text| !text.chars().any(char::is_control))
+    }
+
+    fn key_repeats(&self, keycode: u32) -> bool {
+        unsafe { (self.lib.xkb_keymap_key_repeats)(self.keymap, keycode) > 0 }
+    }
+}
+
+impl Drop for Xkb {
+    fn drop(&mut self) {
+        unsafe {
+            (self.lib.xkb_state_unref)(self.state);
+            (self.lib.xkb_keymap_unref)(self.keymap);
+            (self.lib.xkb_context_unref)(self.context);
+        }
+    }
+}
+
+/// A key being repeated.
+struct KeyRepeat {
+    scan_code: u32,
+    deadline:  Instant,
+}
+
+/// Keyboard of a seat.
+pub(super) struct Keyboard {
+    seat:            WlSeat,
+    keyboard:        WlKeyboard,
+    xkb:             Option<Xkb>,
+    focus:           Option<WlSurface>,
+    modifiers:       Modifiers,
+    repeat_delay:    Duration,
+    repeat_interval: Option<Duration>,
+    repeat:          Option<KeyRepeat>,
+}
+
+impl Keyboard {
+    pub(super) fn new(seat: WlSeat, qh: &QueueHandle<WaylandState>) -> Self {
+        let keyboard = seat.get_keyboard(qh, ());
+
+        Self {
+            seat,
+            keyboard,
+            xkb: None,
+            focus: None,
+            modifiers: Modifiers::empty(),
+            repeat_delay: DEFAULT_REPEAT_DELAY,
+            repeat_interval: Some(DEFAULT_REPEAT_INTERVAL),
+            repeat: None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn seat(&self) -> &WlSeat { &self.seat }
+
+    #[inline]
+    pub(super) fn focus(&self) -> Option<&WlSurface> { self.focus.as_ref() }
+
+    pub(super) fn release(&self) {
+        if self.keyboard.version() >= 3 {
+            self.keyboard.release();
+        }
+    }
+
+    /// Returns time of the next key repeat.
+    #[inline]
+    pub(super) fn repeat_deadline(&self) -> Option<Instant> {
+        self.repeat.as_ref().map(|repeat| repeat.deadline)
+    }
+
+    /// Generates a repeated key press if it's time to.
+    pub(super) fn poll_repeat(&mut self, now: Instant) -> Option<KeyboardEvent> {
+        let interval = self.repeat_interval?;
+        let repeat = self.repeat.as_mut()?;
+        if repeat.deadline > now {
+            return None;
+        }
+
+        // Repeats missed by a busy handler are dropped instead of being
+        // delivered in a burst.
+        repeat.deadline = (repeat.deadline + interval).max(now);
+        let scan_code = repeat.scan_code;
+        self.input_event(scan_code, KeyState::Pressed, true)
+    }
+
+    fn input_event(&self, scan_code: u32, state: KeyState, repeat: bool) -> Option<KeyboardEvent> {
+        let xkb = self.xkb.as_ref()?;
+        let keycode = scan_code + XKB_OFFSET;
+        let text = match state {
+            KeyState::Pressed => xkb.text(keycode),
+            KeyState::Released => None,
+        };
+
+        Some(KeyboardEvent::Input {
+            scan_code,
+            key: keysym_to_key(xkb.keysym(keycode)),
+            text,
+            state,
+            repeat,
+            modifiers: self.modifiers,
+        })
+    }
+
+    /// Handles a protocol event.
+    ///
+    /// Returns a keyboard event for the focused surface.
+    fn handle_event(&mut self, event: wl_keyboard::Event) -> Option<KeyboardEvent> {
+        match event {
+            wl_keyboard::Event::Keymap {
+                format,
+                fd,
+                size,
+            } => {
+                self.xkb = match format {
+                    WEnum::Value(KeymapFormat::XkbV1) => Xkb::new(fd, size),
+                    _ => None,
+                };
+                None
+            }
+            wl_keyboard::Event::Enter {
+                surface, ..
+            } => {
+                self.focus = Some(surface);
+                None
+            }
+            wl_keyboard::Event::Leave {
+                ..
+            } => {
+                self.focus = None;
+                self.repeat = None;
+                None
+            }
+            wl_keyboard::Event::Key {
+                key,
+                state,
+                ..
+            } => {
+                let state = match state {
+                    WEnum::Value(WlKeyState::Pressed) => KeyState::Pressed,
+                    WEnum::Value(WlKeyState::Released) => KeyState::Released,
+                    _ => return None,
+                };
+
+                match state {
+                    KeyState::Pressed => {
+                        let repeats = self
+                            .xkb
+                            .as_ref()
+                            .map(|xkb| xkb.key_repeats(key + XKB_OFFSET))
+                            .unwrap_or(false);
+                        if repeats && self.repeat_interval.is_some() {
+                            self.repeat = Some(KeyRepeat {
+                                scan_code: key,
+                                deadline:  Instant::now() + self.repeat_delay,
+                            });
+                        }
+                    }
+                    KeyState::Released => {
+                        if self.repeat.as_ref().map(|repeat| repeat.scan_code) == Some(key) {
+                            self.repeat = None;
+                        }
+                    }
+                }
+
+                self.input_event(key, state, false)
+            }
+            wl_keyboard::Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                let xkb = self.xkb.as_mut()?;
+                xkb.update_mask(mods_depressed, mods_latched, mods_locked, group);
+
+                let modifiers = xkb.modifiers();
+                if modifiers == self.modifiers {
+                    return None;
+                }
+                self.modifiers = modifiers;
+                Some(KeyboardEvent::ModifiersChanged(modifiers))
+            }
+            wl_keyboard::Event::RepeatInfo {
+                rate,
+                delay,
+            } => {
+                // A zero rate disables repeating
+                self.repeat_delay = Duration::from_millis(delay.max(0) as u64);
+                self.repeat_interval = match rate {
+                    rate if rate > 0 => Some(Duration::from_micros(1_000_000 / rate as u64)),
+                    _ => None,
+                };
+                if self.repeat_interval.is_none() {
+                    self.repeat = None;
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_keyboard::Event::Enter {
+            serial, ..
+        }
+        | wl_keyboard::Event::Key {
+            serial, ..
+        } = &event
+        {
+            state.set_input_serial(*serial);
+        }
+
+        let keyboard = match state
+            .keyboards_mut()
+            .iter_mut()
+            .find(|keyboard| &keyboard.keyboard == proxy)
+        {
+            Some(keyboard) => keyboard,
+            None => return,
+        };
+
+        let event = keyboard.handle_event(event);
+        let focus = keyboard.focus().cloned();
+        if let (Some(event), Some(surface)) = (event, focus) {
+            if let Some(window) = state.window(&surface) {
+                window.handle_keyboard_event(event);
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+mod app_state;
+mod application;
+mod clipboard;
+mod dnd;
+mod gestures;
+mod keyboard;
+mod monitor;
+mod state;
+mod tablet;
+mod text_input;
+#[cfg(feature = "tray")]
+mod tray;
+mod window;
+mod window_utils;
+
+pub(crate) use application::*;
+pub(crate) use clipboard::SelectionImpl;
+pub(crate) use monitor::*;
+#[cfg(feature = "tray")]
+pub(crate) use tray::*;
+pub(crate) use window::*;
+
+pub(crate) use super::common::{
+    ClipboardImpl,
+    EventLoopProxyImpl,
+    IconImpl,
+    MenuImpl,
+    MenuItemImpl,
+};
+#[cfg(feature = "notifications")]
+pub(crate) use super::freedesktop::NotificationImpl;
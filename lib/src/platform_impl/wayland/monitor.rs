@@ -0,0 +1,90 @@
+use dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use smithay_client_toolkit::{
+    output::{Mode, OutputInfo},
+    reexports::client::protocol::wl_output::WlOutput,
+};
+
+use crate::{platform::MonitorApi, Monitor, VideoMode};
+
+// Wayland doesn't report color depths, buffers are 32-bit in practice.
+const BIT_DEPTH: u16 = 32;
+
+#[derive(Debug, Clone)]
+pub(crate) struct MonitorImpl {
+    output: WlOutput,
+    info:   OutputInfo,
+}
+
+impl MonitorImpl {
+    #[inline]
+    pub(super) fn new(output: WlOutput, info: OutputInfo) -> Self {
+        Self {
+            output,
+            info,
+        }
+    }
+
+    #[inline]
+    pub(super) fn output(&self) -> &WlOutput { &self.output }
+
+    #[inline]
+    fn current_mode(&self) -> Option<&Mode> { self.info.modes.iter().find(|mode| mode.current) }
+}
+
+impl PartialEq for MonitorImpl {
+    fn eq(&self, other: &Self) -> bool { self.output == other.output }
+}
+
+impl Eq for MonitorImpl {}
+
+impl MonitorApi for MonitorImpl {
+    fn name(&self) -> Option<String> {
+        self.info
+            .name
+            .clone()
+            .or_else(|| (!self.info.model.is_empty()).then(|| self.info.model.clone()))
+    }
+
+    fn position(&self) -> PhysicalPosition<i32> {
+        let (x, y) = self.info.logical_position.unwrap_or(self.info.location);
+        LogicalPosition::new(x, y).to_physical(self.scale_factor())
+    }
+
+    fn size(&self) -> PhysicalSize<u32> {
+        match (self.current_mode(), self.info.logical_size) {
+            (Some(mode), _) => {
+                let (width, height) = mode.dimensions;
+                PhysicalSize::new(width as u32, height as u32)
+            }
+            (None, Some((width, height))) => {
+                LogicalSize::new(width as u32, height as u32).to_physical(self.scale_factor())
+            }
+            (None, None) => PhysicalSize::default(),
+        }
+    }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { self.info.scale_factor as f64 }
+
+    fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.current_mode()
+            .map(|mode| mode.refresh_rate as u32)
+            .filter(|refresh_rate| *refresh_rate > 0)
+    }
+
+    fn video_modes(&self) -> Vec<VideoMode> {
+        self.info
+            .modes
+            .iter()
+            .map(|mode| {
+                let (width, height) = mode.dimensions;
+                VideoMode::new(
+                    PhysicalSize::new(width as u32, height as u32),
+                    BIT_DEPTH,
+                    mode.refresh_rate as u32,
+                    Monitor::new(self.clone()),
+                )
+            })
+            .collect()
+    }
+}
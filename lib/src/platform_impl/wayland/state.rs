@@ -0,0 +1,992 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    rc::{Rc, Weak},
+    sync::Arc,
+    time::Instant,
+};
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState, SurfaceData},
+    data_device_manager::{
+        data_device::{DataDevice, DataDeviceData, DataDeviceHandler},
+        data_offer::{DataOfferHandler, DragOffer, SelectionOffer},
+        data_source::{CopyPasteSource, DataSourceHandler},
+        DataDeviceManagerState,
+        WritePipe,
+    },
+    delegate_compositor,
+    delegate_data_device,
+    delegate_output,
+    delegate_pointer,
+    delegate_pointer_constraints,
+    delegate_registry,
+    delegate_relative_pointer,
+    delegate_seat,
+    delegate_shm,
+    delegate_subcompositor,
+    delegate_touch,
+    delegate_xdg_shell,
+    delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    reexports::client::{
+        backend::ObjectId,
+        globals::GlobalList,
+        protocol::{
+            wl_data_device::WlDataDevice,
+            wl_data_device_manager::DndAction,
+            wl_data_source::WlDataSource,
+            wl_output::{Transform, WlOutput},
+            wl_pointer::WlPointer,
+            wl_seat::WlSeat,
+            wl_surface::WlSurface,
+            wl_touch::WlTouch,
+        },
+        Connection,
+        Proxy,
+        QueueHandle,
+    },
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{
+            PointerData,
+            PointerEvent,
+            PointerEventKind,
+            PointerHandler,
+            ThemeSpec,
+            ThemedPointer,
+        },
+        pointer_constraints::{PointerConstraintsHandler, PointerConstraintsState},
+        relative_pointer::{RelativeMotionEvent, RelativePointerHandler, RelativePointerState},
+        touch::{TouchData, TouchHandler},
+        Capability,
+        SeatHandler,
+        SeatState,
+    },
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowHandler},
+            XdgShell,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+    subcompositor::SubcompositorState,
+};
+use wayland_protocols::wp::{
+    pointer_constraints::zv1::client::{
+        zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+        zwp_locked_pointer_v1::ZwpLockedPointerV1,
+    },
+    pointer_gestures::zv1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+    relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1,
+    tablet::zv2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2,
+    text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+};
+
+use super::{
+    dnd::Drag,
+    gestures::PointerGestures,
+    keyboard::Keyboard,
+    monitor::MonitorImpl,
+    tablet::{TabletSeat, Tool},
+    text_input::TextInput,
+    window::WindowState,
+};
+use crate::{platform_impl::common::ClipboardContent, Error, ErrorKind, TouchPhase};
+
+/// Touch point on one of the application windows.
+struct TouchPoint {
+    window:   Rc<WindowState>,
+    /// Last surface position, touch ends don't report it.
+    position: (f64, f64),
+}
+
+/// Protocol state of the connection.
+///
+/// It receives all Wayland events and forwards the ones addressed to windows.
+pub(super) struct WaylandState {
+    registry_state:         RegistryState,
+    seat_state:             SeatState,
+    output_state:           OutputState,
+    compositor_state:       CompositorState,
+    subcompositor_state:    Arc<SubcompositorState>,
+    shm:                    Shm,
+    xdg_shell:              XdgShell,
+    pool:                   SlotPool,
+    pointers:               Vec<ThemedPointer>,
+    pointer_constraints:    PointerConstraintsState,
+    relative_pointer_state: RelativePointerState,
+    relative_pointers:      HashMap<ObjectId, ZwpRelativePointerV1>,
+    gestures_manager:       Option<ZwpPointerGesturesV1>,
+    pointer_gestures:       Vec<PointerGestures>,
+    tablet_manager:         Option<ZwpTabletManagerV2>,
+    tablet_seats:           Vec<TabletSeat>,
+    tablet_tools:           Vec<Tool>,
+    keyboards:              Vec<Keyboard>,
+    touches:                Vec<WlTouch>,
+    touch_points:           HashMap<(ObjectId, i32), TouchPoint>,
+    text_input_manager:     Option<ZwpTextInputManagerV3>,
+    text_inputs:            Vec<TextInput>,
+    data_device_manager:    Option<DataDeviceManagerState>,
+    data_devices:           Vec<DataDevice>,
+    drag:                   Option<Drag>,
+    selection:              Option<(CopyPasteSource, ClipboardContent)>,
+    input_serial:           u32,
+    windows:                HashMap<ObjectId, Weak<WindowState>>,
+}
+
+impl WaylandState {
+    pub(super) fn new(globals: &GlobalList, qh: &QueueHandle<Self>) -> Result<Self, Error> {
+        let compositor_state = CompositorState::bind(globals, qh).map_err(|e| {
+            Error::with_source(ErrorKind::NotSupported, "wl_compositor not available.", e)
+        })?;
+        let subcompositor_state =
+            SubcompositorState::bind(compositor_state.wl_compositor().clone(), globals, qh)
+                .map_err(|e| {
+                    Error::with_source(
+                        ErrorKind::NotSupported,
+                        "wl_subcompositor not available.",
+                        e,
+                    )
+                })?;
+        let shm = Shm::bind(globals, qh)
+            .map_err(|e| Error::with_source(ErrorKind::NotSupported, "wl_shm not available.", e))?;
+        let xdg_shell = XdgShell::bind(globals, qh).map_err(|e| {
+            Error::with_source(ErrorKind::NotSupported, "xdg_wm_base not available.", e)
+        })?;
+        let pool = SlotPool::new(1, &shm)
+            .map_err(|e| Error::with_source(ErrorKind::Os, "shared memory pool not created.", e))?;
+
+        Ok(Self {
+            registry_state: RegistryState::new(globals),
+            seat_state: SeatState::new(globals, qh),
+            output_state: OutputState::new(globals, qh),
+            compositor_state,
+            subcompositor_state: Arc::new(subcompositor_state),
+            shm,
+            xdg_shell,
+            pool,
+            pointers: Vec::new(),
+            // Pointer constraints and relative motion are optional
+            pointer_constraints: PointerConstraintsState::bind(globals, qh),
+            relative_pointer_state: RelativePointerState::bind(globals, qh),
+            relative_pointers: HashMap::new(),
+            // Trackpad gestures are optional
+            gestures_manager: globals.bind(qh, 1..=1, ()).ok(),
+            pointer_gestures: Vec::new(),
+            // Tablets are optional
+            tablet_manager: globals.bind(qh, 1..=1, ()).ok(),
+            tablet_seats: Vec::new(),
+            tablet_tools: Vec::new(),
+            keyboards: Vec::new(),
+            touches: Vec::new(),
+            touch_points: HashMap::new(),
+            // Input methods are optional
+            text_input_manager: globals.bind(qh, 1..=1, ()).ok(),
+            text_inputs: Vec::new(),
+            // Drag-and-drop is optional
+            data_device_manager: DataDeviceManagerState::bind(globals, qh).ok(),
+            data_devices: Vec::new(),
+            drag: None,
+            selection: None,
+            input_serial: 0,
+            windows: HashMap::new(),
+        })
+    }
+
+    #[inline]
+    pub(super) fn compositor_state(&self) -> &CompositorState { &self.compositor_state }
+
+    #[inline]
+    pub(super) fn subcompositor_state(&self) -> &Arc<SubcompositorState> {
+        &self.subcompositor_state
+    }
+
+    #[inline]
+    pub(super) fn shm(&self) -> &Shm { &self.shm }
+
+    #[inline]
+    pub(super) fn xdg_shell(&self) -> &XdgShell { &self.xdg_shell }
+
+    #[inline]
+    pub(super) fn pool(&mut self) -> &mut SlotPool { &mut self.pool }
+
+    #[inline]
+    pub(super) fn pointers(&self) -> &[ThemedPointer] { &self.pointers }
+
+    /// Returns a pointer with the cursor theme.
+    ///
+    /// # Parameters:
+    /// * `pointer` - Pointer of a seat.
+    pub(super) fn themed_pointer(&self, pointer: &WlPointer) -> Option<&ThemedPointer> {
+        self.pointers
+            .iter()
+            .find(|themed_pointer| themed_pointer.pointer() == pointer)
+    }
+
+    #[inline]
+    pub(super) fn pointer_constraints(&self) -> &PointerConstraintsState {
+        &self.pointer_constraints
+    }
+
+    #[inline]
+    pub(super) fn pointer_gestures_mut(&mut self) -> &mut Vec<PointerGestures> {
+        &mut self.pointer_gestures
+    }
+
+    #[inline]
+    pub(super) fn tablet_tools_mut(&mut self) -> &mut Vec<Tool> { &mut self.tablet_tools }
+
+    #[inline]
+    pub(super) fn keyboards_mut(&mut self) -> &mut Vec<Keyboard> { &mut self.keyboards }
+
+    #[inline]
+    pub(super) fn text_inputs_mut(&mut self) -> &mut Vec<TextInput> { &mut self.text_inputs }
+
+    /// Enables or disables text inputs focused on the window according to
+    /// its settings.
+    pub(super) fn update_text_input(&mut self, window: &WindowState) {
+        let surface = window.wl_surface();
+        for text_input in self.text_inputs.iter_mut() {
+            if text_input.focus() == Some(surface) {
+                if let Some(event) = text_input.update(window) {
+                    window.handle_ime_event(event);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub(super) fn register_window(&mut self, window: &Rc<WindowState>) {
+        self.windows
+            .insert(window.surface_id(), Rc::downgrade(window));
+    }
+
+    #[inline]
+    pub(super) fn unregister_window(&mut self, window: &WindowState) {
+        self.windows.remove(&window.surface_id());
+    }
+
+    /// Returns the window owning the surface.
+    ///
+    /// Subsurfaces of client-side decorations are resolved to their parent
+    /// window.
+    pub(super) fn window(&self, surface: &WlSurface) -> Option<Rc<WindowState>> {
+        let parent = surface
+            .data::<SurfaceData>()
+            .and_then(|data| data.parent_surface())
+            .unwrap_or(surface);
+        self.windows
+            .get(&parent.id())
+            .and_then(|window| window.upgrade())
+    }
+
+    /// Returns all live windows.
+    pub(super) fn windows(&self) -> Vec<Rc<WindowState>> {
+        self.windows
+            .values()
+            .filter_map(|window| window.upgrade())
+            .collect()
+    }
+
+    /// Returns all outputs with known properties.
+    pub(super) fn monitors(&self) -> Vec<MonitorImpl> {
+        self.output_state
+            .outputs()
+            .filter_map(|output| self.monitor(&output))
+            .collect()
+    }
+
+    /// Returns the output as a monitor.
+    #[inline]
+    pub(super) fn monitor(&self, output: &WlOutput) -> Option<MonitorImpl> {
+        self.output_state
+            .info(output)
+            .map(|info| MonitorImpl::new(output.clone(), info))
+    }
+
+    /// Records a serial of the latest input event, which requests like setting
+    /// the selection have to refer to.
+    #[inline]
+    pub(super) fn set_input_serial(&mut self, serial: u32) { self.input_serial = serial; }
+
+    /// Offers the content as the clipboard selection.
+    pub(super) fn set_selection(
+        &mut self,
+        qh: &QueueHandle<Self>,
+        content: ClipboardContent,
+    ) -> Result<(), Error> {
+        let (Some(manager), Some(device)) =
+            (self.data_device_manager.as_ref(), self.data_devices.first())
+        else {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                "clipboard not available.",
+            ));
+        };
+
+        let source = manager.create_copy_paste_source(qh, content.mime_types());
+        source.set_selection(device, self.input_serial);
+        if let Some((source, _)) = self.selection.replace((source, content)) {
+            source.inner().destroy();
+        }
+        Ok(())
+    }
+
+    /// Returns the clipboard content offered by the application.
+    #[inline]
+    pub(super) fn selection_content(&self) -> Option<&ClipboardContent> {
+        self.selection.as_ref().map(|(_, content)| content)
+    }
+
+    /// Returns the clipboard selection offered by another client.
+    #[inline]
+    pub(super) fn selection_offer(&self) -> Option<SelectionOffer> {
+        self.data_devices
+            .first()
+            .and_then(|device| device.data().selection_offer())
+    }
+
+    pub(super) fn clear_selection(&mut self) {
+        if let Some((source, _)) = self.selection.take() {
+            if let Some(device) = self.data_devices.first() {
+                device.unset_selection(self.input_serial);
+            }
+            source.inner().destroy();
+        }
+    }
+
+    /// Returns time of the next key repeat.
+    pub(super) fn key_repeat_deadline(&self) -> Option<Instant> {
+        self.keyboards
+            .iter()
+            .filter_map(|keyboard| keyboard.repeat_deadline())
+            .min()
+    }
+
+    /// Sends repeated key presses to focused windows.
+    pub(super) fn handle_key_repeat(&mut self) {
+        let now = Instant::now();
+        let events: Vec<_> = self
+            .keyboards
+            .iter_mut()
+            .filter_map(|keyboard| {
+                let event = keyboard.poll_repeat(now)?;
+                Some((keyboard.focus()?.clone(), event))
+            })
+            .collect();
+
+        for (surface, event) in events.into_iter() {
+            if let Some(window) = self.window(&surface) {
+                window.handle_keyboard_event(event);
+            }
+        }
+    }
+}
+
+impl CompositorHandler for WaylandState {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        new_factor: i32,
+    ) {
+        if let Some(window) = self.window(surface) {
+            window.set_scale_factor(self, qh, new_factor);
+        }
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &WlSurface,
+        _new_transform: Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        _time: u32,
+    ) {
+        if let Some(window) = self.window(surface) {
+            window.frame_done();
+        }
+    }
+
+    fn surface_enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &WlOutput,
+    ) {
+        // Decoration subsurfaces are ignored
+        if let Some(window) = self.window(surface).filter(|w| w.wl_surface() == surface) {
+            window.surface_enter(output);
+        }
+    }
+
+    fn surface_leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &WlOutput,
+    ) {
+        // Decoration subsurfaces are ignored
+        if let Some(window) = self.window(surface).filter(|w| w.wl_surface() == surface) {
+            window.surface_leave(output);
+        }
+    }
+}
+
+impl OutputHandler for WaylandState {
+    #[inline]
+    fn output_state(&mut self) -> &mut OutputState { &mut self.output_state }
+
+    fn new_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
+    }
+}
+
+impl WindowHandler for WaylandState {
+    fn request_close(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, window: &Window) {
+        if let Some(window) = self.window(window.wl_surface()) {
+            window.request_close();
+        }
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        if let Some(window) = self.window(window.wl_surface()) {
+            window.configure(self, qh, configure);
+        }
+    }
+}
+
+impl SeatHandler for WaylandState {
+    #[inline]
+    fn seat_state(&mut self) -> &mut SeatState { &mut self.seat_state }
+
+    fn new_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Keyboard {
+            self.keyboards.push(Keyboard::new(seat.clone(), qh));
+            if let Some(manager) = self.text_input_manager.as_ref() {
+                self.text_inputs
+                    .push(TextInput::new(manager, seat.clone(), qh));
+            }
+        }
+        if capability == Capability::Pointer {
+            let surface = self.compositor_state.create_surface(qh);
+            if let Ok(pointer) = self.seat_state.get_pointer_with_theme(
+                qh,
+                &seat,
+                self.shm.wl_shm(),
+                surface,
+                ThemeSpec::default(),
+            ) {
+                if let Ok(relative_pointer) = self
+                    .relative_pointer_state
+                    .get_relative_pointer(pointer.pointer(), qh)
+                {
+                    self.relative_pointers
+                        .insert(pointer.pointer().id(), relative_pointer);
+                }
+                if let Some(manager) = self.gestures_manager.as_ref() {
+                    self.pointer_gestures.push(PointerGestures::new(
+                        manager,
+                        pointer.pointer().clone(),
+                        qh,
+                    ));
+                }
+                self.pointers.push(pointer);
+            }
+
+            // Drags are driven by pointers
+            let has_data_device = self
+                .data_devices
+                .iter()
+                .any(|device| device.data().seat() == &seat);
+            if let (Some(manager), false) = (self.data_device_manager.as_ref(), has_data_device) {
+                self.data_devices.push(manager.get_data_device(qh, &seat));
+            }
+
+            // Tablet tools drive the pointer too
+            let has_tablet_seat = self
+                .tablet_seats
+                .iter()
+                .any(|tablet_seat| tablet_seat.seat() == &seat);
+            if let (Some(manager), false) = (self.tablet_manager.as_ref(), has_tablet_seat) {
+                self.tablet_seats
+                    .push(TabletSeat::new(manager, seat.clone(), qh));
+            }
+        }
+        if capability == Capability::Touch {
+            if let Ok(touch) = self.seat_state.get_touch(qh, &seat) {
+                self.touches.push(touch);
+            }
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        seat: WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Keyboard {
+            self.keyboards.retain(|keyboard| {
+                let is_removed = keyboard.seat() == &seat;
+                if is_removed {
+                    keyboard.release();
+                }
+                !is_removed
+            });
+            self.text_inputs.retain(|text_input| {
+                let is_removed = text_input.seat() == &seat;
+                if is_removed {
+                    text_input.destroy();
+                }
+                !is_removed
+            });
+        }
+        if capability == Capability::Pointer {
+            self.pointers.retain(|pointer| {
+                let is_removed = pointer
+                    .pointer()
+                    .data::<PointerData>()
+                    .map(|data| data.seat() == &seat)
+                    .unwrap_or(false);
+                if is_removed {
+                    if let Some(relative_pointer) =
+                        self.relative_pointers.remove(&pointer.pointer().id())
+                    {
+                        relative_pointer.destroy();
+                    }
+                    self.pointer_gestures.retain(|gestures| {
+                        let is_released = gestures.pointer() == pointer.pointer();
+                        if is_released {
+                            gestures.destroy();
+                        }
+                        !is_released
+                    });
+                    pointer.pointer().release();
+                }
+                !is_removed
+            });
+        }
+        if capability == Capability::Touch {
+            self.touches.retain(|touch| {
+                let is_removed = touch
+                    .data::<TouchData>()
+                    .map(|data| data.seat() == &seat)
+                    .unwrap_or(false);
+                if is_removed {
+                    let id = touch.id();
+                    self.touch_points.retain(|(touch_id, _), _| touch_id != &id);
+                    touch.release();
+                }
+                !is_removed
+            });
+        }
+    }
+
+    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, seat: WlSeat) {
+        self.data_devices
+            .retain(|device| device.data().seat() != &seat);
+        self.tablet_seats.retain(|tablet_seat| {
+            let is_removed = tablet_seat.seat() == &seat;
+            if is_removed {
+                tablet_seat.destroy();
+            }
+            !is_removed
+        });
+    }
+}
+
+impl PointerHandler for WaylandState {
+    fn pointer_frame(
+        &mut self,
+        conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        pointer: &WlPointer,
+        events: &[PointerEvent],
+    ) {
+        if let Some(serial) = events.iter().rev().find_map(|event| match event.kind {
+            PointerEventKind::Press {
+                serial, ..
+            } => Some(serial),
+            _ => None,
+        }) {
+            self.input_serial = serial;
+        }
+        let themed_pointer = self.themed_pointer(pointer);
+
+        for event in events.iter() {
+            if let Some(window) = self.window(&event.surface) {
+                window.handle_pointer_event(conn, pointer, themed_pointer, event);
+            }
+        }
+    }
+}
+
+impl TouchHandler for WaylandState {
+    fn down(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        touch: &WlTouch,
+        serial: u32,
+        _time: u32,
+        surface: WlSurface,
+        id: i32,
+        position: (f64, f64),
+    ) {
+        self.input_serial = serial;
+        if let Some(window) = self.window(&surface) {
+            window.handle_touch(id, TouchPhase::Started, position.0, position.1);
+            self.touch_points.insert(
+                (touch.id(), id),
+                TouchPoint {
+                    window,
+                    position,
+                },
+            );
+        }
+    }
+
+    fn up(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        touch: &WlTouch,
+        _serial: u32,
+        _time: u32,
+        id: i32,
+    ) {
+        if let Some(point) = self.touch_points.remove(&(touch.id(), id)) {
+            let (x, y) = point.position;
+            point.window.handle_touch(id, TouchPhase::Ended, x, y);
+        }
+    }
+
+    fn motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        touch: &WlTouch,
+        _time: u32,
+        id: i32,
+        position: (f64, f64),
+    ) {
+        if let Some(point) = self.touch_points.get_mut(&(touch.id(), id)) {
+            point.position = position;
+            point
+                .window
+                .handle_touch(id, TouchPhase::Moved, position.0, position.1);
+        }
+    }
+
+    fn shape(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &WlTouch,
+        _id: i32,
+        _major: f64,
+        _minor: f64,
+    ) {
+    }
+
+    fn orientation(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _touch: &WlTouch,
+        _id: i32,
+        _orientation: f64,
+    ) {
+    }
+
+    fn cancel(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, touch: &WlTouch) {
+        let touch_id = touch.id();
+        self.touch_points.retain(|(id, point_id), point| {
+            let is_cancelled = id == &touch_id;
+            if is_cancelled {
+                let (x, y) = point.position;
+                point
+                    .window
+                    .handle_touch(*point_id, TouchPhase::Cancelled, x, y);
+            }
+            !is_cancelled
+        });
+    }
+}
+
+impl RelativePointerHandler for WaylandState {
+    fn relative_pointer_motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _relative_pointer: &ZwpRelativePointerV1,
+        pointer: &WlPointer,
+        event: RelativeMotionEvent,
+    ) {
+        // Relative motion is reported even if the cursor is locked
+        for window in self.windows().iter() {
+            if window.has_pointer_focus(pointer) {
+                window.handle_raw_motion(event.delta_unaccel);
+            }
+        }
+    }
+}
+
+impl PointerConstraintsHandler for WaylandState {
+    fn confined(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _confined_pointer: &ZwpConfinedPointerV1,
+        _surface: &WlSurface,
+        _pointer: &WlPointer,
+    ) {
+    }
+
+    fn unconfined(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _confined_pointer: &ZwpConfinedPointerV1,
+        _surface: &WlSurface,
+        _pointer: &WlPointer,
+    ) {
+    }
+
+    fn locked(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _locked_pointer: &ZwpLockedPointerV1,
+        _surface: &WlSurface,
+        _pointer: &WlPointer,
+    ) {
+    }
+
+    fn unlocked(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _locked_pointer: &ZwpLockedPointerV1,
+        _surface: &WlSurface,
+        _pointer: &WlPointer,
+    ) {
+    }
+}
+
+impl DataDeviceHandler for WaylandState {
+    fn enter(
+        &mut self,
+        conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+        x: f64,
+        y: f64,
+        surface: &WlSurface,
+    ) {
+        let offer = data_device
+            .data::<DataDeviceData>()
+            .and_then(|data| data.drag_offer());
+        let (Some(offer), Some(window)) = (offer, self.window(surface)) else {
+            return;
+        };
+
+        let drag = Drag::new(conn, window, &offer);
+        if let Some(data) = drag.data() {
+            drag.window().handle_drag_enter(data, x, y);
+        }
+        self.drag = Some(drag);
+    }
+
+    fn leave(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _data_device: &WlDataDevice) {
+        if let Some(drag) = self.drag.take() {
+            if drag.data().is_some() {
+                drag.window().handle_drag_leave();
+            }
+        }
+    }
+
+    fn motion(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _data_device: &WlDataDevice,
+        x: f64,
+        y: f64,
+    ) {
+        if let Some(drag) = self.drag.as_ref().filter(|drag| drag.data().is_some()) {
+            drag.window().handle_drag_motion(x, y);
+        }
+    }
+
+    fn selection(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _data_device: &WlDataDevice,
+    ) {
+    }
+
+    fn drop_performed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+    ) {
+        let offer = data_device
+            .data::<DataDeviceData>()
+            .and_then(|data| data.drag_offer());
+        let (Some(drag), Some(offer)) = (self.drag.take(), offer) else {
+            return;
+        };
+
+        if let Some(data) = drag.data() {
+            drag.window().handle_drop(data, offer.x, offer.y);
+            offer.finish();
+        }
+        offer.destroy();
+    }
+}
+
+impl DataOfferHandler for WaylandState {
+    fn source_actions(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        offer: &mut DragOffer,
+        _actions: DndAction,
+    ) {
+        offer.set_actions(DndAction::Copy, DndAction::Copy);
+    }
+
+    fn selected_action(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _offer: &mut DragOffer,
+        _actions: DndAction,
+    ) {
+    }
+}
+
+// The application offers the clipboard selection only, it doesn't start drags.
+impl DataSourceHandler for WaylandState {
+    fn accept_mime(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+        _mime: Option<String>,
+    ) {
+    }
+
+    fn send_request(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        source: &WlDataSource,
+        mime: String,
+        mut fd: WritePipe,
+    ) {
+        let data = self
+            .selection
+            .as_ref()
+            .filter(|(selection, _)| selection.inner() == source)
+            .and_then(|(_, content)| content.get(&mime));
+        if let Some(data) = data {
+            let _ = fd.write_all(data);
+        }
+    }
+
+    fn cancelled(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, source: &WlDataSource) {
+        // Another client has taken over the selection
+        if self
+            .selection
+            .as_ref()
+            .is_some_and(|(selection, _)| selection.inner() == source)
+        {
+            self.selection = None;
+        }
+        source.destroy();
+    }
+
+    fn dnd_dropped(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _source: &WlDataSource) {
+    }
+
+    fn dnd_finished(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+    ) {
+    }
+
+    fn action(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _source: &WlDataSource,
+        _action: DndAction,
+    ) {
+    }
+}
+
+impl ShmHandler for WaylandState {
+    #[inline]
+    fn shm_state(&mut self) -> &mut Shm { &mut self.shm }
+}
+
+impl ProvidesRegistryState for WaylandState {
+    registry_handlers![OutputState, SeatState];
+
+    #[inline]
+    fn registry(&mut self) -> &mut RegistryState { &mut self.registry_state }
+}
+
+delegate_compositor!(WaylandState);
+delegate_data_device!(WaylandState);
+delegate_subcompositor!(WaylandState);
+delegate_output!(WaylandState);
+delegate_shm!(WaylandState);
+delegate_seat!(WaylandState);
+delegate_pointer!(WaylandState);
+delegate_touch!(WaylandState);
+delegate_pointer_constraints!(WaylandState);
+delegate_relative_pointer!(WaylandState);
+delegate_xdg_shell!(WaylandState);
+delegate_xdg_window!(WaylandState);
+delegate_registry!(WaylandState);
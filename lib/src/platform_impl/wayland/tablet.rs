@@ -0,0 +1,333 @@
+use std::rc::Rc;
+
+use smithay_client_toolkit::reexports::client::{
+    event_created_child,
+    protocol::wl_seat::WlSeat,
+    Connection,
+    Dispatch,
+    QueueHandle,
+    WEnum,
+};
+use wayland_protocols::wp::tablet::zv2::client::{
+    zwp_tablet_manager_v2::{self, ZwpTabletManagerV2},
+    zwp_tablet_pad_group_v2::{self, ZwpTabletPadGroupV2},
+    zwp_tablet_pad_ring_v2::{self, ZwpTabletPadRingV2},
+    zwp_tablet_pad_strip_v2::{self, ZwpTabletPadStripV2},
+    zwp_tablet_pad_v2::{self, ZwpTabletPadV2},
+    zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
+    zwp_tablet_tool_v2::{self, Type as ToolType, ZwpTabletToolV2},
+    zwp_tablet_v2::{self, ZwpTabletV2},
+};
+
+use super::{state::WaylandState, window::WindowState};
+use crate::{TabletEvent, TabletTool};
+
+/// Maximum value of the normalized pressure axis.
+const MAX_PRESSURE: f64 = 65535.0;
+
+/// Tablets of a seat (tablet-v2 protocol).
+///
+/// See: https://wayland.app/protocols/tablet-v2
+pub(super) struct TabletSeat {
+    seat:        WlSeat,
+    tablet_seat: ZwpTabletSeatV2,
+}
+
+impl TabletSeat {
+    pub(super) fn new(
+        manager: &ZwpTabletManagerV2,
+        seat: WlSeat,
+        qh: &QueueHandle<WaylandState>,
+    ) -> Self {
+        let tablet_seat = manager.get_tablet_seat(&seat, qh, ());
+
+        Self {
+            seat,
+            tablet_seat,
+        }
+    }
+
+    #[inline]
+    pub(super) fn seat(&self) -> &WlSeat { &self.seat }
+
+    #[inline]
+    pub(super) fn destroy(&self) { self.tablet_seat.destroy(); }
+}
+
+/// Tablet tool state.
+///
+/// Tool events are grouped by frames, changes are accumulated and sent to
+/// the focused window at the end of a frame.
+pub(super) struct Tool {
+    tool:       ZwpTabletToolV2,
+    kind:       TabletTool,
+    focus:      Option<Rc<WindowState>>,
+    position:   (f64, f64),
+    pressure:   f64,
+    tilt:       (f64, f64),
+    rotation:   f64,
+    is_entered: bool,
+    is_moved:   bool,
+    is_left:    bool,
+    tip_event:  Option<TabletEvent>,
+}
+
+impl Tool {
+    fn new(tool: ZwpTabletToolV2) -> Self {
+        Self {
+            tool,
+            kind: TabletTool::Other,
+            focus: None,
+            position: (0.0, 0.0),
+            pressure: 0.0,
+            tilt: (0.0, 0.0),
+            rotation: 0.0,
+            is_entered: false,
+            is_moved: false,
+            is_left: false,
+            tip_event: None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn destroy(&self) { self.tool.destroy(); }
+
+    /// Sends accumulated changes to the focused window.
+    fn flush(&mut self) {
+        let Some(window) = self.focus.as_ref() else {
+            return;
+        };
+
+        if self.is_entered {
+            window.handle_tablet_event(TabletEvent::ProximityEntered {
+                tool: self.kind
+            });
+        }
+        if self.is_moved {
+            window.handle_tablet_event(TabletEvent::Motion {
+                position: window.to_physical_position(self.position.0, self.position.1),
+                pressure: self.pressure,
+                tilt:     self.tilt,
+                rotation: self.rotation,
+            });
+        }
+        if let Some(event) = self.tip_event.take() {
+            window.handle_tablet_event(event);
+        }
+        if self.is_left {
+            window.handle_tablet_event(TabletEvent::ProximityLeft);
+            self.focus = None;
+        }
+        self.is_entered = false;
+        self.is_moved = false;
+        self.is_left = false;
+    }
+}
+
+#[inline]
+fn to_tablet_tool(tool_type: WEnum<ToolType>) -> TabletTool {
+    match tool_type {
+        WEnum::Value(ToolType::Pen | ToolType::Brush | ToolType::Pencil | ToolType::Airbrush) => {
+            TabletTool::Pen
+        }
+        WEnum::Value(ToolType::Eraser) => TabletTool::Eraser,
+        _ => TabletTool::Other,
+    }
+}
+
+impl Dispatch<ZwpTabletManagerV2, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTabletManagerV2,
+        _event: zwp_tablet_manager_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // The manager has no events
+    }
+}
+
+impl Dispatch<ZwpTabletSeatV2, ()> for WaylandState {
+    event_created_child!(WaylandState, ZwpTabletSeatV2, [
+        zwp_tablet_seat_v2::EVT_TABLET_ADDED_OPCODE => (ZwpTabletV2, ()),
+        zwp_tablet_seat_v2::EVT_TOOL_ADDED_OPCODE => (ZwpTabletToolV2, ()),
+        zwp_tablet_seat_v2::EVT_PAD_ADDED_OPCODE => (ZwpTabletPadV2, ()),
+    ]);
+
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwpTabletSeatV2,
+        event: zwp_tablet_seat_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let zwp_tablet_seat_v2::Event::ToolAdded {
+            id,
+        } = event
+        {
+            state.tablet_tools_mut().push(Tool::new(id));
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletToolV2, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwpTabletToolV2,
+        event: zwp_tablet_tool_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let window = match &event {
+            zwp_tablet_tool_v2::Event::ProximityIn {
+                surface, ..
+            } => state.window(surface),
+            _ => None,
+        };
+        if let zwp_tablet_tool_v2::Event::Down {
+            serial,
+        } = &event
+        {
+            state.set_input_serial(*serial);
+        }
+        let tools = state.tablet_tools_mut();
+        let Some(index) = tools.iter().position(|tool| &tool.tool == proxy) else {
+            return;
+        };
+        let tool = &mut tools[index];
+
+        match event {
+            zwp_tablet_tool_v2::Event::Type {
+                tool_type,
+            } => tool.kind = to_tablet_tool(tool_type),
+            zwp_tablet_tool_v2::Event::ProximityIn {
+                ..
+            } => {
+                tool.focus = window;
+                tool.is_entered = true;
+                tool.is_left = false;
+            }
+            zwp_tablet_tool_v2::Event::ProximityOut => tool.is_left = true,
+            zwp_tablet_tool_v2::Event::Down {
+                ..
+            } => tool.tip_event = Some(TabletEvent::Down),
+            zwp_tablet_tool_v2::Event::Up => tool.tip_event = Some(TabletEvent::Up),
+            zwp_tablet_tool_v2::Event::Motion {
+                x,
+                y,
+            } => {
+                tool.position = (x, y);
+                tool.is_moved = true;
+            }
+            zwp_tablet_tool_v2::Event::Pressure {
+                pressure,
+            } => {
+                tool.pressure = pressure as f64 / MAX_PRESSURE;
+                tool.is_moved = true;
+            }
+            zwp_tablet_tool_v2::Event::Tilt {
+                tilt_x,
+                tilt_y,
+            } => {
+                // Tilt is reported in degrees
+                tool.tilt = (tilt_x / 90.0, tilt_y / 90.0);
+                tool.is_moved = true;
+            }
+            zwp_tablet_tool_v2::Event::Rotation {
+                degrees,
+            } => {
+                tool.rotation = degrees;
+                tool.is_moved = true;
+            }
+            zwp_tablet_tool_v2::Event::Frame {
+                ..
+            } => tool.flush(),
+            zwp_tablet_tool_v2::Event::Removed => {
+                tools.remove(index).destroy();
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletV2, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        proxy: &ZwpTabletV2,
+        event: zwp_tablet_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // Tablets are only described, tools are tracked instead
+        if let zwp_tablet_v2::Event::Removed = event {
+            proxy.destroy();
+        }
+    }
+}
+
+// Pads (buttons, rings and strips on the tablet frame) aren't supported, but
+// their objects are created by the compositor anyway.
+
+impl Dispatch<ZwpTabletPadV2, ()> for WaylandState {
+    event_created_child!(WaylandState, ZwpTabletPadV2, [
+        zwp_tablet_pad_v2::EVT_GROUP_OPCODE => (ZwpTabletPadGroupV2, ()),
+    ]);
+
+    fn event(
+        _state: &mut Self,
+        proxy: &ZwpTabletPadV2,
+        event: zwp_tablet_pad_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let zwp_tablet_pad_v2::Event::Removed = event {
+            proxy.destroy();
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletPadGroupV2, ()> for WaylandState {
+    event_created_child!(WaylandState, ZwpTabletPadGroupV2, [
+        zwp_tablet_pad_group_v2::EVT_RING_OPCODE => (ZwpTabletPadRingV2, ()),
+        zwp_tablet_pad_group_v2::EVT_STRIP_OPCODE => (ZwpTabletPadStripV2, ()),
+    ]);
+
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTabletPadGroupV2,
+        _event: zwp_tablet_pad_group_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletPadRingV2, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTabletPadRingV2,
+        _event: zwp_tablet_pad_ring_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletPadStripV2, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTabletPadStripV2,
+        _event: zwp_tablet_pad_strip_v2::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
@@ -0,0 +1,220 @@
+use smithay_client_toolkit::reexports::client::{
+    protocol::{wl_seat::WlSeat, wl_surface::WlSurface},
+    Connection,
+    Dispatch,
+    QueueHandle,
+};
+use wayland_protocols::wp::text_input::zv3::client::{
+    zwp_text_input_manager_v3::{self, ZwpTextInputManagerV3},
+    zwp_text_input_v3::{self, ContentHint, ContentPurpose, ZwpTextInputV3},
+};
+
+use super::{state::WaylandState, window::WindowState};
+use crate::ImeEvent;
+
+/// Text input of a seat (text-input-v3 protocol).
+///
+/// The compositor forwards it to the active input method, see: https://wayland.app/protocols/text-input-unstable-v3
+pub(super) struct TextInput {
+    seat:            WlSeat,
+    text_input:      ZwpTextInputV3,
+    focus:           Option<WlSurface>,
+    is_enabled:      bool,
+    has_preedit:     bool,
+    pending_preedit: Option<(String, Option<(usize, usize)>)>,
+    pending_commit:  Option<String>,
+}
+
+impl TextInput {
+    pub(super) fn new(
+        manager: &ZwpTextInputManagerV3,
+        seat: WlSeat,
+        qh: &QueueHandle<WaylandState>,
+    ) -> Self {
+        let text_input = manager.get_text_input(&seat, qh, ());
+
+        Self {
+            seat,
+            text_input,
+            focus: None,
+            is_enabled: false,
+            has_preedit: false,
+            pending_preedit: None,
+            pending_commit: None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn seat(&self) -> &WlSeat { &self.seat }
+
+    #[inline]
+    pub(super) fn focus(&self) -> Option<&WlSurface> { self.focus.as_ref() }
+
+    #[inline]
+    pub(super) fn destroy(&self) { self.text_input.destroy(); }
+
+    /// Enables or disables the text input according to window settings.
+    ///
+    /// Returns an event for the window if the state has been changed.
+    pub(super) fn update(&mut self, window: &WindowState) -> Option<ImeEvent> {
+        if window.is_ime_allowed() {
+            if !self.is_enabled {
+                self.text_input.enable();
+                self.text_input
+                    .set_content_type(ContentHint::None, ContentPurpose::Normal);
+            }
+            let (position, size) = window.ime_cursor_area();
+            self.text_input
+                .set_cursor_rectangle(position.x, position.y, size.width, size.height);
+            self.text_input.commit();
+
+            if self.is_enabled {
+                return None;
+            }
+            self.is_enabled = true;
+            Some(ImeEvent::Enabled)
+        } else {
+            if !self.is_enabled {
+                return None;
+            }
+            self.disable();
+            Some(ImeEvent::Disabled)
+        }
+    }
+
+    fn disable(&mut self) {
+        self.text_input.disable();
+        self.text_input.commit();
+        self.is_enabled = false;
+        self.has_preedit = false;
+    }
+
+    /// Applies pending changes.
+    ///
+    /// Returns events for the focused window.
+    fn apply(&mut self) -> Vec<ImeEvent> {
+        let preedit = self.pending_preedit.take();
+        let commit = self.pending_commit.take();
+        let mut events = Vec::new();
+        if !self.is_enabled {
+            return events;
+        }
+
+        // The composition is replaced by committed text
+        if let Some(commit) = commit {
+            if self.has_preedit {
+                self.has_preedit = false;
+                events.push(ImeEvent::Preedit {
+                    text:   String::new(),
+                    cursor: None,
+                });
+            }
+            events.push(ImeEvent::Commit(commit));
+        }
+
+        match preedit {
+            Some((text, cursor)) if !text.is_empty() => {
+                self.has_preedit = true;
+                events.push(ImeEvent::Preedit {
+                    text,
+                    cursor,
+                });
+            }
+            _ if self.has_preedit => {
+                self.has_preedit = false;
+                events.push(ImeEvent::Preedit {
+                    text:   String::new(),
+                    cursor: None,
+                });
+            }
+            _ => (),
+        }
+
+        events
+    }
+}
+
+impl Dispatch<ZwpTextInputManagerV3, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTextInputManagerV3,
+        _event: zwp_text_input_manager_v3::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // The manager has no events
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwpTextInputV3,
+        event: zwp_text_input_v3::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let text_input = match state
+            .text_inputs_mut()
+            .iter_mut()
+            .find(|text_input| &text_input.text_input == proxy)
+        {
+            Some(text_input) => text_input,
+            None => return,
+        };
+
+        match event {
+            zwp_text_input_v3::Event::Enter {
+                surface,
+            } => {
+                text_input.focus = Some(surface.clone());
+                if let Some(window) = state.window(&surface) {
+                    state.update_text_input(&window);
+                }
+            }
+            zwp_text_input_v3::Event::Leave {
+                surface,
+            } => {
+                text_input.focus = None;
+                if text_input.is_enabled {
+                    text_input.disable();
+                    if let Some(window) = state.window(&surface) {
+                        window.handle_ime_event(ImeEvent::Disabled);
+                    }
+                }
+            }
+            zwp_text_input_v3::Event::PreeditString {
+                text,
+                cursor_begin,
+                cursor_end,
+            } => {
+                // Negative offsets hide the cursor
+                let cursor = (cursor_begin >= 0 && cursor_end >= 0)
+                    .then_some((cursor_begin as usize, cursor_end as usize));
+                text_input.pending_preedit = Some((text.unwrap_or_default(), cursor));
+            }
+            zwp_text_input_v3::Event::CommitString {
+                text,
+            } => {
+                text_input.pending_commit = text;
+            }
+            zwp_text_input_v3::Event::Done {
+                ..
+            } => {
+                let events = text_input.apply();
+                let window = text_input
+                    .focus()
+                    .cloned()
+                    .and_then(|surface| state.window(&surface));
+                if let Some(window) = window {
+                    for event in events.into_iter() {
+                        window.handle_ime_event(event);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
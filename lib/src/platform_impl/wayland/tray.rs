@@ -0,0 +1,49 @@
+use crate::{
+    platform::{TrayApi, Wrapper},
+    platform_impl::freedesktop::StatusNotifierItem,
+    ContextOwner,
+    Error,
+    Icon,
+    Menu,
+    TrayId,
+};
+
+/// Tray icon, Wayland has no protocol for it, so it's exported through the
+/// session bus.
+#[derive(Debug)]
+pub(crate) struct TrayImpl(StatusNotifierItem);
+
+impl TrayApi for TrayImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        id: TrayId,
+        icon: Icon,
+        tooltip: Option<String>,
+        menu: Option<Menu>,
+    ) -> Result<Self, Error> {
+        let state = ctx.context().get_impl().state();
+        let item = StatusNotifierItem::new(
+            state.create_proxy(),
+            state.menu_callbacks(),
+            id,
+            state.app_id(),
+            &icon,
+            tooltip,
+            menu.as_ref(),
+        )?;
+
+        Ok(Self(item))
+    }
+
+    #[inline]
+    fn id(&self) -> TrayId { self.0.id() }
+
+    #[inline]
+    fn set_icon(&mut self, icon: Icon) { self.0.set_icon(&icon); }
+
+    #[inline]
+    fn set_tooltip(&mut self, tooltip: Option<String>) { self.0.set_tooltip(tooltip); }
+
+    #[inline]
+    fn set_menu(&mut self, menu: Option<Menu>) { self.0.set_menu(menu.as_ref()); }
+}
@@ -0,0 +1,1346 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Debug,
+    num::NonZeroU32,
+    rc::Rc,
+    time::Duration,
+};
+
+use dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use smithay_client_toolkit::{
+    reexports::{
+        client::{
+            backend::ObjectId,
+            protocol::{wl_output::WlOutput, wl_pointer::WlPointer, wl_shm, wl_surface::WlSurface},
+            Connection,
+            Proxy,
+            QueueHandle,
+        },
+        csd_frame::{DecorationsFrame, FrameAction, FrameClick, WindowState as XdgWindowState},
+    },
+    seat::pointer::{CursorIcon, PointerData, PointerEvent, PointerEventKind, ThemedPointer},
+    shell::{
+        xdg::{
+            fallback_frame::FallbackFrame,
+            window::{DecorationMode, Window, WindowConfigure},
+            XdgSurface,
+        },
+        WaylandSurface,
+    },
+    shm::slot::Buffer,
+};
+use wayland_protocols::wp::pointer_constraints::zv1::client::{
+    zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+    zwp_locked_pointer_v1::ZwpLockedPointerV1,
+    zwp_pointer_constraints_v1::Lifetime,
+};
+
+use super::{
+    app_state::AppState,
+    state::WaylandState,
+    window_utils::{
+        to_decoration_mode,
+        to_decorations,
+        to_mouse_button,
+        to_resize_edge,
+        to_wm_capabilities,
+        BTN_LEFT,
+        BTN_RIGHT,
+    },
+};
+use crate::{
+    platform::{WindowApi, Wrapper},
+    platform_impl::common::{default_options, DragData},
+    ActiveApplication,
+    ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    CustomCursor,
+    Error,
+    ErrorKind,
+    Event,
+    Fullscreen,
+    GestureEvent,
+    ImeEvent,
+    InitMode,
+    KeyState,
+    KeyboardEvent,
+    Menu,
+    Monitor,
+    MouseButtonState,
+    MouseEvent,
+    ScrollingDelta,
+    ScrollingPhase,
+    TabletEvent,
+    TouchPhase,
+    WindowEvent,
+    WindowId,
+    WindowOptions,
+};
+
+const DEFAULT_SIZE: (f64, f64) = (800.0, 600.0);
+
+// Opaque black in `ARGB8888`, like the background of X11 windows.
+const BACKGROUND_COLOR: u32 = 0xFF000000;
+
+/// Pointer constraint created by [WindowState::set_cursor_grab].
+#[derive(Debug)]
+enum PointerConstraint {
+    Confined(ZwpConfinedPointerV1),
+    Locked(ZwpLockedPointerV1),
+}
+
+impl PointerConstraint {
+    fn destroy(&self) {
+        match self {
+            Self::Confined(confined_pointer) => confined_pointer.destroy(),
+            Self::Locked(locked_pointer) => locked_pointer.destroy(),
+        }
+    }
+}
+
+#[inline]
+fn non_zero(value: u32) -> NonZeroU32 { NonZeroU32::new(value).unwrap_or(NonZeroU32::MIN) }
+
+pub(super) struct WindowState {
+    app_state:       Rc<AppState>,
+    window:          Window,
+    frame:           RefCell<Option<FallbackFrame<WaylandState>>>,
+    last_configure:  RefCell<Option<WindowConfigure>>,
+    title:           RefCell<String>,
+    options:         Cell<WindowOptions>,
+    init_mode:       Cell<Option<InitMode>>,
+    min_size:        Cell<Option<LogicalSize<u32>>>,
+    max_size:        Cell<Option<LogicalSize<u32>>>,
+    size:            Cell<LogicalSize<u32>>,
+    prev_size:       Cell<PhysicalSize<u32>>,
+    scale_factor:    Cell<i32>,
+    xdg_state:       Cell<XdgWindowState>,
+    is_visible:      Cell<bool>,
+    is_minimized:    Cell<bool>,
+    is_destroyed:    Cell<bool>,
+    is_ime_allowed:  Cell<bool>,
+    ime_cursor_area: Cell<(LogicalPosition<i32>, LogicalSize<i32>)>,
+    needs_redraw:    Cell<bool>,
+    frame_pending:   Cell<bool>,
+    is_presented:    Cell<bool>,
+    cursor:          RefCell<Cursor>,
+    cursor_visible:  Cell<bool>,
+    cursor_grab:     Cell<CursorGrabMode>,
+    cursor_surface:  WlSurface,
+    cursor_buffer:   RefCell<Option<Buffer>>,
+    pointer_focus:   RefCell<Vec<WlPointer>>,
+    constraints:     RefCell<Vec<PointerConstraint>>,
+    outputs:         RefCell<Vec<WlOutput>>,
+    fullscreen:      RefCell<Option<Fullscreen>>,
+}
+
+impl Debug for WindowState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowState")
+            .field("window", &self.window)
+            .field("title", &self.title)
+            .field("options", &self.options)
+            .field("size", &self.size)
+            .field("scale_factor", &self.scale_factor)
+            .field("xdg_state", &self.xdg_state)
+            .field("is_visible", &self.is_visible)
+            .finish()
+    }
+}
+
+impl WindowState {
+    #[inline]
+    pub(super) fn surface_id(&self) -> ObjectId { self.window.wl_surface().id() }
+
+    #[inline]
+    pub(super) fn wl_surface(&self) -> &WlSurface { self.window.wl_surface() }
+
+    #[inline]
+    pub(super) fn window_id(&self) -> WindowId {
+        self.window.wl_surface().id().protocol_id() as WindowId
+    }
+
+    #[inline]
+    fn queue_event(&self, event: WindowEvent) {
+        self.app_state
+            .queue_event(Event::Window(event, self.window_id()));
+    }
+
+    #[inline]
+    fn has_xdg_state(&self, state: XdgWindowState) -> bool { self.xdg_state.get().contains(state) }
+
+    #[inline]
+    fn to_physical(&self, size: LogicalSize<u32>) -> PhysicalSize<u32> {
+        size.to_physical(self.scale_factor())
+    }
+
+    /// Returns the size of the window geometry, borders of client-side
+    /// decorations included.
+    fn outer_size(&self, size: LogicalSize<u32>) -> LogicalSize<u32> {
+        match self.frame.borrow().as_ref() {
+            Some(frame) if !frame.is_hidden() => {
+                let (width, height) = frame.add_borders(size.width, size.height);
+                LogicalSize::new(width, height)
+            }
+            _ => size,
+        }
+    }
+
+    fn update_size_limits(&self) {
+        let to_tuple = |size: LogicalSize<u32>| {
+            let size = self.outer_size(size);
+            (size.width, size.height)
+        };
+
+        // xdg-shell has no dedicated flag for fixed size windows.
+        if !self.options.get().resizable {
+            let size = Some(to_tuple(self.size.get()));
+            self.window.set_min_size(size);
+            self.window.set_max_size(size);
+        } else {
+            self.window.set_min_size(self.min_size.get().map(to_tuple));
+            self.window.set_max_size(self.max_size.get().map(to_tuple));
+        }
+    }
+
+    /// Lays out the surface and its decorations for the configured state.
+    ///
+    /// A size suggested by the compositor takes precedence over the current
+    /// one.
+    fn layout(
+        &self,
+        wayland: &mut WaylandState,
+        qh: &QueueHandle<WaylandState>,
+        configure: &WindowConfigure,
+        new_size: (Option<NonZeroU32>, Option<NonZeroU32>),
+    ) {
+        let options = self.options.get();
+        let use_frame = configure.decoration_mode == DecorationMode::Client && !options.borderless;
+        let current = self.size.get();
+
+        let mut frame = self.frame.borrow_mut();
+        if use_frame && frame.is_none() {
+            *frame = FallbackFrame::new(
+                &self.window,
+                wayland.shm(),
+                wayland.subcompositor_state().clone(),
+                qh.clone(),
+            )
+            .ok();
+        }
+
+        let size = match frame.as_mut() {
+            Some(frame) if use_frame => {
+                frame.set_hidden(false);
+                frame.update_state(configure.state);
+                frame.update_wm_capabilities(to_wm_capabilities(&options, configure.capabilities));
+
+                let (width, height) = match new_size {
+                    (Some(width), Some(height)) => frame.subtract_borders(width, height),
+                    _ => (None, None),
+                };
+                let width = width.unwrap_or(non_zero(current.width));
+                let height = height.unwrap_or(non_zero(current.height));
+                frame.resize(width, height);
+
+                let (x, y) = frame.location();
+                let (outer_width, outer_height) = frame.add_borders(width.get(), height.get());
+                self.window.xdg_surface().set_window_geometry(
+                    x,
+                    y,
+                    outer_width as i32,
+                    outer_height as i32,
+                );
+                LogicalSize::new(width.get(), height.get())
+            }
+            frame => {
+                if let Some(frame) = frame {
+                    frame.set_hidden(true);
+                }
+
+                let width = new_size.0.map_or(current.width, NonZeroU32::get);
+                let height = new_size.1.map_or(current.height, NonZeroU32::get);
+                self.window
+                    .xdg_surface()
+                    .set_window_geometry(0, 0, width as i32, height as i32);
+                LogicalSize::new(width, height)
+            }
+        };
+        drop(frame);
+
+        self.size.set(size);
+        let physical_size = self.to_physical(size);
+        if physical_size != self.prev_size.get() {
+            self.prev_size.set(physical_size);
+            self.queue_event(WindowEvent::Resized(physical_size));
+        }
+
+        self.update_size_limits();
+        self.draw(wayland);
+    }
+
+    /// Lays out the window again with the last configured state.
+    fn relayout(&self, wayland: &mut WaylandState, qh: &QueueHandle<WaylandState>) {
+        let configure = self.last_configure.borrow().clone();
+        match configure {
+            Some(configure) => self.layout(wayland, qh, &configure, (None, None)),
+            None => self.update_size_limits(),
+        }
+    }
+
+    /// Fills the surface with the background color and presents it along
+    /// with decorations.
+    ///
+    /// A surface is mapped only once it has a buffer attached, so the window
+    /// gets a background like X11 windows do. Once the user has presented a
+    /// frame, the surface content is left to the user and only decorations are
+    /// drawn.
+    fn draw(&self, wayland: &mut WaylandState) {
+        let surface = self.window.wl_surface();
+
+        if !self.is_presented.get() {
+            let size = self.content_size();
+            let stride = size.width as i32 * 4;
+
+            if let Ok((buffer, canvas)) = wayland.pool().create_buffer(
+                size.width as i32,
+                size.height as i32,
+                stride,
+                wl_shm::Format::Argb8888,
+            ) {
+                for pixel in canvas.chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&BACKGROUND_COLOR.to_le_bytes());
+                }
+                surface.set_buffer_scale(self.scale_factor.get());
+                if buffer.attach_to(surface).is_ok() {
+                    surface.damage_buffer(0, 0, size.width as i32, size.height as i32);
+                }
+            }
+        }
+
+        if let Some(frame) = self.frame.borrow_mut().as_mut() {
+            if frame.is_dirty() && !frame.is_hidden() {
+                frame.draw();
+            }
+        }
+        surface.commit();
+    }
+
+    pub(super) fn configure(
+        &self,
+        wayland: &mut WaylandState,
+        qh: &QueueHandle<WaylandState>,
+        configure: WindowConfigure,
+    ) {
+        let is_first = self.last_configure.borrow().is_none();
+        let was_activated = self.has_xdg_state(XdgWindowState::ACTIVATED);
+        let was_fullscreen = self.is_fullscreen();
+        self.xdg_state.set(configure.state);
+
+        self.layout(wayland, qh, &configure, configure.new_size);
+        *self.last_configure.borrow_mut() = Some(configure);
+
+        let is_activated = self.has_xdg_state(XdgWindowState::ACTIVATED);
+        if is_activated != was_activated {
+            if is_activated {
+                self.is_minimized.set(false);
+            }
+            self.queue_event(WindowEvent::Focused(is_activated));
+        }
+
+        let is_fullscreen = self.is_fullscreen();
+        if is_fullscreen != was_fullscreen {
+            if !is_fullscreen {
+                self.fullscreen.borrow_mut().take();
+            }
+            self.queue_event(WindowEvent::FullscreenChanged(is_fullscreen));
+        }
+
+        // Compositors ignore minimization of unmapped windows.
+        if is_first && self.init_mode.take() == Some(InitMode::Minimized) {
+            self.window.set_minimized();
+            self.is_minimized.set(true);
+        }
+
+        self.request_redraw();
+    }
+
+    #[inline]
+    pub(super) fn request_close(&self) { self.queue_event(WindowEvent::CloseRequested); }
+
+    #[inline]
+    pub(super) fn handle_ime_event(&self, event: ImeEvent) {
+        self.queue_event(WindowEvent::Ime(event));
+    }
+
+    #[inline]
+    pub(super) fn handle_gesture(&self, event: GestureEvent) {
+        self.queue_event(WindowEvent::Gesture(event));
+    }
+
+    #[inline]
+    pub(super) fn handle_tablet_event(&self, event: TabletEvent) {
+        self.queue_event(WindowEvent::Tablet(event));
+    }
+
+    pub(super) fn handle_keyboard_event(&self, event: KeyboardEvent) {
+        // Accelerators of the application menu consume key presses
+        if let KeyboardEvent::Input {
+            key,
+            state: KeyState::Pressed,
+            modifiers,
+            ..
+        } = &event
+        {
+            if self.app_state.trigger_accelerator(key, *modifiers) {
+                return;
+            }
+        }
+        self.queue_event(WindowEvent::Keyboard(event));
+    }
+
+    pub(super) fn set_scale_factor(
+        &self,
+        wayland: &mut WaylandState,
+        qh: &QueueHandle<WaylandState>,
+        scale_factor: i32,
+    ) {
+        if self.scale_factor.replace(scale_factor) != scale_factor {
+            self.queue_event(WindowEvent::ScaleFactorChanged(scale_factor as f64));
+            self.relayout(wayland, qh);
+        }
+    }
+
+    fn set_frame_cursor(
+        &self,
+        conn: &Connection,
+        themed_pointer: Option<&ThemedPointer>,
+        cursor: CursorIcon,
+    ) {
+        if let Some(themed_pointer) = themed_pointer {
+            let _ = themed_pointer.set_cursor(conn, cursor);
+        }
+    }
+
+    pub(super) fn handle_pointer_event(
+        &self,
+        conn: &Connection,
+        pointer: &WlPointer,
+        themed_pointer: Option<&ThemedPointer>,
+        event: &PointerEvent,
+    ) {
+        if &event.surface != self.window.wl_surface() {
+            self.handle_frame_pointer_event(conn, pointer, themed_pointer, event);
+            return;
+        }
+
+        let scale_factor = self.scale_factor();
+        let (x, y) = event.position;
+        let mouse_event = match event.kind {
+            PointerEventKind::Enter {
+                ..
+            } => {
+                self.pointer_focus.borrow_mut().push(pointer.clone());
+                if let Some(themed_pointer) = themed_pointer {
+                    self.apply_cursor(conn, themed_pointer);
+                }
+                MouseEvent::Entered
+            }
+            PointerEventKind::Leave {
+                ..
+            } => {
+                self.pointer_focus
+                    .borrow_mut()
+                    .retain(|focused| focused != pointer);
+                MouseEvent::Exited
+            }
+            PointerEventKind::Motion {
+                ..
+            } => MouseEvent::Moved {
+                position: PhysicalPosition::new(x * scale_factor, y * scale_factor),
+            },
+            PointerEventKind::Press {
+                button, ..
+            } => MouseEvent::Input {
+                button: to_mouse_button(button),
+                state:  MouseButtonState::Pressed,
+            },
+            PointerEventKind::Release {
+                button, ..
+            } => MouseEvent::Input {
+                button: to_mouse_button(button),
+                state:  MouseButtonState::Released,
+            },
+            PointerEventKind::Axis {
+                horizontal,
+                vertical,
+                ..
+            } => {
+                // Wayland axes grow to the right and down, unlike b3 deltas.
+                let delta = if horizontal.discrete != 0 || vertical.discrete != 0 {
+                    ScrollingDelta::Line(-horizontal.discrete as f32, -vertical.discrete as f32)
+                } else {
+                    ScrollingDelta::Pixel(
+                        -horizontal.absolute * scale_factor,
+                        -vertical.absolute * scale_factor,
+                    )
+                };
+                let phase = if horizontal.stop || vertical.stop {
+                    ScrollingPhase::Ended
+                } else {
+                    ScrollingPhase::Changed
+                };
+                MouseEvent::Scroll {
+                    delta,
+                    phase,
+                }
+            }
+        };
+
+        self.queue_event(WindowEvent::Mouse(mouse_event));
+    }
+
+    fn handle_frame_pointer_event(
+        &self,
+        conn: &Connection,
+        pointer: &WlPointer,
+        themed_pointer: Option<&ThemedPointer>,
+        event: &PointerEvent,
+    ) {
+        let mut frame = self.frame.borrow_mut();
+        let frame = match frame.as_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let (x, y) = event.position;
+        let action = match event.kind {
+            PointerEventKind::Enter {
+                ..
+            } => {
+                if let Some(cursor) =
+                    frame.click_point_moved(Duration::ZERO, &event.surface.id(), x, y)
+                {
+                    self.set_frame_cursor(conn, themed_pointer, cursor);
+                }
+                None
+            }
+            PointerEventKind::Motion {
+                time,
+            } => {
+                let time = Duration::from_millis(time as u64);
+                if let Some(cursor) = frame.click_point_moved(time, &event.surface.id(), x, y) {
+                    self.set_frame_cursor(conn, themed_pointer, cursor);
+                }
+                None
+            }
+            PointerEventKind::Leave {
+                ..
+            } => {
+                frame.click_point_left();
+                None
+            }
+            PointerEventKind::Press {
+                button,
+                serial,
+                time,
+            }
+            | PointerEventKind::Release {
+                button,
+                serial,
+                time,
+            } => {
+                let pressed = matches!(event.kind, PointerEventKind::Press { .. });
+                let click = match button {
+                    BTN_LEFT => Some(FrameClick::Normal),
+                    BTN_RIGHT => Some(FrameClick::Alternate),
+                    _ => None,
+                };
+                click
+                    .and_then(|click| {
+                        frame.on_click(Duration::from_millis(time as u64), click, pressed)
+                    })
+                    .map(|action| (action, serial))
+            }
+            _ => None,
+        };
+
+        // Hovered buttons are highlighted, so the frame may need a redraw.
+        if frame.is_dirty() && !frame.is_hidden() {
+            frame.draw();
+            self.window.wl_surface().commit();
+        }
+
+        if let Some((action, serial)) = action {
+            self.handle_frame_action(pointer, serial, action);
+        }
+    }
+
+    fn handle_frame_action(&self, pointer: &WlPointer, serial: u32, action: FrameAction) {
+        let seat = match pointer.data::<PointerData>() {
+            Some(data) => data.seat(),
+            None => return,
+        };
+        let options = self.options.get();
+
+        match action {
+            FrameAction::Close if options.closable => self.request_close(),
+            FrameAction::Minimize => {
+                self.window.set_minimized();
+                self.is_minimized.set(true);
+            }
+            FrameAction::Maximize => self.window.set_maximized(),
+            FrameAction::UnMaximize => self.window.unset_maximized(),
+            FrameAction::ShowMenu(x, y) => self.window.show_window_menu(seat, serial, (x, y)),
+            FrameAction::Resize(edge) if options.resizable => {
+                if let Some(edge) = to_resize_edge(edge) {
+                    self.window.resize(seat, serial, edge);
+                }
+            }
+            FrameAction::Move if options.draggable => self.window.move_(seat, serial),
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub(super) fn set_title(&self, title: String) {
+        self.window.set_title(title.clone());
+        if let Some(frame) = self.frame.borrow_mut().as_mut() {
+            frame.set_title(title.clone());
+        }
+        *self.title.borrow_mut() = title;
+    }
+
+    #[inline]
+    pub(super) fn title(&self) -> String { self.title.borrow().clone() }
+
+    pub(super) fn set_options(&self, options: WindowOptions) {
+        self.options.set(options);
+        self.window
+            .request_decoration_mode(Some(to_decoration_mode(&options)));
+
+        // Compositors without xdg-decoration don't send a new configure, so
+        // decorations are updated right away.
+        self.relayout(&mut self.app_state.wayland(), self.app_state.queue_handle());
+    }
+
+    #[inline]
+    pub(super) fn options(&self) -> WindowOptions { self.options.get() }
+
+    fn sync_with_init_mode(&self) {
+        match self.init_mode.get() {
+            Some(InitMode::Maximized) => {
+                self.init_mode.take();
+                self.window.set_maximized();
+            }
+            Some(InitMode::Fullscreen) => {
+                self.init_mode.take();
+                self.window.set_fullscreen(None);
+            }
+            _ => (),
+        }
+    }
+
+    pub(super) fn show(&self) {
+        if self.is_visible.replace(true) {
+            return;
+        }
+
+        // The initial state must be requested before the initial commit, the
+        // compositor replies to it with the first configure.
+        self.sync_with_init_mode();
+        self.window.commit();
+        let _ = self.app_state.conn().flush();
+
+        self.queue_event(WindowEvent::Showed);
+    }
+
+    #[inline]
+    pub(super) fn show_modal(&self) {
+        // Core xdg-shell has no modal windows.
+        self.show();
+    }
+
+    pub(super) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        // Compositors don't let clients change video modes, exclusive modes
+        // fall back to the borderless mode on the monitor of the mode.
+        let monitor = match &fullscreen {
+            Some(Fullscreen::Borderless(monitor)) => monitor.clone(),
+            Some(Fullscreen::Exclusive(mode)) => Some(mode.monitor().clone()),
+            None => None,
+        };
+        match fullscreen.is_some() {
+            true => self
+                .window
+                .set_fullscreen(monitor.as_ref().map(|monitor| monitor.get_impl().output())),
+            false => self.window.unset_fullscreen(),
+        }
+        *self.fullscreen.borrow_mut() = fullscreen;
+        let _ = self.app_state.conn().flush();
+    }
+
+    /// Returns the fullscreen mode requested for the window.
+    ///
+    /// The borderless mode is reported if the compositor has made the window
+    /// fullscreen on its own.
+    pub(super) fn fullscreen(&self) -> Option<Fullscreen> {
+        match self.is_fullscreen() {
+            true => Some(
+                self.fullscreen
+                    .borrow()
+                    .clone()
+                    .unwrap_or(Fullscreen::Borderless(None)),
+            ),
+            false => None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_fullscreen(&self) -> bool { self.has_xdg_state(XdgWindowState::FULLSCREEN) }
+
+    pub(super) fn set_frame_size(&self, size: Size) {
+        let size: LogicalSize<u32> = size.to_logical(self.scale_factor());
+        let size = match self.frame.borrow().as_ref() {
+            Some(frame) if !frame.is_hidden() => {
+                let (width, height) =
+                    frame.subtract_borders(non_zero(size.width), non_zero(size.height));
+                LogicalSize::new(
+                    width.map_or(1, NonZeroU32::get),
+                    height.map_or(1, NonZeroU32::get),
+                )
+            }
+            _ => size,
+        };
+
+        self.size
+            .set(LogicalSize::new(size.width.max(1), size.height.max(1)));
+        self.relayout(&mut self.app_state.wayland(), self.app_state.queue_handle());
+    }
+
+    #[inline]
+    pub(super) fn frame_size(&self) -> PhysicalSize<u32> {
+        self.to_physical(self.outer_size(self.size.get()))
+    }
+
+    #[inline]
+    pub(super) fn set_position(&self, _position: Position) {
+        // Wayland clients can't position their windows.
+    }
+
+    #[inline]
+    pub(super) fn position(&self) -> PhysicalPosition<i32> {
+        // Wayland clients don't know the global position of their windows.
+        PhysicalPosition::default()
+    }
+
+    pub(super) fn set_min_size(&self, min_size: Size) {
+        self.min_size
+            .set(Some(min_size.to_logical(self.scale_factor())));
+        self.update_size_limits();
+        self.window.commit();
+    }
+
+    #[inline]
+    pub(super) fn min_size(&self) -> PhysicalSize<u32> {
+        self.min_size
+            .get()
+            .map(|size| self.to_physical(size))
+            .unwrap_or_default()
+    }
+
+    pub(super) fn set_max_size(&self, max_size: Size) {
+        self.max_size
+            .set(Some(max_size.to_logical(self.scale_factor())));
+        self.update_size_limits();
+        self.window.commit();
+    }
+
+    #[inline]
+    pub(super) fn max_size(&self) -> PhysicalSize<u32> {
+        self.max_size
+            .get()
+            .map(|size| self.to_physical(size))
+            .unwrap_or(PhysicalSize::new(u32::MAX, u32::MAX))
+    }
+
+    #[inline]
+    pub(super) fn maximize(&self) {
+        if self.last_configure.borrow().is_none() {
+            self.init_mode.set(Some(InitMode::Maximized));
+        } else {
+            self.window.set_maximized();
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_maximized(&self) -> bool { self.has_xdg_state(XdgWindowState::MAXIMIZED) }
+
+    #[inline]
+    pub(super) fn content_size(&self) -> PhysicalSize<u32> { self.to_physical(self.size.get()) }
+
+    #[inline]
+    pub(super) fn is_visible(&self) -> bool { self.is_visible.get() }
+
+    pub(super) fn close(&self) {
+        if self.is_destroyed.replace(true) {
+            return;
+        }
+
+        self.app_state.unregister_window(self);
+        self.frame.borrow_mut().take();
+        for constraint in self.constraints.take().iter() {
+            constraint.destroy();
+        }
+        self.cursor_surface.destroy();
+        self.cursor_buffer.borrow_mut().take();
+
+        // Detaching the buffer unmaps the surface, the role object itself is
+        // destroyed along with the window.
+        let surface = self.window.wl_surface();
+        surface.attach(None, 0, 0);
+        surface.commit();
+        let _ = self.app_state.conn().flush();
+
+        self.is_visible.set(false);
+        self.queue_event(WindowEvent::Destroyed);
+    }
+
+    pub(super) fn minimize(&self) {
+        if self.last_configure.borrow().is_none() {
+            self.init_mode.set(Some(InitMode::Minimized));
+            return;
+        }
+
+        self.window.set_minimized();
+        self.is_minimized.set(true);
+    }
+
+    #[inline]
+    pub(super) fn is_minimized(&self) -> bool {
+        // xdg-shell doesn't report minimization, so it is tracked until the
+        // window gets activated again.
+        self.is_minimized.get()
+    }
+
+    #[inline]
+    pub(super) fn restore(&self) {
+        // Wayland clients can't unminimize their windows, the compositor
+        // restores them when they get activated.
+    }
+
+    #[inline]
+    pub(super) fn scale_factor(&self) -> f64 { self.scale_factor.get() as f64 }
+
+    pub(super) fn set_ime_allowed(&self, allowed: bool) {
+        self.is_ime_allowed.set(allowed);
+        self.app_state.wayland().update_text_input(self);
+        let _ = self.app_state.conn().flush();
+    }
+
+    #[inline]
+    pub(super) fn is_ime_allowed(&self) -> bool { self.is_ime_allowed.get() }
+
+    pub(super) fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor();
+        self.ime_cursor_area.set((
+            position.to_logical(scale_factor),
+            size.to_logical(scale_factor),
+        ));
+        self.app_state.wayland().update_text_input(self);
+        let _ = self.app_state.conn().flush();
+    }
+
+    /// Returns an area of the text cursor in surface coordinates.
+    #[inline]
+    pub(super) fn ime_cursor_area(&self) -> (LogicalPosition<i32>, LogicalSize<i32>) {
+        self.ime_cursor_area.get()
+    }
+
+    /// Marks the window as needing a redraw.
+    #[inline]
+    pub(super) fn request_redraw(&self) { self.needs_redraw.set(true); }
+
+    /// Checks if a redraw has been requested and the compositor is ready for a
+    /// new frame.
+    #[inline]
+    pub(super) fn needs_redraw(&self) -> bool {
+        self.needs_redraw.get() && !self.frame_pending.get()
+    }
+
+    /// Resets the redraw request and returns if it was set.
+    ///
+    /// The request is kept while a frame callback is pending.
+    #[inline]
+    pub(super) fn take_redraw_request(&self) -> bool {
+        !self.frame_pending.get() && self.needs_redraw.replace(false)
+    }
+
+    /// Requests a frame callback for the next surface commit.
+    ///
+    /// Redraws are held back until the compositor signals a good time to
+    /// draw the next frame. From now on the surface content belongs to the
+    /// user, so the background isn't drawn anymore.
+    pub(super) fn pre_present_notify(&self) {
+        self.is_presented.set(true);
+        if !self.frame_pending.replace(true) {
+            let surface = self.window.wl_surface();
+            surface.frame(self.app_state.queue_handle(), surface.clone());
+        }
+    }
+
+    /// Handles a frame callback.
+    #[inline]
+    pub(super) fn frame_done(&self) { self.frame_pending.set(false); }
+
+    /// Checks if the pointer is over the window content.
+    #[inline]
+    pub(super) fn has_pointer_focus(&self, pointer: &WlPointer) -> bool {
+        self.pointer_focus.borrow().contains(pointer)
+    }
+
+    /// Handles the surface entering an output.
+    #[inline]
+    pub(super) fn surface_enter(&self, output: &WlOutput) {
+        let mut outputs = self.outputs.borrow_mut();
+        outputs.retain(|o| o != output);
+        outputs.push(output.clone());
+    }
+
+    /// Handles the surface leaving an output.
+    #[inline]
+    pub(super) fn surface_leave(&self, output: &WlOutput) {
+        self.outputs.borrow_mut().retain(|o| o != output);
+    }
+
+    /// Returns the monitor the surface entered last.
+    ///
+    /// Wayland doesn't expose window positions, so the overlap with outputs
+    /// is unknown.
+    pub(super) fn current_monitor(&self) -> Option<Monitor> {
+        let output = self.outputs.borrow().last().cloned()?;
+        self.app_state.wayland().monitor(&output).map(Monitor::new)
+    }
+
+    #[inline]
+    pub(super) fn to_physical_position(&self, x: f64, y: f64) -> PhysicalPosition<f64> {
+        LogicalPosition::new(x, y).to_physical(self.scale_factor())
+    }
+
+    /// Handles a touch point at the surface position.
+    #[inline]
+    pub(super) fn handle_touch(&self, id: i32, phase: TouchPhase, x: f64, y: f64) {
+        self.queue_event(WindowEvent::Touch {
+            id: id as u32 as u64,
+            phase,
+            position: self.to_physical_position(x, y),
+            force: None,
+        });
+    }
+
+    /// Handles a drag entering the window at the surface position.
+    pub(super) fn handle_drag_enter(&self, data: &DragData, x: f64, y: f64) {
+        self.queue_event(WindowEvent::DragEntered {
+            paths:    data.paths.clone(),
+            text:     data.text.clone(),
+            position: self.to_physical_position(x, y),
+        });
+    }
+
+    #[inline]
+    pub(super) fn handle_drag_motion(&self, x: f64, y: f64) {
+        self.queue_event(WindowEvent::DragMoved {
+            position: self.to_physical_position(x, y),
+        });
+    }
+
+    pub(super) fn handle_drop(&self, data: &DragData, x: f64, y: f64) {
+        self.queue_event(WindowEvent::DragDropped {
+            paths:    data.paths.clone(),
+            text:     data.text.clone(),
+            position: self.to_physical_position(x, y),
+        });
+    }
+
+    #[inline]
+    pub(super) fn handle_drag_leave(&self) { self.queue_event(WindowEvent::DragLeft); }
+
+    #[inline]
+    pub(super) fn handle_raw_motion(&self, delta: (f64, f64)) {
+        self.queue_event(WindowEvent::Mouse(MouseEvent::RawMotion {
+            delta,
+        }));
+    }
+
+    /// Shows the cursor of the window for the pointer.
+    ///
+    /// The cursor must be set again on every pointer enter.
+    fn apply_cursor(&self, conn: &Connection, themed_pointer: &ThemedPointer) {
+        if !self.cursor_visible.get() {
+            let _ = themed_pointer.hide_cursor();
+            return;
+        }
+
+        match &*self.cursor.borrow() {
+            Cursor::Icon(icon) => {
+                let _ = themed_pointer.set_cursor(conn, *icon);
+            }
+            Cursor::Custom(cursor) => {
+                let pointer = themed_pointer.pointer();
+                let serial = pointer
+                    .data::<PointerData>()
+                    .and_then(|data| data.latest_enter_serial());
+                if let Some(serial) = serial {
+                    let (hotspot_x, hotspot_y) = cursor.hotspot();
+                    pointer.set_cursor(
+                        serial,
+                        Some(&self.cursor_surface),
+                        hotspot_x as i32,
+                        hotspot_y as i32,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Applies the cursor to all pointers over the window content.
+    fn update_cursor(&self, wayland: &WaylandState) {
+        let conn = self.app_state.conn();
+        for pointer in self.pointer_focus.borrow().iter() {
+            if let Some(themed_pointer) = wayland.themed_pointer(pointer) {
+                self.apply_cursor(conn, themed_pointer);
+            }
+        }
+        let _ = conn.flush();
+    }
+
+    /// Draws a custom cursor image on the cursor surface.
+    fn draw_custom_cursor(&self, wayland: &mut WaylandState, cursor: &CustomCursor) {
+        let (width, height) = (cursor.width() as i32, cursor.height() as i32);
+        let buffer =
+            wayland
+                .pool()
+                .create_buffer(width, height, width * 4, wl_shm::Format::Argb8888);
+
+        if let Ok((buffer, canvas)) = buffer {
+            let pixels = cursor.premultiplied_argb();
+            for (pixel, argb) in canvas.chunks_exact_mut(4).zip(pixels) {
+                pixel.copy_from_slice(&argb.to_le_bytes());
+            }
+            if buffer.attach_to(&self.cursor_surface).is_ok() {
+                self.cursor_surface.damage_buffer(0, 0, width, height);
+            }
+            self.cursor_surface.commit();
+            // The compositor reads the buffer as long as it is attached
+            *self.cursor_buffer.borrow_mut() = Some(buffer);
+        }
+    }
+
+    pub(super) fn set_cursor(&self, cursor: Cursor) {
+        let mut wayland = self.app_state.wayland();
+        if let Cursor::Custom(cursor) = &cursor {
+            self.draw_custom_cursor(&mut wayland, cursor);
+        }
+        *self.cursor.borrow_mut() = cursor;
+        self.update_cursor(&wayland);
+    }
+
+    #[inline]
+    pub(super) fn cursor(&self) -> Cursor { self.cursor.borrow().clone() }
+
+    pub(super) fn set_cursor_visible(&self, visible: bool) {
+        if self.cursor_visible.replace(visible) != visible {
+            self.update_cursor(&self.app_state.wayland());
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_cursor_visible(&self) -> bool { self.cursor_visible.get() }
+
+    pub(super) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), Error> {
+        let wayland = self.app_state.wayland();
+        let constraints = wayland.pointer_constraints();
+        let qh = self.app_state.queue_handle();
+        let surface = self.window.wl_surface();
+
+        // A constraint is activated by the compositor once the pointer of
+        // the seat gets over the window.
+        let mut new_constraints = Vec::new();
+        for themed_pointer in wayland.pointers().iter() {
+            let pointer = themed_pointer.pointer();
+            let constraint = match mode {
+                CursorGrabMode::None => break,
+                CursorGrabMode::Confined => constraints
+                    .confine_pointer(surface, pointer, None, Lifetime::Persistent, qh)
+                    .map(PointerConstraint::Confined),
+                CursorGrabMode::Locked => constraints
+                    .lock_pointer(surface, pointer, None, Lifetime::Persistent, qh)
+                    .map(PointerConstraint::Locked),
+            };
+            let constraint = constraint.map_err(|e| {
+                Error::with_source(
+                    ErrorKind::NotSupported,
+                    "zwp_pointer_constraints_v1 not available.",
+                    e,
+                )
+            })?;
+            new_constraints.push(constraint);
+        }
+
+        // Only one constraint is allowed per pointer and surface
+        for constraint in self.constraints.replace(new_constraints).iter() {
+            constraint.destroy();
+        }
+        self.cursor_grab.set(mode);
+        let _ = self.app_state.conn().flush();
+        Ok(())
+    }
+
+    #[inline]
+    pub(super) fn cursor_grab(&self) -> CursorGrabMode { self.cursor_grab.get() }
+
+    pub(super) fn set_cursor_position(&self, position: Position) -> Result<(), Error> {
+        // Wayland clients can only hint where a locked cursor is left after
+        // unlocking.
+        let position: LogicalPosition<f64> = position.to_logical(self.scale_factor());
+        let constraints = self.constraints.borrow();
+        let mut locked_pointers = constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                PointerConstraint::Locked(locked_pointer) => Some(locked_pointer),
+                _ => None,
+            })
+            .peekable();
+        if locked_pointers.peek().is_none() {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                "cursor can only be moved while it is locked.",
+            ));
+        }
+
+        for locked_pointer in locked_pointers {
+            locked_pointer.set_cursor_position_hint(position.x, position.y);
+        }
+        // The hint is double-buffered state of the surface
+        self.window.wl_surface().commit();
+        let _ = self.app_state.conn().flush();
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct WindowImpl {
+    state: Rc<WindowState>,
+}
+
+impl WindowApi for WindowImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        mode: InitMode,
+        options: Option<WindowOptions>,
+        size: Option<Size>,
+    ) -> Result<Self, Error> {
+        // Extract the application context
+        let app_state = ctx.context().get_impl().state().clone();
+        let options = options.unwrap_or_else(default_options);
+
+        // The scale factor is unknown until the surface enters an output.
+        let size: LogicalSize<u32> = size
+            .unwrap_or(Size::Logical(DEFAULT_SIZE.into()))
+            .to_logical(1.0);
+        let size = LogicalSize::new(size.width.max(1), size.height.max(1));
+
+        // Create xdg-shell window
+        let (window, cursor_surface) = {
+            let wayland = app_state.wayland();
+            let qh = app_state.queue_handle();
+            let surface = wayland.compositor_state().create_surface(qh);
+            let window = wayland
+                .xdg_shell()
+                .create_window(surface, to_decorations(&options), qh);
+            (window, wayland.compositor_state().create_surface(qh))
+        };
+        // Compositors match windows with desktop entries by the app ID
+        if let Some(app_id) = app_state.app_id() {
+            window.set_app_id(app_id);
+        }
+
+        let state = Rc::new(WindowState {
+            app_state: app_state.clone(),
+            window,
+            frame: RefCell::new(None),
+            last_configure: RefCell::new(None),
+            title: RefCell::new(String::new()),
+            options: Cell::new(options),
+            init_mode: Cell::new(Some(mode)),
+            min_size: Cell::new(None),
+            max_size: Cell::new(None),
+            size: Cell::new(size),
+            prev_size: Cell::new(PhysicalSize::new(size.width, size.height)),
+            scale_factor: Cell::new(1),
+            xdg_state: Cell::new(XdgWindowState::empty()),
+            is_visible: Cell::new(false),
+            is_minimized: Cell::new(false),
+            is_destroyed: Cell::new(false),
+            is_ime_allowed: Cell::new(false),
+            ime_cursor_area: Cell::new((LogicalPosition::default(), LogicalSize::default())),
+            needs_redraw: Cell::new(false),
+            frame_pending: Cell::new(false),
+            is_presented: Cell::new(false),
+            cursor: RefCell::new(Cursor::default()),
+            cursor_visible: Cell::new(true),
+            cursor_grab: Cell::new(CursorGrabMode::None),
+            cursor_surface,
+            cursor_buffer: RefCell::new(None),
+            pointer_focus: RefCell::new(Vec::new()),
+            constraints: RefCell::new(Vec::new()),
+            outputs: RefCell::new(Vec::new()),
+            fullscreen: RefCell::new(None),
+        });
+
+        // Set post-creation window options
+        state.update_size_limits();
+
+        app_state.register_window(&state);
+
+        Ok(Self {
+            state,
+        })
+    }
+
+    #[inline]
+    fn id(&self) -> WindowId { self.state.window_id() }
+
+    #[inline]
+    fn set_title(&mut self, title: String) { self.state.set_title(title); }
+
+    #[inline]
+    fn title(&self) -> String { self.state.title() }
+
+    #[inline]
+    fn set_options(&mut self, options: WindowOptions) { self.state.set_options(options); }
+
+    #[inline]
+    fn options(&self) -> WindowOptions { self.state.options() }
+
+    #[inline]
+    fn show(&mut self, _app: &ActiveApplication) { self.state.show(); }
+
+    #[inline]
+    fn show_modal(&mut self, _app: &ActiveApplication) { self.state.show_modal(); }
+
+    #[inline]
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
+        self.state.set_fullscreen(fullscreen);
+    }
+
+    #[inline]
+    fn fullscreen(&self) -> Option<Fullscreen> { self.state.fullscreen() }
+
+    #[inline]
+    fn is_fullscreen(&self) -> bool { self.state.is_fullscreen() }
+
+    #[inline]
+    fn set_frame_size(&mut self, size: Size) { self.state.set_frame_size(size); }
+
+    #[inline]
+    fn frame_size(&self) -> PhysicalSize<u32> { self.state.frame_size() }
+
+    #[inline]
+    fn set_position(&mut self, position: Position) { self.state.set_position(position); }
+
+    #[inline]
+    fn position(&self) -> PhysicalPosition<i32> { self.state.position() }
+
+    #[inline]
+    fn set_min_size(&mut self, min_size: Size) { self.state.set_min_size(min_size); }
+
+    #[inline]
+    fn min_size(&self) -> PhysicalSize<u32> { self.state.min_size() }
+
+    #[inline]
+    fn set_max_size(&mut self, max_size: Size) { self.state.set_max_size(max_size); }
+
+    #[inline]
+    fn max_size(&self) -> PhysicalSize<u32> { self.state.max_size() }
+
+    #[inline]
+    fn maximize(&mut self) { self.state.maximize(); }
+
+    #[inline]
+    fn is_maximized(&self) -> bool { self.state.is_maximized() }
+
+    #[inline]
+    fn content_size(&self) -> PhysicalSize<u32> { self.state.content_size() }
+
+    #[inline]
+    fn is_visible(&self) -> bool { self.state.is_visible() }
+
+    #[inline]
+    fn close(&mut self) { self.state.close(); }
+
+    #[inline]
+    fn minimize(&mut self) { self.state.minimize(); }
+
+    #[inline]
+    fn is_minimized(&self) -> bool { self.state.is_minimized() }
+
+    #[inline]
+    fn restore(&mut self) { self.state.restore(); }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { self.state.scale_factor() }
+
+    #[inline]
+    fn current_monitor(&self) -> Option<Monitor> { self.state.current_monitor() }
+
+    #[inline]
+    fn set_ime_allowed(&mut self, allowed: bool) { self.state.set_ime_allowed(allowed); }
+
+    #[inline]
+    fn is_ime_allowed(&self) -> bool { self.state.is_ime_allowed() }
+
+    #[inline]
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        self.state.set_ime_cursor_area(position, size);
+    }
+
+    #[inline]
+    fn request_redraw(&mut self) { self.state.request_redraw(); }
+
+    #[inline]
+    fn pre_present_notify(&mut self) { self.state.pre_present_notify(); }
+
+    #[inline]
+    fn set_cursor(&mut self, cursor: Cursor) { self.state.set_cursor(cursor); }
+
+    #[inline]
+    fn cursor(&self) -> Cursor { self.state.cursor() }
+
+    #[inline]
+    fn set_cursor_visible(&mut self, visible: bool) { self.state.set_cursor_visible(visible); }
+
+    #[inline]
+    fn is_cursor_visible(&self) -> bool { self.state.is_cursor_visible() }
+
+    #[inline]
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error> {
+        self.state.set_cursor_grab(mode)
+    }
+
+    #[inline]
+    fn cursor_grab(&self) -> CursorGrabMode { self.state.cursor_grab() }
+
+    #[inline]
+    fn set_cursor_position(&mut self, position: Position) -> Result<(), Error> {
+        self.state.set_cursor_position(position)
+    }
+
+    fn show_context_menu(&mut self, _menu: &Menu, _position: Position) -> Result<(), Error> {
+        // Popup surfaces are drawn by clients, and there is no text renderer
+        Err(Error::new(
+            ErrorKind::NotSupported,
+            "context menus are not supported on Wayland.",
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+use smithay_client_toolkit::{
+    reexports::{
+        csd_frame::{ResizeEdge, WindowManagerCapabilities},
+        protocols::xdg::shell::client::xdg_toplevel::ResizeEdge as XdgResizeEdge,
+    },
+    shell::xdg::window::{DecorationMode, WindowDecorations},
+};
+
+use crate::{MouseButton, WindowOptions};
+
+// See: linux/input-event-codes.h
+pub(super) const BTN_LEFT: u32 = 0x110;
+pub(super) const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+
+/// Converts window options into decorations requested on window creation.
+#[inline]
+pub(super) fn to_decorations(options: &WindowOptions) -> WindowDecorations {
+    if options.borderless {
+        WindowDecorations::RequestClient
+    } else {
+        WindowDecorations::RequestServer
+    }
+}
+
+/// Converts window options into the xdg-decoration mode.
+///
+/// Borderless windows ask for client-side decorations and draw none.
+#[inline]
+pub(super) fn to_decoration_mode(options: &WindowOptions) -> DecorationMode {
+    if options.borderless {
+        DecorationMode::Client
+    } else {
+        DecorationMode::Server
+    }
+}
+
+/// Filters compositor capabilities shown by client-side decorations.
+pub(super) fn to_wm_capabilities(
+    options: &WindowOptions,
+    capabilities: WindowManagerCapabilities,
+) -> WindowManagerCapabilities {
+    let mut capabilities = capabilities;
+    if !options.minimizable {
+        capabilities.remove(WindowManagerCapabilities::MINIMIZE);
+    }
+    if !options.fullscreen || !options.resizable {
+        capabilities.remove(WindowManagerCapabilities::MAXIMIZE);
+    }
+    if !options.fullscreen {
+        capabilities.remove(WindowManagerCapabilities::FULLSCREEN);
+    }
+    capabilities
+}
+
+pub(super) fn to_resize_edge(edge: ResizeEdge) -> Option<XdgResizeEdge> {
+    let edge = match edge {
+        ResizeEdge::Top => XdgResizeEdge::Top,
+        ResizeEdge::Bottom => XdgResizeEdge::Bottom,
+        ResizeEdge::Left => XdgResizeEdge::Left,
+        ResizeEdge::TopLeft => XdgResizeEdge::TopLeft,
+        ResizeEdge::BottomLeft => XdgResizeEdge::BottomLeft,
+        ResizeEdge::Right => XdgResizeEdge::Right,
+        ResizeEdge::TopRight => XdgResizeEdge::TopRight,
+        ResizeEdge::BottomRight => XdgResizeEdge::BottomRight,
+        _ => return None,
+    };
+    Some(edge)
+}
+
+pub(super) fn to_mouse_button(button: u32) -> MouseButton {
+    match button {
+        BTN_LEFT => MouseButton::Left,
+        BTN_MIDDLE => MouseButton::Middle,
+        BTN_RIGHT => MouseButton::Right,
+        BTN_SIDE => MouseButton::Back,
+        BTN_EXTRA => MouseButton::Forward,
+        button => MouseButton::Other {
+            id: button as u16
+        },
+    }
+}
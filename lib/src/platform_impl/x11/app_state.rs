@@ -0,0 +1,555 @@
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    mem,
+    os::fd::AsFd,
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use rustix::{
+    event::{poll, PollFd, PollFlags},
+    io::Errno,
+};
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xinput::{
+            ConnectionExt as _,
+            Device,
+            EventMask as XIEventMaskList,
+            XIEventMask,
+            GESTURE_PINCH_BEGIN_EVENT,
+            GESTURE_SWIPE_END_EVENT,
+        },
+        xproto::{Screen, Window as XWindow},
+    },
+    resource_manager::{self, Database},
+    rust_connection::RustConnection,
+};
+
+#[cfg(feature = "tray")]
+use super::tray::XEmbedIcon;
+use super::{
+    atoms::Atoms,
+    clipboard::Selections,
+    context_menu::ContextMenu,
+    cursor::Cursors,
+    dnd::Dnd,
+    events::handle_x_event,
+    keyboard::Keyboard,
+    monitor::{query_monitors, MonitorImpl},
+    tablet::Tablets,
+    touch::TouchDevices,
+    window::WindowState,
+    xim::Xim,
+};
+#[cfg(feature = "tray")]
+use crate::platform_impl::common::MenuCallbacks;
+use crate::{
+    platform::Wrapper,
+    platform_impl::common::{Accelerators, EventLoopProxyImpl, PendingEvent, UserEvents},
+    ActiveApplication,
+    ControlFlow,
+    Error,
+    ErrorKind,
+    Event,
+    EventHandler,
+    Icon,
+    Key,
+    LifeCycle,
+    Menu,
+    MenuCallback,
+    Modifiers,
+    StartCause,
+    UserEvent,
+    WindowEvent,
+};
+
+const DEFAULT_DPI: f64 = 96.0;
+
+pub(super) struct AppState {
+    conn:           RustConnection,
+    screen_num:     usize,
+    atoms:          Atoms,
+    cursors:        Cursors,
+    keyboard:       RefCell<Keyboard>,
+    xim:            RefCell<Xim>,
+    dnd:            RefCell<Dnd>,
+    context_menu:   RefCell<Option<ContextMenu>>,
+    accelerators:   RefCell<Accelerators>,
+    touch_devices:  RefCell<TouchDevices>,
+    pinch_scale:    Cell<f64>,
+    tablets:        RefCell<Tablets>,
+    xinput_version: (u16, u16),
+    selections:     RefCell<Selections>,
+    scale_factor:   f64,
+    app:            RefCell<Option<ActiveApplication>>,
+    is_running:     Cell<bool>,
+    control_flow:   Cell<ControlFlow>,
+    handler:        RefCell<Option<Box<dyn EventHandler<UserEvent>>>>,
+    pending_events: RefCell<VecDeque<PendingEvent>>,
+    user_events:    UserEvents,
+    windows:        RefCell<HashMap<XWindow, Weak<WindowState>>>,
+    #[cfg(feature = "tray")]
+    tray_icons:     RefCell<HashMap<XWindow, Weak<XEmbedIcon>>>,
+    focused_window: Cell<Option<XWindow>>,
+    icon:           RefCell<Option<Vec<u32>>>,
+    app_id:         Option<String>,
+}
+
+impl Debug for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState")
+            .field("screen_num", &self.screen_num)
+            .field("scale_factor", &self.scale_factor)
+            .field("is_running", &self.is_running)
+            .field("pending_events", &self.pending_events)
+            .finish()
+    }
+}
+
+impl AppState {
+    pub(super) fn new(
+        conn: RustConnection,
+        screen_num: usize,
+        atoms: Atoms,
+        keyboard: Keyboard,
+        user_events: UserEvents,
+        app_id: Option<String>,
+    ) -> Self {
+        // X11 has no per-monitor scaling, so the scale factor is derived from
+        // the `Xft.dpi` resource like most toolkits do.
+        let db = resource_manager::new_from_default(&conn)
+            .unwrap_or_else(|_| Database::new_from_data(&[]));
+        let scale_factor = db
+            .get_value::<f64>("Xft.dpi", "")
+            .ok()
+            .flatten()
+            .map(|dpi| dpi / DEFAULT_DPI)
+            .filter(|scale_factor| *scale_factor > 0.0)
+            .unwrap_or(1.0);
+        let cursors = Cursors::new(&conn, screen_num, &db);
+
+        let root = conn.setup().roots[screen_num].root;
+        let xim = Xim::new(&conn, root, atoms);
+        let selections = Selections::new(&conn, root);
+        let xinput_version = Self::query_xinput_version(&conn);
+        if xinput_version >= (2, 0) {
+            Self::select_raw_events(&conn, root);
+        }
+
+        Self {
+            conn,
+            screen_num,
+            atoms,
+            cursors,
+            keyboard: RefCell::new(keyboard),
+            xim: RefCell::new(xim),
+            dnd: RefCell::new(Dnd::default()),
+            context_menu: RefCell::new(None),
+            accelerators: RefCell::new(Accelerators::default()),
+            touch_devices: RefCell::new(TouchDevices::default()),
+            pinch_scale: Cell::new(1.0),
+            tablets: RefCell::new(Tablets::default()),
+            xinput_version,
+            selections: RefCell::new(selections),
+            scale_factor,
+            app: RefCell::new(None),
+            is_running: Cell::new(false),
+            control_flow: Cell::new(ControlFlow::default()),
+            handler: RefCell::new(None),
+            pending_events: RefCell::new(VecDeque::new()),
+            windows: RefCell::new(HashMap::new()),
+            #[cfg(feature = "tray")]
+            tray_icons: RefCell::new(HashMap::new()),
+            focused_window: Cell::new(None),
+            icon: RefCell::new(None),
+            user_events,
+            app_id,
+        }
+    }
+
+    /// Returns the supported XInput version.
+    fn query_xinput_version(conn: &RustConnection) -> (u16, u16) {
+        conn.xinput_xi_query_version(2, 4)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| (reply.major_version, reply.minor_version))
+            .unwrap_or_default()
+    }
+
+    /// Subscribes to raw pointer motion and buttons of all master devices.
+    ///
+    /// Raw events are reported to the root window even while the cursor is
+    /// grabbed or stays at the same position, which XInput 2 servers only.
+    /// They also carry axes of tablet tools.
+    fn select_raw_events(conn: &RustConnection, root: XWindow) {
+        let mask = XIEventMaskList {
+            deviceid: Device::ALL_MASTER.into(),
+            mask:     vec![
+                XIEventMask::RAW_MOTION
+                    | XIEventMask::RAW_BUTTON_PRESS
+                    | XIEventMask::RAW_BUTTON_RELEASE,
+            ],
+        };
+        let _ = conn.xinput_xi_select_events(root, &[mask]);
+    }
+
+    /// Subscribes to touch and trackpad gesture events of the window.
+    ///
+    /// Touches are reported since XInput 2.2, windows that don't select them
+    /// receive emulated pointer events instead. Gestures are reported since
+    /// XInput 2.4.
+    pub(super) fn select_touch_and_gestures(&self, window: XWindow) {
+        if self.xinput_version < (2, 2) {
+            return;
+        }
+
+        let touch_mask =
+            XIEventMask::TOUCH_BEGIN | XIEventMask::TOUCH_UPDATE | XIEventMask::TOUCH_END;
+        let mut mask = [u32::from(touch_mask), 0];
+        if self.xinput_version >= (2, 4) {
+            // Gesture bits have no named masks and don't fit the first word
+            for event in GESTURE_PINCH_BEGIN_EVENT..=GESTURE_SWIPE_END_EVENT {
+                mask[event as usize / 32] |= 1 << (event % 32);
+            }
+        }
+        let mask = XIEventMaskList {
+            deviceid: Device::ALL_MASTER.into(),
+            mask:     mask.into_iter().map(XIEventMask::from).collect(),
+        };
+        let _ = self.conn.xinput_xi_select_events(window, &[mask]);
+    }
+
+    #[inline]
+    pub(super) fn conn(&self) -> &RustConnection { &self.conn }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn screen_num(&self) -> usize { self.screen_num }
+
+    #[inline]
+    pub(super) fn screen(&self) -> &Screen { &self.conn.setup().roots[self.screen_num] }
+
+    #[inline]
+    pub(super) fn atoms(&self) -> &Atoms { &self.atoms }
+
+    #[inline]
+    pub(super) fn cursors(&self) -> &Cursors { &self.cursors }
+
+    #[inline]
+    pub(super) fn keyboard(&self) -> RefMut<'_, Keyboard> { self.keyboard.borrow_mut() }
+
+    #[inline]
+    pub(super) fn xim(&self) -> RefMut<'_, Xim> { self.xim.borrow_mut() }
+
+    #[inline]
+    pub(super) fn dnd(&self) -> RefMut<'_, Dnd> { self.dnd.borrow_mut() }
+
+    #[inline]
+    pub(super) fn context_menu(&self) -> RefMut<'_, Option<ContextMenu>> {
+        self.context_menu.borrow_mut()
+    }
+
+    #[inline]
+    pub(super) fn touch_devices(&self) -> RefMut<'_, TouchDevices> {
+        self.touch_devices.borrow_mut()
+    }
+
+    /// Replaces the scale of the active pinch gesture and returns the previous
+    /// one.
+    #[inline]
+    pub(super) fn replace_pinch_scale(&self, scale: f64) -> f64 { self.pinch_scale.replace(scale) }
+
+    #[inline]
+    pub(super) fn tablets(&self) -> RefMut<'_, Tablets> { self.tablets.borrow_mut() }
+
+    #[inline]
+    pub(super) fn selections(&self) -> RefMut<'_, Selections> { self.selections.borrow_mut() }
+
+    #[inline]
+    pub(super) fn scale_factor(&self) -> f64 { self.scale_factor }
+
+    #[inline]
+    pub(super) fn monitors(&self) -> Vec<MonitorImpl> {
+        query_monitors(&self.conn, self.screen(), self.scale_factor)
+    }
+
+    #[inline]
+    pub(super) fn set_active_application(&self, active_application: ActiveApplication) {
+        *self.app.borrow_mut() = Some(active_application);
+    }
+
+    #[inline]
+    pub(super) fn set_handler(&self, handler: impl EventHandler<UserEvent> + 'static) {
+        *self.handler.borrow_mut() = Some(Box::new(handler));
+    }
+
+    #[inline]
+    pub(super) fn is_running(&self) -> bool { self.is_running.get() }
+
+    #[inline]
+    pub(super) fn set_is_running(&self, value: bool) { self.is_running.set(value) }
+
+    #[inline]
+    pub(super) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.control_flow.set(control_flow);
+    }
+
+    #[inline]
+    pub(super) fn control_flow(&self) -> ControlFlow { self.control_flow.get() }
+
+    #[inline]
+    pub(super) fn register_window(&self, window: &Rc<WindowState>) {
+        self.windows
+            .borrow_mut()
+            .insert(window.native_id(), Rc::downgrade(window));
+    }
+
+    #[inline]
+    pub(super) fn unregister_window(&self, id: XWindow) { self.windows.borrow_mut().remove(&id); }
+
+    #[inline]
+    pub(super) fn window(&self, id: XWindow) -> Option<Rc<WindowState>> {
+        self.windows
+            .borrow()
+            .get(&id)
+            .and_then(|window| window.upgrade())
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn register_tray_icon(&self, tray_icon: &Rc<XEmbedIcon>) {
+        self.tray_icons
+            .borrow_mut()
+            .insert(tray_icon.window(), Rc::downgrade(tray_icon));
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn unregister_tray_icon(&self, id: XWindow) {
+        self.tray_icons.borrow_mut().remove(&id);
+    }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn tray_icon(&self, id: XWindow) -> Option<Rc<XEmbedIcon>> {
+        self.tray_icons
+            .borrow()
+            .get(&id)
+            .and_then(|tray_icon| tray_icon.upgrade())
+    }
+
+    #[inline]
+    pub(super) fn set_focused_window(&self, id: Option<XWindow>) { self.focused_window.set(id); }
+
+    /// Returns a window with the keyboard focus.
+    #[inline]
+    pub(super) fn focused_window(&self) -> Option<XWindow> { self.focused_window.get() }
+
+    /// Returns all live windows.
+    fn windows(&self) -> Vec<Rc<WindowState>> {
+        self.windows
+            .borrow()
+            .values()
+            .filter_map(|window| window.upgrade())
+            .collect()
+    }
+
+    #[inline]
+    pub(super) fn create_proxy(&self) -> EventLoopProxyImpl { self.user_events.create_proxy() }
+
+    #[cfg(feature = "tray")]
+    #[inline]
+    pub(super) fn menu_callbacks(&self) -> Rc<MenuCallbacks> { self.user_events.callbacks() }
+
+    #[inline]
+    pub(super) fn app_id(&self) -> Option<&str> { self.app_id.as_deref() }
+
+    #[inline]
+    pub(super) fn icon(&self) -> Option<Vec<u32>> { self.icon.borrow().clone() }
+
+    #[inline]
+    pub(super) fn queue_event(&self, event: impl Into<PendingEvent>) {
+        self.pending_events.borrow_mut().push_back(event.into());
+    }
+
+    pub(super) fn handle_event(&self, event: Event<UserEvent>) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            let mut handler = self.handler.borrow_mut();
+
+            if let Some(handler) = handler.as_mut() {
+                handler.on_event(app, event);
+            }
+        }
+    }
+
+    /// Calls a menu callback with the active application.
+    fn handle_callback(&self, callback: &MenuCallback) {
+        let mut app = self.app.borrow_mut();
+
+        if let Some(app) = app.as_mut() {
+            callback.call(app);
+        }
+    }
+
+    fn dispatch_pending_events(&self) {
+        // The handler may queue new events (e.g. by showing a window), so the
+        // queue is drained until it stays empty.
+        loop {
+            let events = mem::take(&mut *self.pending_events.borrow_mut());
+            if events.is_empty() {
+                break;
+            }
+            for event in events.into_iter() {
+                match event {
+                    PendingEvent::Event(event) => self.handle_event(event),
+                    PendingEvent::Callback(callback) => self.handle_callback(&callback),
+                }
+            }
+        }
+    }
+
+    /// Delivers a single redraw event to every window that requested one.
+    ///
+    /// Redraws requested by the handler while redrawing are delivered in the
+    /// next iteration.
+    fn dispatch_redraw_requests(&self) {
+        for window in self.windows().iter() {
+            if window.take_redraw_request() {
+                self.handle_event(Event::Window(
+                    WindowEvent::RedrawRequested,
+                    window.window_id(),
+                ));
+            }
+        }
+    }
+
+    /// Waits until an X event or a user event arrives.
+    ///
+    /// # Parameters:
+    /// * `timeout` - Maximum wait time, `None` means an unlimited wait.
+    fn wait_for_events(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        // Events may already be read from the socket
+        let event = self
+            .conn
+            .poll_for_event()
+            .map_err(|e| Error::with_source(ErrorKind::Connection, "X events not read.", e))?;
+        if let Some(event) = event {
+            handle_x_event(self, event);
+            return Ok(());
+        }
+        // Events queued while the loop was about to wait must not be delayed
+        let has_pending = !self.pending_events.borrow().is_empty()
+            || self.windows().iter().any(|window| window.needs_redraw());
+        let timeout = match has_pending {
+            true => Some(Duration::ZERO),
+            false => timeout,
+        };
+
+        let conn_fd = self.conn.stream().as_fd();
+        let user_fd = self.user_events.fd();
+        let mut fds = [
+            PollFd::new(&conn_fd, PollFlags::IN | PollFlags::ERR),
+            PollFd::new(&user_fd, PollFlags::IN),
+        ];
+        // Round up to not wake up before the deadline
+        let timeout = timeout
+            .map(|timeout| timeout.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32)
+            .unwrap_or(-1);
+        match poll(&mut fds, timeout) {
+            Ok(_) | Err(Errno::INTR) => Ok(()),
+            Err(e) => Err(Error::with_source(ErrorKind::Os, "X events not polled.", e)),
+        }
+    }
+
+    pub(super) fn run(&self) {
+        self.set_is_running(true);
+        self.handle_event(Event::LifeCycle(LifeCycle::Started));
+        self.handle_event(Event::NewEvents(StartCause::Init));
+
+        while self.is_running() {
+            self.dispatch_pending_events();
+            self.dispatch_redraw_requests();
+            self.dispatch_pending_events();
+            if !self.is_running() {
+                break;
+            }
+            self.handle_event(Event::AboutToWait);
+            if !self.is_running() {
+                break;
+            }
+
+            // Errors of requests without replies are delivered as events, so a
+            // failed flush or wait means the connection is gone.
+            let control_flow = self.control_flow();
+            let start = Instant::now();
+            if self.conn.flush().is_err() || self.wait_for_events(control_flow.timeout()).is_err() {
+                break;
+            }
+            self.handle_event(Event::NewEvents(control_flow.start_cause(start)));
+
+            while let Ok(Some(event)) = self.conn.poll_for_event() {
+                handle_x_event(self, event);
+            }
+            self.pending_events
+                .borrow_mut()
+                .extend(self.user_events.take());
+        }
+
+        self.handle_event(Event::LifeCycle(LifeCycle::Finished));
+        self.set_is_running(false);
+
+        // Break the reference cycle between the state and the active
+        // application, and release windows owned by the handler.
+        self.handler.borrow_mut().take();
+        self.app.borrow_mut().take();
+        let _ = self.conn.flush();
+    }
+
+    #[inline]
+    pub(super) fn set_menu(&self, menu: Option<&Menu>) {
+        // X11 has no global application menu, only its accelerators work.
+        *self.accelerators.borrow_mut() = Accelerators::new(menu);
+    }
+
+    /// Triggers the accelerator of the application menu matching the key
+    /// press.
+    ///
+    /// Returns `false` if no accelerator matches the key press.
+    pub(super) fn trigger_accelerator(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let choice = self.accelerators.borrow().choose(key, modifiers);
+        match choice {
+            Some(choice) => {
+                if let Some(event) = choice.into_pending() {
+                    self.queue_event(event);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(super) fn set_icon(&self, icon: Option<&Icon>) {
+        let icon = icon.map(|icon| {
+            let icon = icon.get_impl();
+            let mut data = vec![icon.width(), icon.height()];
+            data.extend(icon.argb());
+            data
+        });
+
+        for window in self.windows().iter() {
+            window.set_icon(icon.as_deref());
+        }
+
+        *self.icon.borrow_mut() = icon;
+        let _ = self.conn.flush();
+    }
+
+    #[inline]
+    pub(super) fn stop(&self) { self.set_is_running(false); }
+}
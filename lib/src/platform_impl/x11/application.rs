@@ -0,0 +1,155 @@
+use std::rc::Rc;
+
+use x11rb::rust_connection::RustConnection;
+
+use super::{app_state::AppState, atoms::Atoms, clipboard::SelectionImpl, keyboard::Keyboard};
+use crate::{
+    platform::{ActiveApplicationApi, ApplicationApi, Wrapper},
+    platform_impl::common::{ClipboardImpl, EventLoopProxyImpl, UserEvents},
+    ActiveApplication,
+    ApplicationOptions,
+    Clipboard,
+    ClipboardKind,
+    Context,
+    ContextOwner,
+    ControlFlow,
+    Error,
+    ErrorKind,
+    EventHandler,
+    Icon,
+    Menu,
+    Monitor,
+    UserEvent,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ContextImpl {
+    state: Rc<AppState>,
+}
+
+impl ContextImpl {
+    #[inline]
+    pub(super) fn state(&self) -> &Rc<AppState> { &self.state }
+}
+
+#[derive(Debug)]
+pub(crate) struct ActiveApplicationImpl(Context);
+
+impl ActiveApplicationImpl {
+    #[inline]
+    fn new(context: Context) -> Self { Self(context) }
+
+    #[inline]
+    fn state(&self) -> &Rc<AppState> { self.0.get_impl().state() }
+}
+
+impl ActiveApplicationApi for ActiveApplicationImpl {
+    #[inline]
+    fn set_menu(&mut self, menu: Option<&Menu>) { self.state().set_menu(menu); }
+
+    #[inline]
+    fn set_icon(&mut self, icon: Option<&Icon>) { self.state().set_icon(icon); }
+
+    #[inline]
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        self.state().set_control_flow(control_flow);
+    }
+
+    #[inline]
+    fn control_flow(&self) -> ControlFlow { self.state().control_flow() }
+
+    #[inline]
+    fn monitors(&self) -> Vec<Monitor> {
+        self.state()
+            .monitors()
+            .into_iter()
+            .map(Monitor::new)
+            .collect()
+    }
+
+    fn primary_monitor(&self) -> Option<Monitor> {
+        let mut monitors = self.state().monitors();
+        let index = monitors
+            .iter()
+            .position(|monitor| monitor.is_primary())
+            .unwrap_or(0);
+        (index < monitors.len()).then(|| Monitor::new(monitors.swap_remove(index)))
+    }
+
+    #[inline]
+    fn clipboard(&self, kind: ClipboardKind) -> Clipboard {
+        let selection = SelectionImpl::new(self.state().clone(), kind);
+        Clipboard::new(ClipboardImpl::new(selection))
+    }
+
+    #[inline]
+    fn stop(&mut self) { self.state().stop(); }
+}
+
+impl ContextOwner for ActiveApplicationImpl {
+    #[inline]
+    fn context(&self) -> &Context { &self.0 }
+}
+
+#[derive(Debug)]
+pub(crate) struct ApplicationImpl {
+    context: Context,
+}
+
+impl ApplicationApi for ApplicationImpl {
+    #[inline]
+    fn new(options: &ApplicationOptions) -> Result<Self, Error> {
+        // Connect to the display from the `DISPLAY` environment variable
+        let (conn, screen_num) = RustConnection::connect(None).map_err(|e| {
+            Error::with_source(ErrorKind::Connection, "X server connection failed.", e)
+        })?;
+
+        let atoms = Atoms::new(&conn)
+            .map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "X server connection failed.", e)
+            })?
+            .reply()
+            .map_err(|e| Error::with_source(ErrorKind::Connection, "X atoms not interned.", e))?;
+
+        let keyboard = Keyboard::new(&conn)?;
+        let user_events = UserEvents::new()?;
+
+        // Configure the application context
+        let state = Rc::new(AppState::new(
+            conn,
+            screen_num,
+            atoms,
+            keyboard,
+            user_events,
+            options.app_id.clone(),
+        ));
+        let context = Context::new(ContextImpl {
+            state: state.clone(),
+        });
+
+        // Configure the active application
+        let active_app = ActiveApplicationImpl::new(context.clone());
+        let active_app = ActiveApplication::new(active_app);
+        state.set_active_application(active_app);
+
+        Ok(Self {
+            context,
+        })
+    }
+
+    #[inline]
+    fn run(&mut self, handler: impl EventHandler<UserEvent> + 'static) {
+        let state = self.context.get_impl().state();
+        // Register an event handler
+        state.set_handler(handler);
+        state.run();
+    }
+
+    #[inline]
+    fn create_proxy(&self) -> EventLoopProxyImpl { self.context.get_impl().state().create_proxy() }
+}
+
+impl ContextOwner for ApplicationImpl {
+    #[inline]
+    fn context(&self) -> &Context { &self.context }
+}
@@ -0,0 +1,52 @@
+use x11rb::atom_manager;
+
+atom_manager! {
+    pub(super) Atoms: AtomsCookie {
+        WM_PROTOCOLS,
+        WM_DELETE_WINDOW,
+        WM_CHANGE_STATE,
+        WM_STATE,
+        UTF8_STRING,
+        CLIPBOARD,
+        TARGETS,
+        INCR,
+        XIM_SERVERS,
+        _MOTIF_WM_HINTS,
+        _NET_FRAME_EXTENTS,
+        _NET_WM_ICON,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_HIDDEN,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        _NET_WM_STATE_MODAL,
+        _NET_WM_WINDOW_TYPE,
+        _NET_WM_WINDOW_TYPE_POPUP_MENU,
+        _NET_SYSTEM_TRAY_OPCODE,
+        _XEMBED_INFO,
+        _XIM_MOREDATA,
+        _XIM_PROTOCOL,
+        _XIM_XCONNECT,
+        _B3_XIM_DATA,
+        _B3_SELECTION,
+        XdndAware,
+        XdndEnter,
+        XdndPosition,
+        XdndStatus,
+        XdndLeave,
+        XdndDrop,
+        XdndFinished,
+        XdndSelection,
+        XdndTypeList,
+        XdndActionCopy,
+        TEXT_URI_LIST: b"text/uri-list",
+        TEXT_PLAIN_UTF8: b"text/plain;charset=utf-8",
+        TEXT_PLAIN: b"text/plain",
+        ABS_MT_PRESSURE: b"Abs MT Pressure",
+        ABS_PRESSURE: b"Abs Pressure",
+        ABS_TILT_X: b"Abs Tilt X",
+        ABS_TILT_Y: b"Abs Tilt Y",
+    }
+}
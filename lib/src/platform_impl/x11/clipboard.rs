@@ -0,0 +1,411 @@
+//! Selection support, covering the clipboard and the primary selection.
+//!
+//! See: https://tronche.com/gui/x/icccm/sec-2.html
+
+use std::{
+    collections::HashMap,
+    os::fd::AsFd,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rustix::{
+    event::{poll, PollFd, PollFlags},
+    io::Errno,
+};
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        xproto::{
+            Atom,
+            AtomEnum,
+            ConnectionExt as _,
+            CreateWindowAux,
+            EventMask,
+            PropMode,
+            Property,
+            SelectionClearEvent,
+            SelectionNotifyEvent,
+            SelectionRequestEvent,
+            Window as XWindow,
+            WindowClass,
+            SELECTION_NOTIFY_EVENT,
+        },
+        Event as XEvent,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+    COPY_FROM_PARENT,
+    CURRENT_TIME,
+    NONE,
+};
+
+use super::{app_state::AppState, events::handle_x_event};
+use crate::{platform_impl::common::ClipboardContent, ClipboardKind, Error, ErrorKind};
+
+/// Maximum wait time for a selection owner to respond.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Selection owned by the application.
+#[derive(Debug)]
+struct OwnedSelection {
+    content: ClipboardContent,
+    /// Atoms of the content MIME types in the same order.
+    targets: Vec<Atom>,
+}
+
+/// Selections owned by the application.
+#[derive(Debug)]
+pub(super) struct Selections {
+    /// Hidden window that owns selections and receives converted ones.
+    window: XWindow,
+    owned:  HashMap<Atom, OwnedSelection>,
+}
+
+impl Selections {
+    pub(super) fn new(conn: &RustConnection, root: XWindow) -> Self {
+        let window = conn.generate_id().unwrap_or(NONE);
+        let _ = conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        );
+
+        Self {
+            window,
+            owned: HashMap::new(),
+        }
+    }
+}
+
+#[inline]
+fn selection_atom(state: &AppState, kind: ClipboardKind) -> Atom {
+    match kind {
+        ClipboardKind::Clipboard => state.atoms().CLIPBOARD,
+        ClipboardKind::Primary => AtomEnum::PRIMARY.into(),
+    }
+}
+
+#[inline]
+fn connection_error<E>(e: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Error::with_source(ErrorKind::Connection, "selection request failed.", e)
+}
+
+fn intern_atoms<'a>(
+    conn: &RustConnection,
+    names: impl Iterator<Item = &'a str>,
+) -> Result<Vec<Atom>, Error> {
+    // Requests are sent at once to not wait for each reply
+    let cookies = names
+        .map(|name| conn.intern_atom(false, name.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(connection_error)?;
+    cookies
+        .into_iter()
+        .map(|cookie| cookie.reply().map(|reply| reply.atom))
+        .collect::<Result<_, _>>()
+        .map_err(connection_error)
+}
+
+fn atom_names(conn: &RustConnection, atoms: &[Atom]) -> Result<Vec<String>, Error> {
+    let cookies = atoms
+        .iter()
+        .map(|atom| conn.get_atom_name(*atom))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(connection_error)?;
+    cookies
+        .into_iter()
+        .map(|cookie| {
+            cookie
+                .reply()
+                .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+        })
+        .collect::<Result<_, _>>()
+        .map_err(connection_error)
+}
+
+/// Waits for an event that satisfies the predicate.
+///
+/// Other events are handled as usual, so the application keeps serving its
+/// own selections while waiting.
+fn wait_for_event<F>(state: &AppState, mut predicate: F) -> Result<XEvent, Error>
+where
+    F: FnMut(&XEvent) -> bool,
+{
+    let conn = state.conn();
+    conn.flush().map_err(connection_error)?;
+
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match conn.poll_for_event().map_err(connection_error)? {
+            Some(event) if predicate(&event) => return Ok(event),
+            Some(event) => handle_x_event(state, event),
+            None => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if timeout.is_zero() {
+                    return Err(Error::new(
+                        ErrorKind::Os,
+                        "selection owner has not responded.",
+                    ));
+                }
+
+                let conn_fd = conn.stream().as_fd();
+                let mut fds = [PollFd::new(&conn_fd, PollFlags::IN | PollFlags::ERR)];
+                let timeout = timeout.as_millis().max(1) as i32;
+                match poll(&mut fds, timeout) {
+                    Ok(_) | Err(Errno::INTR) => (),
+                    Err(e) => {
+                        return Err(Error::with_source(ErrorKind::Os, "X events not polled.", e))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads and deletes a property holding converted selection data.
+fn read_property(state: &AppState, window: XWindow, property: Atom) -> Result<Vec<u8>, Error> {
+    let conn = state.conn();
+    let reply = conn
+        .get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX)
+        .map_err(connection_error)?
+        .reply()
+        .map_err(connection_error)?;
+    if reply.type_ != state.atoms().INCR {
+        return Ok(reply.value);
+    }
+
+    // Large data is transferred in chunks, the owner writes the next one once
+    // the previous one is deleted and finishes with an empty chunk.
+    let mut data = Vec::new();
+    loop {
+        wait_for_event(state, |event| {
+            matches!(event, XEvent::PropertyNotify(event)
+                if event.window == window
+                    && event.atom == property
+                    && event.state == Property::NEW_VALUE)
+        })?;
+        let chunk = conn
+            .get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX)
+            .map_err(connection_error)?
+            .reply()
+            .map_err(connection_error)?
+            .value;
+        if chunk.is_empty() {
+            return Ok(data);
+        }
+        data.extend(chunk);
+    }
+}
+
+/// Asks the selection owner to convert the selection to the target and
+/// returns the converted data.
+fn convert(state: &AppState, selection: Atom, target: Atom) -> Result<Vec<u8>, Error> {
+    let window = state.selections().window;
+    let property = state.atoms()._B3_SELECTION;
+    state
+        .conn()
+        .convert_selection(window, selection, target, property, CURRENT_TIME)
+        .map_err(connection_error)?;
+
+    let event = wait_for_event(state, |event| {
+        matches!(event, XEvent::SelectionNotify(event)
+            if event.requestor == window && event.selection == selection)
+    })?;
+    match event {
+        XEvent::SelectionNotify(event) if event.property != NONE => {
+            read_property(state, window, event.property)
+        }
+        _ => Err(Error::new(
+            ErrorKind::NotFound,
+            "clipboard has no data of the requested type.",
+        )),
+    }
+}
+
+/// Serves a request of another client for a selection owned by the
+/// application.
+///
+/// Data that doesn't fit into a single request is refused, since sending it
+/// in chunks isn't supported.
+pub(super) fn handle_selection_request(state: &AppState, event: &SelectionRequestEvent) {
+    let atoms = state.atoms();
+    let conn = state.conn();
+    // Obsolete clients don't specify the property
+    let property = match event.property {
+        NONE => event.target,
+        property => property,
+    };
+
+    let is_converted = {
+        let selections = state.selections();
+        match selections.owned.get(&event.selection) {
+            Some(owned) if event.target == atoms.TARGETS => {
+                let mut targets = vec![atoms.TARGETS];
+                targets.extend(owned.targets.iter().copied());
+                conn.change_property32(
+                    PropMode::REPLACE,
+                    event.requestor,
+                    property,
+                    AtomEnum::ATOM,
+                    &targets,
+                )
+                .is_ok()
+            }
+            Some(owned) => {
+                let max_length = conn.maximum_request_bytes().saturating_sub(32);
+                let data = owned
+                    .targets
+                    .iter()
+                    .position(|target| *target == event.target)
+                    .and_then(|index| owned.content.mime_types().nth(index))
+                    .and_then(|mime| owned.content.get(mime))
+                    .filter(|data| data.len() <= max_length);
+                data.is_some_and(|data| {
+                    conn.change_property8(
+                        PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        event.target,
+                        data,
+                    )
+                    .is_ok()
+                })
+            }
+            None => false,
+        }
+    };
+
+    let notify = SelectionNotifyEvent {
+        response_type: SELECTION_NOTIFY_EVENT,
+        sequence:      0,
+        time:          event.time,
+        requestor:     event.requestor,
+        selection:     event.selection,
+        target:        event.target,
+        property:      if is_converted { property } else { NONE },
+    };
+    let _ = conn.send_event(false, event.requestor, EventMask::NO_EVENT, notify);
+    let _ = conn.flush();
+}
+
+/// Drops a selection another client has taken over.
+pub(super) fn handle_selection_clear(state: &AppState, event: &SelectionClearEvent) {
+    let mut selections = state.selections();
+    if event.owner == selections.window {
+        selections.owned.remove(&event.selection);
+    }
+}
+
+/// Selection of the X server.
+#[derive(Debug, Clone)]
+pub(crate) struct SelectionImpl {
+    state: Rc<AppState>,
+    kind:  ClipboardKind,
+}
+
+impl SelectionImpl {
+    #[inline]
+    pub(super) fn new(state: Rc<AppState>, kind: ClipboardKind) -> Self {
+        Self {
+            state,
+            kind,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn kind(&self) -> ClipboardKind { self.kind }
+
+    pub(crate) fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+        let state = &self.state;
+        let conn = state.conn();
+        let selection = selection_atom(state, self.kind);
+        let targets = intern_atoms(conn, content.mime_types())?;
+
+        let window = state.selections().window;
+        conn.set_selection_owner(window, selection, CURRENT_TIME)
+            .map_err(connection_error)?;
+        let owner = conn
+            .get_selection_owner(selection)
+            .map_err(connection_error)?
+            .reply()
+            .map_err(connection_error)?
+            .owner;
+        if owner != window {
+            return Err(Error::new(
+                ErrorKind::Os,
+                "selection ownership not acquired.",
+            ));
+        }
+
+        state.selections().owned.insert(
+            selection,
+            OwnedSelection {
+                content,
+                targets,
+            },
+        );
+        Ok(())
+    }
+
+    pub(crate) fn mime_types(&self) -> Result<Vec<String>, Error> {
+        let state = &self.state;
+        let selection = selection_atom(state, self.kind);
+        if let Some(owned) = state.selections().owned.get(&selection) {
+            return Ok(owned.content.mime_types().map(String::from).collect());
+        }
+
+        let targets = match convert(state, selection, state.atoms().TARGETS) {
+            Ok(targets) => targets,
+            // An empty selection has no owner to convert it
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let targets: Vec<Atom> = targets
+            .chunks_exact(4)
+            .map(|atom| u32::from_ne_bytes([atom[0], atom[1], atom[2], atom[3]]))
+            .filter(|atom| *atom != NONE)
+            .collect();
+        atom_names(state.conn(), &targets)
+    }
+
+    pub(crate) fn receive(&self, mime: &str) -> Result<Vec<u8>, Error> {
+        let state = &self.state;
+        let selection = selection_atom(state, self.kind);
+        // Converting an own selection would wait for the reply forever
+        if let Some(owned) = state.selections().owned.get(&selection) {
+            return owned.content.get(mime).map(<[u8]>::to_vec).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "clipboard has no data of the requested type.",
+                )
+            });
+        }
+
+        let target = intern_atoms(state.conn(), [mime].into_iter())?[0];
+        convert(state, selection, target)
+    }
+
+    pub(crate) fn clear(&self) {
+        let state = &self.state;
+        let selection = selection_atom(state, self.kind);
+        if state.selections().owned.remove(&selection).is_some() {
+            let _ = state
+                .conn()
+                .set_selection_owner(NONE, selection, CURRENT_TIME);
+            let _ = state.conn().flush();
+        }
+    }
+}
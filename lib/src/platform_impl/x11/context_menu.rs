@@ -0,0 +1,943 @@
+//! Context menus.
+//!
+//! X11 has no menus of its own, so every open level of a context menu is an
+//! override-redirect window drawn with a core font. The first level grabs the
+//! pointer and the keyboard while the menu is open, so their events are
+//! reported to it with positions relative to the root window.
+
+use std::{mem, rc::Rc};
+
+use dpi::PhysicalPosition;
+use x11rb::{
+    connection::Connection as _,
+    protocol::xproto::{
+        Arc as XArc,
+        AtomEnum,
+        ButtonPressEvent,
+        ChangeGCAux,
+        Char2b,
+        ConnectionExt as _,
+        CoordMode,
+        CreateGCAux,
+        CreateWindowAux,
+        EventMask,
+        ExposeEvent,
+        Font,
+        Gcontext,
+        GrabMode,
+        GrabStatus,
+        KeyPressEvent,
+        MotionNotifyEvent,
+        Point,
+        PolyShape,
+        PropMode,
+        Rectangle,
+        Window as XWindow,
+        WindowClass,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+    COPY_FROM_PARENT,
+    CURRENT_TIME,
+    NONE,
+};
+
+use super::app_state::AppState;
+use crate::{
+    platform::{MenuItemApi, Wrapper},
+    platform_impl::common::{CheckEntry, MenuChoice},
+    Action,
+    CheckState,
+    Error,
+    ErrorKind,
+    Event,
+    Key,
+    Menu,
+    NamedKey,
+    WindowEvent,
+    WindowId,
+};
+
+/// Core font of menu items, the `fixed` alias is used if it's missing.
+const FONT_NAME: &[u8] = b"-misc-fixed-medium-r-normal--13-*-*-*-*-*-iso10646-1";
+const FALLBACK_FONT_NAME: &[u8] = b"fixed";
+/// Image text requests draw up to 255 characters.
+const MAX_TEXT_LEN: usize = 255;
+/// Buttons above it are scroll wheel steps and extra buttons.
+const LAST_MOUSE_BUTTON: u8 = 3;
+const BORDER_WIDTH: i32 = 1;
+
+// Sizes in logical pixels.
+const HORIZONTAL_PADDING: f64 = 12.0;
+const VERTICAL_PADDING: f64 = 4.0;
+const ARROW_SIZE: f64 = 4.0;
+const MARK_SIZE: f64 = 8.0;
+const MIN_WIDTH: f64 = 120.0;
+
+// Colors as `0xRRGGBB`.
+const BACKGROUND_COLOR: u32 = 0xf6f5f4;
+const TEXT_COLOR: u32 = 0x241f31;
+const DISABLED_TEXT_COLOR: u32 = 0x9a9996;
+const SELECTION_COLOR: u32 = 0x3584e4;
+const SELECTED_TEXT_COLOR: u32 = 0xffffff;
+const SEPARATOR_COLOR: u32 = 0xdeddda;
+const BORDER_COLOR: u32 = 0x9a9996;
+
+/// Snapshot of a menu item taken when the menu was shown.
+#[derive(Debug)]
+struct Entry {
+    text:              Vec<Char2b>,
+    /// Text width in pixels.
+    width:             i32,
+    accelerator:       Vec<Char2b>,
+    accelerator_width: i32,
+    enabled:           bool,
+    separator:         bool,
+    action:            Option<Action>,
+    check:             Option<CheckEntry>,
+    submenu:           Option<Rc<[Entry]>>,
+}
+
+impl Entry {
+    #[inline]
+    fn is_selectable(&self) -> bool { self.enabled && !self.separator }
+}
+
+/// Pixel values of the menu colors.
+#[derive(Debug)]
+struct Palette {
+    background:    u32,
+    text:          u32,
+    disabled_text: u32,
+    selection:     u32,
+    selected_text: u32,
+    separator:     u32,
+    border:        u32,
+    /// Pixels allocated in the default colormap.
+    allocated:     Vec<u32>,
+}
+
+impl Palette {
+    fn new(state: &AppState) -> Self {
+        let screen = state.screen();
+        let mut allocated = Vec::new();
+        let mut alloc = |color: u32, fallback: u32| {
+            // Channels are scaled from 8 to 16 bits
+            let channel = |shift: u32| ((color >> shift) & 0xff) as u16 * 0x101;
+            let pixel = state
+                .conn()
+                .alloc_color(screen.default_colormap, channel(16), channel(8), channel(0))
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| reply.pixel);
+            allocated.extend(pixel);
+            pixel.unwrap_or(fallback)
+        };
+
+        let (white, black) = (screen.white_pixel, screen.black_pixel);
+        Self {
+            background: alloc(BACKGROUND_COLOR, white),
+            text: alloc(TEXT_COLOR, black),
+            disabled_text: alloc(DISABLED_TEXT_COLOR, black),
+            selection: alloc(SELECTION_COLOR, black),
+            selected_text: alloc(SELECTED_TEXT_COLOR, white),
+            separator: alloc(SEPARATOR_COLOR, black),
+            border: alloc(BORDER_COLOR, black),
+            allocated,
+        }
+    }
+}
+
+/// Menu sizes in physical pixels.
+#[derive(Debug, Clone, Copy)]
+struct Metrics {
+    ascent:     i32,
+    row_height: i32,
+    padding_x:  i32,
+    padding_y:  i32,
+    arrow_size: i32,
+    mark_size:  i32,
+    min_width:  i32,
+}
+
+impl Metrics {
+    #[inline]
+    fn entry_height(&self, entry: &Entry) -> i32 {
+        if entry.separator {
+            self.padding_y * 2 + 1
+        } else {
+            self.row_height
+        }
+    }
+}
+
+/// Open menu or submenu.
+#[derive(Debug)]
+struct Level {
+    window:     XWindow,
+    entries:    Rc<[Entry]>,
+    /// Position of the top-left corner of the border on the root window.
+    x:          i32,
+    y:          i32,
+    /// Size of the content.
+    width:      i32,
+    height:     i32,
+    /// Width of the column with check marks, zero if no entry is checkable.
+    mark_width: i32,
+    selected:   Option<usize>,
+}
+
+impl Level {
+    #[inline]
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let border = BORDER_WIDTH * 2;
+        (self.x..self.x + self.width + border).contains(&x)
+            && (self.y..self.y + self.height + border).contains(&y)
+    }
+
+    /// Returns a position of the entry top relative to the content.
+    fn entry_top(&self, metrics: &Metrics, index: usize) -> i32 {
+        let heights = self.entries[..index]
+            .iter()
+            .map(|entry| metrics.entry_height(entry));
+        metrics.padding_y + heights.sum::<i32>()
+    }
+
+    /// Returns an index of the entry at the position on the root window.
+    fn entry_at(&self, metrics: &Metrics, y: i32) -> Option<usize> {
+        let mut top = self.y + BORDER_WIDTH + metrics.padding_y;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let bottom = top + metrics.entry_height(entry);
+            if (top..bottom).contains(&y) {
+                return Some(index);
+            }
+            top = bottom;
+        }
+        None
+    }
+
+    /// Chooses the entry, a checkable entry updates its check state and
+    /// states of its radio group.
+    fn choose(&self, index: usize) -> MenuChoice {
+        let entry = &self.entries[index];
+        let siblings = self.entries.iter().filter_map(|entry| entry.check.as_ref());
+        MenuChoice {
+            action: entry.action.clone(),
+            state:  entry.check.as_ref().map(|check| check.choose(siblings)),
+        }
+    }
+}
+
+/// Context menu open in one of the application windows.
+#[derive(Debug)]
+pub(super) struct ContextMenu {
+    owner:   XWindow,
+    font:    Font,
+    gc:      Gcontext,
+    palette: Palette,
+    metrics: Metrics,
+    levels:  Vec<Level>,
+    /// A button release chooses an item only if the menu is armed, otherwise
+    /// the release of the button that has opened the menu would choose one.
+    armed:   bool,
+}
+
+impl ContextMenu {
+    fn new(state: &AppState, owner: XWindow) -> Result<Self, Error> {
+        let conn = state.conn();
+        let id_err = |e| Error::with_source(ErrorKind::Os, "X resource ID not generated.", e);
+        let font = conn.generate_id().map_err(id_err)?;
+        let gc = conn.generate_id().map_err(id_err)?;
+
+        let is_opened = |name: &[u8]| {
+            conn.open_font(font, name)
+                .map(|cookie| cookie.check().is_ok())
+                .unwrap_or(false)
+        };
+        if !is_opened(FONT_NAME) && !is_opened(FALLBACK_FONT_NAME) {
+            return Err(Error::new(ErrorKind::NotSupported, "menu font not found."));
+        }
+        let (ascent, descent) = conn
+            .query_font(font)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| (reply.font_ascent as i32, reply.font_descent as i32))
+            .unwrap_or((11, 2));
+
+        let palette = Palette::new(state);
+        let _ = conn.create_gc(
+            gc,
+            state.screen().root,
+            &CreateGCAux::new().font(font).graphics_exposures(0),
+        );
+
+        let scale = |size: f64| (size * state.scale_factor()).round() as i32;
+        let padding_y = scale(VERTICAL_PADDING);
+        Ok(Self {
+            owner,
+            font,
+            gc,
+            palette,
+            metrics: Metrics {
+                ascent,
+                row_height: ascent + descent + padding_y * 2,
+                padding_x: scale(HORIZONTAL_PADDING),
+                padding_y,
+                arrow_size: scale(ARROW_SIZE),
+                mark_size: scale(MARK_SIZE),
+                min_width: scale(MIN_WIDTH),
+            },
+            levels: Vec::new(),
+            armed: false,
+        })
+    }
+
+    /// Takes a snapshot of menu items.
+    fn to_entries(&self, conn: &RustConnection, menu: &Menu) -> Rc<[Entry]> {
+        menu.items()
+            .iter()
+            .map(|item| {
+                let item = item.get_impl();
+                let to_text = |text: String| {
+                    let text = text
+                        .chars()
+                        .take(MAX_TEXT_LEN)
+                        .map(to_char2b)
+                        .collect::<Vec<_>>();
+                    let width = match text.is_empty() {
+                        true => 0,
+                        false => conn
+                            .query_text_extents(self.font, &text)
+                            .ok()
+                            .and_then(|cookie| cookie.reply().ok())
+                            .map(|reply| reply.overall_width)
+                            .unwrap_or(0),
+                    };
+                    (text, width)
+                };
+                let (text, width) = to_text(item.title());
+                let accelerator = item
+                    .accelerator()
+                    .map(|accelerator| accelerator.to_string());
+                let (accelerator, accelerator_width) = to_text(accelerator.unwrap_or_default());
+
+                Entry {
+                    text,
+                    width,
+                    accelerator,
+                    accelerator_width,
+                    enabled: item.enabled(),
+                    separator: item.is_separator(),
+                    action: item.action().cloned(),
+                    check: CheckEntry::new(item),
+                    submenu: item.submenu().map(|submenu| self.to_entries(conn, submenu)),
+                }
+            })
+            .collect()
+    }
+
+    /// Opens a new level of the menu.
+    ///
+    /// The level is shown to the left of `left` if it doesn't fit the screen
+    /// to the right of `x`.
+    fn open_level(
+        &mut self,
+        state: &AppState,
+        entries: Rc<[Entry]>,
+        x: i32,
+        y: i32,
+        left: i32,
+    ) -> Result<(), Error> {
+        let conn = state.conn();
+        let atoms = state.atoms();
+        let screen = state.screen();
+        let metrics = self.metrics;
+
+        let has_submenus = entries.iter().any(|entry| entry.submenu.is_some());
+        let arrow_width = match has_submenus {
+            true => metrics.padding_x + metrics.arrow_size,
+            false => 0,
+        };
+        let has_checks = entries.iter().any(|entry| entry.check.is_some());
+        let mark_width = match has_checks {
+            true => metrics.mark_size + metrics.padding_x / 2,
+            false => 0,
+        };
+        let text_width = entries.iter().map(|entry| entry.width).max().unwrap_or(0);
+        let accelerator_width = entries
+            .iter()
+            .map(|entry| entry.accelerator_width)
+            .max()
+            .filter(|width| *width > 0)
+            .map(|width| width + metrics.padding_x * 2)
+            .unwrap_or(0);
+        let width = text_width + metrics.padding_x * 2 + mark_width + accelerator_width;
+        let width = width + arrow_width;
+        let width = width.max(metrics.min_width);
+        let heights = entries.iter().map(|entry| metrics.entry_height(entry));
+        let height = metrics.padding_y * 2 + heights.sum::<i32>();
+
+        // Menus are kept on the screen
+        let (outer_width, outer_height) = (width + BORDER_WIDTH * 2, height + BORDER_WIDTH * 2);
+        let (screen_width, screen_height) = (
+            screen.width_in_pixels as i32,
+            screen.height_in_pixels as i32,
+        );
+        let x = match x + outer_width > screen_width {
+            true => (left - outer_width).max(0),
+            false => x,
+        };
+        let y = y.min(screen_height - outer_height).max(0);
+
+        let window = conn
+            .generate_id()
+            .map_err(|e| Error::with_source(ErrorKind::Os, "X window ID not generated.", e))?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            x as i16,
+            y as i16,
+            width.max(1) as u16,
+            height.max(1) as u16,
+            BORDER_WIDTH as u16,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixel(self.palette.background)
+                .border_pixel(self.palette.border)
+                .override_redirect(1)
+                .save_under(1)
+                .event_mask(EventMask::EXPOSURE),
+        )
+        .map_err(|e| Error::with_source(ErrorKind::Connection, "context menu not created.", e))?;
+        // Compositors style windows by their type
+        let _ = conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            atoms._NET_WM_WINDOW_TYPE,
+            AtomEnum::ATOM,
+            &[atoms._NET_WM_WINDOW_TYPE_POPUP_MENU],
+        );
+        let _ = conn.map_window(window);
+
+        self.levels.push(Level {
+            window,
+            entries,
+            x,
+            y,
+            width,
+            height,
+            mark_width,
+            selected: None,
+        });
+        Ok(())
+    }
+
+    /// Closes levels starting from the depth.
+    fn close_levels(&mut self, conn: &RustConnection, depth: usize) {
+        for level in self.levels.drain(depth.min(self.levels.len())..) {
+            let _ = conn.destroy_window(level.window);
+        }
+    }
+
+    /// Opens a submenu of the selected entry of the level.
+    fn open_submenu(&mut self, state: &AppState, depth: usize) {
+        let level = &self.levels[depth];
+        let submenu = level
+            .selected
+            .and_then(|index| Some((index, level.entries[index].submenu.clone()?)));
+        if let Some((index, entries)) = submenu {
+            let x = level.x + level.width + BORDER_WIDTH;
+            let y = level.y + level.entry_top(&self.metrics, index) - self.metrics.padding_y;
+            let left = level.x + BORDER_WIDTH;
+            let _ = self.open_level(state, entries, x, y, left);
+        }
+    }
+
+    fn grab(&self, conn: &RustConnection) -> Result<(), Error> {
+        let window = self.levels[0].window;
+        let status = conn
+            .grab_pointer(
+                false,
+                window,
+                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                NONE,
+                NONE,
+                CURRENT_TIME,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.status);
+        if status != Some(GrabStatus::SUCCESS) {
+            return Err(Error::new(ErrorKind::Os, "pointer not grabbed."));
+        }
+
+        // Keys only navigate, so the menu works without them
+        let _ = conn
+            .grab_keyboard(
+                false,
+                window,
+                CURRENT_TIME,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )
+            .map(|cookie| cookie.reply());
+        Ok(())
+    }
+
+    /// Returns a depth of the topmost level at the position on the root
+    /// window.
+    fn level_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.levels.iter().rposition(|level| level.contains(x, y))
+    }
+
+    /// Returns a depth of the level and an index of the entry at the position
+    /// on the root window.
+    fn entry_at(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let depth = self.level_at(x, y)?;
+        let index = self.levels[depth].entry_at(&self.metrics, y)?;
+        Some((depth, index))
+    }
+
+    fn select(&mut self, state: &AppState, depth: usize, selected: Option<usize>) {
+        let conn = state.conn();
+        self.close_levels(conn, depth + 1);
+        self.levels[depth].selected = selected;
+        self.draw(conn, depth);
+        self.open_submenu(state, depth);
+        let _ = conn.flush();
+    }
+
+    /// Selects the entry under the pointer.
+    fn hover(&mut self, state: &AppState, x: i32, y: i32) {
+        if let Some((depth, index)) = self.entry_at(x, y) {
+            let level = &self.levels[depth];
+            let selected = Some(index).filter(|index| level.entries[*index].is_selectable());
+            if level.selected != selected {
+                self.armed = true;
+                self.select(state, depth, selected);
+            }
+        }
+    }
+
+    /// Returns a depth of the deepest level with a selected entry.
+    ///
+    /// Submenus are opened as soon as their entries are selected, so the
+    /// keyboard navigates the level with the selection rather than the
+    /// deepest one.
+    fn active_depth(&self) -> usize {
+        self.levels
+            .iter()
+            .rposition(|level| level.selected.is_some())
+            .unwrap_or(0)
+    }
+
+    /// Moves the selection of the level by the step.
+    fn move_selection(&mut self, state: &AppState, depth: usize, step: isize) {
+        let level = &self.levels[depth];
+        let len = level.entries.len() as isize;
+        let start = match level.selected {
+            Some(index) => index as isize,
+            None if step > 0 => -1,
+            None => len,
+        };
+
+        let selected = (1..=len)
+            .map(|offset| (start + offset * step).rem_euclid(len.max(1)) as usize)
+            .find(|index| level.entries[*index].is_selectable());
+        if selected.is_some() {
+            self.select(state, depth, selected);
+        }
+    }
+
+    fn draw(&self, conn: &RustConnection, depth: usize) {
+        let level = &self.levels[depth];
+        let metrics = &self.metrics;
+        let palette = &self.palette;
+        let fill = |color: u32, rectangle: Rectangle| {
+            let _ = conn.change_gc(self.gc, &ChangeGCAux::new().foreground(color));
+            let _ = conn.poly_fill_rectangle(level.window, self.gc, &[rectangle]);
+        };
+
+        let has_submenus = level.entries.iter().any(|entry| entry.submenu.is_some());
+        let arrow_width = match has_submenus {
+            true => metrics.padding_x + metrics.arrow_size,
+            false => 0,
+        };
+
+        let mut top = metrics.padding_y;
+        for (index, entry) in level.entries.iter().enumerate() {
+            let height = metrics.entry_height(entry);
+            if entry.separator {
+                fill(
+                    palette.separator,
+                    Rectangle {
+                        x:      0,
+                        y:      (top + height / 2) as i16,
+                        width:  level.width as u16,
+                        height: 1,
+                    },
+                );
+                top += height;
+                continue;
+            }
+
+            let (background, foreground) = match (level.selected == Some(index), entry.enabled) {
+                (true, _) => (palette.selection, palette.selected_text),
+                (false, true) => (palette.background, palette.text),
+                (false, false) => (palette.background, palette.disabled_text),
+            };
+            fill(
+                background,
+                Rectangle {
+                    x:      0,
+                    y:      top as i16,
+                    width:  level.width as u16,
+                    height: height as u16,
+                },
+            );
+            let _ = conn.change_gc(
+                self.gc,
+                &ChangeGCAux::new()
+                    .foreground(foreground)
+                    .background(background),
+            );
+            if let Some(check) = &entry.check {
+                self.draw_mark(conn, level.window, check, top + height / 2);
+            }
+            let _ = conn.image_text16(
+                level.window,
+                self.gc,
+                (metrics.padding_x + level.mark_width) as i16,
+                (top + metrics.padding_y + metrics.ascent) as i16,
+                &entry.text,
+            );
+
+            if !entry.accelerator.is_empty() {
+                let x = level.width - metrics.padding_x - arrow_width - entry.accelerator_width;
+                let _ = conn.image_text16(
+                    level.window,
+                    self.gc,
+                    x as i16,
+                    (top + metrics.padding_y + metrics.ascent) as i16,
+                    &entry.accelerator,
+                );
+            }
+
+            if entry.submenu.is_some() {
+                let x = level.width - metrics.padding_x - metrics.arrow_size;
+                let y = top + height / 2;
+                let size = metrics.arrow_size;
+                let points = [(x, y - size), (x + size, y), (x, y + size)].map(|(x, y)| Point {
+                    x: x as i16,
+                    y: y as i16,
+                });
+                let _ = conn.fill_poly(
+                    level.window,
+                    self.gc,
+                    PolyShape::CONVEX,
+                    CoordMode::ORIGIN,
+                    &points,
+                );
+            }
+            top += height;
+        }
+    }
+
+    /// Draws a check mark of the entry with the foreground color.
+    ///
+    /// # Parameters:
+    /// * `conn` - X server connection.
+    /// * `window` - Level window.
+    /// * `check` - Check entry.
+    /// * `middle` - Vertical middle of the entry.
+    fn draw_mark(&self, conn: &RustConnection, window: XWindow, check: &CheckEntry, middle: i32) {
+        let (x, size) = (self.metrics.padding_x, self.metrics.mark_size);
+        let top = middle - size / 2;
+        match (check.state(), check.is_radio()) {
+            (CheckState::Unchecked, _) => (),
+            (CheckState::Checked, true) => {
+                let dot = size / 2;
+                let _ = conn.poly_fill_arc(
+                    window,
+                    self.gc,
+                    &[XArc {
+                        x:      (x + (size - dot) / 2) as i16,
+                        y:      (middle - dot / 2) as i16,
+                        width:  dot as u16,
+                        height: dot as u16,
+                        angle1: 0,
+                        angle2: 360 * 64,
+                    }],
+                );
+            }
+            (CheckState::Checked, false) => {
+                let points = [(0, size / 2), (size / 3, size), (size, 0)].map(|(dx, dy)| Point {
+                    x: (x + dx) as i16,
+                    y: (top + dy) as i16,
+                });
+                let width = (size / 4).max(1) as u32;
+                let _ = conn.change_gc(self.gc, &ChangeGCAux::new().line_width(width));
+                let _ = conn.poly_line(CoordMode::ORIGIN, window, self.gc, &points);
+                let _ = conn.change_gc(self.gc, &ChangeGCAux::new().line_width(0));
+            }
+            (CheckState::Mixed, _) => {
+                let thickness = (size / 4).max(1);
+                let _ = conn.poly_fill_rectangle(
+                    window,
+                    self.gc,
+                    &[Rectangle {
+                        x:      x as i16,
+                        y:      (middle - thickness / 2) as i16,
+                        width:  size as u16,
+                        height: thickness as u16,
+                    }],
+                );
+            }
+        }
+    }
+
+    fn close(mut self, state: &AppState) {
+        let conn = state.conn();
+        let _ = conn.ungrab_pointer(CURRENT_TIME);
+        let _ = conn.ungrab_keyboard(CURRENT_TIME);
+        self.close_levels(conn, 0);
+        let _ = conn.free_gc(self.gc);
+        let _ = conn.close_font(self.font);
+        if !self.palette.allocated.is_empty() {
+            let colormap = state.screen().default_colormap;
+            let _ = conn.free_colors(colormap, 0, &self.palette.allocated);
+        }
+        let _ = conn.flush();
+    }
+}
+
+/// Converts a character into a core font index.
+fn to_char2b(character: char) -> Char2b {
+    // Core fonts are indexed by UCS-2 code points
+    let code = u16::try_from(u32::from(character)).unwrap_or(u16::from(b'?'));
+    Char2b {
+        byte1: (code >> 8) as u8,
+        byte2: code as u8,
+    }
+}
+
+#[inline]
+fn queue_window_event(state: &AppState, window: XWindow, event: WindowEvent) {
+    state.queue_event(Event::Window(event, window as WindowId));
+}
+
+/// Closes the context menu and reports it to the owner window.
+///
+/// # Parameters:
+/// * `state` - Application state.
+/// * `choice` - Chosen item.
+fn close(state: &AppState, choice: MenuChoice) {
+    let context_menu = state.context_menu().take();
+    if let Some(context_menu) = context_menu {
+        let owner = context_menu.owner;
+        context_menu.close(state);
+
+        if let Some(event) = choice.into_pending() {
+            state.queue_event(event);
+        }
+        queue_window_event(state, owner, WindowEvent::ContextMenuClosed);
+    }
+}
+
+/// Shows a context menu, the open one is closed first.
+///
+/// # Parameters:
+/// * `state` - Application state.
+/// * `owner` - Window the menu belongs to.
+/// * `menu` - Menu.
+/// * `position` - Top-left corner of the menu relative to the window.
+pub(super) fn show(
+    state: &AppState,
+    owner: XWindow,
+    menu: &Menu,
+    position: PhysicalPosition<i32>,
+) -> Result<(), Error> {
+    close(state, MenuChoice::default());
+
+    let conn = state.conn();
+    let origin = conn
+        .translate_coordinates(
+            owner,
+            state.screen().root,
+            position.x.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            position.y.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+        )
+        .map_err(|e| Error::with_source(ErrorKind::Connection, "X server connection failed.", e))?
+        .reply()
+        .map_err(|e| Error::with_source(ErrorKind::NotFound, "window not found.", e))?;
+
+    let mut context_menu = ContextMenu::new(state, owner)?;
+    let entries = context_menu.to_entries(conn, menu);
+    let (x, y) = (origin.dst_x as i32, origin.dst_y as i32);
+    let result = context_menu
+        .open_level(state, entries, x, y, x)
+        .and_then(|_| context_menu.grab(conn));
+    if let Err(e) = result {
+        context_menu.close(state);
+        return Err(e);
+    }
+
+    let _ = conn.flush();
+    *state.context_menu() = Some(context_menu);
+    Ok(())
+}
+
+/// Closes the context menu of the destroyed window.
+pub(super) fn handle_destroy(state: &AppState, window: XWindow) {
+    let is_owner = state
+        .context_menu()
+        .as_ref()
+        .map(|context_menu| context_menu.owner == window)
+        .unwrap_or(false);
+    if is_owner {
+        close(state, MenuChoice::default());
+    }
+}
+
+/// Redraws a level of the context menu.
+///
+/// Returns `false` if the window isn't a level of the context menu.
+pub(super) fn handle_expose(state: &AppState, event: &ExposeEvent) -> bool {
+    let context_menu = state.context_menu();
+    let depth = context_menu.as_ref().and_then(|context_menu| {
+        context_menu
+            .levels
+            .iter()
+            .position(|level| level.window == event.window)
+    });
+    match (context_menu.as_ref(), depth) {
+        (Some(context_menu), Some(depth)) => {
+            if event.count == 0 {
+                context_menu.draw(state.conn(), depth);
+                let _ = state.conn().flush();
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Handles a button press or release while the context menu is open.
+///
+/// Returns `false` if no context menu is open.
+pub(super) fn handle_button(state: &AppState, event: &ButtonPressEvent, is_pressed: bool) -> bool {
+    let mut current = state.context_menu();
+    let context_menu = match current.as_mut() {
+        Some(context_menu) => context_menu,
+        None => return false,
+    };
+    if event.detail > LAST_MOUSE_BUTTON {
+        return true;
+    }
+
+    let (x, y) = (event.root_x as i32, event.root_y as i32);
+    let armed = mem::replace(&mut context_menu.armed, true);
+    let choice = match (is_pressed, context_menu.level_at(x, y)) {
+        // A click outside of the menu closes it
+        (true, None) => Some(MenuChoice::default()),
+        (true, Some(_)) => None,
+        (false, _) if !armed => None,
+        (false, _) => context_menu
+            .entry_at(x, y)
+            .map(|(depth, index)| (&context_menu.levels[depth], index))
+            .filter(|(level, index)| {
+                let entry = &level.entries[*index];
+                entry.is_selectable() && entry.submenu.is_none()
+            })
+            .map(|(level, index)| level.choose(index)),
+    };
+
+    drop(current);
+    if let Some(choice) = choice {
+        close(state, choice);
+    }
+    true
+}
+
+/// Selects the entry under the pointer while the context menu is open.
+///
+/// Returns `false` if no context menu is open.
+pub(super) fn handle_motion(state: &AppState, event: &MotionNotifyEvent) -> bool {
+    let mut context_menu = state.context_menu();
+    match context_menu.as_mut() {
+        Some(context_menu) => {
+            context_menu.hover(state, event.root_x as i32, event.root_y as i32);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Navigates the context menu with the keyboard while it's open.
+///
+/// Returns `false` if no context menu is open.
+pub(super) fn handle_key(state: &AppState, event: &KeyPressEvent, is_pressed: bool) -> bool {
+    let mut current = state.context_menu();
+    let context_menu = match current.as_mut() {
+        Some(context_menu) => context_menu,
+        None => return false,
+    };
+    if !is_pressed {
+        return true;
+    }
+
+    let conn = state.conn();
+    let depth = context_menu.active_depth();
+    let key = state.keyboard().key(event.detail, event.state);
+    let choice = match key {
+        Key::Named(NamedKey::Escape | NamedKey::ArrowLeft) if depth > 0 => {
+            context_menu.close_levels(conn, depth);
+            let _ = conn.flush();
+            None
+        }
+        Key::Named(NamedKey::Escape) => Some(MenuChoice::default()),
+        Key::Named(NamedKey::ArrowDown) => {
+            context_menu.move_selection(state, depth, 1);
+            None
+        }
+        Key::Named(NamedKey::ArrowUp) => {
+            context_menu.move_selection(state, depth, -1);
+            None
+        }
+        Key::Named(NamedKey::ArrowRight | NamedKey::Enter | NamedKey::Space) => {
+            let level = &context_menu.levels[depth];
+            let selected = level.selected;
+            let has_submenu = selected
+                .map(|index| level.entries[index].submenu.is_some())
+                .unwrap_or(false);
+
+            if has_submenu {
+                // The submenu is closed if it has been left with the keyboard
+                if context_menu.levels.len() == depth + 1 {
+                    context_menu.open_submenu(state, depth);
+                }
+                if context_menu.levels.len() > depth + 1 {
+                    context_menu.move_selection(state, depth + 1, 1);
+                }
+                None
+            } else {
+                let level = &context_menu.levels[depth];
+                selected
+                    .filter(|_| key != Key::Named(NamedKey::ArrowRight))
+                    .map(|index| level.choose(index))
+            }
+        }
+        _ => None,
+    };
+
+    drop(current);
+    if let Some(choice) = choice {
+        close(state, choice);
+    }
+    true
+}
@@ -0,0 +1,183 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    iter,
+};
+
+use x11rb::{
+    connection::Connection,
+    cursor::Handle,
+    protocol::{
+        render::{ConnectionExt as _, PictType, Pictformat},
+        xproto::{ConnectionExt as _, Cursor as XCursor, ImageFormat, Window as XWindow},
+    },
+    resource_manager::Database,
+    rust_connection::RustConnection,
+    NONE,
+};
+
+use crate::{CursorIcon, CustomCursor, Error, ErrorKind};
+
+/// Cursors shared by all windows.
+///
+/// Theme cursors are loaded on the first use and live as long as the
+/// connection.
+#[derive(Debug)]
+pub(super) struct Cursors {
+    root:        XWindow,
+    handle:      Option<Handle>,
+    argb_format: Option<Pictformat>,
+    icons:       RefCell<HashMap<CursorIcon, XCursor>>,
+    blank:       Cell<Option<XCursor>>,
+}
+
+impl Cursors {
+    pub(super) fn new(conn: &RustConnection, screen_num: usize, db: &Database) -> Self {
+        let handle = Handle::new(conn, screen_num, db)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+        // Premultiplied 32-bit ARGB, the only format the X server accepts for
+        // colored cursors.
+        let argb_format = conn
+            .render_query_pict_formats()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| {
+                reply
+                    .formats
+                    .into_iter()
+                    .find(|format| {
+                        let direct = format.direct;
+                        format.type_ == PictType::DIRECT
+                            && format.depth == 32
+                            && (direct.alpha_shift, direct.alpha_mask) == (24, 0xff)
+                            && (direct.red_shift, direct.red_mask) == (16, 0xff)
+                            && (direct.green_shift, direct.green_mask) == (8, 0xff)
+                            && (direct.blue_shift, direct.blue_mask) == (0, 0xff)
+                    })
+                    .map(|format| format.id)
+            });
+
+        Self {
+            root: conn.setup().roots[screen_num].root,
+            handle,
+            argb_format,
+            icons: RefCell::new(HashMap::new()),
+            blank: Cell::new(None),
+        }
+    }
+
+    /// Returns a cursor of the current theme.
+    ///
+    /// Falls back to the default cursor of the window (`NONE`) if the theme
+    /// has no such cursor.
+    pub(super) fn icon(&self, conn: &RustConnection, icon: CursorIcon) -> XCursor {
+        if let Some(cursor) = self.icons.borrow().get(&icon) {
+            return *cursor;
+        }
+
+        let cursor = self
+            .handle
+            .as_ref()
+            .and_then(|handle| {
+                iter::once(icon.name())
+                    .chain(icon.alt_names().iter().copied())
+                    .map(|name| handle.load_cursor(conn, name).unwrap_or(NONE))
+                    .find(|cursor| *cursor != NONE)
+            })
+            .unwrap_or(NONE);
+        self.icons.borrow_mut().insert(icon, cursor);
+        cursor
+    }
+
+    /// Returns an invisible cursor.
+    pub(super) fn blank(&self, conn: &RustConnection) -> XCursor {
+        if let Some(cursor) = self.blank.get() {
+            return cursor;
+        }
+
+        let cursor = self.create_blank(conn).unwrap_or(NONE);
+        self.blank.set(Some(cursor));
+        cursor
+    }
+
+    fn create_blank(&self, conn: &RustConnection) -> Result<XCursor, Error> {
+        let pixmap = conn
+            .generate_id()
+            .map_err(|e| Error::with_source(ErrorKind::Os, "X pixmap ID not generated.", e))?;
+        let cursor = conn
+            .generate_id()
+            .map_err(|e| Error::with_source(ErrorKind::Os, "X cursor ID not generated.", e))?;
+
+        // An empty 1-bit mask makes every pixel transparent
+        conn.create_pixmap(1, pixmap, self.root, 1, 1)
+            .map_err(|e| Error::with_source(ErrorKind::Connection, "X pixmap not created.", e))?;
+        conn.create_cursor(cursor, pixmap, pixmap, 0, 0, 0, 0, 0, 0, 0, 0)
+            .map_err(|e| Error::with_source(ErrorKind::Connection, "X cursor not created.", e))?;
+        let _ = conn.free_pixmap(pixmap);
+
+        Ok(cursor)
+    }
+
+    /// Creates a cursor from a custom image.
+    ///
+    /// The caller owns the cursor and has to free it.
+    pub(super) fn create_custom(
+        &self,
+        conn: &RustConnection,
+        cursor: &CustomCursor,
+    ) -> Result<XCursor, Error> {
+        let format = self.argb_format.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotSupported,
+                "X server does not support colored cursors.",
+            )
+        })?;
+        let id = |what| {
+            conn.generate_id()
+                .map_err(|e| Error::with_source(ErrorKind::Os, what, e))
+        };
+        let pixmap = id("X pixmap ID not generated.")?;
+        let gc = id("X graphics context ID not generated.")?;
+        let picture = id("X picture ID not generated.")?;
+        let xcursor = id("X cursor ID not generated.")?;
+
+        let (width, height) = (cursor.width(), cursor.height());
+        let (hotspot_x, hotspot_y) = cursor.hotspot();
+        let data: Vec<u8> = cursor
+            .premultiplied_argb()
+            .into_iter()
+            .flat_map(u32::to_ne_bytes)
+            .collect();
+
+        let map_err = |e| Error::with_source(ErrorKind::Connection, "X cursor not created.", e);
+        conn.create_pixmap(32, pixmap, self.root, width, height)
+            .map_err(map_err)?;
+        conn.create_gc(gc, pixmap, &Default::default())
+            .map_err(map_err)?;
+        conn.put_image(
+            ImageFormat::Z_PIXMAP,
+            pixmap,
+            gc,
+            width,
+            height,
+            0,
+            0,
+            0,
+            32,
+            &data,
+        )
+        .map_err(map_err)?;
+        conn.render_create_picture(picture, pixmap, format, &Default::default())
+            .map_err(map_err)?;
+        conn.render_create_cursor(xcursor, picture, hotspot_x, hotspot_y)
+            .map_err(map_err)?;
+
+        // The cursor keeps its own copy of the image
+        let _ = conn.render_free_picture(picture);
+        let _ = conn.free_gc(gc);
+        let _ = conn.free_pixmap(pixmap);
+
+        Ok(xcursor)
+    }
+}
@@ -0,0 +1,303 @@
+//! XDND protocol support.
+//!
+//! See: https://www.freedesktop.org/wiki/Specifications/XDND/
+
+use std::mem;
+
+use dpi::PhysicalPosition;
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{
+        Atom,
+        AtomEnum,
+        ClientMessageEvent,
+        ConnectionExt as _,
+        EventMask,
+        SelectionNotifyEvent,
+        Timestamp,
+        Window as XWindow,
+    },
+    CURRENT_TIME,
+    NONE,
+};
+
+use super::{app_state::AppState, atoms::Atoms};
+use crate::{
+    platform_impl::common::{DragData, DRAG_MIME_TYPES, MIME_TEXT, MIME_TEXT_UTF8, MIME_URI_LIST},
+    Event,
+    WindowEvent,
+    WindowId,
+};
+
+/// Supported version of the protocol.
+pub(super) const XDND_VERSION: u32 = 5;
+
+/// State of a drag over one of the application windows.
+#[derive(Debug, Default)]
+pub(super) struct Dnd {
+    source:    XWindow,
+    target:    XWindow,
+    version:   u32,
+    type_:     Option<Atom>,
+    position:  PhysicalPosition<f64>,
+    data:      Option<DragData>,
+    requested: bool,
+    entered:   bool,
+    dropped:   bool,
+}
+
+impl Dnd {
+    #[inline]
+    fn is_active(&self) -> bool { self.source != NONE }
+}
+
+/// Returns MIME types of dragged data with their atoms in the order of
+/// preference.
+fn supported_types(atoms: &Atoms) -> [(Atom, &'static str); 3] {
+    DRAG_MIME_TYPES.map(|mime| {
+        let atom = match mime {
+            MIME_URI_LIST => atoms.TEXT_URI_LIST,
+            MIME_TEXT_UTF8 => atoms.TEXT_PLAIN_UTF8,
+            _ => atoms.TEXT_PLAIN,
+        };
+        (atom, mime)
+    })
+}
+
+#[inline]
+fn queue_window_event(state: &AppState, window: XWindow, event: WindowEvent) {
+    state.queue_event(Event::Window(event, window as WindowId));
+}
+
+fn send_message(state: &AppState, window: XWindow, type_: Atom, data: [u32; 5]) {
+    let event = ClientMessageEvent::new(32, window, type_, data);
+    let _ = state
+        .conn()
+        .send_event(false, window, EventMask::NO_EVENT, event);
+    let _ = state.conn().flush();
+}
+
+/// Handles an XDND client message.
+///
+/// Returns `false` if the message doesn't belong to the protocol.
+pub(super) fn handle_client_message(state: &AppState, event: &ClientMessageEvent) -> bool {
+    let atoms = state.atoms();
+    let data = event.data.as_data32();
+    match event.type_ {
+        t if t == atoms.XdndEnter => handle_enter(state, event.window, data),
+        t if t == atoms.XdndPosition => handle_position(state, event.window, data),
+        t if t == atoms.XdndLeave => handle_leave(state, data[0]),
+        t if t == atoms.XdndDrop => handle_drop(state, data[0]),
+        _ => return false,
+    }
+    true
+}
+
+fn handle_enter(state: &AppState, target: XWindow, data: [u32; 5]) {
+    let atoms = state.atoms();
+    let source = data[0];
+    let version = data[1] >> 24;
+
+    // Sources offering more than three types list them in a property
+    let types = if data[1] & 1 != 0 {
+        state
+            .conn()
+            .get_property(
+                false,
+                source,
+                atoms.XdndTypeList,
+                AtomEnum::ATOM,
+                0,
+                u32::MAX,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|value| value.collect()))
+            .unwrap_or_default()
+    } else {
+        data[2..]
+            .iter()
+            .copied()
+            .filter(|atom| *atom != NONE)
+            .collect::<Vec<_>>()
+    };
+    let type_ = supported_types(atoms)
+        .into_iter()
+        .map(|(atom, _)| atom)
+        .find(|atom| types.contains(atom));
+
+    *state.dnd() = Dnd {
+        source,
+        target,
+        version,
+        type_,
+        ..Default::default()
+    };
+}
+
+fn handle_position(state: &AppState, target: XWindow, data: [u32; 5]) {
+    let atoms = state.atoms();
+    let (source, time) = (data[0], data[3]);
+    let (x, y) = ((data[2] >> 16) as i16, data[2] as i16);
+
+    let mut dnd = state.dnd();
+    if dnd.source != source || dnd.target != target {
+        return;
+    }
+
+    let position = state
+        .conn()
+        .translate_coordinates(state.screen().root, target, x, y)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|reply| PhysicalPosition::new(reply.dst_x as f64, reply.dst_y as f64))
+        .unwrap_or(dnd.position);
+    dnd.position = position;
+
+    // Accept the drop anywhere in the window and keep receiving positions
+    let action = match dnd.type_ {
+        Some(_) => atoms.XdndActionCopy,
+        None => NONE,
+    };
+    send_message(
+        state,
+        source,
+        atoms.XdndStatus,
+        [target, (dnd.type_.is_some() as u32) | 2, 0, 0, action],
+    );
+
+    // Data is fetched once, the first position carries the timestamp the
+    // selection has to be converted with.
+    if let (Some(type_), false) = (dnd.type_, dnd.requested) {
+        dnd.requested = true;
+        let time: Timestamp = if dnd.version >= 1 { time } else { CURRENT_TIME };
+        let _ = state.conn().convert_selection(
+            target,
+            atoms.XdndSelection,
+            type_,
+            atoms.XdndSelection,
+            time,
+        );
+        let _ = state.conn().flush();
+    }
+
+    if dnd.entered {
+        drop(dnd);
+        queue_window_event(
+            state,
+            target,
+            WindowEvent::DragMoved {
+                position,
+            },
+        );
+    }
+}
+
+fn handle_leave(state: &AppState, source: XWindow) {
+    if state.dnd().source != source {
+        return;
+    }
+
+    let dnd = mem::take(&mut *state.dnd());
+    if dnd.entered {
+        queue_window_event(state, dnd.target, WindowEvent::DragLeft);
+    }
+}
+
+fn handle_drop(state: &AppState, source: XWindow) {
+    let mut dnd = state.dnd();
+    if dnd.source != source {
+        return;
+    }
+
+    // The drop completes once the requested data arrives
+    if dnd.requested && dnd.data.is_none() {
+        dnd.dropped = true;
+        return;
+    }
+    drop(dnd);
+    finish(state);
+}
+
+/// Handles the converted selection holding dragged data.
+pub(super) fn handle_selection_notify(state: &AppState, event: &SelectionNotifyEvent) -> bool {
+    let atoms = state.atoms();
+    if event.selection != atoms.XdndSelection {
+        return false;
+    }
+
+    let mut dnd = state.dnd();
+    if !dnd.is_active() || event.requestor != dnd.target {
+        return true;
+    }
+
+    // Large transfers use the `INCR` mechanism, which isn't supported
+    let data = match event.property {
+        NONE => None,
+        property => state
+            .conn()
+            .get_property(true, event.requestor, property, AtomEnum::ANY, 0, u32::MAX)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.value),
+    };
+    let mime = supported_types(atoms)
+        .into_iter()
+        .find(|(atom, _)| Some(*atom) == dnd.type_)
+        .map(|(_, mime)| mime)
+        .unwrap_or(MIME_TEXT);
+    let data = data
+        .map(|data| DragData::from_mime(mime, &data))
+        .unwrap_or_default();
+
+    let (target, position, dropped) = (dnd.target, dnd.position, dnd.dropped);
+    dnd.data = Some(data.clone());
+    dnd.entered = true;
+    drop(dnd);
+
+    queue_window_event(
+        state,
+        target,
+        WindowEvent::DragEntered {
+            paths: data.paths,
+            text: data.text,
+            position,
+        },
+    );
+    if dropped {
+        finish(state);
+    }
+    true
+}
+
+/// Completes the drop and tells the source about it.
+fn finish(state: &AppState) {
+    let atoms = state.atoms();
+    let dnd = mem::take(&mut *state.dnd());
+
+    let accepted = dnd.data.is_some();
+    match dnd.data {
+        Some(data) => queue_window_event(
+            state,
+            dnd.target,
+            WindowEvent::DragDropped {
+                paths:    data.paths,
+                text:     data.text,
+                position: dnd.position,
+            },
+        ),
+        None if dnd.entered => queue_window_event(state, dnd.target, WindowEvent::DragLeft),
+        None => (),
+    }
+
+    let action = match accepted {
+        true => atoms.XdndActionCopy,
+        false => NONE,
+    };
+    send_message(
+        state,
+        dnd.source,
+        atoms.XdndFinished,
+        [dnd.target, accepted as u32, action, 0, 0],
+    );
+}
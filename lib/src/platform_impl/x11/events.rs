@@ -0,0 +1,278 @@
+use dpi::{PhysicalPosition, PhysicalSize};
+use x11rb::protocol::{
+    xinput::{Fp3232, RawMotionEvent},
+    xproto::{ButtonPressEvent, KeyPressEvent, Mapping, NotifyMode, Window as XWindow},
+    Event as XEvent,
+};
+
+#[cfg(feature = "tray")]
+use super::tray;
+use super::{
+    app_state::AppState,
+    clipboard,
+    context_menu,
+    dnd,
+    gestures::{handle_pinch, handle_swipe},
+    tablet,
+    touch::handle_touch,
+    xim::XimEvent,
+};
+use crate::{
+    CursorGrabMode,
+    Event,
+    GesturePhase,
+    KeyState,
+    KeyboardEvent,
+    MouseButton,
+    MouseButtonState,
+    MouseEvent,
+    ScrollingDelta,
+    ScrollingPhase,
+    TouchPhase,
+    WindowEvent,
+    WindowId,
+};
+
+const LEFT_MOUSE_BUTTON: u8 = 1;
+const MIDDLE_MOUSE_BUTTON: u8 = 2;
+const RIGHT_MOUSE_BUTTON: u8 = 3;
+const SCROLL_UP_BUTTON: u8 = 4;
+const SCROLL_DOWN_BUTTON: u8 = 5;
+const SCROLL_LEFT_BUTTON: u8 = 6;
+const SCROLL_RIGHT_BUTTON: u8 = 7;
+const BACK_MOUSE_BUTTON: u8 = 8;
+const FORWARD_MOUSE_BUTTON: u8 = 9;
+
+#[inline]
+fn queue_window_event(state: &AppState, window: XWindow, event: WindowEvent) {
+    state.queue_event(Event::Window(event, window as WindowId));
+}
+
+fn handle_button(state: &AppState, event: ButtonPressEvent, button_state: MouseButtonState) {
+    let delta = match event.detail {
+        SCROLL_UP_BUTTON => Some((0.0, 1.0)),
+        SCROLL_DOWN_BUTTON => Some((0.0, -1.0)),
+        SCROLL_LEFT_BUTTON => Some((1.0, 0.0)),
+        SCROLL_RIGHT_BUTTON => Some((-1.0, 0.0)),
+        _ => None,
+    };
+
+    let mouse_event = match delta {
+        // X11 reports every wheel step as a press/release pair of buttons.
+        Some(_) if button_state == MouseButtonState::Released => return,
+        Some((x, y)) => MouseEvent::Scroll {
+            delta: ScrollingDelta::Line(x, y),
+            phase: ScrollingPhase::Changed,
+        },
+        None => {
+            let button = match event.detail {
+                LEFT_MOUSE_BUTTON => MouseButton::Left,
+                MIDDLE_MOUSE_BUTTON => MouseButton::Middle,
+                RIGHT_MOUSE_BUTTON => MouseButton::Right,
+                BACK_MOUSE_BUTTON => MouseButton::Back,
+                FORWARD_MOUSE_BUTTON => MouseButton::Forward,
+                button_number => MouseButton::Other {
+                    id: button_number as u16,
+                },
+            };
+            MouseEvent::Input {
+                button,
+                state: button_state,
+            }
+        }
+    };
+
+    queue_window_event(state, event.event, WindowEvent::Mouse(mouse_event));
+}
+
+#[inline]
+pub(super) fn to_f64(value: &Fp3232) -> f64 {
+    value.integral as f64 + value.frac as f64 / (1u64 << 32) as f64
+}
+
+/// Extracts unaccelerated deltas of the X and Y axes.
+fn raw_motion_delta(event: &RawMotionEvent) -> (f64, f64) {
+    // Values are only sent for axes set in the mask
+    let mask = event.valuator_mask.first().copied().unwrap_or(0);
+    let mut values = event.axisvalues_raw.iter();
+    let mut axis = |bit: u32| match mask & (1 << bit) != 0 {
+        true => values.next().map(to_f64).unwrap_or(0.0),
+        false => 0.0,
+    };
+    (axis(0), axis(1))
+}
+
+fn handle_key(state: &AppState, event: KeyPressEvent, key_state: KeyState) {
+    let events = state.keyboard().handle_key(&event, key_state);
+    for keyboard_event in events.into_iter() {
+        // Accelerators of the application menu consume key presses
+        if let KeyboardEvent::Input {
+            key,
+            state: KeyState::Pressed,
+            modifiers,
+            ..
+        } = &keyboard_event
+        {
+            if state.trigger_accelerator(key, *modifiers) {
+                continue;
+            }
+        }
+        queue_window_event(state, event.event, WindowEvent::Keyboard(keyboard_event));
+    }
+}
+
+/// Passes a key event to the input method, or handles it directly if the
+/// window has no input context.
+fn handle_raw_key(state: &AppState, event: KeyPressEvent, key_state: KeyState) {
+    let is_forwarded = state.xim().forward_key(state.conn(), &event);
+    if !is_forwarded {
+        handle_key(state, event, key_state);
+    }
+}
+
+fn handle_xim_events(state: &AppState, events: Vec<XimEvent>) {
+    for event in events.into_iter() {
+        match event {
+            XimEvent::Ime(window, ime_event) => {
+                queue_window_event(state, window, WindowEvent::Ime(ime_event));
+            }
+            XimEvent::Key(event, key_state) => handle_key(state, event, key_state),
+        }
+    }
+}
+
+/// Translates an X event into b3 events.
+pub(super) fn handle_x_event(state: &AppState, event: XEvent) {
+    match event {
+        XEvent::Expose(event) if context_menu::handle_expose(state, &event) => (),
+        #[cfg(feature = "tray")]
+        XEvent::Expose(event) if tray::handle_expose(state, &event) => (),
+        XEvent::Expose(event) if event.count == 0 => {
+            if let Some(window) = state.window(event.window) {
+                window.request_redraw();
+            }
+        }
+        XEvent::ConfigureNotify(event) => {
+            if let Some(window) = state.window(event.window) {
+                let size = PhysicalSize::new(event.width as u32, event.height as u32);
+                window.emit_configure_events(size);
+            }
+        }
+        XEvent::SelectionNotify(event) if dnd::handle_selection_notify(state, &event) => (),
+        XEvent::SelectionRequest(event) => clipboard::handle_selection_request(state, &event),
+        XEvent::SelectionClear(event) => clipboard::handle_selection_clear(state, &event),
+        XEvent::PropertyNotify(event) => {
+            if let Some(window) = state.window(event.window) {
+                window.handle_property_change(event.atom);
+            }
+        }
+        XEvent::FocusIn(event) if event.mode == NotifyMode::NORMAL => {
+            state.set_focused_window(Some(event.event));
+            state.xim().set_focus(state.conn(), event.event, true);
+            queue_window_event(state, event.event, WindowEvent::Focused(true));
+        }
+        XEvent::FocusOut(event) if event.mode == NotifyMode::NORMAL => {
+            if state.focused_window() == Some(event.event) {
+                state.set_focused_window(None);
+            }
+            state.keyboard().reset_pressed_keys();
+            state.xim().set_focus(state.conn(), event.event, false);
+            queue_window_event(state, event.event, WindowEvent::Focused(false));
+        }
+        XEvent::ClientMessage(event) if event.window == state.xim().window() => {
+            let events = state.xim().handle_client_message(state.conn(), &event);
+            handle_xim_events(state, events);
+        }
+        XEvent::ClientMessage(event) if dnd::handle_client_message(state, &event) => (),
+        XEvent::ClientMessage(event) => {
+            let atoms = state.atoms();
+            if event.type_ == atoms.WM_PROTOCOLS
+                && event.data.as_data32()[0] == atoms.WM_DELETE_WINDOW
+            {
+                queue_window_event(state, event.window, WindowEvent::CloseRequested);
+            }
+        }
+        XEvent::DestroyNotify(event) if state.xim().is_server_window(event.window) => {
+            let events = state.xim().disconnect();
+            handle_xim_events(state, events);
+        }
+        XEvent::DestroyNotify(event) => {
+            context_menu::handle_destroy(state, event.window);
+            state.xim().set_allowed(state.conn(), event.window, false);
+            state.unregister_window(event.window);
+            queue_window_event(state, event.window, WindowEvent::Destroyed);
+        }
+        XEvent::KeyPress(event) if context_menu::handle_key(state, &event, true) => (),
+        XEvent::KeyRelease(event) if context_menu::handle_key(state, &event, false) => (),
+        XEvent::KeyPress(event) => handle_raw_key(state, event, KeyState::Pressed),
+        XEvent::KeyRelease(event) => handle_raw_key(state, event, KeyState::Released),
+        XEvent::MappingNotify(event) if event.request != Mapping::POINTER => {
+            let _ = state.keyboard().update_mapping(state.conn());
+        }
+        XEvent::ButtonPress(event) if context_menu::handle_button(state, &event, true) => (),
+        XEvent::ButtonRelease(event) if context_menu::handle_button(state, &event, false) => (),
+        #[cfg(feature = "tray")]
+        XEvent::ButtonPress(event) if tray::handle_button_press(state, &event) => (),
+        XEvent::ButtonPress(event) => handle_button(state, event, MouseButtonState::Pressed),
+        XEvent::ButtonRelease(event) => handle_button(state, event, MouseButtonState::Released),
+        XEvent::MotionNotify(event) if context_menu::handle_motion(state, &event) => (),
+        XEvent::MotionNotify(event) => {
+            // A locked cursor reports raw motion only
+            let window = state.window(event.event);
+            if let Some(window) = window.filter(|w| w.cursor_grab() == CursorGrabMode::Locked) {
+                window.center_cursor(Some((event.event_x, event.event_y)));
+                return;
+            }
+
+            let position = PhysicalPosition::new(event.event_x as f64, event.event_y as f64);
+            tablet::handle_motion(state, event.event, position);
+            queue_window_event(
+                state,
+                event.event,
+                WindowEvent::Mouse(MouseEvent::Moved {
+                    position,
+                }),
+            );
+        }
+        XEvent::XinputRawMotion(event) => {
+            tablet::handle_raw_motion(state, &event);
+
+            // Raw events come from the root window and belong to the focused one
+            if let Some(window) = state.focused_window() {
+                let delta = raw_motion_delta(&event);
+                if delta != (0.0, 0.0) {
+                    queue_window_event(
+                        state,
+                        window,
+                        WindowEvent::Mouse(MouseEvent::RawMotion {
+                            delta,
+                        }),
+                    );
+                }
+            }
+        }
+        XEvent::XinputTouchBegin(event) => handle_touch(state, event, TouchPhase::Started),
+        XEvent::XinputTouchUpdate(event) => handle_touch(state, event, TouchPhase::Moved),
+        XEvent::XinputTouchEnd(event) => handle_touch(state, event, TouchPhase::Ended),
+        XEvent::XinputGesturePinchBegin(event) => handle_pinch(state, event, GesturePhase::Started),
+        XEvent::XinputGesturePinchUpdate(event) => {
+            handle_pinch(state, event, GesturePhase::Changed)
+        }
+        XEvent::XinputGesturePinchEnd(event) => handle_pinch(state, event, GesturePhase::Ended),
+        XEvent::XinputGestureSwipeBegin(event) => handle_swipe(state, event, GesturePhase::Started),
+        XEvent::XinputGestureSwipeUpdate(event) => {
+            handle_swipe(state, event, GesturePhase::Changed)
+        }
+        XEvent::XinputGestureSwipeEnd(event) => handle_swipe(state, event, GesturePhase::Ended),
+        XEvent::EnterNotify(event) if event.mode == NotifyMode::NORMAL => {
+            queue_window_event(state, event.event, WindowEvent::Mouse(MouseEvent::Entered));
+        }
+        XEvent::XinputRawButtonPress(event) => tablet::handle_raw_button(state, &event, true),
+        XEvent::XinputRawButtonRelease(event) => tablet::handle_raw_button(state, &event, false),
+        XEvent::LeaveNotify(event) if event.mode == NotifyMode::NORMAL => {
+            tablet::handle_leave(state, event.event);
+            queue_window_event(state, event.event, WindowEvent::Mouse(MouseEvent::Exited));
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,97 @@
+//! XInput 2.4 trackpad gesture support.
+
+use x11rb::protocol::xinput::{
+    Fp1616,
+    GesturePinchBeginEvent,
+    GesturePinchEventFlags,
+    GestureSwipeBeginEvent,
+    GestureSwipeEventFlags,
+};
+
+use super::app_state::AppState;
+use crate::{Event, GestureEvent, GesturePhase, WindowEvent, WindowId};
+
+#[inline]
+fn to_f64(value: Fp1616) -> f64 { value as f64 / 65536.0 }
+
+#[inline]
+fn queue_gesture(state: &AppState, window: u32, event: GestureEvent) {
+    state.queue_event(Event::Window(
+        WindowEvent::Gesture(event),
+        window as WindowId,
+    ));
+}
+
+/// Handles a pinch, which drives both pinch and rotation gestures.
+pub(super) fn handle_pinch(state: &AppState, event: GesturePinchBeginEvent, phase: GesturePhase) {
+    let phase = match phase {
+        GesturePhase::Ended
+            if event
+                .flags
+                .contains(GesturePinchEventFlags::GESTURE_PINCH_CANCELLED) =>
+        {
+            GesturePhase::Cancelled
+        }
+        phase => phase,
+    };
+    let (scale_delta, angle_delta) = match phase {
+        GesturePhase::Started => {
+            state.replace_pinch_scale(1.0);
+            (0.0, 0.0)
+        }
+        GesturePhase::Changed => {
+            // Scale is relative to the beginning of the gesture
+            let scale = to_f64(event.scale);
+            (
+                scale - state.replace_pinch_scale(scale),
+                to_f64(event.delta_angle),
+            )
+        }
+        _ => (0.0, 0.0),
+    };
+
+    queue_gesture(
+        state,
+        event.event,
+        GestureEvent::Pinch {
+            delta: scale_delta,
+            phase,
+        },
+    );
+    // Angles are clockwise
+    queue_gesture(
+        state,
+        event.event,
+        GestureEvent::Rotate {
+            delta: -angle_delta,
+            phase,
+        },
+    );
+}
+
+pub(super) fn handle_swipe(state: &AppState, event: GestureSwipeBeginEvent, phase: GesturePhase) {
+    let phase = match phase {
+        GesturePhase::Ended
+            if event
+                .flags
+                .contains(GestureSwipeEventFlags::GESTURE_SWIPE_CANCELLED) =>
+        {
+            GesturePhase::Cancelled
+        }
+        phase => phase,
+    };
+    let delta = match phase {
+        GesturePhase::Changed => (to_f64(event.delta_x), to_f64(event.delta_y)),
+        _ => (0.0, 0.0),
+    };
+
+    queue_gesture(
+        state,
+        event.event,
+        GestureEvent::Swipe {
+            fingers: event.detail,
+            delta,
+            phase,
+        },
+    );
+}
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xkb::{self, BoolCtrl, ConnectionExt as _, PerClientFlag},
+        xproto::{ConnectionExt as _, KeyButMask, KeyPressEvent},
+    },
+    rust_connection::RustConnection,
+};
+use xkeysym::{key, Keysym};
+
+use crate::{
+    platform_impl::common::{keysym_to_key, keysym_to_text},
+    Error,
+    ErrorKind,
+    Key,
+    KeyState,
+    KeyboardEvent,
+    Modifiers,
+    NamedKey,
+};
+
+/// Offset between X keycodes and evdev scan codes.
+const EVDEV_OFFSET: u8 = 8;
+
+/// Keyboard state of the connection.
+///
+/// Keys are translated with the core keyboard mapping, see: https://www.x.org/releases/current/doc/xproto/x11protocol.html#Keyboards
+#[derive(Debug)]
+pub(super) struct Keyboard {
+    min_keycode:         u8,
+    keysyms_per_keycode: u8,
+    keysyms:             Vec<u32>,
+    pressed_keys:        HashSet<u8>,
+    modifiers:           Modifiers,
+}
+
+impl Keyboard {
+    pub(super) fn new(conn: &RustConnection) -> Result<Self, Error> {
+        // Without detectable auto-repeat a held key generates pairs of
+        // press/release events, so repeats can't be told apart from presses.
+        // The request is optional: old servers keep the default behavior.
+        if let Ok(cookie) = conn.xkb_use_extension(1, 0) {
+            if cookie.reply().map(|reply| reply.supported).unwrap_or(false) {
+                let _ = conn
+                    .xkb_per_client_flags(
+                        xkb::ID::USE_CORE_KBD.into(),
+                        PerClientFlag::DETECTABLE_AUTO_REPEAT,
+                        PerClientFlag::DETECTABLE_AUTO_REPEAT,
+                        BoolCtrl::default(),
+                        BoolCtrl::default(),
+                        BoolCtrl::default(),
+                    )
+                    .map(|cookie| cookie.reply());
+            }
+        }
+
+        let mut keyboard = Self {
+            min_keycode:         conn.setup().min_keycode,
+            keysyms_per_keycode: 0,
+            keysyms:             Vec::new(),
+            pressed_keys:        HashSet::new(),
+            modifiers:           Modifiers::empty(),
+        };
+        keyboard.update_mapping(conn)?;
+        Ok(keyboard)
+    }
+
+    /// Reloads the keyboard mapping.
+    ///
+    /// It must be called when the server reports a mapping change.
+    pub(super) fn update_mapping(&mut self, conn: &RustConnection) -> Result<(), Error> {
+        let setup = conn.setup();
+        let count = setup.max_keycode - setup.min_keycode + 1;
+        let reply = conn
+            .get_keyboard_mapping(setup.min_keycode, count)
+            .map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "X server connection failed.", e)
+            })?
+            .reply()
+            .map_err(|e| {
+                Error::with_source(ErrorKind::Connection, "X keyboard mapping not loaded.", e)
+            })?;
+
+        self.min_keycode = setup.min_keycode;
+        self.keysyms_per_keycode = reply.keysyms_per_keycode;
+        self.keysyms = reply.keysyms;
+        Ok(())
+    }
+
+    /// Translates a key press or release into keyboard events.
+    ///
+    /// # Parameters:
+    /// * `event` - X key event.
+    /// * `state` - Key state.
+    pub(super) fn handle_key(
+        &mut self,
+        event: &KeyPressEvent,
+        state: KeyState,
+    ) -> Vec<KeyboardEvent> {
+        let keysym = self.keysym(event.detail, event.state);
+        let key = keysym_to_key(keysym);
+
+        let repeat = match state {
+            KeyState::Pressed => !self.pressed_keys.insert(event.detail),
+            KeyState::Released => {
+                self.pressed_keys.remove(&event.detail);
+                false
+            }
+        };
+
+        // The event state holds modifiers as they were before the event, so
+        // modifier keys are applied manually.
+        let mut modifiers = to_modifiers(event.state);
+        let is_pressed = state == KeyState::Pressed;
+        match key {
+            Key::Named(NamedKey::Shift) => modifiers.set(Modifiers::SHIFT, is_pressed),
+            Key::Named(NamedKey::Control) => modifiers.set(Modifiers::CONTROL, is_pressed),
+            Key::Named(NamedKey::Alt) => modifiers.set(Modifiers::ALT, is_pressed),
+            Key::Named(NamedKey::Super) => modifiers.set(Modifiers::SUPER, is_pressed),
+            _ => (),
+        }
+
+        let text = match state {
+            KeyState::Pressed if !modifiers.contains(Modifiers::CONTROL) => keysym_to_text(keysym),
+            _ => None,
+        };
+
+        let mut events = vec![KeyboardEvent::Input {
+            scan_code: event.detail.saturating_sub(EVDEV_OFFSET) as u32,
+            key,
+            text,
+            state,
+            repeat,
+            modifiers,
+        }];
+        if modifiers != self.modifiers {
+            self.modifiers = modifiers;
+            events.push(KeyboardEvent::ModifiersChanged(modifiers));
+        }
+        events
+    }
+
+    /// Returns a key of the keycode without changing the keyboard state.
+    ///
+    /// # Parameters:
+    /// * `keycode` - X keycode.
+    /// * `state` - Modifier state.
+    #[inline]
+    pub(super) fn key(&self, keycode: u8, state: KeyButMask) -> Key {
+        keysym_to_key(self.keysym(keycode, state))
+    }
+
+    /// Resets pressed keys.
+    ///
+    /// Keys released while the window is unfocused aren't reported.
+    #[inline]
+    pub(super) fn reset_pressed_keys(&mut self) { self.pressed_keys.clear(); }
+
+    /// Returns a keysym of the keycode according to the modifier state.
+    fn keysym(&self, keycode: u8, state: KeyButMask) -> Keysym {
+        let per_keycode = self.keysyms_per_keycode as usize;
+        let start = (keycode.saturating_sub(self.min_keycode)) as usize * per_keycode;
+        let keysyms = match self.keysyms.get(start..start + per_keycode) {
+            Some(keysyms) => keysyms,
+            None => return Keysym::NoSymbol,
+        };
+        let column = |index: usize| {
+            keysyms
+                .get(index)
+                .copied()
+                .filter(|keysym| *keysym != key::NoSymbol)
+        };
+
+        // Columns 4 and 5 hold the third shift level (AltGr).
+        let offset = if state.contains(KeyButMask::MOD5) && column(4).is_some() {
+            4
+        } else {
+            0
+        };
+        let lower = match column(offset) {
+            Some(keysym) => Keysym::new(keysym),
+            None => return Keysym::NoSymbol,
+        };
+        let upper = column(offset + 1).map(Keysym::new).unwrap_or(lower);
+
+        let is_shifted = state.contains(KeyButMask::SHIFT);
+        let is_locked = state.contains(KeyButMask::LOCK);
+        if upper.is_keypad_key() && state.contains(KeyButMask::MOD2) {
+            // Num Lock inverts the meaning of Shift for the keypad
+            if is_shifted {
+                lower
+            } else {
+                upper
+            }
+        } else {
+            // Caps Lock only affects letters
+            let is_letter = lower
+                .key_char()
+                .map(|character| character.is_alphabetic())
+                .unwrap_or(false);
+            if is_shifted != (is_locked && is_letter) {
+                upper
+            } else {
+                lower
+            }
+        }
+    }
+}
+
+/// Converts an X modifier mask into modifiers.
+fn to_modifiers(state: KeyButMask) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, state.contains(KeyButMask::SHIFT));
+    modifiers.set(Modifiers::CONTROL, state.contains(KeyButMask::CONTROL));
+    modifiers.set(Modifiers::ALT, state.contains(KeyButMask::MOD1));
+    modifiers.set(Modifiers::SUPER, state.contains(KeyButMask::MOD4));
+    modifiers.set(Modifiers::CAPS_LOCK, state.contains(KeyButMask::LOCK));
+    modifiers
+}
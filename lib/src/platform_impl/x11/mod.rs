@@ -0,0 +1,37 @@
+mod app_state;
+mod application;
+mod atoms;
+mod clipboard;
+mod context_menu;
+mod cursor;
+mod dnd;
+mod events;
+mod gestures;
+mod keyboard;
+mod monitor;
+mod tablet;
+mod touch;
+#[cfg(feature = "tray")]
+mod tray;
+mod valuator;
+mod window;
+mod window_utils;
+mod xim;
+mod xim_protocol;
+
+pub(crate) use application::*;
+pub(crate) use clipboard::SelectionImpl;
+pub(crate) use monitor::*;
+#[cfg(feature = "tray")]
+pub(crate) use tray::TrayImpl;
+pub(crate) use window::*;
+
+pub(crate) use super::common::{
+    ClipboardImpl,
+    EventLoopProxyImpl,
+    IconImpl,
+    MenuImpl,
+    MenuItemImpl,
+};
+#[cfg(feature = "notifications")]
+pub(crate) use super::freedesktop::NotificationImpl;
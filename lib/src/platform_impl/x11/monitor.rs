@@ -0,0 +1,292 @@
+use dpi::{PhysicalPosition, PhysicalSize};
+use x11rb::{
+    errors::ReplyError,
+    protocol::{
+        randr::{self, ConnectionExt as _, ModeFlag, ModeInfo, SetConfig},
+        xproto::{ConnectionExt as _, Screen, Window as XWindow},
+    },
+    rust_connection::RustConnection,
+    NONE,
+};
+
+use crate::{platform::MonitorApi, Error, ErrorKind, Monitor, VideoMode};
+
+/// Returns a refresh rate of the mode in millihertz.
+fn to_refresh_rate(mode: &ModeInfo) -> Option<u32> {
+    let mut vtotal = mode.vtotal as u64;
+    if mode.mode_flags.contains(ModeFlag::DOUBLE_SCAN) {
+        vtotal *= 2;
+    }
+    if mode.mode_flags.contains(ModeFlag::INTERLACE) {
+        vtotal /= 2;
+    }
+    let total = mode.htotal as u64 * vtotal;
+    (total != 0).then(|| ((mode.dot_clock as u64 * 1000 + total / 2) / total) as u32)
+}
+
+/// RandR mode of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct X11VideoMode {
+    id: randr::Mode,
+    size: PhysicalSize<u32>,
+    refresh_rate_millihertz: u32,
+}
+
+impl X11VideoMode {
+    fn new(mode: &ModeInfo) -> Option<Self> {
+        Some(Self {
+            id: mode.id,
+            size: PhysicalSize::new(mode.width as u32, mode.height as u32),
+            refresh_rate_millihertz: to_refresh_rate(mode)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MonitorImpl {
+    output:                  randr::Output,
+    crtc:                    randr::Crtc,
+    name:                    Option<String>,
+    position:                PhysicalPosition<i32>,
+    size:                    PhysicalSize<u32>,
+    scale_factor:            f64,
+    bit_depth:               u16,
+    refresh_rate_millihertz: Option<u32>,
+    modes:                   Vec<X11VideoMode>,
+    is_primary:              bool,
+}
+
+impl MonitorImpl {
+    /// Returns the whole screen as a monitor.
+    ///
+    /// Used if the X server doesn't support RandR 1.5.
+    fn from_screen(screen: &Screen, scale_factor: f64) -> Self {
+        Self {
+            output: NONE,
+            crtc: NONE,
+            name: None,
+            position: PhysicalPosition::default(),
+            size: PhysicalSize::new(
+                screen.width_in_pixels as u32,
+                screen.height_in_pixels as u32,
+            ),
+            scale_factor,
+            bit_depth: screen.root_depth as u16,
+            refresh_rate_millihertz: None,
+            modes: Vec::new(),
+            is_primary: true,
+        }
+    }
+
+    /// Checks if the monitor contains the point.
+    #[inline]
+    pub(super) fn contains(&self, point: PhysicalPosition<i32>) -> bool {
+        let (x, y) = (point.x - self.position.x, point.y - self.position.y);
+        (0..self.size.width as i32).contains(&x) && (0..self.size.height as i32).contains(&y)
+    }
+
+    #[inline]
+    pub(super) fn is_primary(&self) -> bool { self.is_primary }
+
+    /// Returns the CRTC driving the monitor.
+    #[inline]
+    pub(super) fn crtc(&self) -> randr::Crtc { self.crtc }
+
+    /// Returns the RandR mode matching the video mode.
+    pub(super) fn mode_id(&self, mode: &VideoMode) -> Option<randr::Mode> {
+        self.modes
+            .iter()
+            .find(|known| {
+                (known.size, known.refresh_rate_millihertz)
+                    == (mode.size(), mode.refresh_rate_millihertz())
+            })
+            .map(|known| known.id)
+    }
+}
+
+impl PartialEq for MonitorImpl {
+    fn eq(&self, other: &Self) -> bool {
+        (self.output, self.position) == (other.output, other.position)
+    }
+}
+
+impl Eq for MonitorImpl {}
+
+impl MonitorApi for MonitorImpl {
+    #[inline]
+    fn name(&self) -> Option<String> { self.name.clone() }
+
+    #[inline]
+    fn position(&self) -> PhysicalPosition<i32> { self.position }
+
+    #[inline]
+    fn size(&self) -> PhysicalSize<u32> { self.size }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { self.scale_factor }
+
+    #[inline]
+    fn refresh_rate_millihertz(&self) -> Option<u32> { self.refresh_rate_millihertz }
+
+    fn video_modes(&self) -> Vec<VideoMode> {
+        self.modes
+            .iter()
+            .map(|mode| {
+                VideoMode::new(
+                    mode.size,
+                    self.bit_depth,
+                    mode.refresh_rate_millihertz,
+                    Monitor::new(self.clone()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns active monitors of the screen.
+///
+/// # Parameters:
+/// * `conn` - X server connection.
+/// * `screen` - X screen.
+/// * `scale_factor` - Scale factor of the screen.
+pub(super) fn query_monitors(
+    conn: &RustConnection,
+    screen: &Screen,
+    scale_factor: f64,
+) -> Vec<MonitorImpl> {
+    match query_randr_monitors(conn, screen, scale_factor) {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        _ => vec![MonitorImpl::from_screen(screen, scale_factor)],
+    }
+}
+
+fn query_randr_monitors(
+    conn: &RustConnection,
+    screen: &Screen,
+    scale_factor: f64,
+) -> Result<Vec<MonitorImpl>, ReplyError> {
+    let resources = conn
+        .randr_get_screen_resources_current(screen.root)?
+        .reply()?;
+    let timestamp = resources.config_timestamp;
+    let monitors = conn.randr_get_monitors(screen.root, true)?.reply()?;
+
+    let mut result = Vec::with_capacity(monitors.monitors.len());
+    for info in monitors.monitors.into_iter() {
+        let name = conn
+            .get_atom_name(info.name)?
+            .reply()
+            .ok()
+            .map(|reply| String::from_utf8_lossy(&reply.name).into_owned());
+
+        // A monitor may consist of several outputs, the first one drives it
+        let output = info.outputs.first().copied().unwrap_or(NONE);
+        let output_info = match output {
+            NONE => None,
+            output => conn.randr_get_output_info(output, timestamp)?.reply().ok(),
+        };
+        let crtc = output_info
+            .as_ref()
+            .map(|output_info| output_info.crtc)
+            .unwrap_or(NONE);
+        let current_mode = match crtc {
+            NONE => None,
+            crtc => conn
+                .randr_get_crtc_info(crtc, timestamp)?
+                .reply()
+                .ok()
+                .map(|crtc_info| crtc_info.mode),
+        };
+
+        let mut modes: Vec<X11VideoMode> = Vec::new();
+        let mode_ids = output_info
+            .map(|output_info| output_info.modes)
+            .unwrap_or_default();
+        for mode in resources.modes.iter() {
+            if !mode_ids.contains(&mode.id) {
+                continue;
+            }
+            // Modes differing in timings only look the same to users
+            if let Some(mode) = X11VideoMode::new(mode) {
+                let is_duplicate = modes.iter().any(|known| {
+                    (known.size, known.refresh_rate_millihertz)
+                        == (mode.size, mode.refresh_rate_millihertz)
+                });
+                if !is_duplicate {
+                    modes.push(mode);
+                }
+            }
+        }
+        let refresh_rate_millihertz = resources
+            .modes
+            .iter()
+            .find(|mode| Some(mode.id) == current_mode)
+            .and_then(to_refresh_rate);
+
+        result.push(MonitorImpl {
+            output,
+            crtc,
+            name,
+            position: PhysicalPosition::new(info.x as i32, info.y as i32),
+            size: PhysicalSize::new(info.width as u32, info.height as u32),
+            scale_factor,
+            bit_depth: screen.root_depth as u16,
+            refresh_rate_millihertz,
+            modes,
+            is_primary: info.primary,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Switches the CRTC to the mode.
+///
+/// Returns the previous mode of the CRTC.
+///
+/// # Parameters:
+/// * `conn` - X server connection.
+/// * `root` - Root window of the screen.
+/// * `crtc` - CRTC to configure.
+/// * `mode` - New RandR mode.
+pub(super) fn set_crtc_mode(
+    conn: &RustConnection,
+    root: XWindow,
+    crtc: randr::Crtc,
+    mode: randr::Mode,
+) -> Result<randr::Mode, Error> {
+    let map_err = |e| Error::with_source(ErrorKind::Connection, "RandR mode not set.", e);
+    let (previous, status) = try_set_crtc_mode(conn, root, crtc, mode).map_err(map_err)?;
+    match status {
+        SetConfig::SUCCESS => Ok(previous),
+        _ => Err(Error::new(ErrorKind::Os, "RandR mode not set.")),
+    }
+}
+
+fn try_set_crtc_mode(
+    conn: &RustConnection,
+    root: XWindow,
+    crtc: randr::Crtc,
+    mode: randr::Mode,
+) -> Result<(randr::Mode, SetConfig), ReplyError> {
+    let resources = conn.randr_get_screen_resources_current(root)?.reply()?;
+    let info = conn
+        .randr_get_crtc_info(crtc, resources.config_timestamp)?
+        .reply()?;
+    if info.mode == mode {
+        return Ok((mode, SetConfig::SUCCESS));
+    }
+    let reply = conn
+        .randr_set_crtc_config(
+            crtc,
+            info.timestamp,
+            resources.config_timestamp,
+            info.x,
+            info.y,
+            mode,
+            info.rotation,
+            &info.outputs,
+        )?
+        .reply()?;
+    Ok((info.mode, reply.status))
+}
@@ -0,0 +1,234 @@
+//! Pen tablet support.
+//!
+//! Selecting XInput 2 pointer events on a window stops delivery of core ones,
+//! so tablet axes are taken from raw events of the root window and combined
+//! with core pointer events.
+
+use std::collections::HashMap;
+
+use dpi::PhysicalPosition;
+use x11rb::protocol::{
+    xinput::{DeviceId, RawButtonPressEvent, RawMotionEvent},
+    xproto::Window as XWindow,
+};
+
+use super::{
+    app_state::AppState,
+    valuator::{is_set, query_device, Valuator},
+};
+use crate::{Event, TabletEvent, TabletTool, WindowEvent, WindowId};
+
+/// Tip of a tool is reported as the first button.
+const TIP_BUTTON: u32 = 1;
+
+/// Tablet tool device.
+#[derive(Debug, Clone, Copy)]
+struct ToolDevice {
+    tool:     TabletTool,
+    pressure: Valuator,
+    tilt_x:   Option<Valuator>,
+    tilt_y:   Option<Valuator>,
+}
+
+/// Tool in proximity of the tablet over a window.
+#[derive(Debug)]
+struct ActiveTool {
+    device:   DeviceId,
+    window:   XWindow,
+    position: PhysicalPosition<f64>,
+}
+
+/// Tablet tools and the state of the active one.
+#[derive(Debug, Default)]
+pub(super) struct Tablets {
+    /// Tool devices, queried once per device. Other devices are `None`.
+    devices:  HashMap<DeviceId, Option<ToolDevice>>,
+    active:   Option<ActiveTool>,
+    /// Device of the raw motion waiting for the pointer position.
+    pending:  Option<DeviceId>,
+    pressure: f64,
+    tilt:     (f64, f64),
+}
+
+fn query_tool_device(state: &AppState, device: DeviceId) -> Option<ToolDevice> {
+    let atoms = state.atoms();
+    let info = query_device(state, device)?;
+
+    // Touch screens report pressure too, but they aren't tablets
+    let pressure = Valuator::find(&info, &[atoms.ABS_PRESSURE])?;
+    if Valuator::find(&info, &[atoms.ABS_MT_PRESSURE]).is_some() {
+        return None;
+    }
+
+    // Drivers name devices after tools, e.g. "Wacom Intuos Pen eraser"
+    let name = String::from_utf8_lossy(&info.name).to_lowercase();
+    let tool = if name.contains("eraser") {
+        TabletTool::Eraser
+    } else if name.contains("cursor") || name.contains("mouse") {
+        TabletTool::Other
+    } else {
+        TabletTool::Pen
+    };
+
+    Some(ToolDevice {
+        tool,
+        pressure,
+        tilt_x: Valuator::find(&info, &[atoms.ABS_TILT_X]),
+        tilt_y: Valuator::find(&info, &[atoms.ABS_TILT_Y]),
+    })
+}
+
+fn tool_device(state: &AppState, device: DeviceId) -> Option<ToolDevice> {
+    state
+        .tablets()
+        .devices
+        .entry(device)
+        .or_insert_with(|| query_tool_device(state, device))
+        .as_ref()
+        .copied()
+}
+
+#[inline]
+fn queue_tablet_event(state: &AppState, window: XWindow, event: TabletEvent) {
+    state.queue_event(Event::Window(
+        WindowEvent::Tablet(event),
+        window as WindowId,
+    ));
+}
+
+fn queue_motion(state: &AppState) {
+    let tablets = state.tablets();
+    if let Some(active) = tablets.active.as_ref() {
+        let event = TabletEvent::Motion {
+            position: active.position,
+            pressure: tablets.pressure,
+            tilt:     tablets.tilt,
+            rotation: 0.0,
+        };
+        let window = active.window;
+        drop(tablets);
+        queue_tablet_event(state, window, event);
+    }
+}
+
+/// Finishes proximity of the active tool.
+fn leave_proximity(state: &AppState) {
+    let active = {
+        let mut tablets = state.tablets();
+        tablets.pending = None;
+        tablets.active.take()
+    };
+    if let Some(active) = active {
+        queue_tablet_event(state, active.window, TabletEvent::ProximityLeft);
+    }
+}
+
+/// Finishes proximity of the active tool if the pointer has left the window.
+pub(super) fn handle_leave(state: &AppState, window: XWindow) {
+    let is_active_window = state
+        .tablets()
+        .active
+        .as_ref()
+        .is_some_and(|active| active.window == window);
+    if is_active_window {
+        leave_proximity(state);
+    }
+}
+
+/// Handles raw motion, which comes before the core pointer motion.
+pub(super) fn handle_raw_motion(state: &AppState, event: &RawMotionEvent) {
+    let Some(device) = tool_device(state, event.sourceid) else {
+        // Another pointing device has taken over the cursor
+        let is_tool_active = state.tablets().active.is_some();
+        if is_tool_active {
+            leave_proximity(state);
+        }
+        return;
+    };
+
+    let mask = &event.valuator_mask;
+    let values = &event.axisvalues;
+    let mut tablets = state.tablets();
+    if let Some(pressure) = device.pressure.normalized(mask, values) {
+        tablets.pressure = pressure;
+    }
+    // Tilt is normalized from `-1.0` to `1.0`
+    let tilt = |valuator: Option<Valuator>| {
+        valuator
+            .and_then(|valuator| valuator.normalized(mask, values))
+            .map(|value| value * 2.0 - 1.0)
+    };
+    if let Some(tilt_x) = tilt(device.tilt_x) {
+        tablets.tilt.0 = tilt_x;
+    }
+    if let Some(tilt_y) = tilt(device.tilt_y) {
+        tablets.tilt.1 = tilt_y;
+    }
+
+    // The first valuators are the X and Y axes, the core motion follows them
+    let is_moved = is_set(mask, 0) || is_set(mask, 1);
+    let is_same_tool = tablets
+        .active
+        .as_ref()
+        .is_some_and(|active| active.device == event.sourceid);
+    if is_moved || !is_same_tool {
+        tablets.pending = Some(event.sourceid);
+    } else {
+        drop(tablets);
+        queue_motion(state);
+    }
+}
+
+/// Handles core pointer motion over the window.
+pub(super) fn handle_motion(state: &AppState, window: XWindow, position: PhysicalPosition<f64>) {
+    let Some(device) = state.tablets().pending.take() else {
+        return;
+    };
+
+    let is_entered = {
+        let mut tablets = state.tablets();
+        let is_entered = !tablets
+            .active
+            .as_ref()
+            .is_some_and(|active| active.device == device && active.window == window);
+        tablets.active = Some(ActiveTool {
+            device,
+            window,
+            position,
+        });
+        is_entered
+    };
+    if is_entered {
+        if let Some(tool) = tool_device(state, device).map(|device| device.tool) {
+            queue_tablet_event(
+                state,
+                window,
+                TabletEvent::ProximityEntered {
+                    tool,
+                },
+            );
+        }
+    }
+    queue_motion(state);
+}
+
+/// Handles raw button events of the active tool.
+pub(super) fn handle_raw_button(state: &AppState, event: &RawButtonPressEvent, is_pressed: bool) {
+    if event.detail != TIP_BUTTON {
+        return;
+    }
+
+    let window = state
+        .tablets()
+        .active
+        .as_ref()
+        .filter(|active| active.device == event.sourceid)
+        .map(|active| active.window);
+    if let Some(window) = window {
+        let event = match is_pressed {
+            true => TabletEvent::Down,
+            false => TabletEvent::Up,
+        };
+        queue_tablet_event(state, window, event);
+    }
+}
@@ -0,0 +1,51 @@
+//! XInput 2.2 touch support.
+
+use std::collections::HashMap;
+
+use dpi::PhysicalPosition;
+use x11rb::protocol::xinput::{DeviceId, Fp1616, TouchBeginEvent};
+
+use super::{
+    app_state::AppState,
+    valuator::{query_device, Valuator},
+};
+use crate::{Event, TouchPhase, WindowEvent, WindowId};
+
+/// Pressure valuators of touch devices, queried once per device.
+#[derive(Debug, Default)]
+pub(super) struct TouchDevices {
+    pressure_axes: HashMap<DeviceId, Option<Valuator>>,
+}
+
+fn query_pressure_axis(state: &AppState, device: DeviceId) -> Option<Valuator> {
+    let atoms = state.atoms();
+    let info = query_device(state, device)?;
+    Valuator::find(&info, &[atoms.ABS_MT_PRESSURE, atoms.ABS_PRESSURE])
+}
+
+/// Returns normalized pressure of the touch, if the device reports it.
+fn force(state: &AppState, event: &TouchBeginEvent) -> Option<f64> {
+    let axis = state
+        .touch_devices()
+        .pressure_axes
+        .entry(event.sourceid)
+        .or_insert_with(|| query_pressure_axis(state, event.sourceid))
+        .as_ref()
+        .copied()?;
+    axis.normalized(&event.valuator_mask, &event.axisvalues)
+}
+
+#[inline]
+fn to_position(x: Fp1616, y: Fp1616) -> PhysicalPosition<f64> {
+    PhysicalPosition::new(x as f64 / 65536.0, y as f64 / 65536.0)
+}
+
+pub(super) fn handle_touch(state: &AppState, event: TouchBeginEvent, phase: TouchPhase) {
+    let touch_event = WindowEvent::Touch {
+        id: event.detail as u64,
+        phase,
+        position: to_position(event.event_x, event.event_y),
+        force: force(state, &event),
+    };
+    state.queue_event(Event::Window(touch_event, event.event as WindowId));
+}
@@ -0,0 +1,411 @@
+//! System tray icons.
+//!
+//! Icons are exported through the session bus if the desktop has a
+//! StatusNotifierItem host, otherwise they are docked into the XEmbed system
+//! tray, see: https://specifications.freedesktop.org/systemtray-spec/latest/
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Debug,
+    rc::Rc,
+};
+
+use x11rb::{
+    connection::{Connection as _, RequestConnection as _},
+    protocol::xproto::{
+        AtomEnum,
+        BackPixmap,
+        ButtonPressEvent,
+        ClientMessageEvent,
+        ConnectionExt as _,
+        CreateGCAux,
+        CreateWindowAux,
+        EventMask,
+        ExposeEvent,
+        Gcontext,
+        ImageFormat,
+        ImageOrder,
+        PropMode,
+        Timestamp,
+        Window as XWindow,
+        WindowClass,
+    },
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+    COPY_FROM_PARENT,
+    CURRENT_TIME,
+    NONE,
+};
+
+use super::app_state::AppState;
+use crate::{
+    platform::{TrayApi, Wrapper},
+    platform_impl::freedesktop::StatusNotifierItem,
+    ContextOwner,
+    Error,
+    ErrorKind,
+    Event,
+    Icon,
+    Menu,
+    TrayEventKind,
+    TrayId,
+};
+
+const SYSTEM_TRAY_REQUEST_DOCK: u32 = 0;
+const XEMBED_VERSION: u32 = 0;
+const XEMBED_MAPPED: u32 = 1;
+const LEFT_MOUSE_BUTTON: u8 = 1;
+/// Maximum time between clicks of a double click in milliseconds.
+const DOUBLE_CLICK_INTERVAL: Timestamp = 500;
+/// Size of the icon window until the tray resizes it.
+const DEFAULT_SIZE: u16 = 24;
+
+/// Icon docked into the XEmbed system tray.
+///
+/// The window background is inherited from the tray, the icon image is
+/// blended over it on every exposure.
+pub(super) struct XEmbedIcon {
+    app_state:  Rc<AppState>,
+    id:         TrayId,
+    window:     XWindow,
+    gc:         Gcontext,
+    /// Icon size and ARGB pixels.
+    image:      RefCell<(u32, u32, Vec<u32>)>,
+    last_click: Cell<Option<Timestamp>>,
+}
+
+impl Debug for XEmbedIcon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XEmbedIcon")
+            .field("id", &self.id)
+            .field("window", &self.window)
+            .finish()
+    }
+}
+
+impl XEmbedIcon {
+    fn new(app_state: &Rc<AppState>, id: TrayId, icon: &Icon) -> Result<Rc<Self>, Error> {
+        let conn = app_state.conn();
+        let atoms = app_state.atoms();
+        let screen = app_state.screen();
+
+        // Every screen has its own tray
+        let selection = format!("_NET_SYSTEM_TRAY_S{}", app_state.screen_num());
+        let manager = conn
+            .intern_atom(false, selection.as_bytes())
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| conn.get_selection_owner(reply.atom).ok())
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.owner)
+            .filter(|owner| *owner != NONE)
+            .ok_or_else(|| Error::new(ErrorKind::NotSupported, "system tray not found."))?;
+
+        let id_err = |e| Error::with_source(ErrorKind::Os, "X window ID not generated.", e);
+        let window = conn.generate_id().map_err(id_err)?;
+        let gc = conn.generate_id().map_err(id_err)?;
+        let map_err = |e| Error::with_source(ErrorKind::Connection, "tray icon not created.", e);
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            DEFAULT_SIZE,
+            DEFAULT_SIZE,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixmap(BackPixmap::PARENT_RELATIVE)
+                .event_mask(EventMask::EXPOSURE | EventMask::BUTTON_PRESS),
+        )
+        .map_err(map_err)?;
+        conn.create_gc(gc, window, &CreateGCAux::new())
+            .map_err(map_err)?;
+        // The tray maps the window itself
+        let _ = conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            atoms._XEMBED_INFO,
+            atoms._XEMBED_INFO,
+            &[XEMBED_VERSION, XEMBED_MAPPED],
+        );
+
+        let event = ClientMessageEvent::new(
+            32,
+            manager,
+            atoms._NET_SYSTEM_TRAY_OPCODE,
+            [CURRENT_TIME, SYSTEM_TRAY_REQUEST_DOCK, window, 0, 0],
+        );
+        let _ = conn.send_event(false, manager, EventMask::NO_EVENT, event);
+        let _ = conn.flush();
+
+        let tray_icon = Rc::new(Self {
+            app_state: app_state.clone(),
+            id,
+            window,
+            gc,
+            image: RefCell::new(to_image(icon)),
+            last_click: Cell::new(None),
+        });
+        app_state.register_tray_icon(&tray_icon);
+        Ok(tray_icon)
+    }
+
+    #[inline]
+    pub(super) fn window(&self) -> XWindow { self.window }
+
+    fn set_icon(&self, icon: &Icon) {
+        *self.image.borrow_mut() = to_image(icon);
+        // Exposing the window redraws it
+        let conn = self.app_state.conn();
+        let _ = conn.clear_area(true, self.window, 0, 0, 0, 0);
+        let _ = conn.flush();
+    }
+
+    fn set_tooltip(&self, tooltip: Option<String>) {
+        // Trays show the window name as a tooltip
+        let conn = self.app_state.conn();
+        let atoms = self.app_state.atoms();
+        let name = tooltip.unwrap_or_default();
+        let _ = conn.change_property8(
+            PropMode::REPLACE,
+            self.window,
+            atoms._NET_WM_NAME,
+            atoms.UTF8_STRING,
+            name.as_bytes(),
+        );
+        let _ = conn.change_property8(
+            PropMode::REPLACE,
+            self.window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            name.as_bytes(),
+        );
+        let _ = conn.flush();
+    }
+
+    /// Draws the icon scaled to the window size over the tray background.
+    fn draw(&self) {
+        let conn = self.app_state.conn();
+        let Some(geometry) = conn
+            .get_geometry(self.window)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            return;
+        };
+        let (width, height) = (geometry.width as u32, geometry.height as u32);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let _ = conn.clear_area(false, self.window, 0, 0, 0, 0);
+        let Some(background) = conn
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                self.window,
+                0,
+                0,
+                width as u16,
+                height as u16,
+                u32::MAX,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            return;
+        };
+        // Only 32 bits per pixel true color visuals are supported
+        if background.data.len() != (width * height * 4) as usize {
+            return;
+        }
+
+        let is_lsb_first = conn.setup().image_byte_order == ImageOrder::LSB_FIRST;
+        let image = self.image.borrow();
+        let (icon_width, icon_height, pixels) = &*image;
+        let data: Vec<u8> = background
+            .data
+            .chunks_exact(4)
+            .enumerate()
+            .flat_map(|(index, bytes)| {
+                let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                let background = match is_lsb_first {
+                    true => u32::from_le_bytes(bytes),
+                    false => u32::from_be_bytes(bytes),
+                };
+                let (x, y) = (index as u32 % width, index as u32 / width);
+                let source = (y * icon_height / height) * icon_width + x * icon_width / width;
+                let pixel = blend(pixels[source as usize], background);
+                match is_lsb_first {
+                    true => pixel.to_le_bytes(),
+                    false => pixel.to_be_bytes(),
+                }
+            })
+            .collect();
+
+        // Large icons are drawn in strips to fit the maximum request length
+        let row_length = width as usize * 4;
+        let max_rows = (conn.maximum_request_bytes() / 2 / row_length).max(1);
+        for (index, rows) in data.chunks(max_rows * row_length).enumerate() {
+            let _ = conn.put_image(
+                ImageFormat::Z_PIXMAP,
+                self.window,
+                self.gc,
+                width as u16,
+                (rows.len() / row_length) as u16,
+                0,
+                (index * max_rows) as i16,
+                0,
+                geometry.depth,
+                rows,
+            );
+        }
+    }
+
+    fn handle_click(&self, time: Timestamp) {
+        let is_double_click = self
+            .last_click
+            .get()
+            .is_some_and(|last| time.wrapping_sub(last) <= DOUBLE_CLICK_INTERVAL);
+        // The third click starts a new pair
+        self.last_click.set((!is_double_click).then_some(time));
+
+        self.app_state.queue_event(Event::Tray {
+            id:   self.id,
+            kind: TrayEventKind::Click,
+        });
+        if is_double_click {
+            self.app_state.queue_event(Event::Tray {
+                id:   self.id,
+                kind: TrayEventKind::DoubleClick,
+            });
+        }
+    }
+}
+
+impl Drop for XEmbedIcon {
+    fn drop(&mut self) {
+        // The tray undocks destroyed windows
+        let conn = self.app_state.conn();
+        self.app_state.unregister_tray_icon(self.window);
+        let _ = conn.free_gc(self.gc);
+        let _ = conn.destroy_window(self.window);
+        let _ = conn.flush();
+    }
+}
+
+#[inline]
+fn to_image(icon: &Icon) -> (u32, u32, Vec<u32>) {
+    let icon = icon.get_impl();
+    (icon.width(), icon.height(), icon.argb())
+}
+
+/// Blends an ARGB pixel over an opaque RGB one.
+fn blend(pixel: u32, background: u32) -> u32 {
+    let alpha = pixel >> 24;
+    (0..3).fold(0, |result, channel| {
+        let shift = channel * 8;
+        let source = (pixel >> shift) & 0xff;
+        let destination = (background >> shift) & 0xff;
+        result | ((source * alpha + destination * (255 - alpha)) / 255) << shift
+    })
+}
+
+/// Redraws the tray icon if the window is one.
+///
+/// Returns `true` if the event has been handled.
+pub(super) fn handle_expose(state: &AppState, event: &ExposeEvent) -> bool {
+    match state.tray_icon(event.window) {
+        Some(tray_icon) => {
+            if event.count == 0 {
+                tray_icon.draw();
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sends a click of the tray icon if the window is one.
+///
+/// Returns `true` if the event has been handled.
+pub(super) fn handle_button_press(state: &AppState, event: &ButtonPressEvent) -> bool {
+    match state.tray_icon(event.event) {
+        Some(tray_icon) => {
+            if event.detail == LEFT_MOUSE_BUTTON {
+                tray_icon.handle_click(event.time);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug)]
+enum TrayBackend {
+    StatusNotifier(StatusNotifierItem),
+    XEmbed(Rc<XEmbedIcon>),
+}
+
+#[derive(Debug)]
+pub(crate) struct TrayImpl(TrayBackend);
+
+impl TrayApi for TrayImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        id: TrayId,
+        icon: Icon,
+        tooltip: Option<String>,
+        menu: Option<Menu>,
+    ) -> Result<Self, Error> {
+        let state = ctx.context().get_impl().state();
+        let item = StatusNotifierItem::new(
+            state.create_proxy(),
+            state.menu_callbacks(),
+            id,
+            state.app_id(),
+            &icon,
+            tooltip.clone(),
+            menu.as_ref(),
+        );
+        match item {
+            Ok(item) => Ok(Self(TrayBackend::StatusNotifier(item))),
+            Err(_) => {
+                let tray_icon = XEmbedIcon::new(state, id, &icon)?;
+                tray_icon.set_tooltip(tooltip);
+                Ok(Self(TrayBackend::XEmbed(tray_icon)))
+            }
+        }
+    }
+
+    #[inline]
+    fn id(&self) -> TrayId {
+        match &self.0 {
+            TrayBackend::StatusNotifier(item) => item.id(),
+            TrayBackend::XEmbed(tray_icon) => tray_icon.id,
+        }
+    }
+
+    fn set_icon(&mut self, icon: Icon) {
+        match &self.0 {
+            TrayBackend::StatusNotifier(item) => item.set_icon(&icon),
+            TrayBackend::XEmbed(tray_icon) => tray_icon.set_icon(&icon),
+        }
+    }
+
+    fn set_tooltip(&mut self, tooltip: Option<String>) {
+        match &self.0 {
+            TrayBackend::StatusNotifier(item) => item.set_tooltip(tooltip),
+            TrayBackend::XEmbed(tray_icon) => tray_icon.set_tooltip(tooltip),
+        }
+    }
+
+    fn set_menu(&mut self, menu: Option<Menu>) {
+        // XEmbed trays have no menus
+        if let TrayBackend::StatusNotifier(item) = &self.0 {
+            item.set_menu(menu.as_ref());
+        }
+    }
+}
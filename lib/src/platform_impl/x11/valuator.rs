@@ -0,0 +1,73 @@
+//! XInput 2 valuators (device axes) shared by touch and tablet support.
+
+use x11rb::protocol::xinput::{
+    ConnectionExt as _,
+    DeviceClassData,
+    DeviceId,
+    Fp3232,
+    XIDeviceInfo,
+};
+
+use super::{app_state::AppState, events::to_f64};
+
+/// Valuator of an input device.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Valuator {
+    number: u16,
+    min:    f64,
+    max:    f64,
+}
+
+impl Valuator {
+    /// Finds the valuator with one of the labels among the device classes.
+    pub(super) fn find(info: &XIDeviceInfo, labels: &[u32]) -> Option<Self> {
+        info.classes.iter().find_map(|class| match &class.data {
+            DeviceClassData::Valuator(valuator) if labels.contains(&valuator.label) => Some(Self {
+                number: valuator.number,
+                min:    to_f64(&valuator.min),
+                max:    to_f64(&valuator.max),
+            }),
+            _ => None,
+        })
+    }
+
+    /// Returns the value of the valuator normalized from `0.0` to `1.0`.
+    ///
+    /// # Parameters:
+    /// * `mask` - Valuator mask of an event.
+    /// * `values` - Values of valuators set in the mask.
+    pub(super) fn normalized(&self, mask: &[u32], values: &[Fp3232]) -> Option<f64> {
+        if self.max <= self.min {
+            return None;
+        }
+
+        // Values are only sent for valuators set in the mask
+        let number = self.number as usize;
+        if !is_set(mask, number) {
+            return None;
+        }
+        let index = (0..number).filter(|bit| is_set(mask, *bit)).count();
+        let value = to_f64(values.get(index)?);
+        Some(((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0))
+    }
+}
+
+/// Checks that the valuator is set in the mask.
+#[inline]
+pub(super) fn is_set(mask: &[u32], number: usize) -> bool {
+    mask.get(number / 32)
+        .is_some_and(|word| word & (1 << (number % 32)) != 0)
+}
+
+/// Returns the device description.
+pub(super) fn query_device(state: &AppState, device: DeviceId) -> Option<XIDeviceInfo> {
+    state
+        .conn()
+        .xinput_xi_query_device(device)
+        .ok()?
+        .reply()
+        .ok()?
+        .infos
+        .into_iter()
+        .next()
+}
@@ -0,0 +1,1028 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use x11rb::{
+    connection::Connection,
+    properties::{WmHints, WmHintsState, WmSizeHints, WmSizeHintsSpecification},
+    protocol::{
+        randr,
+        xproto::{
+            AtomEnum,
+            ChangeWindowAttributesAux,
+            ClientMessageEvent,
+            ConfigureWindowAux,
+            ConnectionExt as _,
+            CreateWindowAux,
+            Cursor as XCursor,
+            EventMask,
+            GrabMode,
+            GrabStatus,
+            MapState,
+            PropMode,
+            Window as XWindow,
+            WindowClass,
+        },
+    },
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+    COPY_FROM_PARENT,
+    CURRENT_TIME,
+    NONE,
+};
+
+use super::{
+    app_state::AppState,
+    context_menu,
+    dnd::XDND_VERSION,
+    monitor::set_crtc_mode,
+    window_utils::to_motif_hints,
+};
+use crate::{
+    platform::{WindowApi, Wrapper},
+    platform_impl::common::default_options,
+    ActiveApplication,
+    ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    Error,
+    ErrorKind,
+    Event,
+    Fullscreen,
+    InitMode,
+    Menu,
+    Monitor,
+    WindowEvent,
+    WindowId,
+    WindowOptions,
+};
+
+const DEFAULT_POSITION: (i32, i32) = (200, 200);
+const DEFAULT_SIZE: (f64, f64) = (800.0, 600.0);
+
+// `_NET_WM_STATE` client message actions.
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+// `WM_STATE` values, see ICCCM 4.1.3.1.
+const ICONIC_STATE: u32 = 3;
+
+#[derive(Debug)]
+pub(super) struct WindowState {
+    app_state:      Rc<AppState>,
+    id:             XWindow,
+    title:          RefCell<String>,
+    options:        Cell<WindowOptions>,
+    init_mode:      Cell<Option<InitMode>>,
+    min_size:       Cell<Option<PhysicalSize<u32>>>,
+    max_size:       Cell<Option<PhysicalSize<u32>>>,
+    prev_position:  Cell<PhysicalPosition<i32>>,
+    prev_size:      Cell<PhysicalSize<u32>>,
+    is_destroyed:   Cell<bool>,
+    is_ime_allowed: Cell<bool>,
+    needs_redraw:   Cell<bool>,
+    cursor:         RefCell<Cursor>,
+    custom_cursor:  Cell<XCursor>,
+    cursor_visible: Cell<bool>,
+    cursor_grab:    Cell<CursorGrabMode>,
+    fullscreen:     RefCell<Option<Fullscreen>>,
+    was_fullscreen: Cell<bool>,
+    saved_mode:     Cell<Option<(randr::Crtc, randr::Mode)>>,
+}
+
+impl WindowState {
+    #[inline]
+    pub(super) fn native_id(&self) -> XWindow { self.id }
+
+    #[inline]
+    pub(super) fn window_id(&self) -> WindowId { self.id as WindowId }
+
+    #[inline]
+    fn queue_event(&self, event: WindowEvent) {
+        self.app_state
+            .queue_event(Event::Window(event, self.window_id()));
+    }
+
+    fn property32(&self, property: u32, type_: impl Into<u32>) -> Vec<u32> {
+        self.app_state
+            .conn()
+            .get_property(false, self.id, property, type_, 0, u32::MAX)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|value| value.collect()))
+            .unwrap_or_default()
+    }
+
+    fn is_mapped(&self) -> bool {
+        self.app_state
+            .conn()
+            .get_window_attributes(self.id)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|attributes| attributes.map_state != MapState::UNMAPPED)
+            .unwrap_or(false)
+    }
+
+    /// Returns `_NET_FRAME_EXTENTS` as `(left, right, top, bottom)`.
+    fn frame_extents(&self) -> (u32, u32, u32, u32) {
+        let extents = self.property32(
+            self.app_state.atoms()._NET_FRAME_EXTENTS,
+            AtomEnum::CARDINAL,
+        );
+        match extents[..] {
+            [left, right, top, bottom] => (left, right, top, bottom),
+            _ => (0, 0, 0, 0),
+        }
+    }
+
+    fn has_net_wm_state(&self, states: &[u32]) -> bool {
+        let atoms = self.app_state.atoms();
+        let current = self.property32(atoms._NET_WM_STATE, AtomEnum::ATOM);
+        states.iter().all(|state| current.contains(state))
+    }
+
+    /// Changes `_NET_WM_STATE` of the window.
+    ///
+    /// Mapped windows must ask the window manager for a change, but the
+    /// property of a withdrawn window can be edited directly.
+    ///
+    /// See: https://specifications.freedesktop.org/wm-spec/latest/ar01s05.html#id-1.6.8
+    fn change_net_wm_state(&self, action: u32, first: u32, second: u32) {
+        let conn = self.app_state.conn();
+        let atoms = self.app_state.atoms();
+
+        if self.is_mapped() {
+            let event = ClientMessageEvent::new(
+                32,
+                self.id,
+                atoms._NET_WM_STATE,
+                [action, first, second, 1, 0],
+            );
+            let _ = conn.send_event(
+                false,
+                self.app_state.screen().root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            );
+        } else {
+            let mut states = self.property32(atoms._NET_WM_STATE, AtomEnum::ATOM);
+            for state in [first, second].into_iter().filter(|state| *state != 0) {
+                let present = states.contains(&state);
+                let add = match action {
+                    NET_WM_STATE_ADD => true,
+                    NET_WM_STATE_REMOVE => false,
+                    _ => !present,
+                };
+                if add && !present {
+                    states.push(state);
+                } else if !add {
+                    states.retain(|s| *s != state);
+                }
+            }
+            let _ = conn.change_property32(
+                PropMode::REPLACE,
+                self.id,
+                atoms._NET_WM_STATE,
+                AtomEnum::ATOM,
+                &states,
+            );
+        }
+        let _ = conn.flush();
+    }
+
+    fn update_size_hints(&self) {
+        let mut hints = WmSizeHints::new();
+        let position = self.prev_position.get();
+        hints.position = Some((
+            WmSizeHintsSpecification::ProgramSpecified,
+            position.x,
+            position.y,
+        ));
+        hints.min_size = self
+            .min_size
+            .get()
+            .map(|size| (size.width as i32, size.height as i32));
+        hints.max_size = self
+            .max_size
+            .get()
+            .map(|size| (size.width as i32, size.height as i32));
+
+        // X11 has no dedicated flag for fixed size windows.
+        if !self.options.get().resizable {
+            let size = self.content_size();
+            let size = (size.width as i32, size.height as i32);
+            hints.min_size = Some(size);
+            hints.max_size = Some(size);
+        }
+
+        let _ = hints.set_normal_hints(self.app_state.conn(), self.id);
+    }
+
+    pub(super) fn set_icon(&self, icon: Option<&[u32]>) {
+        let conn = self.app_state.conn();
+        let atom = self.app_state.atoms()._NET_WM_ICON;
+        let _ = match icon {
+            Some(icon) => {
+                conn.change_property32(PropMode::REPLACE, self.id, atom, AtomEnum::CARDINAL, icon)
+            }
+            None => conn.delete_property(self.id, atom),
+        };
+    }
+
+    pub(super) fn emit_configure_events(&self, size: PhysicalSize<u32>) {
+        if size != self.prev_size.get() {
+            self.prev_size.set(size);
+            self.queue_event(WindowEvent::Resized(size));
+        }
+
+        let position = self.position();
+        if position != self.prev_position.get() {
+            self.prev_position.set(position);
+            self.queue_event(WindowEvent::Moved(position));
+        }
+    }
+
+    #[inline]
+    pub(super) fn set_title(&self, title: String) {
+        let conn = self.app_state.conn();
+        let atoms = self.app_state.atoms();
+        let _ = conn.change_property8(
+            PropMode::REPLACE,
+            self.id,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            title.as_bytes(),
+        );
+        let _ = conn.change_property8(
+            PropMode::REPLACE,
+            self.id,
+            atoms._NET_WM_NAME,
+            atoms.UTF8_STRING,
+            title.as_bytes(),
+        );
+        let _ = conn.flush();
+        *self.title.borrow_mut() = title;
+    }
+
+    #[inline]
+    pub(super) fn title(&self) -> String { self.title.borrow().clone() }
+
+    pub(super) fn set_options(&self, options: WindowOptions) {
+        let atom = self.app_state.atoms()._MOTIF_WM_HINTS;
+        let _ = self.app_state.conn().change_property32(
+            PropMode::REPLACE,
+            self.id,
+            atom,
+            atom,
+            &to_motif_hints(&options),
+        );
+        self.options.set(options);
+        self.update_size_hints();
+        let _ = self.app_state.conn().flush();
+    }
+
+    #[inline]
+    pub(super) fn options(&self) -> WindowOptions { self.options.get() }
+
+    fn sync_with_init_mode(&self) {
+        let atoms = self.app_state.atoms();
+        match self.init_mode.take() {
+            Some(InitMode::Maximized) => self.change_net_wm_state(
+                NET_WM_STATE_ADD,
+                atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+            ),
+            Some(InitMode::Fullscreen) => {
+                self.change_net_wm_state(NET_WM_STATE_ADD, atoms._NET_WM_STATE_FULLSCREEN, 0)
+            }
+            Some(InitMode::Minimized) => {
+                let mut hints = WmHints::new();
+                hints.input = Some(true);
+                hints.initial_state = Some(WmHintsState::Iconic);
+                let _ = hints.set(self.app_state.conn(), self.id);
+            }
+            _ => (),
+        }
+    }
+
+    pub(super) fn show(&self) {
+        // The initial mode must be applied before the window gets mapped.
+        self.sync_with_init_mode();
+
+        let _ = self.app_state.conn().map_window(self.id);
+        let _ = self.app_state.conn().flush();
+
+        self.queue_event(WindowEvent::Showed);
+    }
+
+    pub(super) fn show_modal(&self) {
+        let atoms = self.app_state.atoms();
+        self.change_net_wm_state(NET_WM_STATE_ADD, atoms._NET_WM_STATE_MODAL, 0);
+        self.show();
+    }
+
+    pub(super) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let atoms = self.app_state.atoms();
+        let monitor = match &fullscreen {
+            Some(Fullscreen::Borderless(monitor)) => monitor.clone(),
+            Some(Fullscreen::Exclusive(mode)) => Some(mode.monitor().clone()),
+            None => None,
+        };
+        let action = match fullscreen.is_some() {
+            true => NET_WM_STATE_ADD,
+            false => NET_WM_STATE_REMOVE,
+        };
+        *self.fullscreen.borrow_mut() = fullscreen;
+
+        // Window managers make windows fullscreen on the monitor they are on.
+        if let Some(monitor) = monitor {
+            let position = monitor.position();
+            let aux = ConfigureWindowAux::new().x(position.x).y(position.y);
+            let _ = self.app_state.conn().configure_window(self.id, &aux);
+        }
+        self.change_net_wm_state(action, atoms._NET_WM_STATE_FULLSCREEN, 0);
+
+        // A state change is applied on `_NET_WM_STATE` updates, switch video
+        // modes of already fullscreen windows right away.
+        if self.was_fullscreen.get() {
+            self.sync_video_mode();
+        }
+    }
+
+    /// Returns the fullscreen mode requested for the window.
+    ///
+    /// The borderless mode is reported if the window has been made fullscreen
+    /// by the window manager.
+    pub(super) fn fullscreen(&self) -> Option<Fullscreen> {
+        match self.is_fullscreen() {
+            true => Some(
+                self.fullscreen
+                    .borrow()
+                    .clone()
+                    .unwrap_or(Fullscreen::Borderless(None)),
+            ),
+            false => None,
+        }
+    }
+
+    /// Switches the monitor to the exclusive video mode of the window, or
+    /// restores the previous one.
+    fn sync_video_mode(&self) {
+        let conn = self.app_state.conn();
+        let root = self.app_state.screen().root;
+
+        if let Some((crtc, mode)) = self.saved_mode.take() {
+            let _ = set_crtc_mode(conn, root, crtc, mode);
+        }
+
+        let fullscreen = self.fullscreen.borrow();
+        if let (Some(Fullscreen::Exclusive(mode)), true) = (&*fullscreen, self.was_fullscreen.get())
+        {
+            let monitor = mode.monitor().get_impl();
+            if let Some(mode_id) = monitor.mode_id(mode) {
+                if let Ok(previous) = set_crtc_mode(conn, root, monitor.crtc(), mode_id) {
+                    self.saved_mode.set(Some((monitor.crtc(), previous)));
+                }
+            }
+        }
+        let _ = conn.flush();
+    }
+
+    /// Handles a change of the window property.
+    pub(super) fn handle_property_change(&self, atom: u32) {
+        if atom != self.app_state.atoms()._NET_WM_STATE {
+            return;
+        }
+
+        let is_fullscreen = self.is_fullscreen();
+        if is_fullscreen != self.was_fullscreen.replace(is_fullscreen) {
+            if !is_fullscreen {
+                self.fullscreen.borrow_mut().take();
+            }
+            self.sync_video_mode();
+            self.queue_event(WindowEvent::FullscreenChanged(is_fullscreen));
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_fullscreen(&self) -> bool {
+        self.has_net_wm_state(&[self.app_state.atoms()._NET_WM_STATE_FULLSCREEN])
+    }
+
+    pub(super) fn set_frame_size(&self, size: Size) {
+        let size: PhysicalSize<u32> = size.to_physical(self.scale_factor());
+        let (left, right, top, bottom) = self.frame_extents();
+        let aux = ConfigureWindowAux::new()
+            .width(size.width.saturating_sub(left + right).max(1))
+            .height(size.height.saturating_sub(top + bottom).max(1));
+        let _ = self.app_state.conn().configure_window(self.id, &aux);
+        let _ = self.app_state.conn().flush();
+    }
+
+    pub(super) fn frame_size(&self) -> PhysicalSize<u32> {
+        let size = self.content_size();
+        let (left, right, top, bottom) = self.frame_extents();
+        PhysicalSize::new(size.width + left + right, size.height + top + bottom)
+    }
+
+    pub(super) fn set_position(&self, position: Position) {
+        let position: PhysicalPosition<i32> = position.to_physical(self.scale_factor());
+        let aux = ConfigureWindowAux::new().x(position.x).y(position.y);
+        let _ = self.app_state.conn().configure_window(self.id, &aux);
+        let _ = self.app_state.conn().flush();
+    }
+
+    pub(super) fn position(&self) -> PhysicalPosition<i32> {
+        let root = self.app_state.screen().root;
+        let (left, _, top, _) = self.frame_extents();
+        self.app_state
+            .conn()
+            .translate_coordinates(self.id, root, 0, 0)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| {
+                PhysicalPosition::new(
+                    reply.dst_x as i32 - left as i32,
+                    reply.dst_y as i32 - top as i32,
+                )
+            })
+            .unwrap_or(self.prev_position.get())
+    }
+
+    pub(super) fn set_min_size(&self, min_size: Size) {
+        self.min_size
+            .set(Some(min_size.to_physical(self.scale_factor())));
+        self.update_size_hints();
+        let _ = self.app_state.conn().flush();
+    }
+
+    #[inline]
+    pub(super) fn min_size(&self) -> PhysicalSize<u32> { self.min_size.get().unwrap_or_default() }
+
+    pub(super) fn set_max_size(&self, max_size: Size) {
+        self.max_size
+            .set(Some(max_size.to_physical(self.scale_factor())));
+        self.update_size_hints();
+        let _ = self.app_state.conn().flush();
+    }
+
+    #[inline]
+    pub(super) fn max_size(&self) -> PhysicalSize<u32> {
+        self.max_size
+            .get()
+            .unwrap_or(PhysicalSize::new(u32::MAX, u32::MAX))
+    }
+
+    #[inline]
+    pub(super) fn maximize(&self) {
+        let atoms = self.app_state.atoms();
+        self.change_net_wm_state(
+            NET_WM_STATE_ADD,
+            atoms._NET_WM_STATE_MAXIMIZED_VERT,
+            atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+        );
+    }
+
+    #[inline]
+    pub(super) fn is_maximized(&self) -> bool {
+        let atoms = self.app_state.atoms();
+        self.has_net_wm_state(&[
+            atoms._NET_WM_STATE_MAXIMIZED_VERT,
+            atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+        ])
+    }
+
+    pub(super) fn content_size(&self) -> PhysicalSize<u32> {
+        self.app_state
+            .conn()
+            .get_geometry(self.id)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|geometry| PhysicalSize::new(geometry.width as u32, geometry.height as u32))
+            .unwrap_or(self.prev_size.get())
+    }
+
+    pub(super) fn is_visible(&self) -> bool {
+        self.app_state
+            .conn()
+            .get_window_attributes(self.id)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|attributes| attributes.map_state == MapState::VIEWABLE)
+            .unwrap_or(false)
+    }
+
+    pub(super) fn close(&self) {
+        if !self.is_destroyed.replace(true) {
+            if let Some((crtc, mode)) = self.saved_mode.take() {
+                let root = self.app_state.screen().root;
+                let _ = set_crtc_mode(self.app_state.conn(), root, crtc, mode);
+            }
+            let custom_cursor = self.custom_cursor.replace(NONE);
+            if custom_cursor != NONE {
+                let _ = self.app_state.conn().free_cursor(custom_cursor);
+            }
+            let _ = self.app_state.conn().destroy_window(self.id);
+            let _ = self.app_state.conn().flush();
+        }
+    }
+
+    pub(super) fn minimize(&self) {
+        if !self.is_mapped() {
+            self.init_mode.set(Some(InitMode::Minimized));
+            return;
+        }
+
+        // See ICCCM 4.1.4.
+        let atoms = self.app_state.atoms();
+        let event = ClientMessageEvent::new(
+            32,
+            self.id,
+            atoms.WM_CHANGE_STATE,
+            [ICONIC_STATE, 0, 0, 0, 0],
+        );
+        let _ = self.app_state.conn().send_event(
+            false,
+            self.app_state.screen().root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        );
+        let _ = self.app_state.conn().flush();
+    }
+
+    pub(super) fn is_minimized(&self) -> bool {
+        let atoms = self.app_state.atoms();
+        let wm_state = self.property32(atoms.WM_STATE, atoms.WM_STATE);
+        wm_state.first() == Some(&ICONIC_STATE)
+            || self.has_net_wm_state(&[atoms._NET_WM_STATE_HIDDEN])
+    }
+
+    pub(super) fn restore(&self) {
+        if self.is_minimized() {
+            // Mapping an iconic window switches it back to the normal state.
+            let _ = self.app_state.conn().map_window(self.id);
+            let _ = self.app_state.conn().flush();
+        }
+    }
+
+    #[inline]
+    pub(super) fn scale_factor(&self) -> f64 { self.app_state.scale_factor() }
+
+    pub(super) fn current_monitor(&self) -> Option<Monitor> {
+        // The monitor under the window center has the largest part of it in
+        // most layouts.
+        let position = self.position();
+        let size = self.frame_size();
+        let center = PhysicalPosition::new(
+            position.x + size.width as i32 / 2,
+            position.y + size.height as i32 / 2,
+        );
+        let monitors = self.app_state.monitors();
+        let index = monitors
+            .iter()
+            .position(|monitor| monitor.contains(center))
+            .or_else(|| monitors.iter().position(|monitor| monitor.is_primary()))
+            .unwrap_or(0);
+        monitors.into_iter().nth(index).map(Monitor::new)
+    }
+
+    pub(super) fn set_ime_allowed(&self, allowed: bool) {
+        self.is_ime_allowed.set(allowed);
+        let event = self
+            .app_state
+            .xim()
+            .set_allowed(self.app_state.conn(), self.id, allowed);
+        if let Some(event) = event {
+            self.queue_event(WindowEvent::Ime(event));
+        }
+        let _ = self.app_state.conn().flush();
+    }
+
+    #[inline]
+    pub(super) fn is_ime_allowed(&self) -> bool { self.is_ime_allowed.get() }
+
+    pub(super) fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        // The input method places its window below the spot
+        let scale_factor = self.scale_factor();
+        let position: PhysicalPosition<i32> = position.to_physical(scale_factor);
+        let size: PhysicalSize<u32> = size.to_physical(scale_factor);
+        let spot = (
+            position.x.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            (position.y + size.height as i32).clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+        );
+        self.app_state
+            .xim()
+            .set_spot(self.app_state.conn(), self.id, spot);
+        let _ = self.app_state.conn().flush();
+    }
+
+    /// Marks the window as needing a redraw.
+    #[inline]
+    pub(super) fn request_redraw(&self) { self.needs_redraw.set(true); }
+
+    /// Checks if a redraw has been requested.
+    #[inline]
+    pub(super) fn needs_redraw(&self) -> bool { self.needs_redraw.get() }
+
+    /// Resets the redraw request and returns if it was set.
+    #[inline]
+    pub(super) fn take_redraw_request(&self) -> bool { self.needs_redraw.replace(false) }
+
+    /// Applies the current cursor to the window.
+    fn update_cursor(&self) {
+        let conn = self.app_state.conn();
+        let cursors = self.app_state.cursors();
+        let cursor = match (self.cursor_visible.get(), &*self.cursor.borrow()) {
+            (false, _) => cursors.blank(conn),
+            (true, Cursor::Icon(icon)) => cursors.icon(conn, *icon),
+            (true, Cursor::Custom(_)) => self.custom_cursor.get(),
+        };
+        let aux = ChangeWindowAttributesAux::new().cursor(cursor);
+        let _ = conn.change_window_attributes(self.id, &aux);
+        let _ = conn.flush();
+    }
+
+    pub(super) fn set_cursor(&self, cursor: Cursor) {
+        let conn = self.app_state.conn();
+        // The server keeps a freed cursor as long as it is in use
+        let custom_cursor = match &cursor {
+            Cursor::Custom(cursor) => self
+                .app_state
+                .cursors()
+                .create_custom(conn, cursor)
+                .unwrap_or(NONE),
+            Cursor::Icon(_) => NONE,
+        };
+        let prev_custom_cursor = self.custom_cursor.replace(custom_cursor);
+
+        *self.cursor.borrow_mut() = cursor;
+        self.update_cursor();
+
+        if prev_custom_cursor != NONE {
+            let _ = conn.free_cursor(prev_custom_cursor);
+        }
+    }
+
+    #[inline]
+    pub(super) fn cursor(&self) -> Cursor { self.cursor.borrow().clone() }
+
+    pub(super) fn set_cursor_visible(&self, visible: bool) {
+        if self.cursor_visible.replace(visible) != visible {
+            self.update_cursor();
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_cursor_visible(&self) -> bool { self.cursor_visible.get() }
+
+    pub(super) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), Error> {
+        let conn = self.app_state.conn();
+        match mode {
+            CursorGrabMode::None => {
+                let _ = conn.ungrab_pointer(CURRENT_TIME);
+            }
+            CursorGrabMode::Confined | CursorGrabMode::Locked => {
+                // X11 can only confine the cursor, a locked cursor is moved back
+                // to the center of the window after every motion.
+                let event_mask =
+                    EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION;
+                let reply = conn
+                    .grab_pointer(
+                        true,
+                        self.id,
+                        event_mask,
+                        GrabMode::ASYNC,
+                        GrabMode::ASYNC,
+                        self.id,
+                        NONE,
+                        CURRENT_TIME,
+                    )
+                    .map_err(|e| {
+                        Error::with_source(ErrorKind::Connection, "pointer not grabbed.", e)
+                    })?
+                    .reply()
+                    .map_err(|e| Error::with_source(ErrorKind::Os, "pointer not grabbed.", e))?;
+                if reply.status != GrabStatus::SUCCESS {
+                    return Err(Error::new(
+                        ErrorKind::Os,
+                        format!("pointer not grabbed: {:?}.", reply.status),
+                    ));
+                }
+            }
+        }
+
+        self.cursor_grab.set(mode);
+        if mode == CursorGrabMode::Locked {
+            self.center_cursor(None);
+        }
+        let _ = conn.flush();
+        Ok(())
+    }
+
+    #[inline]
+    pub(super) fn cursor_grab(&self) -> CursorGrabMode { self.cursor_grab.get() }
+
+    /// Moves the cursor back to the center of the window.
+    ///
+    /// # Parameters:
+    /// * `position` - Current cursor position, if known.
+    pub(super) fn center_cursor(&self, position: Option<(i16, i16)>) {
+        let size = self.prev_size.get();
+        let center = ((size.width / 2) as i16, (size.height / 2) as i16);
+        // Warping generates a motion event as well
+        if position != Some(center) {
+            let conn = self.app_state.conn();
+            let _ = conn.warp_pointer(NONE, self.id, 0, 0, 0, 0, center.0, center.1);
+            let _ = conn.flush();
+        }
+    }
+
+    pub(super) fn set_cursor_position(&self, position: Position) -> Result<(), Error> {
+        let position: PhysicalPosition<i32> = position.to_physical(self.scale_factor());
+        let conn = self.app_state.conn();
+        conn.warp_pointer(
+            NONE,
+            self.id,
+            0,
+            0,
+            0,
+            0,
+            position.x.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            position.y.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+        )
+        .map_err(|e| Error::with_source(ErrorKind::Connection, "cursor not moved.", e))?;
+        let _ = conn.flush();
+        Ok(())
+    }
+
+    pub(super) fn show_context_menu(&self, menu: &Menu, position: Position) -> Result<(), Error> {
+        let position = position.to_physical(self.scale_factor());
+        context_menu::show(&self.app_state, self.id, menu, position)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct WindowImpl {
+    state: Rc<WindowState>,
+}
+
+impl WindowApi for WindowImpl {
+    fn new(
+        ctx: &impl ContextOwner,
+        mode: InitMode,
+        options: Option<WindowOptions>,
+        size: Option<Size>,
+    ) -> Result<Self, Error> {
+        // Extract the application context
+        let app_state = ctx.context().get_impl().state().clone();
+        let conn = app_state.conn();
+        let screen = app_state.screen();
+        let atoms = app_state.atoms();
+
+        let size: PhysicalSize<u32> = size
+            .unwrap_or(Size::Logical(DEFAULT_SIZE.into()))
+            .to_physical(app_state.scale_factor());
+        let position = PhysicalPosition::new(DEFAULT_POSITION.0, DEFAULT_POSITION.1);
+
+        let id = conn
+            .generate_id()
+            .map_err(|e| Error::with_source(ErrorKind::Os, "X window ID not generated.", e))?;
+        let event_mask = EventMask::EXPOSURE
+            | EventMask::STRUCTURE_NOTIFY
+            | EventMask::FOCUS_CHANGE
+            | EventMask::PROPERTY_CHANGE
+            | EventMask::KEY_PRESS
+            | EventMask::KEY_RELEASE
+            | EventMask::BUTTON_PRESS
+            | EventMask::BUTTON_RELEASE
+            | EventMask::POINTER_MOTION
+            | EventMask::ENTER_WINDOW
+            | EventMask::LEAVE_WINDOW;
+
+        // Create X window
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            id,
+            screen.root,
+            position.x as i16,
+            position.y as i16,
+            size.width.clamp(1, u16::MAX as u32) as u16,
+            size.height.clamp(1, u16::MAX as u32) as u16,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixel(screen.black_pixel)
+                .event_mask(event_mask),
+        )
+        .map_err(|e| Error::with_source(ErrorKind::Connection, "X window not created.", e))?
+        .check()
+        .map_err(|e| Error::with_source(ErrorKind::Os, "X window not created.", e))?;
+
+        // Let the window manager ask for closing instead of killing the client
+        let _ = conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atoms.WM_PROTOCOLS,
+            AtomEnum::ATOM,
+            &[atoms.WM_DELETE_WINDOW],
+        );
+        // Accept drops of files and text
+        let _ = conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atoms.XdndAware,
+            AtomEnum::ATOM,
+            &[XDND_VERSION],
+        );
+        app_state.select_touch_and_gestures(id);
+        let _ = conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atoms._NET_WM_PID,
+            AtomEnum::CARDINAL,
+            &[std::process::id()],
+        );
+        // Window managers and docks group windows by their class
+        if let Some(app_id) = app_state.app_id() {
+            let class = format!("{}\0{}\0", app_id, app_id);
+            let _ = conn.change_property8(
+                PropMode::REPLACE,
+                id,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                class.as_bytes(),
+            );
+        }
+
+        let state = Rc::new(WindowState {
+            app_state: app_state.clone(),
+            id,
+            title: RefCell::new(String::new()),
+            options: Cell::new(default_options()),
+            init_mode: Cell::new(Some(mode)),
+            min_size: Cell::new(None),
+            max_size: Cell::new(None),
+            prev_position: Cell::new(position),
+            prev_size: Cell::new(size),
+            is_destroyed: Cell::new(false),
+            is_ime_allowed: Cell::new(false),
+            needs_redraw: Cell::new(false),
+            cursor: RefCell::new(Cursor::default()),
+            custom_cursor: Cell::new(NONE),
+            cursor_visible: Cell::new(true),
+            cursor_grab: Cell::new(CursorGrabMode::None),
+            fullscreen: RefCell::new(None),
+            was_fullscreen: Cell::new(false),
+            saved_mode: Cell::new(None),
+        });
+
+        // Set post-creation window options
+        state.set_options(options.unwrap_or_else(default_options));
+        if let Some(icon) = app_state.icon() {
+            state.set_icon(Some(&icon));
+        }
+
+        app_state.register_window(&state);
+
+        Ok(Self {
+            state,
+        })
+    }
+
+    #[inline]
+    fn id(&self) -> WindowId { self.state.window_id() }
+
+    #[inline]
+    fn set_title(&mut self, title: String) { self.state.set_title(title); }
+
+    #[inline]
+    fn title(&self) -> String { self.state.title() }
+
+    #[inline]
+    fn set_options(&mut self, options: WindowOptions) { self.state.set_options(options); }
+
+    #[inline]
+    fn options(&self) -> WindowOptions { self.state.options() }
+
+    #[inline]
+    fn show(&mut self, _app: &ActiveApplication) { self.state.show(); }
+
+    #[inline]
+    fn show_modal(&mut self, _app: &ActiveApplication) { self.state.show_modal(); }
+
+    #[inline]
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
+        self.state.set_fullscreen(fullscreen);
+    }
+
+    #[inline]
+    fn fullscreen(&self) -> Option<Fullscreen> { self.state.fullscreen() }
+
+    #[inline]
+    fn is_fullscreen(&self) -> bool { self.state.is_fullscreen() }
+
+    #[inline]
+    fn set_frame_size(&mut self, size: Size) { self.state.set_frame_size(size); }
+
+    #[inline]
+    fn frame_size(&self) -> PhysicalSize<u32> { self.state.frame_size() }
+
+    #[inline]
+    fn set_position(&mut self, position: Position) { self.state.set_position(position); }
+
+    #[inline]
+    fn position(&self) -> PhysicalPosition<i32> { self.state.position() }
+
+    #[inline]
+    fn set_min_size(&mut self, min_size: Size) { self.state.set_min_size(min_size); }
+
+    #[inline]
+    fn min_size(&self) -> PhysicalSize<u32> { self.state.min_size() }
+
+    #[inline]
+    fn set_max_size(&mut self, max_size: Size) { self.state.set_max_size(max_size); }
+
+    #[inline]
+    fn max_size(&self) -> PhysicalSize<u32> { self.state.max_size() }
+
+    #[inline]
+    fn maximize(&mut self) { self.state.maximize(); }
+
+    #[inline]
+    fn is_maximized(&self) -> bool { self.state.is_maximized() }
+
+    #[inline]
+    fn content_size(&self) -> PhysicalSize<u32> { self.state.content_size() }
+
+    #[inline]
+    fn is_visible(&self) -> bool { self.state.is_visible() }
+
+    #[inline]
+    fn close(&mut self) { self.state.close(); }
+
+    #[inline]
+    fn minimize(&mut self) { self.state.minimize(); }
+
+    #[inline]
+    fn is_minimized(&self) -> bool { self.state.is_minimized() }
+
+    #[inline]
+    fn restore(&mut self) { self.state.restore(); }
+
+    #[inline]
+    fn scale_factor(&self) -> f64 { self.state.scale_factor() }
+
+    #[inline]
+    fn current_monitor(&self) -> Option<Monitor> { self.state.current_monitor() }
+
+    #[inline]
+    fn set_ime_allowed(&mut self, allowed: bool) { self.state.set_ime_allowed(allowed); }
+
+    #[inline]
+    fn is_ime_allowed(&self) -> bool { self.state.is_ime_allowed() }
+
+    #[inline]
+    fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        self.state.set_ime_cursor_area(position, size);
+    }
+
+    #[inline]
+    fn request_redraw(&mut self) { self.state.request_redraw(); }
+
+    #[inline]
+    fn pre_present_notify(&mut self) {
+        // X11 has no frame callbacks, redraws are not throttled.
+    }
+
+    #[inline]
+    fn set_cursor(&mut self, cursor: Cursor) { self.state.set_cursor(cursor); }
+
+    #[inline]
+    fn cursor(&self) -> Cursor { self.state.cursor() }
+
+    #[inline]
+    fn set_cursor_visible(&mut self, visible: bool) { self.state.set_cursor_visible(visible); }
+
+    #[inline]
+    fn is_cursor_visible(&self) -> bool { self.state.is_cursor_visible() }
+
+    #[inline]
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error> {
+        self.state.set_cursor_grab(mode)
+    }
+
+    #[inline]
+    fn cursor_grab(&self) -> CursorGrabMode { self.state.cursor_grab() }
+
+    #[inline]
+    fn set_cursor_position(&mut self, position: Position) -> Result<(), Error> {
+        self.state.set_cursor_position(position)
+    }
+
+    fn show_context_menu(&mut self, menu: &Menu, position: Position) -> Result<(), Error> {
+        self.state.show_context_menu(menu, position)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::WindowOptions;
+
+// See: https://github.com/gnome/gtk/blob/main/gdk/x11/MwmUtil.h
+const MWM_HINTS_FUNCTIONS: u32 = 1 << 0;
+const MWM_HINTS_DECORATIONS: u32 = 1 << 1;
+
+const MWM_FUNC_RESIZE: u32 = 1 << 1;
+const MWM_FUNC_MOVE: u32 = 1 << 2;
+const MWM_FUNC_MINIMIZE: u32 = 1 << 3;
+const MWM_FUNC_MAXIMIZE: u32 = 1 << 4;
+const MWM_FUNC_CLOSE: u32 = 1 << 5;
+
+const MWM_DECOR_BORDER: u32 = 1 << 1;
+const MWM_DECOR_RESIZEH: u32 = 1 << 2;
+const MWM_DECOR_TITLE: u32 = 1 << 3;
+const MWM_DECOR_MENU: u32 = 1 << 4;
+const MWM_DECOR_MINIMIZE: u32 = 1 << 5;
+const MWM_DECOR_MAXIMIZE: u32 = 1 << 6;
+
+/// Converts window options into the `_MOTIF_WM_HINTS` property value.
+pub(super) fn to_motif_hints(options: &WindowOptions) -> [u32; 5] {
+    let mut functions = 0;
+    let mut decorations = 0;
+
+    if options.draggable {
+        functions |= MWM_FUNC_MOVE;
+    }
+    if options.resizable {
+        functions |= MWM_FUNC_RESIZE;
+        decorations |= MWM_DECOR_RESIZEH;
+    }
+    if options.minimizable {
+        functions |= MWM_FUNC_MINIMIZE;
+        decorations |= MWM_DECOR_MINIMIZE;
+    }
+    if options.fullscreen {
+        functions |= MWM_FUNC_MAXIMIZE;
+        decorations |= MWM_DECOR_MAXIMIZE;
+    }
+    if options.closable {
+        functions |= MWM_FUNC_CLOSE;
+    }
+    if options.titled {
+        decorations |= MWM_DECOR_TITLE | MWM_DECOR_MENU;
+    }
+    if !options.borderless {
+        decorations |= MWM_DECOR_BORDER;
+    } else {
+        decorations = 0;
+    }
+
+    [
+        MWM_HINTS_FUNCTIONS | MWM_HINTS_DECORATIONS,
+        functions,
+        decorations,
+        0,
+        0,
+    ]
+}
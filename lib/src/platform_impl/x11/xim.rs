@@ -0,0 +1,836 @@
+use std::collections::{hash_map::Entry, HashMap, VecDeque};
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{
+        AtomEnum,
+        ChangeWindowAttributesAux,
+        ClientMessageEvent,
+        ConnectionExt as _,
+        CreateWindowAux,
+        EventMask,
+        KeyPressEvent,
+        PropMode,
+        Window as XWindow,
+        WindowClass,
+        KEY_PRESS_EVENT,
+        KEY_RELEASE_EVENT,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT,
+    COPY_FROM_PARENT,
+};
+
+use super::{atoms::Atoms, xim_protocol::*};
+use crate::{ImeEvent, KeyState};
+
+/// Supported input styles in the order of preference.
+const INPUT_STYLES: [u32; 7] = [
+    XIM_PREEDIT_CALLBACKS | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_CALLBACKS | XIM_STATUS_NONE,
+    XIM_PREEDIT_POSITION | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_POSITION | XIM_STATUS_NONE,
+    XIM_PREEDIT_NOTHING | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_NOTHING | XIM_STATUS_NONE,
+    XIM_PREEDIT_NONE | XIM_STATUS_NONE,
+];
+
+/// Text encodings in the order of preference, UTF-8 names go first.
+const ENCODINGS: [&str; 3] = ["UTF8_STRING", "UTF-8", "COMPOUND_TEXT"];
+const UTF8_ENCODINGS: usize = 2;
+
+/// Messages that fit a client message are sent directly.
+const CLIENT_MESSAGE_SIZE: usize = 20;
+
+/// Connection state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// No input method server.
+    Disconnected,
+    /// Waiting for the server transport window.
+    Connecting,
+    /// Waiting for replies to the setup requests.
+    Opening,
+    /// Input contexts can be created.
+    Ready,
+}
+
+/// Input context of a window.
+#[derive(Debug, Default)]
+struct InputContext {
+    id:      Option<u16>,
+    spot:    Option<(i16, i16)>,
+    preedit: Vec<char>,
+    caret:   usize,
+}
+
+impl InputContext {
+    /// Replaces the changed characters of the composition with `text`.
+    fn draw(&mut self, caret: usize, change_first: usize, change_length: usize, text: &str) {
+        let start = change_first.min(self.preedit.len());
+        let end = change_first
+            .saturating_add(change_length)
+            .min(self.preedit.len());
+        self.preedit.splice(start..end, text.chars());
+        self.caret = caret.min(self.preedit.len());
+    }
+
+    /// Moves the caret in the direction and returns its new position.
+    fn move_caret(&mut self, position: usize, direction: u32) -> usize {
+        let caret = self.caret;
+        self.caret = match direction {
+            XIM_FORWARD_CHAR => caret + 1,
+            XIM_BACKWARD_CHAR => caret.saturating_sub(1),
+            XIM_LINE_START => 0,
+            XIM_LINE_END => self.preedit.len(),
+            XIM_ABSOLUTE_POSITION => position,
+            _ => caret,
+        }
+        .min(self.preedit.len());
+        self.caret
+    }
+
+    fn preedit_event(&self) -> ImeEvent {
+        if self.preedit.is_empty() {
+            return ImeEvent::Preedit {
+                text:   String::new(),
+                cursor: None,
+            };
+        }
+
+        let text: String = self.preedit.iter().collect();
+        let caret = self.preedit[..self.caret.min(self.preedit.len())]
+            .iter()
+            .map(|character| character.len_utf8())
+            .sum();
+        ImeEvent::Preedit {
+            text,
+            cursor: Some((caret, caret)),
+        }
+    }
+}
+
+/// Events generated by the input method.
+pub(super) enum XimEvent {
+    /// Input method event for the window.
+    Ime(XWindow, ImeEvent),
+    /// A key event that hasn't been consumed by the input method.
+    Key(KeyPressEvent, KeyState),
+}
+
+/// Input method client (X Input Method protocol over the X transport).
+///
+/// Key events of windows allowing input methods are forwarded to the server,
+/// which either composes text or sends them back.
+#[derive(Debug)]
+pub(super) struct Xim {
+    atoms:          Atoms,
+    window:         XWindow,
+    server_window:  XWindow,
+    comm_window:    XWindow,
+    state:          State,
+    im_id:          u16,
+    ic_attributes:  HashMap<String, u16>,
+    style_id:       Option<u16>,
+    input_style:    u32,
+    is_utf8:        bool,
+    buffer:         Vec<u8>,
+    input_contexts: HashMap<XWindow, InputContext>,
+    pending_ics:    VecDeque<XWindow>,
+    focus:          Option<XWindow>,
+}
+
+impl Xim {
+    /// Connects to the input method server.
+    ///
+    /// Input methods are optional, so connection errors only disable them.
+    pub(super) fn new(conn: &RustConnection, root: XWindow, atoms: Atoms) -> Self {
+        let mut xim = Self {
+            atoms,
+            window: 0,
+            server_window: 0,
+            comm_window: 0,
+            state: State::Disconnected,
+            im_id: 0,
+            ic_attributes: HashMap::new(),
+            style_id: None,
+            input_style: XIM_PREEDIT_NOTHING | XIM_STATUS_NOTHING,
+            is_utf8: false,
+            buffer: Vec::new(),
+            input_contexts: HashMap::new(),
+            pending_ics: VecDeque::new(),
+            focus: None,
+        };
+        let _ = xim.connect(conn, root);
+        xim
+    }
+
+    fn connect(&mut self, conn: &RustConnection, root: XWindow) -> Option<()> {
+        // Servers are listed in the `XIM_SERVERS` property of the root window
+        let servers = conn
+            .get_property(false, root, self.atoms.XIM_SERVERS, AtomEnum::ATOM, 0, 1024)
+            .ok()?
+            .reply()
+            .ok()?;
+        let servers: Vec<u32> = servers.value32()?.collect();
+
+        // `XMODIFIERS=@im=<name>` selects the server
+        let preferred = std::env::var("XMODIFIERS")
+            .ok()
+            .and_then(|value| value.strip_prefix("@im=").map(str::to_owned));
+        let mut server = None;
+        for atom in servers.iter() {
+            let name = conn.get_atom_name(*atom).ok()?.reply().ok()?.name;
+            let name = String::from_utf8_lossy(&name).into_owned();
+            let owner = conn.get_selection_owner(*atom).ok()?.reply().ok()?.owner;
+            if owner == x11rb::NONE {
+                continue;
+            }
+            let is_preferred = preferred
+                .as_ref()
+                .map(|preferred| name.strip_prefix("@server=") == Some(preferred.as_str()))
+                .unwrap_or(false);
+            if is_preferred || server.is_none() {
+                server = Some(owner);
+            }
+            if is_preferred {
+                break;
+            }
+        }
+        let server = server?;
+
+        // Create a transport window
+        let window = conn.generate_id().ok()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .ok()?;
+        // Track the server lifetime
+        conn.change_window_attributes(
+            server,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::STRUCTURE_NOTIFY),
+        )
+        .ok()?;
+
+        let event =
+            ClientMessageEvent::new(32, server, self.atoms._XIM_XCONNECT, [window, 0, 0, 0, 0]);
+        conn.send_event(false, server, EventMask::NO_EVENT, event)
+            .ok()?;
+
+        self.window = window;
+        self.server_window = server;
+        self.state = State::Connecting;
+        Some(())
+    }
+
+    /// Returns the transport window receiving server messages.
+    #[inline]
+    pub(super) fn window(&self) -> XWindow { self.window }
+
+    #[inline]
+    pub(super) fn is_server_window(&self, window: XWindow) -> bool {
+        self.state != State::Disconnected && window == self.server_window
+    }
+
+    /// Resets the client after the server has gone.
+    ///
+    /// Returns events for windows that have lost their input contexts.
+    pub(super) fn disconnect(&mut self) -> Vec<XimEvent> {
+        self.state = State::Disconnected;
+        self.pending_ics.clear();
+        self.buffer.clear();
+        self.input_contexts
+            .iter_mut()
+            .filter_map(|(window, input_context)| {
+                input_context.preedit.clear();
+                input_context.id.take()?;
+                Some(XimEvent::Ime(*window, ImeEvent::Disabled))
+            })
+            .collect()
+    }
+
+    /// Allows or disallows input methods for the window.
+    ///
+    /// Returns an event if input methods have been disabled, enabling
+    /// is reported when the server creates an input context.
+    pub(super) fn set_allowed(
+        &mut self,
+        conn: &RustConnection,
+        window: XWindow,
+        allowed: bool,
+    ) -> Option<ImeEvent> {
+        if allowed {
+            if let Entry::Vacant(entry) = self.input_contexts.entry(window) {
+                entry.insert(InputContext::default());
+                self.create_ic(conn, window);
+            }
+            None
+        } else {
+            let id = self.input_contexts.remove(&window)?.id?;
+            self.send(
+                conn,
+                XIM_DESTROY_IC,
+                Writer::default().u16(self.im_id).u16(id),
+            );
+            Some(ImeEvent::Disabled)
+        }
+    }
+
+    /// Sets a position of the text cursor baseline in window coordinates.
+    pub(super) fn set_spot(&mut self, conn: &RustConnection, window: XWindow, spot: (i16, i16)) {
+        let id = match self.input_contexts.get_mut(&window) {
+            Some(input_context) => {
+                input_context.spot = Some(spot);
+                input_context.id
+            }
+            None => None,
+        };
+        if let (Some(id), Some(attributes)) = (id, self.preedit_attributes(spot)) {
+            let message = Writer::default()
+                .u16(self.im_id)
+                .u16(id)
+                .u16(attributes.len() as u16)
+                .u16(0)
+                .bytes(&attributes);
+            self.send(conn, XIM_SET_IC_VALUES, message);
+        }
+    }
+
+    /// Updates the focus of input contexts.
+    pub(super) fn set_focus(&mut self, conn: &RustConnection, window: XWindow, focused: bool) {
+        if focused {
+            self.focus = Some(window);
+        } else if self.focus == Some(window) {
+            self.focus = None;
+        }
+
+        let opcode = if focused {
+            XIM_SET_IC_FOCUS
+        } else {
+            XIM_UNSET_IC_FOCUS
+        };
+        if let Some(id) = self.ic_id(window) {
+            self.send(conn, opcode, Writer::default().u16(self.im_id).u16(id));
+        }
+    }
+
+    /// Forwards a key event to the input method.
+    ///
+    /// Returns `false` if the window has no input context.
+    pub(super) fn forward_key(&mut self, conn: &RustConnection, event: &KeyPressEvent) -> bool {
+        let id = match self.ic_id(event.event) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        let raw_event: [u8; 32] = event.into();
+        let message = Writer::default()
+            .u16(self.im_id)
+            .u16(id)
+            .u16(0)
+            .u16(0)
+            .bytes(&raw_event);
+        self.send(conn, XIM_FORWARD_EVENT, message);
+        true
+    }
+
+    /// Handles a client message sent to the transport window.
+    pub(super) fn handle_client_message(
+        &mut self,
+        conn: &RustConnection,
+        event: &ClientMessageEvent,
+    ) -> Vec<XimEvent> {
+        if event.type_ == self.atoms._XIM_XCONNECT && event.format == 32 {
+            if self.state == State::Connecting {
+                self.comm_window = event.data.as_data32()[0];
+                self.state = State::Opening;
+                // Version 1.0 of the protocol without authentication
+                let message = Writer::default().u8(b'l').u8(0).u16(1).u16(0).u16(0);
+                self.send(conn, XIM_CONNECT, message);
+            }
+            return Vec::new();
+        }
+
+        if event.type_ == self.atoms._XIM_MOREDATA && event.format == 8 {
+            self.buffer.extend(event.data.as_data8());
+            return Vec::new();
+        }
+        if event.type_ != self.atoms._XIM_PROTOCOL {
+            return Vec::new();
+        }
+
+        let message = match event.format {
+            8 => {
+                let mut message = std::mem::take(&mut self.buffer);
+                message.extend(event.data.as_data8());
+                message
+            }
+            32 => {
+                // Large messages are passed via a property
+                let [length, property, ..] = event.data.as_data32();
+                match conn
+                    .get_property(
+                        true,
+                        self.window,
+                        property,
+                        AtomEnum::ANY,
+                        0,
+                        length.div_ceil(4),
+                    )
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                {
+                    Some(reply) => reply.value,
+                    None => return Vec::new(),
+                }
+            }
+            _ => return Vec::new(),
+        };
+
+        self.handle_message(conn, &message).unwrap_or_default()
+    }
+
+    fn handle_message(&mut self, conn: &RustConnection, message: &[u8]) -> Option<Vec<XimEvent>> {
+        let mut events = Vec::new();
+        match ServerMessage::parse(message)? {
+            ServerMessage::ConnectReply => {
+                let message = Writer::default().str(&locale()).pad();
+                self.send(conn, XIM_OPEN, message);
+            }
+            ServerMessage::OpenReply {
+                im_id,
+                im_attributes,
+                ic_attributes,
+            } => {
+                self.im_id = im_id;
+                self.ic_attributes = ic_attributes;
+                self.style_id = im_attributes.get("queryInputStyle").copied();
+
+                let mut encodings = Writer::default();
+                for encoding in ENCODINGS.iter() {
+                    encodings = encodings.str(encoding);
+                }
+                let message = Writer::default()
+                    .u16(self.im_id)
+                    .u16(encodings.len() as u16)
+                    .bytes(encodings.as_bytes())
+                    .pad()
+                    .u16(0)
+                    .u16(0);
+                self.send(conn, XIM_ENCODING_NEGOTIATION, message);
+            }
+            ServerMessage::EncodingNegotiationReply {
+                index,
+            } => {
+                self.is_utf8 = (0..UTF8_ENCODINGS as i16).contains(&index);
+
+                match self.style_id {
+                    Some(style_id) => {
+                        let message = Writer::default().u16(self.im_id).u16(2).u16(style_id).pad();
+                        self.send(conn, XIM_GET_IM_VALUES, message);
+                    }
+                    None => self.set_ready(conn),
+                }
+            }
+            ServerMessage::GetImValuesReply {
+                attributes,
+            } => {
+                let styles = attributes
+                    .into_iter()
+                    .find(|(id, _)| Some(*id) == self.style_id)
+                    .and_then(|(_, value)| read_input_styles(value))
+                    .unwrap_or_default();
+                if let Some(style) = INPUT_STYLES.iter().find(|style| styles.contains(style)) {
+                    self.input_style = *style;
+                }
+                self.set_ready(conn);
+            }
+            ServerMessage::CreateIcReply {
+                ic_id,
+            } => {
+                let window = self.pending_ics.pop_front()?;
+                match self.input_contexts.get_mut(&window) {
+                    Some(input_context) => {
+                        input_context.id = Some(ic_id);
+                        if self.focus == Some(window) {
+                            self.send(
+                                conn,
+                                XIM_SET_IC_FOCUS,
+                                Writer::default().u16(self.im_id).u16(ic_id),
+                            );
+                        }
+                        events.push(XimEvent::Ime(window, ImeEvent::Enabled));
+                    }
+                    // Input methods have been disallowed in the meantime
+                    None => self.send(
+                        conn,
+                        XIM_DESTROY_IC,
+                        Writer::default().u16(self.im_id).u16(ic_id),
+                    ),
+                }
+            }
+            ServerMessage::ForwardEvent {
+                im_id,
+                ic_id,
+                synchronous,
+                event,
+            } => {
+                if synchronous {
+                    self.send(
+                        conn,
+                        XIM_SYNC_REPLY,
+                        Writer::default().u16(im_id).u16(ic_id),
+                    );
+                }
+
+                let state = match event.response_type & 0x7F {
+                    KEY_PRESS_EVENT => KeyState::Pressed,
+                    KEY_RELEASE_EVENT => KeyState::Released,
+                    _ => return Some(events),
+                };
+                events.push(XimEvent::Key(event, state));
+            }
+            ServerMessage::Sync {
+                im_id,
+                ic_id,
+            } => {
+                self.send(
+                    conn,
+                    XIM_SYNC_REPLY,
+                    Writer::default().u16(im_id).u16(ic_id),
+                );
+            }
+            ServerMessage::Commit {
+                im_id,
+                ic_id,
+                synchronous,
+                text,
+            } => {
+                if synchronous {
+                    self.send(
+                        conn,
+                        XIM_SYNC_REPLY,
+                        Writer::default().u16(im_id).u16(ic_id),
+                    );
+                }
+                let text = match text {
+                    Some(text) => decode_text(text, self.is_utf8),
+                    None => return Some(events),
+                };
+
+                let (window, input_context) = self.input_context_mut(ic_id)?;
+                // The composition is replaced by committed text
+                if !input_context.preedit.is_empty() {
+                    input_context.preedit.clear();
+                    events.push(XimEvent::Ime(window, input_context.preedit_event()));
+                }
+                events.push(XimEvent::Ime(window, ImeEvent::Commit(text)));
+            }
+            ServerMessage::PreeditStart {
+                im_id,
+                ic_id,
+            } => {
+                // No limit of the composition length
+                let message = Writer::default().u16(im_id).u16(ic_id).u32(u32::MAX);
+                self.send(conn, XIM_PREEDIT_START_REPLY, message);
+            }
+            ServerMessage::PreeditDraw {
+                ic_id,
+                caret,
+                change_first,
+                change_length,
+                text,
+            } => {
+                let text = decode_text(text, self.is_utf8);
+                let (window, input_context) = self.input_context_mut(ic_id)?;
+                input_context.draw(caret, change_first, change_length, &text);
+                events.push(XimEvent::Ime(window, input_context.preedit_event()));
+            }
+            ServerMessage::PreeditCaret {
+                im_id,
+                ic_id,
+                position,
+                direction,
+            } => {
+                let (window, input_context) = self.input_context_mut(ic_id)?;
+                let caret = input_context.move_caret(position, direction);
+                if !input_context.preedit.is_empty() {
+                    events.push(XimEvent::Ime(window, input_context.preedit_event()));
+                }
+
+                let message = Writer::default().u16(im_id).u16(ic_id).u32(caret as u32);
+                self.send(conn, XIM_PREEDIT_CARET_REPLY, message);
+            }
+            ServerMessage::PreeditDone {
+                ic_id,
+            } => {
+                let (window, input_context) = self.input_context_mut(ic_id)?;
+                if !input_context.preedit.is_empty() {
+                    input_context.preedit.clear();
+                    input_context.caret = 0;
+                    events.push(XimEvent::Ime(window, input_context.preedit_event()));
+                }
+            }
+            ServerMessage::Other => (),
+        }
+        Some(events)
+    }
+
+    fn set_ready(&mut self, conn: &RustConnection) {
+        self.state = State::Ready;
+        let windows: Vec<XWindow> = self.input_contexts.keys().copied().collect();
+        for window in windows.into_iter() {
+            self.create_ic(conn, window);
+        }
+    }
+
+    fn create_ic(&mut self, conn: &RustConnection, window: XWindow) {
+        if self.state != State::Ready {
+            return;
+        }
+
+        let mut attributes = Writer::default();
+        for (name, value) in [
+            ("inputStyle", self.input_style),
+            ("clientWindow", window),
+            ("focusWindow", window),
+        ] {
+            if let Some(id) = self.ic_attributes.get(name) {
+                attributes = attributes.attribute(*id, &value.to_le_bytes());
+            }
+        }
+        let spot = self
+            .input_contexts
+            .get(&window)
+            .and_then(|input_context| input_context.spot);
+        if let Some(preedit_attributes) = spot.and_then(|spot| self.preedit_attributes(spot)) {
+            attributes = attributes.bytes(&preedit_attributes);
+        }
+
+        let message = Writer::default()
+            .u16(self.im_id)
+            .u16(attributes.len() as u16)
+            .bytes(attributes.as_bytes());
+        self.send(conn, XIM_CREATE_IC, message);
+        self.pending_ics.push_back(window);
+    }
+
+    /// Returns preedit attributes with the spot location.
+    fn preedit_attributes(&self, spot: (i16, i16)) -> Option<Vec<u8>> {
+        let preedit_id = *self.ic_attributes.get("preeditAttributes")?;
+        let spot_id = *self.ic_attributes.get("spotLocation")?;
+        Some(preedit_attributes(preedit_id, spot_id, spot))
+    }
+
+    #[inline]
+    fn ic_id(&self, window: XWindow) -> Option<u16> {
+        self.input_contexts
+            .get(&window)
+            .and_then(|input_context| input_context.id)
+    }
+
+    fn input_context_mut(&mut self, id: u16) -> Option<(XWindow, &mut InputContext)> {
+        self.input_contexts
+            .iter_mut()
+            .find(|(_, input_context)| input_context.id == Some(id))
+            .map(|(window, input_context)| (*window, input_context))
+    }
+
+    fn send(&self, conn: &RustConnection, opcode: u8, body: Writer) {
+        if self.state == State::Disconnected || self.state == State::Connecting {
+            return;
+        }
+
+        let message = body.message(opcode);
+
+        let event = if message.len() <= CLIENT_MESSAGE_SIZE {
+            let mut data = [0u8; CLIENT_MESSAGE_SIZE];
+            data[..message.len()].copy_from_slice(&message);
+            ClientMessageEvent::new(8, self.comm_window, self.atoms._XIM_PROTOCOL, data)
+        } else {
+            let _ = conn.change_property8(
+                PropMode::APPEND,
+                self.comm_window,
+                self.atoms._B3_XIM_DATA,
+                AtomEnum::STRING,
+                &message,
+            );
+            ClientMessageEvent::new(
+                32,
+                self.comm_window,
+                self.atoms._XIM_PROTOCOL,
+                [message.len() as u32, self.atoms._B3_XIM_DATA, 0, 0, 0],
+            )
+        };
+        let _ = conn.send_event(false, self.comm_window, EventMask::NO_EVENT, event);
+    }
+}
+
+/// Returns a locale name for the input method.
+fn locale() -> String {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    // Strip the encoding and the modifier, e.g. `en_US.UTF-8@euro`
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    match locale {
+        "" | "C" | "POSIX" => "en_US".to_owned(),
+        locale => locale.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies a preedit message to the input context like the client does.
+    fn apply(input_context: &mut InputContext, message: &[u8]) -> ImeEvent {
+        match ServerMessage::parse(message).unwrap() {
+            ServerMessage::PreeditDraw {
+                caret,
+                change_first,
+                change_length,
+                text,
+                ..
+            } => {
+                let text = decode_text(text, true);
+                input_context.draw(caret, change_first, change_length, &text);
+            }
+            ServerMessage::PreeditCaret {
+                position,
+                direction,
+                ..
+            } => {
+                input_context.move_caret(position, direction);
+            }
+            message => panic!("unexpected message {:?}", message),
+        }
+        input_context.preedit_event()
+    }
+
+    fn preedit(text: &str, cursor: Option<(usize, usize)>) -> ImeEvent {
+        ImeEvent::Preedit {
+            text: text.into(),
+            cursor,
+        }
+    }
+
+    #[test]
+    fn preedit_draws_splice_the_composition() {
+        let mut input_context = InputContext::default();
+
+        #[rustfmt::skip]
+        let draw = [
+            75, 0, 10, 0,
+            1, 0, 1, 0,                         // input method and context IDs
+            2, 0, 0, 0,                         // caret
+            0, 0, 0, 0,                         // first changed character
+            0, 0, 0, 0,                         // number of changed characters
+            0, 0, 0, 0,                         // status
+            6, 0, 0xe3, 0x81, 0x8b, 0xe3, 0x81, 0xaa, // "かな"
+            8, 0, 0, 0,                         // length of feedbacks
+            2, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        assert_eq!(
+            apply(&mut input_context, &draw),
+            preedit("かな", Some((6, 6)))
+        );
+
+        // The second character is replaced with two characters
+        #[rustfmt::skip]
+        let draw = [
+            75, 0, 10, 0,
+            1, 0, 1, 0,
+            3, 0, 0, 0,
+            1, 0, 0, 0,
+            1, 0, 0, 0,
+            0, 0, 0, 0,
+            6, 0, 0xe3, 0x82, 0x93, 0xe3, 0x81, 0x98, // "んじ"
+            8, 0, 0, 0,
+            2, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        assert_eq!(
+            apply(&mut input_context, &draw),
+            preedit("かんじ", Some((9, 9)))
+        );
+
+        // The first character is deleted, the caret is at the start
+        #[rustfmt::skip]
+        let draw = [
+            75, 0, 7, 0,
+            1, 0, 1, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            1, 0, 0, 0,
+            3, 0, 0, 0,                         // no string, no feedback
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(
+            apply(&mut input_context, &draw),
+            preedit("んじ", Some((0, 0)))
+        );
+
+        // Changes beyond the composition are clamped
+        #[rustfmt::skip]
+        let draw = [
+            75, 0, 7, 0,
+            1, 0, 1, 0,
+            9, 0, 0, 0,
+            0, 0, 0, 0,
+            9, 0, 0, 0,
+            3, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(apply(&mut input_context, &draw), preedit("", None));
+    }
+
+    #[test]
+    fn preedit_carets_move_the_cursor() {
+        let mut input_context = InputContext {
+            preedit: "aかb".chars().collect(),
+            caret: 3,
+            ..Default::default()
+        };
+        let caret = |position: u8, direction: u8| {
+            [
+                76, 0, 4, 0, 1, 0, 1, 0, position, 0, 0, 0, direction, 0, 0, 0, 0, 0, 0, 0,
+            ]
+        };
+
+        assert_eq!(
+            apply(&mut input_context, &caret(0, XIM_BACKWARD_CHAR as u8)),
+            preedit("aかb", Some((4, 4)))
+        );
+        assert_eq!(
+            apply(&mut input_context, &caret(0, XIM_BACKWARD_CHAR as u8)),
+            preedit("aかb", Some((1, 1)))
+        );
+        assert_eq!(
+            apply(&mut input_context, &caret(0, XIM_LINE_START as u8)),
+            preedit("aかb", Some((0, 0)))
+        );
+        assert_eq!(
+            apply(&mut input_context, &caret(0, XIM_FORWARD_CHAR as u8)),
+            preedit("aかb", Some((1, 1)))
+        );
+        assert_eq!(
+            apply(&mut input_context, &caret(9, XIM_ABSOLUTE_POSITION as u8)),
+            preedit("aかb", Some((5, 5)))
+        );
+    }
+}
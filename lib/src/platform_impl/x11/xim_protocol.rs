@@ -0,0 +1,610 @@
+use std::collections::HashMap;
+
+use x11rb::{protocol::xproto::KeyPressEvent, x11_utils::TryParse};
+
+// Request opcodes, see: https://www.x.org/releases/current/doc/libX11/XIM/xim.html
+pub(super) const XIM_CONNECT: u8 = 1;
+const XIM_CONNECT_REPLY: u8 = 2;
+pub(super) const XIM_OPEN: u8 = 30;
+const XIM_OPEN_REPLY: u8 = 31;
+pub(super) const XIM_ENCODING_NEGOTIATION: u8 = 38;
+const XIM_ENCODING_NEGOTIATION_REPLY: u8 = 39;
+pub(super) const XIM_GET_IM_VALUES: u8 = 42;
+const XIM_GET_IM_VALUES_REPLY: u8 = 43;
+pub(super) const XIM_CREATE_IC: u8 = 50;
+const XIM_CREATE_IC_REPLY: u8 = 51;
+pub(super) const XIM_DESTROY_IC: u8 = 52;
+pub(super) const XIM_SET_IC_VALUES: u8 = 54;
+pub(super) const XIM_SET_IC_FOCUS: u8 = 58;
+pub(super) const XIM_UNSET_IC_FOCUS: u8 = 59;
+pub(super) const XIM_FORWARD_EVENT: u8 = 60;
+const XIM_SYNC: u8 = 61;
+pub(super) const XIM_SYNC_REPLY: u8 = 62;
+const XIM_COMMIT: u8 = 63;
+const XIM_PREEDIT_START: u8 = 73;
+pub(super) const XIM_PREEDIT_START_REPLY: u8 = 74;
+const XIM_PREEDIT_DRAW: u8 = 75;
+const XIM_PREEDIT_CARET: u8 = 76;
+pub(super) const XIM_PREEDIT_CARET_REPLY: u8 = 77;
+const XIM_PREEDIT_DONE: u8 = 78;
+
+// Flags of forwarded events and commits
+const XIM_SYNCHRONOUS: u16 = 0x0001;
+const XIM_LOOKUP_CHARS: u16 = 0x0002;
+const XIM_LOOKUP_KEYSYM: u16 = 0x0004;
+
+// Flags of preedit draws
+const XIM_NO_STRING: u32 = 0x0001;
+
+// Caret directions
+pub(super) const XIM_FORWARD_CHAR: u32 = 0;
+pub(super) const XIM_BACKWARD_CHAR: u32 = 1;
+pub(super) const XIM_LINE_START: u32 = 8;
+pub(super) const XIM_LINE_END: u32 = 9;
+pub(super) const XIM_ABSOLUTE_POSITION: u32 = 10;
+
+// Input styles
+pub(super) const XIM_PREEDIT_CALLBACKS: u32 = 0x0002;
+pub(super) const XIM_PREEDIT_POSITION: u32 = 0x0004;
+pub(super) const XIM_PREEDIT_NOTHING: u32 = 0x0008;
+pub(super) const XIM_PREEDIT_NONE: u32 = 0x0010;
+pub(super) const XIM_STATUS_NOTHING: u32 = 0x0400;
+pub(super) const XIM_STATUS_NONE: u32 = 0x0800;
+
+/// Writes protocol messages (always in the little-endian byte order).
+#[derive(Default)]
+pub(super) struct Writer(Vec<u8>);
+
+impl Writer {
+    pub(super) fn u8(mut self, value: u8) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    pub(super) fn u16(mut self, value: u16) -> Self {
+        self.0.extend(value.to_le_bytes());
+        self
+    }
+
+    pub(super) fn u32(mut self, value: u32) -> Self {
+        self.0.extend(value.to_le_bytes());
+        self
+    }
+
+    pub(super) fn bytes(mut self, value: &[u8]) -> Self {
+        self.0.extend(value);
+        self
+    }
+
+    /// Writes a string (`STR`) without padding.
+    pub(super) fn str(self, value: &str) -> Self {
+        self.u8(value.len() as u8).bytes(value.as_bytes())
+    }
+
+    pub(super) fn pad(mut self) -> Self {
+        while !self.0.len().is_multiple_of(4) {
+            self.0.push(0);
+        }
+        self
+    }
+
+    /// Writes an IC attribute (`XICATTRIBUTE`).
+    pub(super) fn attribute(self, id: u16, value: &[u8]) -> Self {
+        self.u16(id).u16(value.len() as u16).bytes(value).pad()
+    }
+
+    #[inline]
+    pub(super) fn len(&self) -> usize { self.0.len() }
+
+    #[inline]
+    pub(super) fn as_bytes(&self) -> &[u8] { &self.0 }
+
+    /// Returns a message of the opcode with the written body.
+    pub(super) fn message(self, opcode: u8) -> Vec<u8> {
+        let body = self.pad().0;
+        Writer::default()
+            .u8(opcode)
+            .u8(0)
+            .u16((body.len() / 4) as u16)
+            .bytes(&body)
+            .0
+    }
+}
+
+/// Reads protocol messages.
+struct Reader<'a> {
+    data:     &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position + length)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> { self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]])) }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    #[inline]
+    fn i32(&mut self) -> Option<i32> { self.u32().map(|value| value as i32) }
+
+    /// Reads a non-negative `INT32` as an index or a length.
+    #[inline]
+    fn index(&mut self) -> Option<usize> { self.i32().map(|value| value.max(0) as usize) }
+
+    /// Skips padding of the data read since `start`.
+    fn pad(&mut self, start: usize) {
+        let length = self.position - start;
+        self.position = (self.position + (4 - length % 4) % 4).min(self.data.len());
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool { self.position >= self.data.len() }
+}
+
+/// Messages of the input method server the client handles.
+#[derive(Debug)]
+pub(super) enum ServerMessage<'a> {
+    ConnectReply,
+    OpenReply {
+        im_id:         u16,
+        im_attributes: HashMap<String, u16>,
+        ic_attributes: HashMap<String, u16>,
+    },
+    EncodingNegotiationReply {
+        /// Index of the chosen encoding, negative if none has been chosen.
+        index: i16,
+    },
+    GetImValuesReply {
+        /// IDs and values of IM attributes.
+        attributes: Vec<(u16, &'a [u8])>,
+    },
+    CreateIcReply {
+        ic_id: u16,
+    },
+    ForwardEvent {
+        im_id:       u16,
+        ic_id:       u16,
+        synchronous: bool,
+        event:       KeyPressEvent,
+    },
+    Sync {
+        im_id: u16,
+        ic_id: u16,
+    },
+    Commit {
+        im_id:       u16,
+        ic_id:       u16,
+        synchronous: bool,
+        /// Committed text, if any.
+        text:        Option<&'a [u8]>,
+    },
+    PreeditStart {
+        im_id: u16,
+        ic_id: u16,
+    },
+    PreeditDraw {
+        ic_id:         u16,
+        caret:         usize,
+        change_first:  usize,
+        change_length: usize,
+        /// Text replacing the changed characters.
+        text:          &'a [u8],
+    },
+    PreeditCaret {
+        im_id:     u16,
+        ic_id:     u16,
+        position:  usize,
+        direction: u32,
+    },
+    PreeditDone {
+        ic_id: u16,
+    },
+    /// Errors, event masks, trigger keys and other messages that don't
+    /// require any actions.
+    Other,
+}
+
+impl<'a> ServerMessage<'a> {
+    /// Parses a message with its header.
+    pub(super) fn parse(message: &'a [u8]) -> Option<Self> {
+        let mut reader = Reader::new(message);
+        let header = reader.bytes(4)?;
+        let opcode = header[0];
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize * 4;
+        let mut reader = Reader::new(reader.bytes(length)?);
+
+        let message = match opcode {
+            XIM_CONNECT_REPLY => Self::ConnectReply,
+            XIM_OPEN_REPLY => {
+                let im_id = reader.u16()?;
+                let length = reader.u16()? as usize;
+                let im_attributes = read_attributes(reader.bytes(length)?)?;
+                let length = reader.u16()? as usize;
+                reader.u16()?;
+                let ic_attributes = read_attributes(reader.bytes(length)?)?;
+                Self::OpenReply {
+                    im_id,
+                    im_attributes,
+                    ic_attributes,
+                }
+            }
+            XIM_ENCODING_NEGOTIATION_REPLY => {
+                reader.u16()?;
+                reader.u16()?;
+                Self::EncodingNegotiationReply {
+                    index: reader.u16()? as i16,
+                }
+            }
+            XIM_GET_IM_VALUES_REPLY => {
+                reader.u16()?;
+                let length = reader.u16()? as usize;
+                let mut list = Reader::new(reader.bytes(length)?);
+                let mut attributes = Vec::new();
+                while !list.is_empty() {
+                    let start = list.position;
+                    let id = list.u16()?;
+                    let length = list.u16()? as usize;
+                    attributes.push((id, list.bytes(length)?));
+                    list.pad(start);
+                }
+                Self::GetImValuesReply {
+                    attributes,
+                }
+            }
+            XIM_CREATE_IC_REPLY => {
+                reader.u16()?;
+                Self::CreateIcReply {
+                    ic_id: reader.u16()?,
+                }
+            }
+            XIM_FORWARD_EVENT => {
+                let im_id = reader.u16()?;
+                let ic_id = reader.u16()?;
+                let flag = reader.u16()?;
+                reader.u16()?;
+                let (event, _) = KeyPressEvent::try_parse(reader.bytes(32)?).ok()?;
+                Self::ForwardEvent {
+                    im_id,
+                    ic_id,
+                    synchronous: flag & XIM_SYNCHRONOUS != 0,
+                    event,
+                }
+            }
+            XIM_SYNC => Self::Sync {
+                im_id: reader.u16()?,
+                ic_id: reader.u16()?,
+            },
+            XIM_COMMIT => {
+                let im_id = reader.u16()?;
+                let ic_id = reader.u16()?;
+                let flag = reader.u16()?;
+                if flag & XIM_LOOKUP_KEYSYM != 0 {
+                    reader.u16()?;
+                    reader.u32()?;
+                }
+                let text = match flag & XIM_LOOKUP_CHARS {
+                    0 => None,
+                    _ => {
+                        let length = reader.u16()? as usize;
+                        Some(reader.bytes(length)?)
+                    }
+                };
+                Self::Commit {
+                    im_id,
+                    ic_id,
+                    synchronous: flag & XIM_SYNCHRONOUS != 0,
+                    text,
+                }
+            }
+            XIM_PREEDIT_START => Self::PreeditStart {
+                im_id: reader.u16()?,
+                ic_id: reader.u16()?,
+            },
+            XIM_PREEDIT_DRAW => {
+                reader.u16()?;
+                let ic_id = reader.u16()?;
+                let caret = reader.index()?;
+                let change_first = reader.index()?;
+                let change_length = reader.index()?;
+                let status = reader.u32()?;
+                let length = reader.u16()? as usize;
+                let text = match status & XIM_NO_STRING {
+                    0 => reader.bytes(length)?,
+                    _ => &[],
+                };
+                Self::PreeditDraw {
+                    ic_id,
+                    caret,
+                    change_first,
+                    change_length,
+                    text,
+                }
+            }
+            XIM_PREEDIT_CARET => Self::PreeditCaret {
+                im_id:     reader.u16()?,
+                ic_id:     reader.u16()?,
+                position:  reader.index()?,
+                direction: reader.u32()?,
+            },
+            XIM_PREEDIT_DONE => {
+                reader.u16()?;
+                Self::PreeditDone {
+                    ic_id: reader.u16()?,
+                }
+            }
+            _ => Self::Other,
+        };
+        Some(message)
+    }
+}
+
+/// Reads a list of attribute definitions (`XIMATTR`/`XICATTR`).
+fn read_attributes(data: &[u8]) -> Option<HashMap<String, u16>> {
+    let mut list = Reader::new(data);
+    let mut attributes = HashMap::new();
+    while !list.is_empty() {
+        let start = list.position;
+        let id = list.u16()?;
+        list.u16()?;
+        let name_length = list.u16()? as usize;
+        let name = list.bytes(name_length)?;
+        // The name is padded along with its length
+        list.pad(start + 4);
+        attributes.insert(String::from_utf8_lossy(name).into_owned(), id);
+    }
+    Some(attributes)
+}
+
+/// Reads a value of the `queryInputStyle` IM attribute (`XIMSTYLES`).
+pub(super) fn read_input_styles(value: &[u8]) -> Option<Vec<u32>> {
+    let mut value = Reader::new(value);
+    let count = value.u16()?;
+    value.u16()?;
+    Some((0..count).filter_map(|_| value.u32()).collect())
+}
+
+/// Returns preedit attributes with the spot location.
+pub(super) fn preedit_attributes(preedit_id: u16, spot_id: u16, spot: (i16, i16)) -> Vec<u8> {
+    let point = Writer::default().u16(spot.0 as u16).u16(spot.1 as u16);
+    let nested = Writer::default().attribute(spot_id, &point.0);
+    Writer::default().attribute(preedit_id, &nested.0).0
+}
+
+/// Decodes text in the negotiated encoding.
+pub(super) fn decode_text(text: &[u8], is_utf8: bool) -> String {
+    if is_utf8 {
+        String::from_utf8_lossy(text).into_owned()
+    } else {
+        decode_compound_text(text)
+    }
+}
+
+/// Decodes compound text.
+///
+/// Only ASCII, Latin-1 and UTF-8 segments are supported, characters of other
+/// character sets are skipped.
+fn decode_compound_text(text: &[u8]) -> String {
+    let mut result = String::new();
+    let mut utf8 = Vec::new();
+    let mut is_utf8 = false;
+    let mut is_ascii = true;
+    let mut is_latin1 = true;
+
+    let mut i = 0;
+    while i < text.len() {
+        let byte = text[i];
+        if byte == 0x1B {
+            // Escape sequence: ESC, intermediate bytes and a final byte
+            let start = i + 1;
+            let mut end = start;
+            while end < text.len() && (0x20..=0x2F).contains(&text[end]) {
+                end += 1;
+            }
+            let sequence = &text[start..(end + 1).min(text.len())];
+            match sequence {
+                b"%G" => is_utf8 = true,
+                b"%@" => {
+                    is_utf8 = false;
+                    result.push_str(&String::from_utf8_lossy(&utf8));
+                    utf8.clear();
+                }
+                b"(B" => is_ascii = true,
+                b"-A" => is_latin1 = true,
+                // Multi-byte sets are designated with an extra `$`
+                [b')' | b'-', ..] | [b'$', b')' | b'-', ..] => is_latin1 = false,
+                [b'(' | b'$', ..] => is_ascii = false,
+                _ => (),
+            }
+            i = end + 1;
+            continue;
+        }
+
+        if is_utf8 {
+            utf8.push(byte);
+        } else if matches!(byte, b'\n' | b'\t')
+            || (0x20..0x7F).contains(&byte) && is_ascii
+            || byte >= 0xA0 && is_latin1
+        {
+            result.push(byte as char);
+        }
+        i += 1;
+    }
+
+    result.push_str(&String::from_utf8_lossy(&utf8));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_messages() {
+        let message = Writer::default().u16(1).u16(2).message(XIM_SYNC_REPLY);
+        assert_eq!(message, [XIM_SYNC_REPLY, 0, 1, 0, 1, 0, 2, 0]);
+
+        // The body is padded to 4 bytes
+        let message = Writer::default().str("en_US").message(XIM_OPEN);
+        assert_eq!(
+            message,
+            [XIM_OPEN, 0, 2, 0, 5, b'e', b'n', b'_', b'U', b'S', 0, 0]
+        );
+    }
+
+    #[test]
+    fn encodes_preedit_attributes() {
+        assert_eq!(
+            preedit_attributes(5, 7, (10, -2)),
+            [5, 0, 8, 0, 7, 0, 4, 0, 10, 0, 0xfe, 0xff]
+        );
+    }
+
+    #[test]
+    fn parses_open_reply() {
+        #[rustfmt::skip]
+        let message = [
+            XIM_OPEN_REPLY, 0, 10, 0,
+            3, 0,                    // input method ID
+            24, 0,                   // length of IM attributes
+            1, 0, 10, 0, 15, 0,      // ID, type and name length
+            b'q', b'u', b'e', b'r', b'y', b'I', b'n', b'p', b'u', b't', b'S', b't', b'y', b'l', b'e',
+            0, 0, 0,
+            8, 0, 0, 0,              // length of IC attributes
+            2, 0, 3, 0, 1, 0, b'x',
+            0,
+        ];
+        let Some(ServerMessage::OpenReply {
+            im_id,
+            im_attributes,
+            ic_attributes,
+        }) = ServerMessage::parse(&message)
+        else {
+            panic!("not an open reply");
+        };
+        assert_eq!(im_id, 3);
+        assert_eq!(
+            im_attributes,
+            HashMap::from([("queryInputStyle".into(), 1)])
+        );
+        assert_eq!(ic_attributes, HashMap::from([("x".into(), 2)]));
+    }
+
+    #[test]
+    fn parses_commits() {
+        #[rustfmt::skip]
+        let message = [
+            XIM_COMMIT, 0, 4, 0,
+            1, 0, 1, 0,                         // input method and context IDs
+            3, 0,                               // synchronous, characters
+            6, 0, 0xe6, 0xbc, 0xa2, 0xe5, 0xad, 0x97, // "漢字"
+            0, 0,
+        ];
+        let Some(ServerMessage::Commit {
+            ic_id: 1,
+            synchronous: true,
+            text: Some(text),
+            ..
+        }) = ServerMessage::parse(&message)
+        else {
+            panic!("not a synchronous commit of text");
+        };
+        assert_eq!(decode_text(text, true), "漢字");
+
+        #[rustfmt::skip]
+        let message = [
+            XIM_COMMIT, 0, 3, 0,
+            1, 0, 1, 0,
+            4, 0,                               // key symbol
+            0, 0, 0x61, 0, 0, 0,
+        ];
+        assert!(matches!(
+            ServerMessage::parse(&message),
+            Some(ServerMessage::Commit {
+                synchronous: false,
+                text: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_preedit_draws() {
+        #[rustfmt::skip]
+        let message = [
+            XIM_PREEDIT_DRAW, 0, 10, 0,
+            1, 0, 1, 0,                         // input method and context IDs
+            3, 0, 0, 0,                         // caret
+            1, 0, 0, 0,                         // first changed character
+            1, 0, 0, 0,                         // number of changed characters
+            0, 0, 0, 0,                         // status
+            6, 0, 0xe3, 0x82, 0x93, 0xe3, 0x81, 0x98, // "んじ"
+            8, 0, 0, 0,                         // length of feedbacks
+            2, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        let Some(ServerMessage::PreeditDraw {
+            ic_id: 1,
+            caret: 3,
+            change_first: 1,
+            change_length: 1,
+            text,
+        }) = ServerMessage::parse(&message)
+        else {
+            panic!("not a preedit draw");
+        };
+        assert_eq!(text, "んじ".as_bytes());
+
+        #[rustfmt::skip]
+        let message = [
+            XIM_PREEDIT_DRAW, 0, 7, 0,
+            1, 0, 1, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            0xff, 0xff, 0xff, 0xff,             // negative lengths are clamped
+            3, 0, 0, 0,                         // no string, no feedback
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert!(matches!(
+            ServerMessage::parse(&message),
+            Some(ServerMessage::PreeditDraw {
+                change_length: 0,
+                text: [],
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        assert!(ServerMessage::parse(&[XIM_PREEDIT_DRAW, 0, 10, 0, 1, 0, 1, 0]).is_none());
+        assert!(ServerMessage::parse(&[XIM_PREEDIT_DRAW, 0, 1, 0, 1, 0, 1, 0]).is_none());
+        assert!(matches!(
+            ServerMessage::parse(&[0xff, 0, 0, 0]),
+            Some(ServerMessage::Other)
+        ));
+    }
+
+    #[test]
+    fn decodes_compound_text() {
+        assert_eq!(decode_text(b"a\x1b%G\xe3\x81\x8b\x1b%@b", false), "aかb");
+        assert_eq!(
+            decode_text(b"\x1b-A\xe9\x1b$)B\xb0\xa1\x1b-A!", false),
+            "é!"
+        );
+        assert_eq!(decode_text(b"\xe3\x81\x8b", true), "か");
+    }
+}
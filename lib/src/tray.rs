@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    platform::TrayApi,
+    platform_impl::TrayImpl,
+    ContextOwner,
+    Error,
+    ErrorKind,
+    Icon,
+    Menu,
+    TrayId,
+};
+
+/// Source of tray icon IDs, they are unique within the process.
+static NEXT_TRAY_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Icon in the system tray (the status bar on macOS).
+///
+/// Activations of the icon are delivered as [Event::Tray](crate::Event::Tray),
+/// items of its menu send [Event::Menu](crate::Event::Menu) as usual. The icon
+/// is removed from the tray when it's dropped.
+///
+/// On Linux the icon is exported with the StatusNotifierItem protocol through
+/// the session bus. The X11 backend falls back to the XEmbed system tray
+/// protocol if the desktop has no StatusNotifierItem host, menus aren't shown
+/// in this case.
+#[derive(Debug)]
+pub struct Tray(TrayImpl);
+
+impl Tray {
+    /// Returns a new builder instance.
+    pub fn builder() -> TrayBuilder { TrayBuilder::new() }
+
+    /// Returns an ID of the tray icon.
+    pub fn id(&self) -> TrayId { self.0.id() }
+
+    /// Sets an icon image.
+    ///
+    /// # Parameters:
+    /// * `icon` - Icon image.
+    pub fn set_icon(&mut self, icon: Icon) { self.0.set_icon(icon); }
+
+    /// Sets a tooltip of the tray icon.
+    ///
+    /// # Parameters:
+    /// * `tooltip` - Tooltip text.
+    pub fn set_tooltip(&mut self, tooltip: Option<String>) { self.0.set_tooltip(tooltip); }
+
+    /// Sets a menu of the tray icon.
+    ///
+    /// On Linux the menu is exported as a snapshot, set the menu again after
+    /// changing its items.
+    ///
+    /// # Parameters:
+    /// * `menu` - Menu.
+    pub fn set_menu(&mut self, menu: Option<Menu>) { self.0.set_menu(menu); }
+}
+
+/// Tray icon builder.
+#[derive(Debug, Default)]
+pub struct TrayBuilder {
+    icon:    Option<Icon>,
+    tooltip: Option<String>,
+    menu:    Option<Menu>,
+}
+
+impl TrayBuilder {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    /// Sets an icon image of the tray icon under building.
+    ///
+    /// # Parameters:
+    /// * `icon` - Icon image.
+    pub fn with_icon(mut self, icon: Icon) -> TrayBuilder {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Sets a tooltip of the tray icon under building.
+    ///
+    /// # Parameters:
+    /// * `tooltip` - Tooltip text.
+    pub fn with_tooltip<S>(mut self, tooltip: S) -> TrayBuilder
+    where
+        S: Into<String>,
+    {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets a menu of the tray icon under building.
+    ///
+    /// # Parameters:
+    /// * `menu` - Menu.
+    pub fn with_menu(mut self, menu: Menu) -> TrayBuilder {
+        self.menu = Some(menu);
+        self
+    }
+
+    /// Builds a new tray icon and adds it to the system tray.
+    ///
+    /// Fails if the icon image isn't set or the desktop has no system tray.
+    ///
+    /// # Parameters:
+    /// * `ctx` - Context owner.
+    pub fn build(self, ctx: &impl ContextOwner) -> Result<Tray, Error> {
+        let icon = self
+            .icon
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "tray icon image not set."))?;
+        let id = NEXT_TRAY_ID.fetch_add(1, Ordering::Relaxed);
+
+        Ok(Tray(TrayImpl::new(ctx, id, icon, self.tooltip, self.menu)?))
+    }
+}
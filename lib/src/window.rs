@@ -1,4 +1,4 @@
-#[cfg(feature = "dh")]
+#[cfg(all(feature = "dh", target_os = "macos", not(feature = "headless")))]
 use b3_display_handler::{HasWindowHandler, WindowHandler};
 use dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 
@@ -7,6 +7,14 @@ use crate::{
     platform_impl::WindowImpl,
     ActiveApplication,
     ContextOwner,
+    Cursor,
+    CursorGrabMode,
+    CursorIcon,
+    CustomCursor,
+    Error,
+    Menu,
+    Monitor,
+    VideoMode,
 };
 
 /// Window options.
@@ -29,9 +37,10 @@ pub struct WindowOptions {
 }
 
 /// Initial mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum InitMode {
     /// A regular window with a specified frame size.
+    #[default]
     Default,
     /// The window will be displayed in minimized mode.
     Minimized,
@@ -41,8 +50,19 @@ pub enum InitMode {
     Fullscreen,
 }
 
-impl Default for InitMode {
-    fn default() -> Self { Self::Default }
+/// Fullscreen mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fullscreen {
+    /// The window covers a monitor without changing its video mode.
+    ///
+    /// `None` stands for the current monitor of the window.
+    Borderless(Option<Monitor>),
+    /// The window covers the monitor of the video mode and switches the
+    /// monitor to the mode.
+    ///
+    /// Wayland and macOS fall back to [Fullscreen::Borderless] on the monitor
+    /// of the mode.
+    Exclusive(VideoMode),
 }
 
 /// Window ID.
@@ -61,8 +81,8 @@ impl Window {
         mode: InitMode,
         options: Option<WindowOptions>,
         size: Option<Size>,
-    ) -> Self {
-        Self(WindowImpl::new(ctx, mode, options, size))
+    ) -> Result<Self, Error> {
+        Ok(Self(WindowImpl::new(ctx, mode, options, size)?))
     }
 
     /// Sets a window title.
@@ -103,8 +123,28 @@ impl Window {
     /// * `app` - Active application.
     pub fn show_modal(&mut self, app: &ActiveApplication) { self.0.show_modal(app); }
 
-    /// Toggles the fullscreen mode of the window.
-    pub fn toggle_fullscreen(&mut self) { self.0.toggle_fullscreen(); }
+    /// Switches the window to a fullscreen mode or back to the windowed one.
+    ///
+    /// [WindowEvent::FullscreenChanged](crate::WindowEvent::FullscreenChanged)
+    /// is sent when the window manager applies the change.
+    ///
+    /// # Parameters:
+    /// * `fullscreen` - Fullscreen mode or `None` for the windowed mode.
+    pub fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
+        self.0.set_fullscreen(fullscreen);
+    }
+
+    /// Returns the fullscreen mode of the window.
+    pub fn fullscreen(&self) -> Option<Fullscreen> { self.0.fullscreen() }
+
+    /// Toggles the borderless fullscreen mode of the window.
+    pub fn toggle_fullscreen(&mut self) {
+        let fullscreen = match self.is_fullscreen() {
+            true => None,
+            false => Some(Fullscreen::Borderless(None)),
+        };
+        self.0.set_fullscreen(fullscreen);
+    }
 
     /// Returns if a window is in the fullscreen mode.
     pub fn is_fullscreen(&self) -> bool { self.0.is_fullscreen() }
@@ -171,6 +211,130 @@ impl Window {
 
     /// Window backing scale factor.
     pub fn scale_factor(&self) -> f64 { self.0.scale_factor() }
+
+    /// Returns a monitor the window is on.
+    ///
+    /// If the window spans several monitors, the one with the largest part of
+    /// the window is returned.
+    pub fn current_monitor(&self) -> Option<Monitor> { self.0.current_monitor() }
+
+    /// Allows or disallows input methods for the window.
+    ///
+    /// Input method events (see [crate::ImeEvent]) are delivered only if input
+    /// methods are allowed. They are disallowed by default.
+    ///
+    /// Input methods are supported on Linux only.
+    ///
+    /// # Parameters:
+    /// * `allowed` - Allow input methods.
+    pub fn set_ime_allowed(&mut self, allowed: bool) { self.0.set_ime_allowed(allowed); }
+
+    /// Checks if input methods are allowed for the window.
+    pub fn is_ime_allowed(&self) -> bool { self.0.is_ime_allowed() }
+
+    /// Sets an area of the text cursor.
+    ///
+    /// Input methods place a candidate window next to this area.
+    ///
+    /// # Parameters:
+    /// * `position` - Cursor position relative to the window content.
+    /// * `size` - Cursor size.
+    pub fn set_ime_cursor_area(&mut self, position: Position, size: Size) {
+        self.0.set_ime_cursor_area(position, size);
+    }
+
+    /// Requests a window redraw.
+    ///
+    /// [WindowEvent::RedrawRequested] is delivered once per event loop
+    /// iteration before [Event::AboutToWait], no matter how many times the
+    /// redraw has been requested.
+    ///
+    /// [WindowEvent::RedrawRequested]: crate::WindowEvent::RedrawRequested
+    /// [Event::AboutToWait]: crate::Event::AboutToWait
+    pub fn request_redraw(&mut self) { self.0.request_redraw(); }
+
+    /// Notifies that a new frame is about to be presented.
+    ///
+    /// Call it right before submitting a frame to the window surface. The
+    /// backend may then throttle subsequent redraw requests to the display
+    /// refresh rate. On Wayland, redraws are held back until the compositor
+    /// signals that the frame has been shown, and the window stops filling its
+    /// surface with the background color once this has been called. On other
+    /// platforms this is a no-op.
+    pub fn pre_present_notify(&mut self) { self.0.pre_present_notify(); }
+
+    /// Sets a cursor icon from the standard set.
+    ///
+    /// The cursor is shown while the pointer is over the window content.
+    ///
+    /// # Parameters:
+    /// * `icon` - Cursor icon.
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) { self.0.set_cursor(Cursor::Icon(icon)); }
+
+    /// Sets a custom cursor image.
+    ///
+    /// # Parameters:
+    /// * `cursor` - Custom cursor.
+    pub fn set_custom_cursor(&mut self, cursor: &CustomCursor) {
+        self.0.set_cursor(Cursor::Custom(cursor.clone()));
+    }
+
+    /// Returns a cursor of the window.
+    pub fn cursor(&self) -> Cursor { self.0.cursor() }
+
+    /// Shows or hides the cursor over the window content.
+    ///
+    /// # Parameters:
+    /// * `visible` - Show the cursor.
+    pub fn set_cursor_visible(&mut self, visible: bool) { self.0.set_cursor_visible(visible); }
+
+    /// Checks if the cursor is visible over the window content.
+    pub fn is_cursor_visible(&self) -> bool { self.0.is_cursor_visible() }
+
+    /// Confines or locks the cursor to the window.
+    ///
+    /// Fails if the platform does not support the mode or the pointer could
+    /// not be grabbed, the previous mode is kept in this case.
+    ///
+    /// # Parameters:
+    /// * `mode` - Cursor grab mode.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), Error> {
+        self.0.set_cursor_grab(mode)
+    }
+
+    /// Returns a cursor grab mode of the window.
+    pub fn cursor_grab(&self) -> CursorGrabMode { self.0.cursor_grab() }
+
+    /// Moves the cursor to a position in the window content.
+    ///
+    /// On Wayland, the cursor can only be moved while it is locked (see
+    /// [CursorGrabMode::Locked]).
+    ///
+    /// # Parameters:
+    /// * `position` - Cursor position relative to the window content.
+    pub fn set_cursor_position(&mut self, position: Position) -> Result<(), Error> {
+        self.0.set_cursor_position(position)
+    }
+
+    /// Shows a context menu at a position in the window.
+    ///
+    /// The method returns immediately, a chosen item sends [Event::Menu] as
+    /// usual and [WindowEvent::ContextMenuClosed] is delivered once the menu
+    /// is closed.
+    ///
+    /// Context menus aren't supported on Wayland yet, where the method fails
+    /// with [ErrorKind::NotSupported] and no events are delivered.
+    ///
+    /// [ErrorKind::NotSupported]: crate::ErrorKind::NotSupported
+    /// [Event::Menu]: crate::Event::Menu
+    /// [WindowEvent::ContextMenuClosed]: crate::WindowEvent::ContextMenuClosed
+    ///
+    /// # Parameters:
+    /// * `menu` - Context menu.
+    /// * `position` - Top-left corner of the menu relative to the window content.
+    pub fn show_context_menu(&mut self, menu: &Menu, position: Position) -> Result<(), Error> {
+        self.0.show_context_menu(menu, position)
+    }
 }
 
 impl Wrapper<WindowImpl> for Window {
@@ -181,7 +345,7 @@ impl Wrapper<WindowImpl> for Window {
     fn get_impl_mut(&mut self) -> &mut WindowImpl { &mut self.0 }
 }
 
-#[cfg(feature = "dh")]
+#[cfg(all(feature = "dh", target_os = "macos", not(feature = "headless")))]
 impl HasWindowHandler for Window {
     fn window_handler(&self) -> WindowHandler { self.0.window_handler() }
 }
@@ -189,10 +353,11 @@ impl HasWindowHandler for Window {
 /// Window builder.
 #[derive(Default)]
 pub struct WindowBuilder {
-    title: Option<String>,
-    mode:  InitMode,
-    flags: Option<WindowOptions>,
-    size:  Option<Size>,
+    title:      Option<String>,
+    mode:       InitMode,
+    flags:      Option<WindowOptions>,
+    size:       Option<Size>,
+    fullscreen: Option<Fullscreen>,
 }
 
 impl WindowBuilder {
@@ -266,18 +431,35 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets a fullscreen mode of the window under building.
+    ///
+    /// Unlike [InitMode::Fullscreen], which is a borderless fullscreen mode on
+    /// the current monitor, the mode can target a specific monitor.
+    ///
+    /// # Parameters:
+    /// * `fullscreen` - Fullscreen mode.
+    pub fn with_fullscreen(mut self, fullscreen: Fullscreen) -> WindowBuilder {
+        self.fullscreen = Some(fullscreen);
+        self
+    }
+
     /// Builds a new window instance with passed parameters.
     ///
+    /// Fails if the windowing system refuses to create the window.
+    ///
     /// # Parameters:
     /// * `ctx` - Context onwer.
-    pub fn build(self, ctx: &impl ContextOwner) -> Window {
-        let mut window = Window::new(ctx, self.mode, self.flags, self.size);
+    pub fn build(self, ctx: &impl ContextOwner) -> Result<Window, Error> {
+        let mut window = Window::new(ctx, self.mode, self.flags, self.size)?;
 
         if let Some(title) = self.title {
             window.set_title(title);
         }
+        if let Some(fullscreen) = self.fullscreen {
+            window.set_fullscreen(Some(fullscreen));
+        }
 
-        window
+        Ok(window)
     }
 }
 
@@ -0,0 +1,128 @@
+//! Tests of parsing, formatting and matching of keyboard accelerators.
+
+use b3_core::{Accelerator, ErrorKind, Key, Modifiers, NamedKey};
+
+fn parse(s: &str) -> Accelerator { s.parse().unwrap() }
+
+fn character(c: &str) -> Key { Key::Character(c.into()) }
+
+#[test]
+fn parses_modifiers_and_keys() {
+    let accelerator = parse("control+ALT+shift+super+a");
+    assert_eq!(
+        accelerator.modifiers(),
+        Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT | Modifiers::SUPER
+    );
+    assert_eq!(accelerator.key(), &character("a"));
+
+    assert_eq!(parse("CmdOrCtrl+Q").modifiers(), Modifiers::CMD_OR_CTRL);
+    assert_eq!(
+        parse("Option+Cmd+Esc").modifiers(),
+        Modifiers::ALT | Modifiers::SUPER
+    );
+    assert_eq!(parse("Esc").key(), &Key::Named(NamedKey::Escape));
+    assert_eq!(parse("Ctrl+F12").key(), &Key::Named(NamedKey::F(12)));
+    assert_eq!(parse("Ctrl+Plus").key(), &character("+"));
+    assert_eq!(parse("Ctrl + Shift + P"), parse("Ctrl+Shift+P"));
+}
+
+#[test]
+fn rejects_invalid_accelerators() {
+    for s in [
+        "",
+        "Ctrl+",
+        "+",
+        "Ctrl+Hyper+P",
+        "Ctrl+Unknown",
+        "Ctrl+F25",
+        "Ctrl+F0",
+        "Ctrl+Ctrl+P",
+        "Ctrl+Control+P",
+        "Cmd+Super+P",
+    ] {
+        let error = s.parse::<Accelerator>().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput, "{:?}", s);
+    }
+
+    assert!(Accelerator::new(Modifiers::CAPS_LOCK, character("a")).is_err());
+    assert!(Accelerator::new(Modifiers::CONTROL, Key::Named(NamedKey::Shift)).is_err());
+    assert!(Accelerator::new(Modifiers::CONTROL, character("ab")).is_err());
+}
+
+#[test]
+fn formats_accelerators_back() {
+    for s in [
+        "Ctrl+Shift+P",
+        "Ctrl+Plus",
+        "Shift+F5",
+        "Ctrl+PageDown",
+        "Enter",
+        "Ctrl+1",
+    ] {
+        assert_eq!(parse(s).to_string(), s);
+    }
+
+    for s in [
+        "CmdOrCtrl+Alt+Shift+Super+Space",
+        "Super+Alt+x",
+        "Cmd+Option+Ctrl+Tab",
+    ] {
+        let accelerator = parse(s);
+        assert_eq!(parse(&accelerator.to_string()), accelerator);
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn formats_macos_modifiers() {
+    assert_eq!(
+        parse("Super+Alt+Shift+Ctrl+P").to_string(),
+        "Ctrl+Option+Shift+Cmd+P"
+    );
+    assert_eq!(parse("CmdOrCtrl+Q").to_string(), "Cmd+Q");
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn formats_pc_modifiers() {
+    assert_eq!(
+        parse("Cmd+Option+Shift+Ctrl+P").to_string(),
+        "Ctrl+Alt+Shift+Super+P"
+    );
+    assert_eq!(parse("CmdOrCtrl+Q").to_string(), "Ctrl+Q");
+}
+
+#[test]
+fn matches_key_presses() {
+    let accelerator = parse("Ctrl+Shift+P");
+    assert!(accelerator.matches(&character("P"), Modifiers::CONTROL | Modifiers::SHIFT));
+    assert!(accelerator.matches(
+        &character("p"),
+        Modifiers::CONTROL | Modifiers::SHIFT | Modifiers::CAPS_LOCK
+    ));
+    assert!(!accelerator.matches(&character("p"), Modifiers::CONTROL));
+    assert!(!accelerator.matches(&character("o"), Modifiers::CONTROL | Modifiers::SHIFT));
+
+    let accelerator = parse("Ctrl+P");
+    assert!(!accelerator.matches(&character("P"), Modifiers::CONTROL | Modifiers::SHIFT));
+    assert!(!accelerator.matches(&character("p"), Modifiers::CONTROL | Modifiers::ALT));
+
+    let accelerator = parse("F5");
+    assert!(accelerator.matches(&Key::Named(NamedKey::F(5)), Modifiers::empty()));
+    assert!(!accelerator.matches(&Key::Named(NamedKey::F(5)), Modifiers::SHIFT));
+}
+
+#[test]
+fn ignores_shift_for_symbols() {
+    let accelerator = parse("Ctrl+Plus");
+    assert!(accelerator.matches(&character("+"), Modifiers::CONTROL));
+    assert!(accelerator.matches(&character("+"), Modifiers::CONTROL | Modifiers::SHIFT));
+    assert!(!accelerator.matches(&character("="), Modifiers::CONTROL | Modifiers::SHIFT));
+
+    let accelerator = parse("Ctrl+1");
+    assert!(accelerator.matches(&character("1"), Modifiers::CONTROL | Modifiers::SHIFT));
+
+    let accelerator = parse("Ctrl+Shift+Plus");
+    assert!(accelerator.matches(&character("+"), Modifiers::CONTROL | Modifiers::SHIFT));
+    assert!(!accelerator.matches(&character("+"), Modifiers::CONTROL));
+}
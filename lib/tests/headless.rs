@@ -0,0 +1,504 @@
+//! Integration tests of the event loop driven by the headless backend.
+#![cfg(feature = "headless")]
+
+use std::{cell::RefCell, mem, rc::Rc};
+
+use b3_core::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    Accelerator,
+    Action,
+    ActiveApplication,
+    Application,
+    CheckState,
+    ContextOwner,
+    ControlFlow,
+    Error,
+    Event,
+    Fullscreen,
+    HeadlessExt,
+    InitMode,
+    Key,
+    KeyState,
+    KeyboardEvent,
+    LifeCycle,
+    Menu,
+    MenuItem,
+    MenuItemState,
+    Modifiers,
+    StartCause,
+    Window,
+    WindowEvent,
+};
+
+/// Runs the application and returns all events delivered to the handler.
+///
+/// The handler is called before an event gets recorded.
+fn run_recorded<T: Send + 'static>(
+    app: Application<T>,
+    mut handler: impl FnMut(&mut ActiveApplication, &Event<T>) + 'static,
+) -> Vec<Event<T>> {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let recorder = events.clone();
+    app.run(move |app: &mut ActiveApplication, event: Event<T>| {
+        handler(app, &event);
+        recorder.borrow_mut().push(event);
+    });
+    events.take()
+}
+
+fn key_press(key: &str, modifiers: Modifiers) -> Event {
+    Event::Window(
+        WindowEvent::Keyboard(KeyboardEvent::Input {
+            scan_code: 0,
+            key: Key::Character(key.into()),
+            text: Some(key.into()),
+            state: KeyState::Pressed,
+            repeat: false,
+            modifiers,
+        }),
+        1,
+    )
+}
+
+fn menu_event(action: &str) -> Event {
+    Event::Menu {
+        action: action.into(),
+        state:  None,
+    }
+}
+
+fn item(ctx: &impl ContextOwner, title: &str, accelerator: &str) -> MenuItem {
+    MenuItem::builder()
+        .with_title(title)
+        .with_action(Action::new_event(title.to_lowercase()))
+        .with_accelerator(accelerator.parse().unwrap())
+        .build(ctx)
+        .unwrap()
+}
+
+fn file_menu(ctx: &impl ContextOwner) -> Menu {
+    let disabled = MenuItem::builder()
+        .with_title("Close")
+        .with_action(Action::new_event("close"))
+        .with_accelerator("Ctrl+W".parse().unwrap())
+        .with_enabled(false)
+        .build(ctx)
+        .unwrap();
+    let checkable = MenuItem::builder()
+        .with_title("Wrap")
+        .with_action(Action::new_event("wrap"))
+        .with_check_state(CheckState::Checked)
+        .build(ctx)
+        .unwrap();
+    let submenu = Menu::builder()
+        .with_item(item(ctx, "Open", "Ctrl+O"))
+        .with_item(MenuItem::separator(ctx).unwrap())
+        .with_item(disabled)
+        .with_item(checkable)
+        .build(ctx);
+    let file = MenuItem::builder()
+        .with_title("File")
+        .with_submenu(submenu)
+        .build(ctx)
+        .unwrap();
+    Menu::builder().with_item(file).build(ctx)
+}
+
+#[test]
+fn loop_delivers_queued_and_user_events() {
+    let app = Application::<u32>::with_user_event().unwrap();
+    app.create_proxy().send_event(7).unwrap();
+    app.queue_event(menu_event("open"));
+
+    let events = run_recorded(app, |_, _| {});
+
+    assert_eq!(
+        events,
+        vec![
+            Event::LifeCycle(LifeCycle::Started),
+            Event::NewEvents(StartCause::Init),
+            Event::Menu {
+                action: "open".into(),
+                state:  None,
+            },
+            Event::User(7),
+            Event::AboutToWait,
+            Event::LifeCycle(LifeCycle::Finished),
+        ]
+    );
+}
+
+#[test]
+fn loop_polls_until_stopped() {
+    let app = Application::new().unwrap();
+    let mut iterations = 0;
+
+    let events = run_recorded(app, move |app, event| match event {
+        Event::LifeCycle(LifeCycle::Started) => app.set_control_flow(ControlFlow::Poll),
+        Event::AboutToWait => {
+            iterations += 1;
+            if iterations == 2 {
+                app.stop();
+            }
+        }
+        _ => (),
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            Event::LifeCycle(LifeCycle::Started),
+            Event::NewEvents(StartCause::Init),
+            Event::AboutToWait,
+            Event::NewEvents(StartCause::Poll),
+            Event::AboutToWait,
+            Event::LifeCycle(LifeCycle::Finished),
+        ]
+    );
+}
+
+#[test]
+fn window_updates_geometry_and_state() {
+    let app = Application::new().unwrap();
+    let mut window = Window::builder()
+        .with_title("Test")
+        .with_logical_size((640, 480))
+        .build(&app)
+        .unwrap();
+    let id = window.id();
+    assert_eq!(window.title(), "Test");
+    assert_eq!(window.frame_size(), PhysicalSize::new(640, 480));
+    assert!(!window.is_visible());
+
+    let events = run_recorded(app, move |app, event| match event {
+        Event::LifeCycle(LifeCycle::Started) => {
+            window.show(app);
+            assert!(window.is_visible());
+
+            window.set_frame_size(LogicalSize::new(1024, 768).into());
+            window.set_max_size(PhysicalSize::new(800, 600).into());
+            assert_eq!(window.frame_size(), PhysicalSize::new(800, 600));
+            assert_eq!(window.content_size(), PhysicalSize::new(800, 600));
+
+            window.set_position(PhysicalPosition::new(10, 20).into());
+            assert_eq!(window.position(), PhysicalPosition::new(10, 20));
+
+            window.maximize();
+            assert!(window.is_maximized());
+            window.minimize();
+            assert!(window.is_minimized());
+            window.restore();
+            assert!(!window.is_minimized());
+
+            window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+            assert!(window.is_fullscreen());
+            window.close();
+            assert!(!window.is_visible());
+        }
+        Event::Window(WindowEvent::Destroyed, _) => app.stop(),
+        _ => (),
+    });
+
+    let window_events = events
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Window(event, window_id) if window_id == id => Some(event),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        window_events,
+        vec![
+            WindowEvent::Showed,
+            WindowEvent::Resized(PhysicalSize::new(1024, 768)),
+            WindowEvent::Resized(PhysicalSize::new(800, 600)),
+            WindowEvent::Moved(PhysicalPosition::new(10, 20)),
+            WindowEvent::FullscreenChanged(true),
+            WindowEvent::Destroyed,
+        ]
+    );
+}
+
+#[test]
+fn window_applies_init_mode_when_shown() {
+    let app = Application::new().unwrap();
+    let mut window = Window::builder()
+        .with_init_mode(InitMode::Maximized)
+        .build(&app)
+        .unwrap();
+    assert!(!window.is_maximized());
+
+    run_recorded(app, move |app, event| {
+        if let Event::LifeCycle(LifeCycle::Started) = event {
+            window.show(app);
+            assert!(window.is_maximized());
+        }
+    });
+}
+
+#[test]
+fn menu_is_recorded() -> Result<(), Error> {
+    let app = Application::builder()
+        .with_menu(|ctx| Ok(file_menu(ctx)))
+        .build()?;
+    assert_eq!(app.menu(), None);
+
+    let snapshots = Rc::new(RefCell::new(Vec::new()));
+    let recorder = snapshots.clone();
+    run_recorded(app, move |app, event| {
+        if let Event::LifeCycle(LifeCycle::Started) = event {
+            recorder.borrow_mut().push(app.menu());
+            app.set_menu(None);
+            recorder.borrow_mut().push(app.menu());
+        }
+    });
+
+    let snapshots = snapshots.take();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[1], None);
+    let menu = snapshots[0].as_ref().unwrap();
+    assert_eq!(menu.len(), 1);
+    assert_eq!(menu[0].title, "File");
+    let items = menu[0].submenu.as_ref().unwrap();
+    assert_eq!(
+        items,
+        &vec![
+            MenuItemState {
+                title:       "Open".into(),
+                event:       Some("open".into()),
+                accelerator: Some(Accelerator::new(
+                    Modifiers::CONTROL,
+                    Key::Character("o".into())
+                )?),
+                enabled:     true,
+                separator:   false,
+                check_state: None,
+                radio_group: None,
+                submenu:     None,
+            },
+            MenuItemState {
+                title:       String::new(),
+                event:       None,
+                accelerator: None,
+                enabled:     true,
+                separator:   true,
+                check_state: None,
+                radio_group: None,
+                submenu:     None,
+            },
+            MenuItemState {
+                title:       "Close".into(),
+                event:       Some("close".into()),
+                accelerator: Some("Ctrl+W".parse().unwrap()),
+                enabled:     false,
+                separator:   false,
+                check_state: None,
+                radio_group: None,
+                submenu:     None,
+            },
+            MenuItemState {
+                title:       "Wrap".into(),
+                event:       Some("wrap".into()),
+                accelerator: None,
+                enabled:     true,
+                separator:   false,
+                check_state: Some(CheckState::Checked),
+                radio_group: None,
+                submenu:     None,
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn accelerators_replace_key_presses() {
+    let app = Application::builder()
+        .with_menu(|ctx| Ok(file_menu(ctx)))
+        .build()
+        .unwrap();
+    app.queue_event(key_press("o", Modifiers::CONTROL));
+    app.queue_event(key_press("w", Modifiers::CONTROL));
+    app.queue_event(key_press("o", Modifiers::ALT));
+
+    let events = run_recorded(app, |_, _| {});
+
+    assert_eq!(
+        events,
+        vec![
+            Event::LifeCycle(LifeCycle::Started),
+            Event::NewEvents(StartCause::Init),
+            menu_event("open"),
+            key_press("w", Modifiers::CONTROL),
+            key_press("o", Modifiers::ALT),
+            Event::AboutToWait,
+            Event::LifeCycle(LifeCycle::Finished),
+        ]
+    );
+}
+
+#[test]
+fn accelerators_follow_menu_changes() {
+    let app = Application::new().unwrap();
+    let mut menu = file_menu(&app);
+
+    let events = run_recorded(app, move |app, event| {
+        if let Event::LifeCycle(LifeCycle::Started) = event {
+            app.set_menu(Some(&menu));
+
+            let file = menu.items_mut()[0].submenu_mut().unwrap();
+            file.items_mut()[0].set_enabled(false);
+            file.insert(1, item(app, "Save", "Ctrl+S"));
+            file.items_mut()[4].set_accelerator(Some("Ctrl+R".parse().unwrap()));
+            // The removed item doesn't belong to the menu anymore
+            let mut close = file.remove(3);
+            close.set_enabled(true);
+            assert_eq!(close.title(), "Close");
+
+            app.queue_event(key_press("o", Modifiers::CONTROL));
+            app.queue_event(key_press("s", Modifiers::CONTROL));
+            app.queue_event(key_press("r", Modifiers::CONTROL));
+            app.queue_event(key_press("w", Modifiers::CONTROL));
+        }
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            Event::LifeCycle(LifeCycle::Started),
+            Event::NewEvents(StartCause::Init),
+            key_press("o", Modifiers::CONTROL),
+            menu_event("save"),
+            Event::Menu {
+                action: "wrap".into(),
+                state:  Some(CheckState::Unchecked),
+            },
+            key_press("w", Modifiers::CONTROL),
+            Event::AboutToWait,
+            Event::LifeCycle(LifeCycle::Finished),
+        ]
+    );
+}
+
+#[test]
+fn radio_and_checkable_items_follow_choices() {
+    let app = Application::new().unwrap();
+    let size = |title: &str, accelerator: &str, checked: bool| {
+        MenuItem::builder()
+            .with_title(title)
+            .with_action(Action::new_event(title.to_lowercase()))
+            .with_accelerator(accelerator.parse().unwrap())
+            .with_radio_group("size")
+            .with_checked(checked)
+            .build(&app)
+            .unwrap()
+    };
+    let wrap = MenuItem::builder()
+        .with_title("Wrap")
+        .with_action(Action::new_event("wrap"))
+        .with_accelerator("Ctrl+R".parse().unwrap())
+        .with_checked(false)
+        .build(&app)
+        .unwrap();
+    let menu = Menu::builder()
+        .with_item(size("Small", "Ctrl+1", true))
+        .with_item(size("Medium", "Ctrl+2", false))
+        .with_item(size("Large", "Ctrl+3", false))
+        .with_item(wrap)
+        .build(&app);
+
+    let snapshots = Rc::new(RefCell::new(Vec::new()));
+    let recorder = snapshots.clone();
+    let events = run_recorded(app, move |app, event| match event {
+        Event::LifeCycle(LifeCycle::Started) => {
+            app.set_menu(Some(&menu));
+            app.queue_event(key_press("2", Modifiers::CONTROL));
+            app.queue_event(key_press("r", Modifiers::CONTROL));
+        }
+        Event::Menu {
+            ..
+        } => {
+            // The snapshot is taken when the menu is set
+            app.set_menu(Some(&menu));
+            let states = app
+                .menu()
+                .unwrap()
+                .into_iter()
+                .map(|item| item.check_state)
+                .collect::<Vec<_>>();
+            recorder.borrow_mut().push(states);
+        }
+        _ => (),
+    });
+
+    let menu_events = events
+        .into_iter()
+        .filter(|event| matches!(event, Event::Menu { .. }))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        menu_events,
+        vec![
+            Event::Menu {
+                action: "medium".into(),
+                state:  Some(CheckState::Checked),
+            },
+            Event::Menu {
+                action: "wrap".into(),
+                state:  Some(CheckState::Checked),
+            },
+        ]
+    );
+
+    let unchecked = Some(CheckState::Unchecked);
+    let checked = Some(CheckState::Checked);
+    assert_eq!(
+        snapshots.take(),
+        vec![
+            vec![unchecked, checked, unchecked, unchecked],
+            vec![unchecked, checked, unchecked, checked],
+        ]
+    );
+}
+
+#[test]
+fn redraw_requests_are_coalesced() {
+    let app = Application::new().unwrap();
+    let mut window = Window::builder().build(&app).unwrap();
+    let id = window.id();
+    let mut first_wait = true;
+
+    let events = run_recorded(app, move |app, event| {
+        let request = match event {
+            Event::LifeCycle(LifeCycle::Started) => {
+                window.show(app);
+                true
+            }
+            Event::AboutToWait => mem::take(&mut first_wait),
+            _ => false,
+        };
+        if request {
+            window.request_redraw();
+            window.request_redraw();
+            window.request_redraw();
+        }
+    });
+
+    let redraws_and_waits = events
+        .into_iter()
+        .filter(|event| {
+            matches!(
+                event,
+                Event::Window(WindowEvent::RedrawRequested, _) | Event::AboutToWait
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        redraws_and_waits,
+        vec![
+            Event::Window(WindowEvent::RedrawRequested, id),
+            Event::AboutToWait,
+            Event::Window(WindowEvent::RedrawRequested, id),
+            Event::AboutToWait,
+        ]
+    );
+}